@@ -8,7 +8,9 @@ import (
 	"time"
 
 	"github.com/ethereum/go-ethereum/accounts/abi/bind"
+	"github.com/ethereum/go-ethereum/common"
 
+	"github.com/Layr-Labs/eigensdk-go/crypto/bls"
 	sdklogging "github.com/Layr-Labs/eigensdk-go/logging"
 	"github.com/Layr-Labs/eigensdk-go/services/avsregistry"
 	blsagg "github.com/Layr-Labs/eigensdk-go/services/bls_aggregation"
@@ -77,6 +79,7 @@ type Aggregator struct {
 	taskResponsesMu         sync.RWMutex
 	substrateClient         gsrpc.SubstrateAPI
 	taskResponseWindowBlock uint32
+	exitIntents             *ExitIntents
 
 	kicker  *Kicker
 	updater *StakeUpdate
@@ -97,6 +100,8 @@ func NewAggregator(c *Config) (*Aggregator, error) {
 		c.EthWsUrl,
 		c.SignerFn,
 		c.Address,
+		c.RegistrySignerFn,
+		c.RegistryAddress,
 		"mangata-finalizer",
 		"0.0.0.0:8888",
 		logger,
@@ -106,6 +111,12 @@ func NewAggregator(c *Config) (*Aggregator, error) {
 		return nil, err
 	}
 
+	if c.RegistryAddress == c.Address {
+		logger.Warn("Registry transactions share the account of the responses, a stuck one delays them", "address", c.Address)
+	} else {
+		logger.Info("Sending registry transactions from a separate account", "responses", c.Address, "registry", c.RegistryAddress)
+	}
+
 	chainId, err := ethRpc.Client.ChainID(context.Background())
 	if err != nil {
 		logger.Error("Cannot get chainId", "err", err)
@@ -132,7 +143,26 @@ func NewAggregator(c *Config) (*Aggregator, error) {
 		return nil, err
 	}
 
-	kicker, err := NewKicker(logger, *ethRpc, uint32(c.KickPeriod), uint32(c.BlockPeriod))
+	exitIntents := NewExitIntents(logger, chainId.Uint64(), func(operatorId bls.OperatorId) (common.Address, error) {
+		return ethRpc.ElClients.AvsRegistryChainReader.GetOperatorAddress(&bind.CallOpts{}, operatorId)
+	}, func(operatorId bls.OperatorId) (bool, error) {
+		block, err := ethRpc.Client.BlockNumber(context.Background())
+		if err != nil {
+			return false, err
+		}
+		operatorIds, err := ethRpc.ElClients.AvsRegistryChainReader.GetOperatorIdList(&bind.CallOpts{}, types.QUORUM_NUMBER, uint32(block))
+		if err != nil {
+			return false, err
+		}
+		for _, id := range operatorIds {
+			if id == operatorId {
+				return true, nil
+			}
+		}
+		return false, nil
+	})
+
+	kicker, err := NewKicker(logger, *ethRpc, exitIntents, uint32(c.KickPeriod), uint32(c.BlockPeriod))
 	if err != nil {
 		logger.Error("Cannot create operator active list filter", "err", err)
 		return nil, err
@@ -152,6 +182,7 @@ func NewAggregator(c *Config) (*Aggregator, error) {
 		taskResponses:           make(map[types.TaskIndex]map[sdktypes.TaskResponseDigest]taskmanager.IMangataTaskManagerTaskResponse),
 		substrateClient:         *substrateRpc,
 		taskResponseWindowBlock: taskResponseWindowBlock,
+		exitIntents:             exitIntents,
 		blockPeriod:             uint32(c.BlockPeriod),
 		kicker:                  kicker,
 		updater:                 updater,
@@ -245,6 +276,12 @@ func (agg *Aggregator) sendNewTask(blockNumber uint32) error {
 	agg.tasks[taskIndex] = newTask
 	agg.tasksMu.Unlock()
 
+	// exiting operators are not ejected for the tasks they miss, the threshold itself is not
+	// lowered as it would let operators weaken it by announcing exits
+	if exiting := agg.exitIntents.Exiting(); len(exiting) > 0 {
+		agg.logger.Warn("Operators announced their exit, their signatures are not expected", "task index", taskIndex, "exiting", exiting)
+	}
+
 	agg.kicker.TriggerNewTask(taskIndex)
 	agg.updater.TriggerNewTask(taskIndex)
 
@@ -90,6 +90,7 @@ func createMockAggregator(
 		tasks:                   make(map[types.TaskIndex]taskmanager.IMangataTaskManagerTask),
 		taskResponses:           make(map[types.TaskIndex]map[sdktypes.TaskResponseDigest]taskmanager.IMangataTaskManagerTaskResponse),
 		taskResponseWindowBlock: 100,
+		exitIntents:             NewExitIntents(logger, 0, nil, nil),
 	}
 	return aggregator, mockAvsWriter, mockBlsAggregationService, nil
 }
@@ -1,7 +1,7 @@
 package aggregator
 
 import (
-	"errors"
+	"fmt"
 	"math/big"
 
 	sdklogging "github.com/Layr-Labs/eigensdk-go/logging"
@@ -27,9 +27,14 @@ type Config struct {
 	BlsCompendiumAddr             common.Address
 	ServiceManagerAddr            common.Address
 
+	// account of the task and response transactions
 	SignerFn signerv2.SignerFn
 	Address  common.Address
 
+	// account of the registry transactions, ejections and stake updates
+	RegistrySignerFn signerv2.SignerFn
+	RegistryAddress  common.Address
+
 	KickPeriod int
 }
 
@@ -43,34 +48,14 @@ func NewConfig(ctx *cli.Context) (*Config, error) {
 
 	chainId := big.NewInt(int64(ctx.GlobalUint(config.ChainIdFlag.Name)))
 
-	var signer signerv2.SignerFn
-	var address common.Address
-	keyStoreFile := ctx.GlobalString(config.EcdsaKeyFileFlag.Name)
-	if keyStoreFile == "" {
-		keyStoreContents := ctx.GlobalString(config.EcdsaKeyJsonFlag.Name)
-		if keyStoreContents == "" {
-			return nil, errors.New("one of --ecdsa-key-file or --ecdsa-key-json must be set")
-		}
-		sk, err := keystore.DecryptKey([]byte(keyStoreContents), ctx.GlobalString(config.EcdsaKeyPasswordFlag.Name))
-		if err != nil {
-			return nil, err
-		}
-		signer, address, err = signerv2.SignerFromConfig(signerv2.Config{
-			PrivateKey: sk.PrivateKey,
-		},
-			chainId,
-		)
-		if err != nil {
-			return nil, err
-		}
-	} else {
-		var err error
-		signer, address, err = signerv2.SignerFromConfig(signerv2.Config{
-			KeystorePath: ctx.GlobalString(config.EcdsaKeyFileFlag.Name),
-			Password:     ctx.GlobalString(config.EcdsaKeyPasswordFlag.Name),
-		},
-			chainId,
-		)
+	signer, address, err := loadSigner(ctx, config.EcdsaKeyFileFlag, config.EcdsaKeyJsonFlag, config.EcdsaKeyPasswordFlag, chainId)
+	if err != nil {
+		return nil, err
+	}
+	// registry transactions share the main account unless given their own
+	registrySigner, registryAddress := signer, address
+	if ctx.GlobalString(config.RegistryEcdsaKeyFileFlag.Name) != "" || ctx.GlobalString(config.RegistryEcdsaKeyJsonFlag.Name) != "" {
+		registrySigner, registryAddress, err = loadSigner(ctx, config.RegistryEcdsaKeyFileFlag, config.RegistryEcdsaKeyJsonFlag, config.RegistryEcdsaKeyPasswordFlag, chainId)
 		if err != nil {
 			return nil, err
 		}
@@ -91,9 +76,38 @@ func NewConfig(ctx *cli.Context) (*Config, error) {
 		ServiceManagerAddr:            common.HexToAddress(ctx.GlobalString(config.AvsServiceManagerFlag.Name)),
 		SignerFn:                      signer,
 		Address:                       address,
+		RegistrySignerFn:              registrySigner,
+		RegistryAddress:               registryAddress,
 	}, nil
 }
 
+// loadSigner decrypts the ecdsa key given by the keystore file or json flag with the password flag.
+func loadSigner(ctx *cli.Context, fileFlag, jsonFlag, passwordFlag cli.StringFlag, chainId *big.Int) (signerv2.SignerFn, common.Address, error) {
+	password := ctx.GlobalString(passwordFlag.Name)
+	keyStoreFile := ctx.GlobalString(fileFlag.Name)
+	if keyStoreFile != "" {
+		return signerv2.SignerFromConfig(signerv2.Config{
+			KeystorePath: keyStoreFile,
+			Password:     password,
+		},
+			chainId,
+		)
+	}
+	keyStoreContents := ctx.GlobalString(jsonFlag.Name)
+	if keyStoreContents == "" {
+		return nil, common.Address{}, fmt.Errorf("one of --%s or --%s must be set", fileFlag.Name, jsonFlag.Name)
+	}
+	sk, err := keystore.DecryptKey([]byte(keyStoreContents), password)
+	if err != nil {
+		return nil, common.Address{}, err
+	}
+	return signerv2.SignerFromConfig(signerv2.Config{
+		PrivateKey: sk.PrivateKey,
+	},
+		chainId,
+	)
+}
+
 var Flags = []cli.Flag{
 	config.EnvironmentFlag,
 	config.EthRpcFlag,
@@ -107,6 +121,9 @@ var Flags = []cli.Flag{
 	config.EcdsaKeyFileFlag,
 	config.EcdsaKeyJsonFlag,
 	config.EcdsaKeyPasswordFlag,
+	config.RegistryEcdsaKeyFileFlag,
+	config.RegistryEcdsaKeyJsonFlag,
+	config.RegistryEcdsaKeyPasswordFlag,
 	config.AvsBlockValidationPeriodFlag,
 	config.AvsKickPeriodFlag,
 	config.AvsUpdateStakePeriodFlag,
@@ -0,0 +1,167 @@
+package chainio
+
+import (
+	"encoding/json"
+	"flag"
+	"math/big"
+	"os"
+	"strings"
+	"testing"
+
+	"github.com/ethereum/go-ethereum/accounts/abi"
+	"github.com/ethereum/go-ethereum/common"
+	"github.com/ethereum/go-ethereum/common/hexutil"
+	"github.com/stretchr/testify/assert"
+
+	blspubkeycompendium "github.com/Layr-Labs/eigensdk-go/contracts/bindings/BLSPublicKeyCompendium"
+	blsregistrycoordinator "github.com/Layr-Labs/eigensdk-go/contracts/bindings/BLSRegistryCoordinatorWithIndices"
+
+	taskmanager "github.com/mangata-finance/eigen-layer-monorepo/avs-aggregator/bindings/MangataTaskManager"
+)
+
+// Calldata fixtures shared with the finalizer, which encodes the same inputs in
+// avs-finalizer/src/chainio/golden.rs. A mismatch means the clients encode a call differently.
+// The fixtures are generated by this reference encoding:
+//
+//	go test ./core/chainio -run Calldata -update-fixtures
+const calldataFixtures = "../../../tests/fixtures/calldata.json"
+
+var updateFixtures = flag.Bool("update-fixtures", false, "write the calldata encoded here to the fixtures")
+
+// DelegationManager calls sent by the operators, the eigensdk bindings this module depends
+// on predate the withdrawal queue.
+const delegationManagerAbi = `[
+	{"type":"function","name":"registerAsOperator","inputs":[
+		{"name":"registeringOperatorDetails","type":"tuple","components":[
+			{"name":"earningsReceiver","type":"address"},
+			{"name":"delegationApprover","type":"address"},
+			{"name":"stakerOptOutWindowBlocks","type":"uint32"}]},
+		{"name":"metadataURI","type":"string"}],"outputs":[]},
+	{"type":"function","name":"queueWithdrawals","inputs":[
+		{"name":"queuedWithdrawalParams","type":"tuple[]","components":[
+			{"name":"strategies","type":"address[]"},
+			{"name":"shares","type":"uint256[]"},
+			{"name":"withdrawer","type":"address"}]}],"outputs":[{"name":"","type":"bytes32[]"}]}
+]`
+
+func assertGolden(t *testing.T, name string, calldata []byte, err error) {
+	t.Helper()
+	assert.Nil(t, err)
+	raw, err := os.ReadFile(calldataFixtures)
+	assert.Nil(t, err)
+	fixtures := map[string]hexutil.Bytes{}
+	assert.Nil(t, json.Unmarshal(raw, &fixtures))
+	if *updateFixtures {
+		fixtures[name] = calldata
+		// keys are sorted, the file only changes where an encoding does
+		raw, err = json.MarshalIndent(fixtures, "", "  ")
+		assert.Nil(t, err)
+		assert.Nil(t, os.WriteFile(calldataFixtures, append(raw, '\n'), 0o644))
+		return
+	}
+	expected, ok := fixtures[name]
+	assert.True(t, ok, "missing fixture %s", name)
+	assert.Equal(t, hexutil.Encode(expected), hexutil.Encode(calldata), name)
+}
+
+// packById encodes a call to the method with the given selector, for overloaded methods.
+func packById(parsed *abi.ABI, selector string, args ...interface{}) ([]byte, error) {
+	method, err := parsed.MethodById(common.FromHex(selector))
+	if err != nil {
+		return nil, err
+	}
+	packed, err := method.Inputs.Pack(args...)
+	return append(method.ID, packed...), err
+}
+
+func TestRegistrationCalldata(t *testing.T) {
+	operator := common.HexToAddress("0x1111111111111111111111111111111111111111")
+
+	registry, err := blsregistrycoordinator.ContractBLSRegistryCoordinatorWithIndicesMetaData.GetAbi()
+	assert.Nil(t, err)
+	calldata, err := packById(registry, "0xc66ab9ca",
+		[]byte{0},
+		blsregistrycoordinator.BN254G1Point{X: big.NewInt(1), Y: big.NewInt(2)},
+		"",
+	)
+	assertGolden(t, "registerOperatorWithCoordinator", calldata, err)
+
+	delegation, err := abi.JSON(strings.NewReader(delegationManagerAbi))
+	assert.Nil(t, err)
+	details := struct {
+		EarningsReceiver         common.Address
+		DelegationApprover       common.Address
+		StakerOptOutWindowBlocks uint32
+	}{EarningsReceiver: operator}
+	calldata, err = delegation.Pack("registerAsOperator", details, "")
+	assertGolden(t, "registerAsOperator", calldata, err)
+
+	compendium, err := blspubkeycompendium.ContractBLSPublicKeyCompendiumMetaData.GetAbi()
+	assert.Nil(t, err)
+	calldata, err = compendium.Pack("registerBLSPublicKey",
+		blspubkeycompendium.BN254G1Point{X: big.NewInt(3), Y: big.NewInt(4)},
+		blspubkeycompendium.BN254G1Point{X: big.NewInt(1), Y: big.NewInt(2)},
+		blspubkeycompendium.BN254G2Point{
+			X: [2]*big.Int{big.NewInt(5), big.NewInt(6)},
+			Y: [2]*big.Int{big.NewInt(7), big.NewInt(8)},
+		},
+	)
+	assertGolden(t, "registerBLSPublicKey", calldata, err)
+}
+
+func TestResponseCalldata(t *testing.T) {
+	task := taskmanager.IMangataTaskManagerTask{
+		BlockNumber:               big.NewInt(42),
+		TaskCreatedBlock:          100,
+		QuorumNumbers:             []byte{0},
+		QuorumThresholdPercentage: 67,
+	}
+	taskResponse := taskmanager.IMangataTaskManagerTaskResponse{ReferenceTaskIndex: 7}
+	for i := range taskResponse.BlockHash {
+		taskResponse.BlockHash[i] = 0xaa
+		taskResponse.StorageProofHash[i] = 0xbb
+	}
+	g1 := func(x, y int64) taskmanager.BN254G1Point {
+		return taskmanager.BN254G1Point{X: big.NewInt(x), Y: big.NewInt(y)}
+	}
+	signature := taskmanager.IBLSSignatureCheckerNonSignerStakesAndSignature{
+		NonSignerQuorumBitmapIndices: []uint32{1},
+		NonSignerPubkeys:             []taskmanager.BN254G1Point{g1(9, 10)},
+		QuorumApks:                   []taskmanager.BN254G1Point{g1(11, 12)},
+		ApkG2: taskmanager.BN254G2Point{
+			X: [2]*big.Int{big.NewInt(13), big.NewInt(14)},
+			Y: [2]*big.Int{big.NewInt(15), big.NewInt(16)},
+		},
+		Sigma:                 g1(17, 18),
+		QuorumApkIndices:      []uint32{2},
+		TotalStakeIndices:     []uint32{3},
+		NonSignerStakeIndices: [][]uint32{{4}},
+	}
+
+	// encoded as RespondToTask in SendAggregatedResponse does
+	parsed, err := taskmanager.ContractMangataTaskManagerMetaData.GetAbi()
+	assert.Nil(t, err)
+	calldata, err := parsed.Pack("respondToTask", task, taskResponse, signature)
+	assertGolden(t, "respondToTask", calldata, err)
+}
+
+func TestWithdrawalCalldata(t *testing.T) {
+	maxUint256 := new(big.Int).Sub(new(big.Int).Lsh(big.NewInt(1), 256), big.NewInt(1))
+	params := []struct {
+		Strategies []common.Address
+		Shares     []*big.Int
+		Withdrawer common.Address
+	}{{
+		Strategies: []common.Address{
+			common.HexToAddress("0x2222222222222222222222222222222222222222"),
+			common.HexToAddress("0x3333333333333333333333333333333333333333"),
+		},
+		Shares:     []*big.Int{big.NewInt(1e18), maxUint256},
+		Withdrawer: common.HexToAddress("0x1111111111111111111111111111111111111111"),
+	}}
+
+	delegation, err := abi.JSON(strings.NewReader(delegationManagerAbi))
+	assert.Nil(t, err)
+	calldata, err := delegation.Pack("queueWithdrawals", params)
+	assertGolden(t, "queueWithdrawals", calldata, err)
+}
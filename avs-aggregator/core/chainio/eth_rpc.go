@@ -1,6 +1,8 @@
 package chainio
 
 import (
+	"fmt"
+
 	"github.com/Layr-Labs/eigensdk-go/chainio/clients"
 	"github.com/Layr-Labs/eigensdk-go/chainio/clients/avsregistry"
 	"github.com/Layr-Labs/eigensdk-go/chainio/clients/elcontracts"
@@ -34,6 +36,8 @@ func NewEthRpc(
 	ethWsUrl string,
 	signer signerv2.SignerFn,
 	address common.Address,
+	registrySigner signerv2.SignerFn,
+	registryAddress common.Address,
 	avsName string,
 	metricsIpPort string,
 	logger sdklogging.Logger,
@@ -57,7 +61,13 @@ func NewEthRpc(
 		return nil, err
 	}
 
+	// tasks and responses are time critical, registry transactions are sent from their own
+	// account so that one stuck in the mempool never holds their nonce
 	txMgr := txmgr.NewSimpleTxManager(ethHttpClient, logger, signer, address)
+	registryTxMgr := txMgr
+	if registryAddress != address {
+		registryTxMgr = txmgr.NewSimpleTxManager(ethHttpClient, logger, registrySigner, registryAddress)
+	}
 	avsReader, err := NewAvsReaderFromConfig(serviceManagerAddr, blsOperatorStateRetrieverAddr, ethHttpClient, logger)
 	if err != nil {
 		logger.Error("Cannot create AvsReader", "err", err)
@@ -88,6 +98,16 @@ func NewEthRpc(
 		return nil, err
 	}
 
+	// ejectOperator is onlyEjector, fail now rather than on the first ejection
+	ejector, err := avsRegistryContractBindings.RegistryCoordinator.Ejector(&bind.CallOpts{})
+	if err != nil {
+		logger.Error("Failed to fetch the registry coordinator ejector", "err", err)
+		return nil, err
+	}
+	if ejector != registryAddress {
+		return nil, fmt.Errorf("registry transactions are sent from %s but the registry coordinator ejector is %s, set it with setEjector", registryAddress.Hex(), ejector.Hex())
+	}
+
 	slasherAddr, err := avsRegistryContractBindings.RegistryCoordinator.Slasher(&bind.CallOpts{})
 	if err != nil {
 		logger.Fatal("Failed to fetch Slasher contract", "err", err)
@@ -143,7 +163,7 @@ func NewEthRpc(
 		ethHttpClient,
 		logger,
 		eigenMetrics,
-		txMgr,
+		registryTxMgr,
 	)
 	if err != nil {
 		logger.Error("Failed to create ELChainWriter", "err", err)
@@ -172,7 +192,7 @@ func NewEthRpc(
 		avsRegistryContractBindings.BlsPubkeyRegistry,
 		logger,
 		ethHttpClient,
-		txMgr,
+		registryTxMgr,
 	)
 	if err != nil {
 		logger.Error("Failed to create AVSRegistryChainWriter", "err", err)
@@ -109,4 +109,28 @@ var (
 		Usage:    "Password to decrypt ecdsa private key",
 		EnvVar:   "ECDSA_KEY_PASSWORD",
 	}
+	// Separate funded account for the registry transactions (ejections and stake updates),
+	// so that they never hold the nonce of the task and response transactions. Ejections are
+	// onlyEjector, the account must be set as the registry coordinator ejector first.
+	RegistryEcdsaKeyFileFlag = cli.StringFlag{
+		Name:     "registry-ecdsa-key-file",
+		Required: false,
+		Usage:    "Path to the encrypted ecdsa private key of the registry transactions, defaults to the main key",
+		Value:    "",
+		EnvVar:   "REGISTRY_ECDSA_KEY_FILE",
+	}
+	RegistryEcdsaKeyJsonFlag = cli.StringFlag{
+		Name:     "registry-ecdsa-key-json",
+		Required: false,
+		Usage:    "Encrypted ecdsa private key json of the registry transactions, defaults to the main key",
+		Value:    "",
+		EnvVar:   "REGISTRY_ECDSA_KEY_JSON",
+	}
+	RegistryEcdsaKeyPasswordFlag = cli.StringFlag{
+		Name:     "registry-ecdsa-key-password",
+		Required: false,
+		Value:    "",
+		Usage:    "Password to decrypt the registry ecdsa private key",
+		EnvVar:   "REGISTRY_ECDSA_KEY_PASSWORD",
+	}
 )
@@ -0,0 +1,213 @@
+package aggregator
+
+import (
+	"encoding/json"
+	"errors"
+	"net/http"
+	"sync"
+	"time"
+
+	"github.com/Layr-Labs/eigensdk-go/crypto/bls"
+	"github.com/Layr-Labs/eigensdk-go/logging"
+	"github.com/ethereum/go-ethereum/accounts"
+	"github.com/ethereum/go-ethereum/common"
+	"github.com/ethereum/go-ethereum/common/hexutil"
+	"github.com/ethereum/go-ethereum/crypto"
+)
+
+const (
+	// longest grace period an operator may announce, an intent exempts the operator from
+	// ejection until it exits
+	maxExitGrace = time.Hour
+	// intents issued longer ago, or further ahead because of clock skew, are rejected so an
+	// old signed intent cannot be replayed
+	maxExitIntentAge = 10 * time.Minute
+)
+
+var (
+	ExitIntentMalformed400       = errors.New("400. Malformed exit intent")
+	ExitIntentStale400           = errors.New("400. Exit intent issued too long ago or in the future")
+	ExitIntentGraceTooLong400    = errors.New("400. Exit intent grace period too long")
+	ExitIntentWrongChain400      = errors.New("400. Exit intent for another chain")
+	ExitIntentBadSignature400    = errors.New("400. Exit intent signature does not match its address")
+	ExitIntentUnknownOperator400 = errors.New("400. Exit intent operator id not registered to its address")
+	ExitIntentAlreadyUsed400     = errors.New("400. Operator already announced its exit")
+	ExitIntentOperatorLookup500  = errors.New("500. Failed to get operator address")
+)
+
+// ExitIntent is announced by an operator ahead of its deregistration, see `opt-out-avs
+// --exit-grace-secs` of the finalizer.
+type ExitIntent struct {
+	EthAddress common.Address `json:"eth_address"`
+	OperatorId common.Hash    `json:"operator_id"`
+	ChainId    uint64         `json:"chain_id"`
+	IssuedAt   uint64         `json:"issued_at"`
+	ExitAt     uint64         `json:"exit_at"` // unix time after which the operator deregisters
+}
+
+// SignedExitIntent carries the JSON encoded intent and its EIP-191 signature by the
+// operator ECDSA key.
+type SignedExitIntent struct {
+	Intent    string        `json:"intent"`
+	Signature hexutil.Bytes `json:"signature"`
+}
+
+// exitExemption is the single exit intent an operator is granted.
+type exitExemption struct {
+	intent ExitIntent
+	// the grace period ended and the deregistration was checked
+	settled bool
+}
+
+// ExitIntents keeps the intents announced by operators so the kicker does not eject
+// operators on their way out. Each operator is exempted once, until its announced exit: an
+// intent cannot be renewed and an operator still registered past its exit is ejected as any
+// other.
+type ExitIntents struct {
+	logger          logging.Logger
+	chainId         uint64
+	operatorAddress func(bls.OperatorId) (common.Address, error)
+	isRegistered    func(bls.OperatorId) (bool, error)
+	exemptions      map[bls.OperatorId]*exitExemption
+	mu              sync.RWMutex
+}
+
+func NewExitIntents(logger logging.Logger, chainId uint64, operatorAddress func(bls.OperatorId) (common.Address, error), isRegistered func(bls.OperatorId) (bool, error)) *ExitIntents {
+	return &ExitIntents{
+		logger:          logger,
+		chainId:         chainId,
+		operatorAddress: operatorAddress,
+		isRegistered:    isRegistered,
+		exemptions:      make(map[bls.OperatorId]*exitExemption),
+	}
+}
+
+// Record verifies that the intent is fresh, announces an exit within the longest grace
+// period, is signed by its address, that its operator id belongs to that address and that the
+// operator did not announce an exit before keeping it.
+func (e *ExitIntents) Record(signed *SignedExitIntent) error {
+	var intent ExitIntent
+	if err := json.Unmarshal([]byte(signed.Intent), &intent); err != nil {
+		return ExitIntentMalformed400
+	}
+	if intent.ExitAt < intent.IssuedAt {
+		return ExitIntentMalformed400
+	}
+	now := time.Now()
+	issuedAt := time.Unix(int64(intent.IssuedAt), 0)
+	if now.Sub(issuedAt) > maxExitIntentAge || issuedAt.Sub(now) > maxExitIntentAge {
+		return ExitIntentStale400
+	}
+	if time.Unix(int64(intent.ExitAt), 0).Sub(now) > maxExitGrace {
+		return ExitIntentGraceTooLong400
+	}
+	if intent.ChainId != e.chainId {
+		return ExitIntentWrongChain400
+	}
+	signer, err := recoverSigner([]byte(signed.Intent), signed.Signature)
+	if err != nil || signer != intent.EthAddress {
+		return ExitIntentBadSignature400
+	}
+	operatorId := bls.OperatorId(intent.OperatorId)
+	registered, err := e.operatorAddress(operatorId)
+	if err != nil {
+		e.logger.Error("Cannot get operator address", "operatorId", intent.OperatorId, "err", err)
+		return ExitIntentOperatorLookup500
+	}
+	if registered != intent.EthAddress {
+		return ExitIntentUnknownOperator400
+	}
+
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if _, ok := e.exemptions[operatorId]; ok {
+		return ExitIntentAlreadyUsed400
+	}
+	e.exemptions[operatorId] = &exitExemption{intent: intent}
+	e.logger.Info("Operator announced its exit", "operator", intent.EthAddress, "operatorId", intent.OperatorId, "exitAt", time.Unix(int64(intent.ExitAt), 0))
+	return nil
+}
+
+// IsExiting tells whether the operator announced an exit which is still ahead.
+func (e *ExitIntents) IsExiting(operatorId bls.OperatorId) bool {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+	exemption, ok := e.exemptions[operatorId]
+	return ok && time.Now().Before(exemptionEnd(exemption.intent))
+}
+
+// Exiting returns the operators whose announced exit is still ahead. The operators past
+// their exit are checked to have deregistered once, their exemption is kept so that they
+// cannot announce another exit.
+func (e *ExitIntents) Exiting() []ExitIntent {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	exiting := []ExitIntent{}
+	now := time.Now()
+	for _, exemption := range e.exemptions {
+		intent := exemption.intent
+		if now.Before(exemptionEnd(intent)) {
+			exiting = append(exiting, intent)
+			continue
+		}
+		if exemption.settled {
+			continue
+		}
+		registered, err := e.isRegistered(bls.OperatorId(intent.OperatorId))
+		if err != nil {
+			e.logger.Error("Cannot check the deregistration of exiting operator", "operator", intent.EthAddress, "err", err)
+			continue
+		}
+		exemption.settled = true
+		if registered {
+			e.logger.Warn("Operator did not deregister within its grace period, it is ejected as any other", "operator", intent.EthAddress, "operatorId", intent.OperatorId)
+		} else {
+			e.logger.Info("Exiting operator deregistered", "operator", intent.EthAddress, "operatorId", intent.OperatorId)
+		}
+	}
+	return exiting
+}
+
+func exemptionEnd(intent ExitIntent) time.Time {
+	return time.Unix(int64(intent.ExitAt), 0)
+}
+
+func (agg *Aggregator) exitIntentHandler(w http.ResponseWriter, req *http.Request) {
+	if req.Method != http.MethodPost {
+		http.Error(w, "Method not supported", http.StatusMethodNotAllowed)
+		return
+	}
+
+	var signed SignedExitIntent
+	if err := json.NewDecoder(req.Body).Decode(&signed); err != nil {
+		http.Error(w, "Error parsing request body", http.StatusBadRequest)
+		return
+	}
+
+	if err := agg.exitIntents.Record(&signed); err != nil {
+		status := http.StatusBadRequest
+		if err == ExitIntentOperatorLookup500 {
+			status = http.StatusInternalServerError
+		}
+		http.Error(w, err.Error(), status)
+		return
+	}
+}
+
+// recoverSigner returns the address which signed the EIP-191 message `msg`.
+func recoverSigner(msg []byte, signature []byte) (common.Address, error) {
+	if len(signature) != crypto.SignatureLength {
+		return common.Address{}, errors.New("invalid signature length")
+	}
+	sig := make([]byte, crypto.SignatureLength)
+	copy(sig, signature)
+	// ethers signatures carry v as 27 or 28, go-ethereum expects 0 or 1
+	if sig[crypto.RecoveryIDOffset] >= 27 {
+		sig[crypto.RecoveryIDOffset] -= 27
+	}
+	pubkey, err := crypto.SigToPub(accounts.TextHash(msg), sig)
+	if err != nil {
+		return common.Address{}, err
+	}
+	return crypto.PubkeyToAddress(*pubkey), nil
+}
@@ -0,0 +1,82 @@
+package aggregator
+
+import (
+	"encoding/json"
+	"testing"
+	"time"
+
+	"github.com/Layr-Labs/eigensdk-go/crypto/bls"
+	sdklogging "github.com/Layr-Labs/eigensdk-go/logging"
+	"github.com/ethereum/go-ethereum/accounts"
+	"github.com/ethereum/go-ethereum/common"
+	"github.com/ethereum/go-ethereum/crypto"
+	"github.com/stretchr/testify/assert"
+)
+
+func TestRecordExitIntent(t *testing.T) {
+	key, err := crypto.GenerateKey()
+	assert.Nil(t, err)
+	address := crypto.PubkeyToAddress(key.PublicKey)
+	intents := NewExitIntents(sdklogging.NewNoopLogger(), 1, func(bls.OperatorId) (common.Address, error) {
+		return address, nil
+	}, func(bls.OperatorId) (bool, error) {
+		return true, nil
+	})
+	sign := func(issuedAt time.Time, grace time.Duration) *SignedExitIntent {
+		intent, err := json.Marshal(ExitIntent{
+			EthAddress: address,
+			OperatorId: MOCK_OPERATOR_ID,
+			ChainId:    1,
+			IssuedAt:   uint64(issuedAt.Unix()),
+			ExitAt:     uint64(issuedAt.Add(grace).Unix()),
+		})
+		assert.Nil(t, err)
+		signature, err := crypto.Sign(accounts.TextHash(intent), key)
+		assert.Nil(t, err)
+		return &SignedExitIntent{Intent: string(intent), Signature: signature}
+	}
+
+	now := time.Now()
+	assert.Equal(t, ExitIntentStale400, intents.Record(sign(now.Add(-time.Hour), time.Minute)))
+	assert.Equal(t, ExitIntentGraceTooLong400, intents.Record(sign(now, 365*24*time.Hour)))
+	assert.False(t, intents.IsExiting(MOCK_OPERATOR_ID))
+
+	assert.Nil(t, intents.Record(sign(now, time.Minute)))
+	assert.True(t, intents.IsExiting(MOCK_OPERATOR_ID))
+	assert.Len(t, intents.Exiting(), 1)
+}
+
+func TestExitIntentNotRenewed(t *testing.T) {
+	key, err := crypto.GenerateKey()
+	assert.Nil(t, err)
+	address := crypto.PubkeyToAddress(key.PublicKey)
+	intents := NewExitIntents(sdklogging.NewNoopLogger(), 1, func(bls.OperatorId) (common.Address, error) {
+		return address, nil
+	}, func(bls.OperatorId) (bool, error) {
+		return true, nil
+	})
+	sign := func(issuedAt time.Time, grace time.Duration) *SignedExitIntent {
+		intent, err := json.Marshal(ExitIntent{
+			EthAddress: address,
+			OperatorId: MOCK_OPERATOR_ID,
+			ChainId:    1,
+			IssuedAt:   uint64(issuedAt.Unix()),
+			ExitAt:     uint64(issuedAt.Add(grace).Unix()),
+		})
+		assert.Nil(t, err)
+		signature, err := crypto.Sign(accounts.TextHash(intent), key)
+		assert.Nil(t, err)
+		return &SignedExitIntent{Intent: string(intent), Signature: signature}
+	}
+
+	// the announced exit passed without a deregistration
+	now := time.Now()
+	assert.Nil(t, intents.Record(sign(now.Add(-5*time.Minute), time.Minute)))
+	assert.False(t, intents.IsExiting(MOCK_OPERATOR_ID))
+	assert.Empty(t, intents.Exiting())
+
+	// a renewed intent does not extend the exemption
+	assert.Equal(t, ExitIntentAlreadyUsed400, intents.Record(sign(now, maxExitGrace)))
+	assert.False(t, intents.IsExiting(MOCK_OPERATOR_ID))
+	assert.Empty(t, intents.Exiting())
+}
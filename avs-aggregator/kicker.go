@@ -17,14 +17,16 @@ type Kicker struct {
 	kickPeriod  uint32
 	blockPeriod uint32
 	ethRpc      chainio.EthRpc
+	exitIntents *ExitIntents
 }
 
-func NewKicker(logger logging.Logger, ethRpc chainio.EthRpc, kickPeriod uint32, blockPeriod uint32) (*Kicker, error) {
+func NewKicker(logger logging.Logger, ethRpc chainio.EthRpc, exitIntents *ExitIntents, kickPeriod uint32, blockPeriod uint32) (*Kicker, error) {
 	return &Kicker{
 		logger:      logger,
 		kickPeriod:  kickPeriod,
 		blockPeriod: blockPeriod,
 		ethRpc:      ethRpc,
+		exitIntents: exitIntents,
 	}, nil
 }
 
@@ -71,6 +73,11 @@ func (k *Kicker) CheckStateAndKick() error {
 	k.logger.Info("OAL check found list of pubkeys", "pubkeys", nonSigningOperatorPubKeys)
 	// fetch address and eject
 	for _, key := range nonSigningOperatorPubKeys {
+		// operators announcing their exit deregister themselves within their grace period
+		if k.exitIntents.IsExiting(key.GetOperatorID()) {
+			k.logger.Info("Not ejecting exiting operator", "operatorId", key.GetOperatorID())
+			continue
+		}
 		address, err := k.ethRpc.ElClients.AvsRegistryChainReader.GetOperatorAddress(&bind.CallOpts{}, key.GetOperatorID())
 		if err != nil {
 			k.logger.Error("Cannot get operator address", "operatorId", key.GetOperatorID(), "err", err)
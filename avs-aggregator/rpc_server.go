@@ -4,6 +4,7 @@ import (
 	"context"
 	"encoding/json"
 	"errors"
+	"io"
 	"net/http"
 
 	taskmanager "github.com/mangata-finance/eigen-layer-monorepo/avs-aggregator/bindings/MangataTaskManager"
@@ -21,10 +22,13 @@ var (
 	UnknownErrorWhileVerifyingSignature400   = errors.New("400. Failed to verify signature")
 	SignatureVerificationFailed400           = errors.New("400. Signature verification failed")
 	CallToGetCheckSignaturesIndicesFailed500 = errors.New("500. Failed to get check signatures indices")
+	NonCanonicalSignature400                 = errors.New("400. BLS signature coordinates are not canonically encoded")
+	InvalidSignaturePoint400                 = errors.New("400. BLS signature is not a point of the G1 subgroup")
 )
 
 func (agg *Aggregator) startServer(ctx context.Context) error {
 	http.HandleFunc("/", agg.handler)
+	http.HandleFunc("/exit-intent", agg.exitIntentHandler)
 	err := http.ListenAndServe(agg.serverIpPortAddr, nil)
 	if err != nil {
 		agg.logger.Fatal("ListenAndServe", "err", err)
@@ -45,11 +49,20 @@ func (agg *Aggregator) handler(w http.ResponseWriter, req *http.Request) {
 		return
 	}
 
+	body, err := io.ReadAll(req.Body)
+	if err != nil {
+		http.Error(w, "Error reading request body", http.StatusBadRequest)
+		return
+	}
 	var response SignedTaskResponse
-	if err := json.NewDecoder(req.Body).Decode(&response); err != nil {
+	if err := json.Unmarshal(body, &response); err != nil {
 		http.Error(w, "Error parsing request body", http.StatusBadRequest)
 		return
 	}
+	if err := checkCanonicalSignature(body); err != nil {
+		http.Error(w, err.Error(), http.StatusBadRequest)
+		return
+	}
 
 	if err := agg.ProcessSignedTaskResponse(&response, nil); err != nil {
 		var status int
@@ -85,6 +98,10 @@ func (agg *Aggregator) ProcessSignedTaskResponse(signedTaskResponse *SignedTaskR
 		agg.logger.Error("Operator not registered", "err", err)
 		return OperatorNotRegistered400
 	}
+	if err := checkSignaturePoint(&signedTaskResponse.BlsSignature); err != nil {
+		agg.logger.Error("Invalid signature point", "operatorId", signedTaskResponse.OperatorId)
+		return err
+	}
 	agg.taskResponsesMu.Lock()
 	if _, ok := agg.taskResponses[taskIndex]; !ok {
 		agg.taskResponses[taskIndex] = make(map[sdktypes.TaskResponseDigest]taskmanager.IMangataTaskManagerTaskResponse)
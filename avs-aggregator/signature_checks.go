@@ -0,0 +1,55 @@
+package aggregator
+
+import (
+	"encoding/json"
+	"math/big"
+	"strings"
+
+	"github.com/Layr-Labs/eigensdk-go/crypto/bls"
+)
+
+// bn254FieldModulus is the modulus of the BN254 base field, a coordinate is canonically
+// encoded below it
+var bn254FieldModulus, _ = new(big.Int).SetString(
+	"21888242871839275222246405745257275088696311157297823662689037894645226208583", 10)
+
+// rawSignedTaskResponse keeps the signature coordinates as sent, the field elements of the
+// decoded signature are already reduced modulo the field modulus
+type rawSignedTaskResponse struct {
+	BlsSignature struct {
+		G1Point struct {
+			X json.RawMessage
+			Y json.RawMessage
+		} `json:"g1_point"`
+	}
+}
+
+// checkCanonicalSignature rejects a signature whose coordinates are encoded at or above the
+// field modulus, x and x + p would otherwise both decode to the same point so the same
+// signature would have several encodings
+func checkCanonicalSignature(body []byte) error {
+	var raw rawSignedTaskResponse
+	if err := json.Unmarshal(body, &raw); err != nil {
+		return NonCanonicalSignature400
+	}
+	for _, coordinate := range []json.RawMessage{raw.BlsSignature.G1Point.X, raw.BlsSignature.G1Point.Y} {
+		value, ok := new(big.Int).SetString(strings.Trim(string(coordinate), `"`), 10)
+		if !ok || value.Sign() < 0 || value.Cmp(bn254FieldModulus) >= 0 {
+			return NonCanonicalSignature400
+		}
+	}
+	return nil
+}
+
+// checkSignaturePoint rejects a signature which is missing, the point at infinity, off the
+// curve or outside the prime order subgroup, any of which can make the aggregate signature
+// check pass without every signer committing to the message
+func checkSignaturePoint(signature *bls.Signature) error {
+	if signature == nil || signature.G1Point == nil || signature.G1Affine == nil {
+		return InvalidSignaturePoint400
+	}
+	if signature.IsInfinity() || !signature.IsOnCurve() || !signature.IsInSubGroup() {
+		return InvalidSignaturePoint400
+	}
+	return nil
+}
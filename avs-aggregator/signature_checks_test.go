@@ -0,0 +1,64 @@
+package aggregator
+
+import (
+	"fmt"
+	"math/big"
+	"testing"
+
+	"github.com/consensys/gnark-crypto/ecc/bn254"
+	"github.com/consensys/gnark-crypto/ecc/bn254/fp"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/Layr-Labs/eigensdk-go/crypto/bls"
+)
+
+func signatureBody(x, y string) []byte {
+	return []byte(fmt.Sprintf(`{"BlsSignature":{"g1_point":{"X":"%s","Y":"%s"}}}`, x, y))
+}
+
+func TestCheckSignature(t *testing.T) {
+	privateKey, err := bls.NewPrivateKey("42")
+	assert.Nil(t, err)
+	keypair := bls.NewKeyPair(privateKey)
+	signature := keypair.SignMessage([32]byte{1})
+	x, y := signature.X.String(), signature.Y.String()
+
+	assert.Nil(t, checkCanonicalSignature(signatureBody(x, y)))
+	assert.Nil(t, checkSignaturePoint(signature))
+
+	// x + p decodes to the same point
+	malleated := new(big.Int).Add(signature.X.BigInt(new(big.Int)), bn254FieldModulus)
+	assert.Equal(t, NonCanonicalSignature400, checkCanonicalSignature(signatureBody(malleated.String(), y)))
+	assert.Equal(t, NonCanonicalSignature400, checkCanonicalSignature(signatureBody("-1", y)))
+
+	assert.Equal(t, InvalidSignaturePoint400, checkSignaturePoint(&bls.Signature{}))
+	assert.Equal(t, InvalidSignaturePoint400, checkSignaturePoint(bls.NewZeroSignature()))
+	offCurve := bls.NewG1Point(big.NewInt(1), big.NewInt(1))
+	assert.Equal(t, InvalidSignaturePoint400, checkSignaturePoint(&bls.Signature{G1Point: offCurve}))
+}
+
+func FuzzCheckSignature(f *testing.F) {
+	_, _, g1, _ := bn254.Generators()
+	f.Add(g1.X.String(), g1.Y.String())
+	f.Add(bn254FieldModulus.String(), "0")
+	f.Add("0", "0")
+	f.Fuzz(func(t *testing.T, x, y string) {
+		// arbitrary input never panics and only a canonical point of G1 passes both checks
+		if checkCanonicalSignature(signatureBody(x, y)) != nil {
+			return
+		}
+		var px, py fp.Element
+		if _, err := px.SetString(x); err != nil {
+			return
+		}
+		if _, err := py.SetString(y); err != nil {
+			return
+		}
+		point := bls.Signature{G1Point: &bls.G1Point{G1Affine: &bn254.G1Affine{X: px, Y: py}}}
+		if checkSignaturePoint(&point) == nil {
+			assert.True(t, point.IsOnCurve())
+			assert.True(t, point.IsInSubGroup())
+			assert.False(t, point.IsInfinity())
+		}
+	})
+}
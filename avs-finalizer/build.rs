@@ -0,0 +1,60 @@
+//! Embeds what identifies a build of the node, reported by `version`.
+//!
+//! The git commit is taken from `AVS_GIT_COMMIT` as given to the build, the source tree has
+//! no repository in release images. Otherwise it is read from the repository, suffixed with
+//! `-dirty` when the tree has uncommitted changes.
+
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-env-changed=AVS_GIT_COMMIT");
+    for git in ["../.git/HEAD", "../.git/index"] {
+        if std::path::Path::new(git).exists() {
+            println!("cargo:rerun-if-changed={}", git);
+        }
+    }
+
+    if let Ok(lock) = std::fs::read("Cargo.lock") {
+        println!(
+            "cargo:rustc-env=AVS_CARGO_LOCK_SHA256={:x}",
+            Sha256::digest(lock)
+        );
+    }
+    if std::env::var("AVS_GIT_COMMIT").is_err() {
+        if let Some(commit) = git_commit() {
+            println!("cargo:rustc-env=AVS_GIT_COMMIT={}", commit);
+        }
+    }
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    if let Ok(output) = Command::new(rustc).arg("--version").output() {
+        let version = String::from_utf8_lossy(&output.stdout);
+        println!("cargo:rustc-env=AVS_RUSTC_VERSION={}", version.trim());
+    }
+    for var in ["TARGET", "PROFILE"] {
+        if let Ok(value) = std::env::var(var) {
+            println!("cargo:rustc-env=AVS_BUILD_{}={}", var, value);
+        }
+    }
+}
+
+/// HEAD of the repository the node is built in, `None` outside of one.
+fn git_commit() -> Option<String> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    };
+    let commit = git(&["rev-parse", "HEAD"])?;
+    let dirty = !git(&["status", "--porcelain", "--untracked-files=no"])?.is_empty();
+    Some(if dirty {
+        format!("{}-dirty", commit)
+    } else {
+        commit
+    })
+}
@@ -0,0 +1,28 @@
+#!/usr/bin/env bash
+# Compares the mean time of each benchmark of the last `cargo bench -p avs-operator-sdk` run
+# with its threshold in thresholds.json (in nanoseconds), fails when one is over it or
+# missing. Run from avs-finalizer, or set CRITERION_DIR to the criterion output directory.
+set -euo pipefail
+
+thresholds="$(dirname "$0")/thresholds.json"
+criterion="${CRITERION_DIR:-target/criterion}"
+status=0
+
+for id in $(jq -r 'keys[]' "$thresholds"); do
+  max=$(jq -r --arg id "$id" '.[$id]' "$thresholds")
+  estimates="$criterion/$id/new/estimates.json"
+  if [ ! -f "$estimates" ]; then
+    echo "MISSING $id: no $estimates"
+    status=1
+    continue
+  fi
+  mean=$(jq -r '.mean.point_estimate | floor' "$estimates")
+  if [ "$mean" -gt "$max" ]; then
+    echo "SLOWER  $id: ${mean} ns, threshold ${max} ns"
+    status=1
+  else
+    echo "ok      $id: ${mean} ns, threshold ${max} ns"
+  fi
+done
+
+exit $status
@@ -0,0 +1,104 @@
+//! Benchmarks of the work done for every task within its response window. Run with
+//! `cargo bench -p avs-operator-sdk`, then `sdk/benches/check_thresholds.sh` fails when a
+//! mean exceeds its threshold in `sdk/benches/thresholds.json`.
+
+use std::hint::black_box;
+
+use avs_operator_sdk::{
+    bindings::shared_types::TaskResponse,
+    crypto::{bn254::BlsKeypair, keystore::EncodedKeystore},
+    proofs::{keccak_reader, MerkleCommitment},
+    response::{
+        decode_bls_task_response, encode_task_response, task_response_digest, verify_task_response,
+    },
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Signatures aggregated per message, about the size of a quorum.
+const SIGNERS: [usize; 2] = [10, 100];
+/// Storage proofs of large blocks reach a few megabytes.
+const PROOF_SIZE: usize = 4 << 20;
+
+fn keypair() -> BlsKeypair {
+    EncodedKeystore::random()
+        .unwrap()
+        .into_bls_keypair()
+        .unwrap()
+}
+
+fn task_response() -> TaskResponse {
+    TaskResponse {
+        reference_task_index: 7,
+        block_hash: [0xaa; 32],
+        storage_proof_hash: [0xbb; 32],
+    }
+}
+
+fn bls(c: &mut Criterion) {
+    let keypair = keypair();
+    let msg = task_response_digest(&task_response());
+    let signature = keypair.sign(msg.as_bytes()).unwrap();
+
+    let mut group = c.benchmark_group("bls");
+    group.bench_function("sign", |b| {
+        b.iter(|| keypair.sign(black_box(msg.as_bytes())).unwrap())
+    });
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            BlsKeypair::verify(keypair.public_g2(), black_box(msg.as_bytes()), signature).unwrap()
+        })
+    });
+    for signers in SIGNERS {
+        let signatures = vec![signature; signers];
+        let keys = vec![keypair.public_g2(); signers];
+        group.bench_with_input(
+            BenchmarkId::new("aggregate", signers),
+            &signatures,
+            |b, signatures| b.iter(|| BlsKeypair::aggregate(black_box(signatures))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("aggregate_public_g2", signers),
+            &keys,
+            |b, keys| b.iter(|| BlsKeypair::aggregate_public_g2(black_box(keys))),
+        );
+    }
+    group.finish();
+}
+
+fn hashing(c: &mut Criterion) {
+    let response = task_response();
+    let proof = vec![0x5a_u8; PROOF_SIZE];
+
+    let mut group = c.benchmark_group("hashing");
+    group.bench_function("task_response_digest", |b| {
+        b.iter(|| task_response_digest(black_box(&response)))
+    });
+    group.throughput(Throughput::Bytes(PROOF_SIZE as u64));
+    group.bench_function("storage_proof_keccak", |b| {
+        b.iter(|| keccak_reader(black_box(proof.as_slice())).unwrap())
+    });
+    group.bench_function("storage_proof_merkle", |b| {
+        b.iter(|| MerkleCommitment::from_reader(black_box(proof.as_slice()), 64 << 10).unwrap())
+    });
+    group.finish();
+}
+
+fn responses(c: &mut Criterion) {
+    let keypair = keypair();
+    let json = encode_task_response(task_response(), &keypair).unwrap();
+
+    let mut group = c.benchmark_group("responses");
+    group.bench_function("encode", |b| {
+        b.iter(|| encode_task_response(task_response(), &keypair).unwrap())
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| decode_bls_task_response(black_box(&json)).unwrap())
+    });
+    group.bench_function("verify", |b| {
+        b.iter(|| verify_task_response(black_box(&json), keypair.public_g2()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bls, hashing, responses);
+criterion_main!(benches);
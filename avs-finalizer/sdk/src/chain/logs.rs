@@ -0,0 +1,95 @@
+use std::borrow::Borrow;
+
+use ethers::{
+    contract::{EthLogDecode, Event, LogMeta},
+    providers::Middleware,
+};
+
+use tracing::{debug, warn};
+
+const INITIAL_RANGE: u64 = 10_000;
+const MAX_RANGE: u64 = 100_000;
+
+/// Provider errors signalling the requested range was too large rather than a failed request.
+const RANGE_LIMIT_ERRORS: &[&str] = &[
+    "query returned more than",
+    "response size exceeded",
+    "block range",
+    "range is too large",
+    "too many",
+    "limit exceeded",
+    "timeout",
+    "timed out",
+];
+
+/// Backfills the logs of `event` from `from_block` to `to_block` with chunked `eth_getLogs` calls. The chunk range halves whenever the provider rejects it for being too large or too
+/// slow and doubles back after successful calls, up to `MAX_RANGE` blocks.
+pub async fn query_chunked<B, M, D>(
+    event: Event<B, M, D>,
+    from_block: u64,
+    to_block: u64,
+) -> eyre::Result<Vec<D>>
+where
+    B: Borrow<M>,
+    M: Middleware + 'static,
+    D: EthLogDecode,
+{
+    Ok(query_chunked_with_meta(event, from_block, to_block)
+        .await?
+        .into_iter()
+        .map(|(log, _)| log)
+        .collect())
+}
+
+/// Same as [`query_chunked`], along with the block and transaction of every log.
+pub async fn query_chunked_with_meta<B, M, D>(
+    mut event: Event<B, M, D>,
+    from_block: u64,
+    to_block: u64,
+) -> eyre::Result<Vec<(D, LogMeta)>>
+where
+    B: Borrow<M>,
+    M: Middleware + 'static,
+    D: EthLogDecode,
+{
+    let mut logs = vec![];
+    let mut start = from_block;
+    let mut range = INITIAL_RANGE;
+    while start <= to_block {
+        let end = to_block.min(start.saturating_add(range - 1));
+        event.filter = event.filter.clone().from_block(start).to_block(end);
+        match event.query_with_meta().await {
+            Ok(chunk) => {
+                debug!("Fetched {} logs of blocks {}..={}", chunk.len(), start, end);
+                logs.extend(chunk);
+                start = end + 1;
+                range = (range * 2).min(MAX_RANGE);
+            }
+            Err(e) if range > 1 && is_range_limit(&e.to_string()) => {
+                range /= 2;
+                warn!(
+                    "getLogs of blocks {}..={} failed, retrying with {} blocks: {}",
+                    start, end, range, e
+                );
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(logs)
+}
+
+fn is_range_limit(error: &str) -> bool {
+    let error = error.to_lowercase();
+    RANGE_LIMIT_ERRORS
+        .iter()
+        .any(|needle| error.contains(needle))
+}
+
+#[test]
+fn test_is_range_limit() {
+    assert!(is_range_limit(
+        "(code: -32005, message: query returned more than 10000 results, data: None)"
+    ));
+    assert!(is_range_limit("Request Timeout"));
+    assert!(!is_range_limit("execution reverted"));
+}
@@ -0,0 +1,4 @@
+//! Transport and log querying helpers shared by clients of the AVS contracts.
+
+pub mod logs;
+pub mod transport;
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use ethers::providers::{Http, Ipc, JsonRpcClient, ProviderError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Debug, str::FromStr};
+
+const IPC_SCHEME: &str = "ipc://";
+
+/// Ethereum JSON-RPC transport selected by the url scheme, `ipc:///path/geth.ipc` connects to
+/// the IPC socket of a colocated execution client and anything else is treated as HTTP.
+#[derive(Debug, Clone)]
+pub enum EthTransport {
+    Http(Http),
+    Ipc(Ipc),
+}
+
+impl EthTransport {
+    pub async fn connect(url: &str) -> eyre::Result<Self> {
+        Ok(match url.strip_prefix(IPC_SCHEME) {
+            Some(path) => Self::Ipc(Ipc::connect(path).await?),
+            None => Self::Http(Http::from_str(url)?),
+        })
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for EthTransport {
+    type Error = ProviderError;
+
+    async fn request<P, R>(&self, method: &str, params: P) -> Result<R, Self::Error>
+    where
+        P: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            Self::Http(http) => Ok(http.request(method, params).await?),
+            Self::Ipc(ipc) => Ok(ipc.request(method, params).await?),
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use ark_bn254::{Fq, Fr, G1Affine, G2Affine};
-use ark_ec::{AffineRepr, CurveGroup};
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
 use ark_ff::{
     fields::{Field, PrimeField},
     BigInt, BigInteger, One,
@@ -9,10 +9,12 @@ use ethers::{
     core::types::{H256, U256},
     types::Address,
 };
-use sp_runtime::traits::{Hash, Keccak256};
+
+use super::keccak256;
 
 pub type PrivateKey = Fr;
 pub type PublicKey = G1Affine;
+pub type PublicKeyG2 = G2Affine;
 pub type BlsSignature = G1Affine;
 pub type OperatorId = H256;
 
@@ -61,8 +63,12 @@ impl BlsKeypair {
     }
 
     pub fn operator_id(&self) -> OperatorId {
-        let xy = self.public.xy().expect("should have public");
-        Keccak256::hash(
+        Self::operator_id_of(self.public)
+    }
+
+    pub fn operator_id_of(public: PublicKey) -> OperatorId {
+        let xy = public.xy().expect("should have public");
+        keccak256(
             [
                 xy.0.into_bigint().to_bytes_be(),
                 xy.1.into_bigint().to_bytes_be(),
@@ -86,7 +92,7 @@ impl BlsKeypair {
             b"EigenLayer_BN254_Pubkey_Registration",
         ]
         .concat();
-        let hash = Keccak256::hash(&bytes);
+        let hash = keccak256(&bytes);
         self.sign(hash.as_bytes())
     }
 
@@ -96,6 +102,35 @@ impl BlsKeypair {
 
         Ok(sig.into_affine())
     }
+    /// Checks `signature` of `msg` against the G2 public key,
+    /// e(signature, G2) == e(H(msg), public_g2)
+    pub fn verify(public_g2: G2Affine, msg: &[u8], signature: BlsSignature) -> eyre::Result<bool> {
+        let h = Self::map_to_curve(msg)?;
+        Ok(Bn254::pairing(signature, G2Affine::generator()) == Bn254::pairing(h, public_g2))
+    }
+
+    /// Checks that the G1 and G2 public keys share the same private key,
+    /// e(public_g1, G2) == e(G1, public_g2)
+    pub fn is_same_key(public: PublicKey, public_g2: G2Affine) -> bool {
+        Bn254::pairing(public, G2Affine::generator())
+            == Bn254::pairing(G1Affine::generator(), public_g2)
+    }
+
+    /// Sum of the signatures of several signers of the same message, which verifies against
+    /// the sum of their G2 public keys, see [`BlsKeypair::aggregate_public_g2`].
+    pub fn aggregate(signatures: &[BlsSignature]) -> BlsSignature {
+        signatures
+            .iter()
+            .fold(G1Affine::zero().into_group(), |sum, s| sum + s)
+            .into_affine()
+    }
+
+    pub fn aggregate_public_g2(keys: &[PublicKeyG2]) -> PublicKeyG2 {
+        keys.iter()
+            .fold(G2Affine::zero().into_group(), |sum, k| sum + k)
+            .into_affine()
+    }
+
     /// implements BN254 map to curve from
     /// contracts/lib/eigenlayer-middleware/lib/eigenlayer-contracts/src/contracts/libraries/BN254.sol
     /// for a hash, maps to a point on curve
@@ -132,3 +167,25 @@ fn test_map_parity() {
     let r = BlsKeypair::map_to_curve(msg).unwrap();
     assert_eq!(r, expected);
 }
+
+#[test]
+fn test_sign_verify() {
+    use crate::crypto::keystore::EncodedKeystore;
+    let keypair = EncodedKeystore::random()
+        .unwrap()
+        .into_bls_keypair()
+        .unwrap();
+    let sig = keypair.sign(b"message").unwrap();
+    assert!(BlsKeypair::verify(keypair.public_g2(), b"message", sig).unwrap());
+    assert!(!BlsKeypair::verify(keypair.public_g2(), b"other", sig).unwrap());
+    assert!(BlsKeypair::is_same_key(keypair.public, keypair.public_g2()));
+
+    let other = EncodedKeystore::random()
+        .unwrap()
+        .into_bls_keypair()
+        .unwrap();
+    let aggregate = BlsKeypair::aggregate(&[sig, other.sign(b"message").unwrap()]);
+    let apk = BlsKeypair::aggregate_public_g2(&[keypair.public_g2(), other.public_g2()]);
+    assert!(BlsKeypair::verify(apk, b"message", aggregate).unwrap());
+    assert!(!BlsKeypair::verify(keypair.public_g2(), b"message", aggregate).unwrap());
+}
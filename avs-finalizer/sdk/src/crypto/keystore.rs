@@ -3,16 +3,18 @@ use aes::{
     Aes128,
 };
 use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::{fields::PrimeField, Field};
+use ark_ff::{fields::PrimeField, BigInteger, Field};
 use eth_keystore::{CryptoJson, KdfparamsType};
 use ethers::{core::rand::thread_rng, signers::LocalWallet};
 use eyre::{eyre, Ok, Report};
 use scrypt::{password_hash::rand_core::RngCore, scrypt, Params as ScryptParams};
 use serde::{Deserialize, Serialize};
-use sp_runtime::traits::{Hash, Keccak256};
 use std::{fmt::Debug, fs::File, io::Read, path::Path};
 
-use crate::crypto::bn254::{BlsKeypair, PrivateKey, PublicKey};
+use crate::crypto::{
+    bn254::{BlsKeypair, PrivateKey, PublicKey},
+    keccak256,
+};
 
 #[derive(Default)]
 pub struct EncodedKeystore {
@@ -52,14 +54,7 @@ impl EncodedKeystore {
             let secret = decrypt_key(keystore, self.password.unwrap_or_default())?;
             PrivateKey::from_be_bytes_mod_order(&secret)
         } else {
-            let rnd = &mut [0_u8; 32];
-            let mut rng = thread_rng();
-            loop {
-                rng.fill_bytes(rnd);
-                if let Some(key) = PrivateKey::from_random_bytes(rnd) {
-                    break key;
-                }
-            }
+            random_private_key()
         };
         let p = PublicKey::generator() * fr;
 
@@ -79,6 +74,35 @@ impl EncodedKeystore {
     }
 }
 
+/// Writes `keypair` as an encrypted keystore file `name` in `dir`, readable by
+/// [`EncodedKeystore::from_path`].
+pub fn write_bls_keystore(
+    dir: &Path,
+    name: &str,
+    keypair: &BlsKeypair,
+    password: &str,
+) -> eyre::Result<()> {
+    eth_keystore::encrypt_key(
+        dir,
+        &mut thread_rng(),
+        keypair.private.into_bigint().to_bytes_be(),
+        password,
+        Some(name),
+    )?;
+    Ok(())
+}
+
+pub(crate) fn random_private_key() -> PrivateKey {
+    let rnd = &mut [0_u8; 32];
+    let mut rng = thread_rng();
+    loop {
+        rng.fill_bytes(rnd);
+        if let Some(key) = PrivateKey::from_random_bytes(rnd) {
+            break key;
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct Keystore {
     crypto: CryptoJson,
@@ -109,7 +133,7 @@ where
     };
 
     // Derive the MAC from the derived key and ciphertext.
-    let derived_mac = Keccak256::hash(
+    let derived_mac = keccak256(
         [&key[16..32], &keystore.crypto.ciphertext]
             .concat()
             .as_ref(),
@@ -0,0 +1,121 @@
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use bindings::shared_types::{G1Point, G2Point};
+use ethers::core::types::{H256, U256};
+
+pub mod bn254;
+pub mod keystore;
+pub mod threshold;
+
+/// Keccak-256 hash of `data`, as computed by the EVM.
+pub fn keccak256(data: &[u8]) -> H256 {
+    H256(ethers::utils::keccak256(data))
+}
+
+/// Conversions between arkworks BN254 points and their Solidity ABI representation.
+pub struct EthConvert;
+impl EthConvert {
+    pub fn to_u256(p: &Fq) -> U256 {
+        U256::from_little_endian(&p.into_bigint().to_bytes_le())
+    }
+
+    pub fn to_g1(xy: G1Affine) -> Option<G1Point> {
+        xy.xy().map(|(x, y)| G1Point {
+            x: EthConvert::to_u256(x),
+            y: EthConvert::to_u256(y),
+        })
+    }
+
+    pub fn to_g2(xy: G2Affine) -> Option<G2Point> {
+        xy.xy().map(|(x, y)| G2Point {
+            x: [EthConvert::to_u256(&x.c1), EthConvert::to_u256(&x.c0)],
+            y: [EthConvert::to_u256(&y.c1), EthConvert::to_u256(&y.c0)],
+        })
+    }
+
+    /// Field element of `v` reduced modulo the field modulus, so `v` and `v + p` give the
+    /// same element. Use [`EthConvert::from_u256_canonical`] for untrusted input.
+    pub fn from_u256(v: U256) -> Fq {
+        let mut bytes = [0_u8; 32];
+        v.to_little_endian(&mut bytes);
+        Fq::from_le_bytes_mod_order(&bytes)
+    }
+
+    /// Field element of `v`, `None` unless `v` is below the field modulus.
+    pub fn from_u256_canonical(v: U256) -> Option<Fq> {
+        let fq = EthConvert::from_u256(v);
+        (EthConvert::to_u256(&fq) == v).then_some(fq)
+    }
+
+    /// Inverse of [`EthConvert::to_g1`], `None` if a coordinate is not canonically encoded or
+    /// the point is not on the curve or not in the prime order subgroup.
+    pub fn from_g1(p: &G1Point) -> Option<G1Affine> {
+        let point = G1Affine::new_unchecked(
+            EthConvert::from_u256_canonical(p.x)?,
+            EthConvert::from_u256_canonical(p.y)?,
+        );
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+
+    /// Inverse of [`EthConvert::to_g2`], `None` if a coordinate is not canonically encoded or
+    /// the point is not on the curve or not in the prime order subgroup.
+    pub fn from_g2(p: &G2Point) -> Option<G2Affine> {
+        let fq = EthConvert::from_u256_canonical;
+        let point = G2Affine::new_unchecked(
+            Fq2::new(fq(p.x[1])?, fq(p.x[0])?),
+            Fq2::new(fq(p.y[1])?, fq(p.y[0])?),
+        );
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+}
+
+#[test]
+fn test_point_decoding_fuzz() {
+    use ark_ec::CurveGroup;
+    use ark_ff::UniformRand;
+    use ethers::core::rand::{thread_rng, Rng};
+
+    let rng = &mut thread_rng();
+    let modulus = U256::from_little_endian(&Fq::MODULUS.to_bytes_le());
+    for _ in 0..1_000 {
+        // arbitrary coordinates never panic and only canonical ones decode
+        let v = U256(rng.gen());
+        assert_eq!(EthConvert::from_u256_canonical(v).is_some(), v < modulus);
+        let g1 = G1Point {
+            x: U256(rng.gen()),
+            y: U256(rng.gen()),
+        };
+        assert!(EthConvert::from_g1(&g1).is_none());
+    }
+    for _ in 0..100 {
+        let g1 = (G1Affine::generator() * ark_bn254::Fr::rand(rng)).into_affine();
+        let encoded = EthConvert::to_g1(g1).unwrap();
+        assert_eq!(EthConvert::from_g1(&encoded), Some(g1));
+        // x + p reduces to the same point but is another encoding of it
+        if let Some(x) = encoded.x.checked_add(modulus) {
+            assert!(EthConvert::from_g1(&G1Point { x, ..encoded }).is_none());
+        }
+
+        let g2 = (G2Affine::generator() * ark_bn254::Fr::rand(rng)).into_affine();
+        let encoded = EthConvert::to_g2(g2).unwrap();
+        assert_eq!(EthConvert::from_g2(&encoded), Some(g2));
+        let mut malleated = encoded.clone();
+        malleated.y[1] = malleated.y[1].saturating_add(modulus);
+        assert!(EthConvert::from_g2(&malleated).is_none());
+
+        // most points of the G2 curve are outside its prime order subgroup
+        if let Some(point) = G2Affine::get_point_from_x_unchecked(Fq2::rand(rng), rng.gen()) {
+            let encoded = EthConvert::to_g2(point).unwrap();
+            assert_eq!(
+                EthConvert::from_g2(&encoded).is_some(),
+                point.is_in_correct_subgroup_assuming_on_curve()
+            );
+        }
+    }
+    assert!(EthConvert::from_g1(&G1Point {
+        x: U256::zero(),
+        y: U256::zero()
+    })
+    .is_none());
+}
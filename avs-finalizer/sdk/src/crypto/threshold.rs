@@ -0,0 +1,203 @@
+//! t-of-n threshold BLS signatures. The operator private key is split with Shamir secret
+//! sharing into `n` shares, any `t` partial signatures of a message combine into the signature
+//! of the full key, so no single machine ever holds the operator key.
+
+use ark_bn254::{Fr, G1Projective, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, One, Zero};
+use bindings::shared_types::{G1Point, G2Point};
+use eyre::{eyre, OptionExt};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    bn254::{BlsKeypair, BlsSignature, OperatorId, PrivateKey, PublicKey},
+    keystore::random_private_key,
+    EthConvert,
+};
+
+/// Public parts of a split key, needed to check partial signatures and the combined one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdPublicKey {
+    pub threshold: u32,
+    pub public_g1: G1Point,
+    pub public_g2: G2Point,
+    pub shares: Vec<PublicShare>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicShare {
+    /// Point the share polynomial was evaluated at, starting from 1
+    pub index: u32,
+    pub public_g2: G2Point,
+}
+
+/// Signature of a message with a single key share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub index: u32,
+    pub signature: G1Point,
+}
+
+/// Splits `keypair` into `shares` keys, any `threshold` of which can sign for it.
+/// Returns the public key and the `(index, share)` pairs.
+pub fn split(
+    keypair: &BlsKeypair,
+    threshold: u32,
+    shares: u32,
+) -> eyre::Result<(ThresholdPublicKey, Vec<(u32, BlsKeypair)>)> {
+    if threshold == 0 || threshold > shares {
+        return Err(eyre!(
+            "threshold must be between 1 and the number of shares ({})",
+            shares
+        ));
+    }
+    // the key is the value at 0 of a random polynomial of degree threshold - 1
+    let coefficients: Vec<PrivateKey> = std::iter::once(keypair.private)
+        .chain((1..threshold).map(|_| random_private_key()))
+        .collect();
+
+    let mut public_shares = vec![];
+    let mut keys = vec![];
+    for index in 1..=shares {
+        let x = Fr::from(index);
+        let private = coefficients
+            .iter()
+            .rev()
+            .fold(Fr::zero(), |acc, coefficient| acc * x + coefficient);
+        let share = BlsKeypair {
+            private,
+            public: (PublicKey::generator() * private).into_affine(),
+        };
+        public_shares.push(PublicShare {
+            index,
+            public_g2: EthConvert::to_g2(share.public_g2())
+                .ok_or_eyre("cannot convert G2 share")?,
+        });
+        keys.push((index, share));
+    }
+
+    Ok((
+        ThresholdPublicKey {
+            threshold,
+            public_g1: EthConvert::to_g1(keypair.public).ok_or_eyre("cannot convert G1 public")?,
+            public_g2: EthConvert::to_g2(keypair.public_g2())
+                .ok_or_eyre("cannot convert G2 public")?,
+            shares: public_shares,
+        },
+        keys,
+    ))
+}
+
+pub fn sign_partial(share: &BlsKeypair, index: u32, msg: &[u8]) -> eyre::Result<PartialSignature> {
+    Ok(PartialSignature {
+        index,
+        signature: EthConvert::to_g1(share.sign(msg)?).ok_or_eyre("cannot convert signature")?,
+    })
+}
+
+impl ThresholdPublicKey {
+    pub fn public(&self) -> eyre::Result<PublicKey> {
+        EthConvert::from_g1(&self.public_g1).ok_or_eyre("invalid threshold G1 public key")
+    }
+
+    pub fn public_g2(&self) -> eyre::Result<G2Affine> {
+        EthConvert::from_g2(&self.public_g2).ok_or_eyre("invalid threshold G2 public key")
+    }
+
+    pub fn operator_id(&self) -> eyre::Result<OperatorId> {
+        Ok(BlsKeypair::operator_id_of(self.public()?))
+    }
+
+    /// Checks `partial` against the public key of its share, returning its signature point.
+    pub fn verify_partial(
+        &self,
+        msg: &[u8],
+        partial: &PartialSignature,
+    ) -> eyre::Result<BlsSignature> {
+        let share = self
+            .shares
+            .iter()
+            .find(|s| s.index == partial.index)
+            .ok_or_else(|| eyre!("unknown key share {}", partial.index))?;
+        let public_g2 =
+            EthConvert::from_g2(&share.public_g2).ok_or_eyre("invalid G2 share public key")?;
+        let signature = EthConvert::from_g1(&partial.signature)
+            .ok_or_eyre("invalid partial signature point")?;
+        if !BlsKeypair::verify(public_g2, msg, signature)? {
+            return Err(eyre!(
+                "invalid partial signature of share {}",
+                partial.index
+            ));
+        }
+        Ok(signature)
+    }
+
+    /// Combines `threshold` partial signatures of distinct shares into the signature of the
+    /// full key, by Lagrange interpolation at 0.
+    pub fn combine(&self, partials: &[(u32, BlsSignature)]) -> eyre::Result<BlsSignature> {
+        let mut indexes: Vec<u32> = vec![];
+        for (index, _) in partials {
+            if !indexes.contains(index) {
+                indexes.push(*index);
+            }
+        }
+        if indexes.len() < self.threshold as usize {
+            return Err(eyre!(
+                "{} partial signatures of distinct shares, {} required",
+                indexes.len(),
+                self.threshold
+            ));
+        }
+        indexes.truncate(self.threshold as usize);
+
+        let mut signature = G1Projective::zero();
+        for index in &indexes {
+            let (_, partial) = partials
+                .iter()
+                .find(|(i, _)| i == index)
+                .expect("index taken from partials");
+            signature += *partial * lagrange_at_zero(*index, &indexes)?;
+        }
+        Ok(signature.into_affine())
+    }
+}
+
+/// Lagrange basis polynomial of `index` over `indexes`, evaluated at 0.
+fn lagrange_at_zero(index: u32, indexes: &[u32]) -> eyre::Result<Fr> {
+    let x = Fr::from(index);
+    let mut value = Fr::one();
+    for other in indexes.iter().filter(|i| **i != index) {
+        let xj = Fr::from(*other);
+        value *= xj * (xj - x).inverse().ok_or_eyre("duplicate share index")?;
+    }
+    Ok(value)
+}
+
+#[test]
+fn test_threshold_sign() {
+    use crate::crypto::keystore::EncodedKeystore;
+    let keypair = EncodedKeystore::random()
+        .unwrap()
+        .into_bls_keypair()
+        .unwrap();
+    let (public, shares) = split(&keypair, 3, 5).unwrap();
+    assert_eq!(public.operator_id().unwrap(), keypair.operator_id());
+
+    let msg = b"task response";
+    let expected = keypair.sign(msg).unwrap();
+    for subset in [[0, 2, 4], [1, 3, 4]] {
+        let partials: Vec<(u32, BlsSignature)> = subset
+            .iter()
+            .map(|i| {
+                let (index, share) = &shares[*i];
+                let partial = sign_partial(share, *index, msg).unwrap();
+                (*index, public.verify_partial(msg, &partial).unwrap())
+            })
+            .collect();
+        assert_eq!(public.combine(&partials).unwrap(), expected);
+        assert!(public.combine(&partials[..2]).is_err());
+    }
+
+    let forged = sign_partial(&shares[0].1, shares[1].0, msg).unwrap();
+    assert!(public.verify_partial(msg, &forged).is_err());
+}
@@ -0,0 +1,24 @@
+//! Building blocks for Mangata AVS operators and monitoring tools.
+//!
+//! - [`crypto`] BN254 BLS keys and signatures, encrypted keystores and task signers
+//! - [`task`] kinds of tasks emitted by the task manager
+//! - [`response`] signing and JSON encoding of task responses sent to the aggregator
+//! - [`ownership`] proofs of custody of the operator ECDSA and BLS keys
+//! - [`proofs`] streaming hashing and Merkle commitments to large task results
+//! - [`chain`] Ethereum JSON-RPC transport and chunked event log queries
+//!
+//! Contract bindings are re-exported as [`bindings`].
+//!
+//! The clients of the EigenLayer and AVS contracts are not part of the crate: the node builds
+//! them from its configuration and calls them through its circuit breakers, metrics and local
+//! store. Integrators call the contracts through [`bindings`] over a provider built on
+//! [`chain::transport`], and query their events with [`chain::logs`].
+
+pub use bindings;
+
+pub mod chain;
+pub mod crypto;
+pub mod ownership;
+pub mod proofs;
+pub mod response;
+pub mod task;
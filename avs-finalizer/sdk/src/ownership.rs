@@ -0,0 +1,110 @@
+use bindings::shared_types::{G1Point, G2Point};
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, Signature},
+};
+use eyre::{eyre, OptionExt};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{
+    bn254::{BlsKeypair, OperatorId},
+    keccak256, EthConvert,
+};
+
+const DOMAIN: &[u8] = b"AvsFinalizer_Ownership_Proof";
+
+/// Proof that the holder of both the ECDSA and the BLS operator keys signed `challenge`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OwnershipProof {
+    pub challenge: String,
+    pub eth_address: Address,
+    pub operator_id: OperatorId,
+    pub bls_g1: G1Point,
+    pub bls_g2: G2Point,
+    /// BLS signature of the ownership digest
+    pub bls_signature: G1Point,
+    /// EIP-191 signature of the ownership digest
+    pub ecdsa_signature: Bytes,
+}
+
+/// Digest signed by both keys, binding the challenge to the address and operator id.
+fn digest(challenge: &str, eth_address: Address, operator_id: OperatorId) -> [u8; 32] {
+    keccak256(
+        [
+            DOMAIN,
+            eth_address.as_bytes(),
+            operator_id.as_bytes(),
+            challenge.as_bytes(),
+        ]
+        .concat()
+        .as_ref(),
+    )
+    .to_fixed_bytes()
+}
+
+pub async fn prove(
+    challenge: &str,
+    wallet: &LocalWallet,
+    keypair: &BlsKeypair,
+) -> eyre::Result<OwnershipProof> {
+    let operator_id = keypair.operator_id();
+    let digest = digest(challenge, wallet.address(), operator_id);
+
+    let bls_signature = keypair.sign(&digest)?;
+    let ecdsa_signature = wallet.sign_message(digest).await?;
+
+    Ok(OwnershipProof {
+        challenge: challenge.to_owned(),
+        eth_address: wallet.address(),
+        operator_id,
+        bls_g1: EthConvert::to_g1(keypair.public).ok_or_eyre("cannot convert G1 public")?,
+        bls_g2: EthConvert::to_g2(keypair.public_g2()).ok_or_eyre("cannot convert G2 public")?,
+        bls_signature: EthConvert::to_g1(bls_signature).ok_or_eyre("cannot convert signature")?,
+        ecdsa_signature: ecdsa_signature.to_vec().into(),
+    })
+}
+
+/// Verifies both signatures of `proof` and that its operator id derives from its BLS key.
+/// Whether the address and operator id are registered with the AVS must be checked on-chain.
+pub fn verify(proof: &OwnershipProof) -> eyre::Result<()> {
+    let g1 = EthConvert::from_g1(&proof.bls_g1).ok_or_eyre("invalid BLS G1 public key")?;
+    let g2 = EthConvert::from_g2(&proof.bls_g2).ok_or_eyre("invalid BLS G2 public key")?;
+    let signature =
+        EthConvert::from_g1(&proof.bls_signature).ok_or_eyre("invalid BLS signature point")?;
+
+    if !BlsKeypair::is_same_key(g1, g2) {
+        return Err(eyre!("BLS G1 and G2 public keys do not match"));
+    }
+    if BlsKeypair::operator_id_of(g1) != proof.operator_id {
+        return Err(eyre!("operator id does not derive from the BLS public key"));
+    }
+
+    let digest = digest(&proof.challenge, proof.eth_address, proof.operator_id);
+    if !BlsKeypair::verify(g2, &digest, signature)? {
+        return Err(eyre!("invalid BLS signature"));
+    }
+    Signature::try_from(proof.ecdsa_signature.as_ref())?
+        .verify(&digest[..], proof.eth_address)
+        .map_err(|e| eyre!("invalid ECDSA signature: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_prove_verify() {
+    use crate::crypto::keystore::EncodedKeystore;
+    let wallet = EncodedKeystore::random().unwrap().into_wallet().unwrap();
+    let keypair = EncodedKeystore::random()
+        .unwrap()
+        .into_bls_keypair()
+        .unwrap();
+
+    let mut proof = prove("governance challenge", &wallet, &keypair)
+        .await
+        .unwrap();
+    verify(&proof).unwrap();
+
+    proof.challenge = "another challenge".into();
+    assert!(verify(&proof).is_err());
+}
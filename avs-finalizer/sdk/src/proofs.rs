@@ -0,0 +1,211 @@
+//! Streaming commitments to task results too large to buffer in memory.
+//!
+//! [`KeccakWriter`] hashes data as it is written, [`MerkleCommitment`] splits a stream into
+//! fixed size chunks and commits to them with a Merkle root, keeping only the 32 byte chunk
+//! hashes, from which compact [`MerkleProof`]s of single chunks are generated.
+
+use std::io::{self, Read, Write};
+
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Incremental Keccak-256 hasher, usable as an [`io::Write`] sink.
+#[derive(Default, Clone)]
+pub struct KeccakWriter(Keccak256);
+
+impl KeccakWriter {
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    pub fn finalize(self) -> H256 {
+        H256(self.0.finalize().into())
+    }
+}
+
+impl Write for KeccakWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Keccak-256 of everything read from `reader`, without buffering it.
+pub fn keccak_reader(mut reader: impl Read) -> io::Result<H256> {
+    let mut hasher = KeccakWriter::default();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+/// Merkle commitment to a stream split in `chunk_size` chunks. Leaves and inner nodes are
+/// domain separated, an odd node at the end of a level is carried up unchanged.
+#[derive(Debug, Clone)]
+pub struct MerkleCommitment {
+    leaves: Vec<H256>,
+}
+
+/// Proof that `leaf` is the chunk at `index` of a commitment to `leaf_count` chunks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub index: usize,
+    pub leaf_count: usize,
+    pub leaf: H256,
+    pub siblings: Vec<H256>,
+}
+
+impl MerkleCommitment {
+    /// Reads `reader` to the end, hashing it chunk by chunk.
+    pub fn from_reader(mut reader: impl Read, chunk_size: usize) -> io::Result<Self> {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        let mut leaves = vec![];
+        let mut chunk = vec![0; chunk_size];
+        loop {
+            let read = read_chunk(&mut reader, &mut chunk)?;
+            if read == 0 {
+                break;
+            }
+            leaves.push(leaf_hash(&chunk[..read]));
+            if read < chunk_size {
+                break;
+            }
+        }
+        Ok(Self { leaves })
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Merkle root, zero for an empty stream.
+    pub fn root(&self) -> H256 {
+        let mut level = self.leaves.clone();
+        while level.len() > 1 {
+            level = next_level(&level);
+        }
+        level.first().copied().unwrap_or_default()
+    }
+
+    /// Proof of the chunk at `index`, `None` if out of range.
+    pub fn prove(&self, mut index: usize) -> Option<MerkleProof> {
+        let leaf = *self.leaves.get(index)?;
+        let proof_index = index;
+        let mut siblings = vec![];
+        let mut level = self.leaves.clone();
+        while level.len() > 1 {
+            let sibling = index ^ 1;
+            if sibling < level.len() {
+                siblings.push(level[sibling]);
+            }
+            level = next_level(&level);
+            index /= 2;
+        }
+        Some(MerkleProof {
+            index: proof_index,
+            leaf_count: self.leaves.len(),
+            leaf,
+            siblings,
+        })
+    }
+}
+
+impl MerkleProof {
+    /// Checks the proof against `root`, `chunk` is the proven data.
+    pub fn verify(&self, root: H256, chunk: &[u8]) -> bool {
+        if leaf_hash(chunk) != self.leaf || self.index >= self.leaf_count {
+            return false;
+        }
+        let mut siblings = self.siblings.iter();
+        let (mut hash, mut index, mut len) = (self.leaf, self.index, self.leaf_count);
+        while len > 1 {
+            let sibling = index ^ 1;
+            if sibling < len {
+                let Some(sibling_hash) = siblings.next() else {
+                    return false;
+                };
+                hash = if index % 2 == 0 {
+                    node_hash(&hash, sibling_hash)
+                } else {
+                    node_hash(sibling_hash, &hash)
+                };
+            }
+            index /= 2;
+            len = len.div_ceil(2);
+        }
+        siblings.next().is_none() && hash == root
+    }
+}
+
+fn read_chunk(reader: &mut impl Read, chunk: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < chunk.len() {
+        match reader.read(&mut chunk[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+fn next_level(level: &[H256]) -> Vec<H256> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [odd] => *odd,
+            _ => unreachable!("chunks of two"),
+        })
+        .collect()
+}
+
+fn leaf_hash(chunk: &[u8]) -> H256 {
+    let mut hasher = KeccakWriter::default();
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(chunk);
+    hasher.finalize()
+}
+
+fn node_hash(left: &H256, right: &H256) -> H256 {
+    let mut hasher = KeccakWriter::default();
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    hasher.finalize()
+}
+
+#[test]
+fn test_keccak_reader() {
+    let data = vec![7_u8; 10_000];
+    assert_eq!(
+        keccak_reader(data.as_slice()).unwrap(),
+        crate::crypto::keccak256(&data)
+    );
+}
+
+#[test]
+fn test_merkle_proofs() {
+    let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let commitment = MerkleCommitment::from_reader(data.as_slice(), 64).unwrap();
+    assert_eq!(commitment.leaf_count(), 16);
+    let root = commitment.root();
+
+    for (index, chunk) in data.chunks(64).enumerate() {
+        let proof = commitment.prove(index).unwrap();
+        assert!(proof.verify(root, chunk));
+        assert!(!proof.verify(root, &chunk[1..]));
+    }
+    assert!(commitment.prove(16).is_none());
+    assert_eq!(
+        MerkleCommitment::from_reader(&[][..], 64).unwrap().root(),
+        H256::zero()
+    );
+}
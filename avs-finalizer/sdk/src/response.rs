@@ -0,0 +1,213 @@
+//! JSON encoding of signed task responses as expected by the aggregator.
+
+use crate::crypto::{
+    bn254::{BlsKeypair, BlsSignature, OperatorId, PrivateKey},
+    keccak256, EthConvert,
+};
+use ark_bn254::G2Affine;
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use bindings::shared_types::{G1Point, TaskResponse};
+use ethers::{
+    abi::AbiEncode,
+    types::{H256, U256},
+};
+use eyre::{eyre, OptionExt};
+use serde::{ser::SerializeStruct, Deserialize, Serialize};
+
+type Bytes32 = [u8; 32];
+
+#[derive(Serialize)]
+struct SignedTaskResponse {
+    #[serde(rename = "TaskResponse")]
+    task_response: TaskResponseWire,
+    #[serde(rename = "BlsSignature")]
+    bls_signature: BlsSignatureWire,
+    #[serde(rename = "OperatorId")]
+    operator_id: Bytes32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TaskResponseWire {
+    #[serde(rename = "ReferenceTaskIndex")]
+    pub reference_task_index: u32,
+    #[serde(rename = "BlockHash")]
+    pub block_hash: Bytes32,
+    #[serde(rename = "StorageProofHash")]
+    pub storage_proof_hash: Bytes32,
+}
+
+impl From<TaskResponseWire> for TaskResponse {
+    fn from(value: TaskResponseWire) -> Self {
+        Self {
+            reference_task_index: value.reference_task_index,
+            block_hash: value.block_hash,
+            storage_proof_hash: value.storage_proof_hash,
+        }
+    }
+}
+
+impl From<TaskResponse> for TaskResponseWire {
+    fn from(value: TaskResponse) -> Self {
+        Self {
+            reference_task_index: value.reference_task_index,
+            block_hash: value.block_hash,
+            storage_proof_hash: value.storage_proof_hash,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BlsSignatureWire {
+    g1_point: G1PointWire,
+}
+
+impl From<BlsSignature> for BlsSignatureWire {
+    fn from(value: BlsSignature) -> Self {
+        Self {
+            g1_point: G1PointWire {
+                x: value.x().unwrap().into_bigint(),
+                y: value.y().unwrap().into_bigint(),
+            },
+        }
+    }
+}
+
+struct G1PointWire {
+    x: <PrivateKey as PrimeField>::BigInt,
+    y: <PrivateKey as PrimeField>::BigInt,
+}
+
+impl Serialize for G1PointWire {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_struct("g1_point", 2)?;
+        s.serialize_field("X", &self.x.to_string())?;
+        s.serialize_field("Y", &self.y.to_string())?;
+        s.end()
+    }
+}
+
+/// Signs `task_response` and encodes it as the JSON body expected by the aggregator.
+pub fn encode_task_response(
+    task_response: TaskResponse,
+    keypair: &BlsKeypair,
+) -> eyre::Result<String> {
+    Ok(serde_json::to_string(&create_response(
+        task_response,
+        keypair,
+    )?)?)
+}
+
+/// Encodes `task_response` with a BLS `signature` produced elsewhere, e.g. combined from
+/// threshold partial signatures of [`task_response_digest`].
+pub fn encode_bls_task_response(
+    task_response: TaskResponse,
+    signature: BlsSignature,
+    operator_id: OperatorId,
+) -> eyre::Result<String> {
+    Ok(serde_json::to_string(&SignedTaskResponse {
+        bls_signature: signature.into(),
+        task_response: task_response.into(),
+        operator_id: operator_id.to_fixed_bytes(),
+    })?)
+}
+
+/// Digest of the ABI encoded `task_response`, the message signed by operators.
+pub fn task_response_digest(task_response: &TaskResponse) -> H256 {
+    keccak256(task_response.clone().encode().as_ref())
+}
+
+/// Decodes a JSON body produced by [`encode_task_response`] and checks its signature against
+/// the BLS `public_g2` key.
+pub fn verify_task_response(json: &str, public_g2: G2Affine) -> eyre::Result<bool> {
+    let (task, signature, _) = decode_bls_task_response(json)?;
+    BlsKeypair::verify(public_g2, task_response_digest(&task).as_bytes(), signature)
+}
+
+/// Task response, BLS signature and operator id of a JSON body produced by
+/// [`encode_task_response`], the signature is not checked.
+pub fn decode_bls_task_response(
+    json: &str,
+) -> eyre::Result<(TaskResponse, BlsSignature, OperatorId)> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let task = serde_json::from_value::<TaskResponseWire>(value["TaskResponse"].clone())?.into();
+    let point = value
+        .get("BlsSignature")
+        .ok_or_eyre("task response is not BLS signed")?;
+    let operator_id: Bytes32 = serde_json::from_value(value["OperatorId"].clone())?;
+    Ok((task, bls_signature(point)?, operator_id.into()))
+}
+
+/// Signature point of the `BlsSignature` object of a task response, rejecting coordinates
+/// which are not canonically encoded and points outside the G1 subgroup.
+fn bls_signature(point: &serde_json::Value) -> eyre::Result<BlsSignature> {
+    let coord = |name: &str| -> eyre::Result<U256> {
+        let s = point["g1_point"][name]
+            .as_str()
+            .ok_or_eyre("missing BLS signature coordinate")?;
+        U256::from_dec_str(s).map_err(|_| eyre!("invalid BLS signature coordinate {}", s))
+    };
+    let signature = G1Point {
+        x: coord("X")?,
+        y: coord("Y")?,
+    };
+    EthConvert::from_g1(&signature)
+        .ok_or_eyre("BLS signature is not a canonically encoded point of the G1 subgroup")
+}
+
+fn create_response(task: TaskResponse, keypair: &BlsKeypair) -> eyre::Result<SignedTaskResponse> {
+    let sig = keypair.sign(task_response_digest(&task).as_bytes())?;
+
+    Ok(SignedTaskResponse {
+        bls_signature: sig.into(),
+        task_response: task.into(),
+        operator_id: keypair.operator_id().to_fixed_bytes(),
+    })
+}
+
+#[test]
+fn test_encode_verify_task_response() {
+    use crate::crypto::keystore::EncodedKeystore;
+    let keypair = EncodedKeystore::random()
+        .unwrap()
+        .into_bls_keypair()
+        .unwrap();
+    let task = TaskResponse {
+        reference_task_index: 7,
+        block_hash: [1; 32],
+        storage_proof_hash: [2; 32],
+    };
+
+    let json = encode_task_response(task, &keypair).unwrap();
+    assert!(verify_task_response(&json, keypair.public_g2()).unwrap());
+    let tampered = json.replacen("7", "8", 1);
+    assert!(!verify_task_response(&tampered, keypair.public_g2()).unwrap());
+}
+
+#[test]
+fn test_verify_rejects_non_canonical_signature() {
+    use crate::crypto::keystore::EncodedKeystore;
+    use ark_bn254::Fq;
+    use ark_ff::BigInteger;
+    let keypair = EncodedKeystore::random()
+        .unwrap()
+        .into_bls_keypair()
+        .unwrap();
+    let task = TaskResponse {
+        reference_task_index: 7,
+        block_hash: [1; 32],
+        storage_proof_hash: [2; 32],
+    };
+    let json = encode_task_response(task, &keypair).unwrap();
+    assert!(verify_task_response(&json, keypair.public_g2()).unwrap());
+
+    // the same signature with X + p is rejected instead of reduced back to it
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let x = U256::from_dec_str(value["BlsSignature"]["g1_point"]["X"].as_str().unwrap()).unwrap();
+    let modulus = U256::from_little_endian(&Fq::MODULUS.to_bytes_le());
+    value["BlsSignature"]["g1_point"]["X"] = (x + modulus).to_string().into();
+    assert!(verify_task_response(&value.to_string(), keypair.public_g2()).is_err());
+}
@@ -0,0 +1,17 @@
+use bindings::mangata_task_manager::NewTaskCreatedFilter;
+use serde::{Deserialize, Serialize};
+
+/// Kinds of tasks emitted by the task manager, used to select per task behaviour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskType {
+    /// Re-execute a Substrate block and attest to its hash and storage proof
+    ExecuteBlock,
+}
+
+impl From<&NewTaskCreatedFilter> for TaskType {
+    fn from(_event: &NewTaskCreatedFilter) -> Self {
+        TaskType::ExecuteBlock
+    }
+}
@@ -0,0 +1,188 @@
+use std::{collections::BTreeMap, path::Path};
+
+use bindings::{
+    bls_public_key_compendium::{OperatorToPubkeyHashCall, RegisterBLSPublicKeyCall},
+    mangata_service_manager::{RegistryCoordinatorCall, TaskManagerCall},
+    mangata_task_manager::{GetCheckSignaturesIndicesCall, GetOperatorStateCall},
+};
+use ethers::{
+    contract::EthCall,
+    providers::Middleware,
+    types::{Address, Bytes, H256},
+};
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::CliArgs;
+
+/// Address books of the known networks by chain id, in the format of `--address-book` files.
+const BUILT_IN: &str = r#"{
+    "5": {
+        "network": "goerli",
+        "service_manager": "0xD2333E11ea617E30fb97900f6ac9782A85f233e7",
+        "bls_pubkey_compendium": "0xc81d3963087Fe09316cd1E032457989C7aC91b19",
+        "bls_operator_state_retriever": "0x737Dd62816a9392e84Fa21C531aF77C00816A3a3"
+    },
+    "31337": {
+        "network": "anvil",
+        "service_manager": "0x9E545E3C0baAB3E08CdfD552C960A1050f373042",
+        "bls_pubkey_compendium": "0xc5a5C42992dECbae36851359345FE25997F5C42d",
+        "bls_operator_state_retriever": "0x67d269191c92Caf3cD7723F116c85e6E9bf55933"
+    }
+}"#;
+
+/// EIP-1967 storage slot of the implementation of a proxy.
+const IMPLEMENTATION_SLOT: H256 = H256([
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+]);
+
+/// Addresses of the contracts the node starts from on one network, the other contracts are
+/// read from them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddressBook {
+    pub network: String,
+    pub service_manager: Address,
+    pub bls_pubkey_compendium: Address,
+    pub bls_operator_state_retriever: Address,
+}
+
+impl AddressBook {
+    /// Book of `--chain-id` from `--address-book`, or the built in one, with the addresses
+    /// given on the command line in place of the book ones. Without a book for the chain, all
+    /// addresses must be given.
+    pub fn resolve(cfg: &CliArgs) -> eyre::Result<Self> {
+        let books = match &cfg.address_book {
+            Some(path) => read(path)?,
+            None => serde_json::from_str(BUILT_IN)?,
+        };
+        let book = books.get(&cfg.chain_id).cloned();
+        let overridden = |name: &str, given: Option<Address>, book: Option<Address>| {
+            given.or(book).ok_or_else(|| {
+                eyre!(
+                    "no address book for chain {}, pass --address-book or --{}",
+                    cfg.chain_id,
+                    name
+                )
+            })
+        };
+        Ok(Self {
+            network: book
+                .as_ref()
+                .map_or_else(|| format!("chain {}", cfg.chain_id), |b| b.network.clone()),
+            service_manager: overridden(
+                "avs-service-manager-addr",
+                cfg.avs_service_manager_addr,
+                book.as_ref().map(|b| b.service_manager),
+            )?,
+            bls_pubkey_compendium: overridden(
+                "bls-compendium-addr",
+                cfg.bls_compendium_addr,
+                book.as_ref().map(|b| b.bls_pubkey_compendium),
+            )?,
+            bls_operator_state_retriever: overridden(
+                "bls-operator-state-retriever-addr",
+                cfg.bls_operator_state_retriever_addr,
+                book.as_ref().map(|b| b.bls_operator_state_retriever),
+            )?,
+        })
+    }
+
+    /// Contracts of the book, with selectors of functions they must implement.
+    fn contracts(&self) -> [(&'static str, Address, Vec<[u8; 4]>); 3] {
+        [
+            (
+                "service_manager",
+                self.service_manager,
+                vec![
+                    TaskManagerCall::selector(),
+                    RegistryCoordinatorCall::selector(),
+                ],
+            ),
+            (
+                "bls_pubkey_compendium",
+                self.bls_pubkey_compendium,
+                vec![
+                    OperatorToPubkeyHashCall::selector(),
+                    RegisterBLSPublicKeyCall::selector(),
+                ],
+            ),
+            (
+                "bls_operator_state_retriever",
+                self.bls_operator_state_retriever,
+                vec![
+                    GetOperatorStateCall::selector(),
+                    GetCheckSignaturesIndicesCall::selector(),
+                ],
+            ),
+        ]
+    }
+
+    /// Checks that each contract of the book is deployed and that its code, or the code of
+    /// its implementation behind an EIP-1967 proxy, dispatches the expected functions.
+    pub async fn validate<M: Middleware>(&self, client: &M) -> eyre::Result<()>
+    where
+        M::Error: 'static,
+    {
+        for (name, address, selectors) in self.contracts() {
+            let mut code = client.get_code(address, None).await?;
+            if code.is_empty() {
+                return Err(eyre!(
+                    "{} {:?} of {} has no code",
+                    name,
+                    address,
+                    self.network
+                ));
+            }
+            let slot = client
+                .get_storage_at(address, IMPLEMENTATION_SLOT, None)
+                .await?;
+            if !slot.is_zero() {
+                code = client.get_code(Address::from(slot), None).await?;
+            }
+            if let Some(missing) = selectors.iter().find(|s| !dispatches(&code, s)) {
+                return Err(eyre!(
+                    "{} {:?} of {} does not implement function 0x{}, is it another contract?",
+                    name,
+                    address,
+                    self.network,
+                    hex::encode(missing)
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read(path: &Path) -> eyre::Result<BTreeMap<u64, AddressBook>> {
+    serde_json::from_slice(&std::fs::read(path)?)
+        .map_err(|e| eyre!("invalid address book {}: {}", path.display(), e))
+}
+
+/// Whether `code` pushes `selector`, as the function dispatcher of Solidity contracts does.
+fn dispatches(code: &Bytes, selector: &[u8; 4]) -> bool {
+    const PUSH4: u8 = 0x63;
+    code.windows(5)
+        .any(|w| w[0] == PUSH4 && w[1..] == selector[..])
+}
+
+#[test]
+fn test_built_in_address_books() {
+    let books: BTreeMap<u64, AddressBook> = serde_json::from_str(BUILT_IN).unwrap();
+    assert_eq!(books[&31337].network, "anvil");
+    assert_eq!(
+        books[&5].service_manager,
+        "0xD2333E11ea617E30fb97900f6ac9782A85f233e7"
+            .parse()
+            .unwrap()
+    );
+    assert!(serde_json::from_str::<BTreeMap<u64, AddressBook>>(
+        r#"{"1": {"network": "x", "service_manager": "0x0000000000000000000000000000000000000001"}}"#
+    )
+    .is_err());
+
+    let code = Bytes::from(vec![0x60, 0x00, 0x63, 0xde, 0xad, 0xbe, 0xef, 0x14]);
+    assert!(dispatches(&code, &[0xde, 0xad, 0xbe, 0xef]));
+    assert!(!dispatches(&code, &[0xad, 0xbe, 0xef, 0x14]));
+}
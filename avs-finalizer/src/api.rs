@@ -0,0 +1,453 @@
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fs::File,
+    io::BufReader,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+};
+
+use eyre::{eyre, OptionExt};
+use hyper::{
+    body::{Bytes, HttpBody},
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    server::conn::Http,
+    service::service_fn,
+    Body, Method, Request, Response, StatusCode,
+};
+use prometheus::{Encoder, TextEncoder};
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+};
+use tokio_rustls::{
+    rustls::{
+        server::AllowAnyAuthenticatedClient, Certificate, PrivateKey, RootCertStore, ServerConfig,
+    },
+    TlsAcceptor,
+};
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::{
+    chainio::breaker::{circuits, CircuitState},
+    cli::ApiArgs,
+    doctor::recent_errors,
+    gossip::Gossip,
+    identity::NodeIdentity,
+    lag::LagReport,
+    metrics::{metrics, RpcUsage},
+    openapi,
+    pressure::AutoscaleSignal,
+    store::Store,
+    task::{PendingTask, VerificationState},
+};
+
+/// Largest gossip message accepted, a signed task response with its keys is about 2 KiB.
+const MAX_GOSSIP_BODY: usize = 16 * 1024;
+
+/// Shared state between the operator API and the operator.
+#[derive(Debug, Default)]
+pub struct ApiState {
+    token: Option<String>,
+    mtls: bool,
+    paused: AtomicBool,
+    /// Operator store, set once the operator opened it
+    store: OnceLock<Store>,
+    /// Tasks received but not signed yet, by task index
+    pending: Mutex<BTreeMap<u32, PendingTask>>,
+    /// Partial signature exchange with other operators, set when peers are configured
+    gossip: OnceLock<Arc<Gossip>>,
+    /// Latest autoscaling signal of the resource sampling
+    autoscale: Mutex<AutoscaleSignal>,
+    /// Latest end-to-end finality lag samples
+    finality_lag: Mutex<LagReport>,
+    /// Identity of the node, set once the operator loaded it
+    identity: OnceLock<NodeIdentity>,
+}
+
+impl ApiState {
+    pub fn new(cfg: &ApiArgs) -> Arc<Self> {
+        Arc::new(Self {
+            token: cfg.api_token.clone(),
+            mtls: cfg.api_client_ca.is_some(),
+            paused: AtomicBool::new(false),
+            store: OnceLock::new(),
+            pending: Mutex::default(),
+            gossip: OnceLock::new(),
+            autoscale: Mutex::default(),
+            finality_lag: Mutex::default(),
+            identity: OnceLock::new(),
+        })
+    }
+
+    pub fn set_store(&self, store: Store) {
+        let _ = self.store.set(store);
+    }
+
+    pub fn set_identity(&self, identity: NodeIdentity) {
+        let _ = self.identity.set(identity);
+    }
+
+    pub fn set_gossip(&self, gossip: Arc<Gossip>) {
+        let _ = self.gossip.set(gossip);
+    }
+
+    pub fn set_autoscale(&self, signal: AutoscaleSignal) {
+        *self.autoscale.lock().expect("poisoned lock") = signal;
+    }
+
+    pub fn set_finality_lag(&self, report: LagReport) {
+        *self.finality_lag.lock().expect("poisoned lock") = report;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Lists `task` as pending until [`Self::untrack_task`].
+    pub fn track_task(&self, task: PendingTask) {
+        self.pending
+            .lock()
+            .expect("poisoned lock")
+            .insert(task.task_index, task);
+    }
+
+    pub fn set_task_state(&self, task_index: u32, state: VerificationState) {
+        if let Some(task) = self
+            .pending
+            .lock()
+            .expect("poisoned lock")
+            .get_mut(&task_index)
+        {
+            task.state = state;
+        }
+    }
+
+    /// Stops listing a task once signed, skipped, dropped or abandoned.
+    pub fn untrack_task(&self, task_index: u32) {
+        self.pending
+            .lock()
+            .expect("poisoned lock")
+            .remove(&task_index);
+    }
+
+    fn pending_tasks(&self) -> Vec<PendingTask> {
+        self.pending
+            .lock()
+            .expect("poisoned lock")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    fn authorized(&self, req: &Request<Body>) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        req.headers()
+            .get(AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()))
+    }
+
+    fn admin_enabled(&self) -> bool {
+        self.token.is_some() || self.mtls
+    }
+}
+
+/// Serves the operator HTTP endpoints:
+/// - `GET /metrics` prometheus metrics
+/// - `GET /rpc-usage` JSON summary of JSON-RPC calls and estimated compute units
+/// - `GET /health` contract circuit breakers, 503 while any circuit is open
+/// - `GET /recent-errors` last warnings and errors logged by the node
+/// - `GET /quorum-snapshots/{block}` task quorum members and stakes at a reference block
+/// - `GET /pending-tasks` tasks received but not signed yet, with their deadline and state
+/// - `GET /autoscaling` task backlog, verification throughput and the resulting load, for the
+///   KEDA metrics API scaler or an HPA external metric
+/// - `GET /finality-lag` latest delays from the finalization of a substrate block to the
+///   acceptance of its task response
+/// - `GET /identity` persistent identity of the node and the keys it ran with
+/// - `POST /gossip` partial signature of another operator, open to peers without the token
+///   as messages carry the ECDSA signature of a registered operator
+/// - `GET /gossip/aggregates` aggregates of the gossiped partial signatures of the tasks the
+///   aggregator did not take the response of
+/// - `GET /openapi.json` OpenAPI description of these endpoints
+/// - `POST /admin/pause`, `POST /admin/resume` stop and resume answering new tasks,
+///   only available when a token or mTLS client authentication is configured
+///
+/// When `--api-token` is set every request must carry it as a bearer token. With
+/// `--api-client-ca` connections must present a client certificate signed by that CA, further
+/// restricted to the `--api-client-cert-sha256` fingerprints when given.
+#[instrument(skip_all)]
+pub async fn serve(cfg: ApiArgs, state: Arc<ApiState>) {
+    if let Err(e) = try_serve(&cfg, state).await {
+        error!("Operator API server stopped: {:?}", e);
+    }
+}
+
+async fn try_serve(cfg: &ApiArgs, state: Arc<ApiState>) -> eyre::Result<()> {
+    let addr = cfg.api_addr.ok_or_eyre("api address not configured")?;
+    let tls = build_tls(cfg)?;
+    let fingerprints: Vec<String> = cfg
+        .api_client_cert_sha256
+        .iter()
+        .map(|f| f.trim_start_matches("0x").to_lowercase())
+        .collect();
+
+    if !state.admin_enabled() {
+        warn!("No api token or client CA configured, admin endpoints are disabled");
+    }
+
+    let listener = TcpListener::bind(addr).await?;
+    info!("Serving operator API on {}", addr);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = state.clone();
+        match tls.clone() {
+            None => {
+                tokio::spawn(serve_connection(stream, state));
+            }
+            Some(acceptor) => {
+                let fingerprints = fingerprints.clone();
+                tokio::spawn(async move {
+                    let stream = match acceptor.accept(stream).await {
+                        Ok(stream) => stream,
+                        Err(e) => return debug!("TLS handshake with {} failed: {}", peer, e),
+                    };
+                    if !fingerprints.is_empty() {
+                        let allowed = stream
+                            .get_ref()
+                            .1
+                            .peer_certificates()
+                            .and_then(|certs| certs.first())
+                            .is_some_and(|cert| {
+                                fingerprints.contains(&hex::encode(Sha256::digest(&cert.0)))
+                            });
+                        if !allowed {
+                            return warn!("Rejected client certificate of {}", peer);
+                        }
+                    }
+                    serve_connection(stream, state).await
+                });
+            }
+        }
+    }
+}
+
+async fn serve_connection<S>(stream: S, state: Arc<ApiState>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let svc = service_fn(move |req| handle(req, state.clone()));
+    if let Err(e) = Http::new().serve_connection(stream, svc).await {
+        debug!("Operator API connection error: {}", e);
+    }
+}
+
+fn build_tls(cfg: &ApiArgs) -> eyre::Result<Option<TlsAcceptor>> {
+    let (Some(cert), Some(key)) = (&cfg.api_tls_cert, &cfg.api_tls_key) else {
+        return Ok(None);
+    };
+    let certs = read_certs(cert)?;
+    let key = read_key(key)?;
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let config = match &cfg.api_client_ca {
+        Some(ca) => {
+            let mut roots = RootCertStore::empty();
+            for cert in read_certs(ca)? {
+                roots.add(&cert)?;
+            }
+            builder
+                .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+                .with_single_cert(certs, key)?
+        }
+        None => builder.with_no_client_auth().with_single_cert(certs, key)?,
+    };
+
+    Ok(Some(TlsAcceptor::from(Arc::new(config))))
+}
+
+fn read_certs(path: &Path) -> eyre::Result<Vec<Certificate>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(rustls_pemfile::certs(&mut reader)?
+        .into_iter()
+        .map(Certificate)
+        .collect())
+}
+
+fn read_key(path: &Path) -> eyre::Result<PrivateKey> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::read_all(&mut reader)?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| eyre!("no private key found in {}", path.display()))
+}
+
+async fn handle(req: Request<Body>, state: Arc<ApiState>) -> Result<Response<Body>, Infallible> {
+    if req.method() == Method::POST && req.uri().path() == "/gossip" {
+        return Ok(receive_gossip(req, &state).await.unwrap_or_else(|e| {
+            debug!("Rejected gossip message: {:?}", e);
+            status(StatusCode::BAD_REQUEST)
+        }));
+    }
+    if !state.authorized(&req) {
+        return Ok(status(StatusCode::UNAUTHORIZED));
+    }
+
+    let res = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => encode_metrics(),
+        (&Method::GET, "/rpc-usage") => json(&metrics().rpc_usage()),
+        (&Method::GET, "/health") => health(),
+        (&Method::GET, "/recent-errors") => json(&recent_errors()),
+        (&Method::GET, path) if path.starts_with("/quorum-snapshots/") => {
+            quorum_snapshot(&state, &path["/quorum-snapshots/".len()..]).await
+        }
+        (&Method::GET, "/pending-tasks") => json(&state.pending_tasks()),
+        (&Method::GET, "/autoscaling") => json(&*state.autoscale.lock().expect("poisoned lock")),
+        (&Method::GET, "/finality-lag") => {
+            json(&*state.finality_lag.lock().expect("poisoned lock"))
+        }
+        (&Method::GET, "/identity") => match state.identity.get() {
+            Some(identity) => json(identity),
+            None => Ok(status(StatusCode::SERVICE_UNAVAILABLE)),
+        },
+        (&Method::GET, "/gossip/aggregates") => match state.gossip.get() {
+            Some(gossip) => json(&gossip.aggregates()),
+            None => Ok(status(StatusCode::NOT_FOUND)),
+        },
+        (&Method::GET, "/openapi.json") => json(&openapi::spec()),
+        (&Method::POST, "/admin/pause" | "/admin/resume") if !state.admin_enabled() => {
+            Ok(status(StatusCode::FORBIDDEN))
+        }
+        (&Method::POST, "/admin/pause") => {
+            warn!("Operator paused through the admin API");
+            state.paused.store(true, Ordering::Relaxed);
+            Ok(status(StatusCode::NO_CONTENT))
+        }
+        (&Method::POST, "/admin/resume") => {
+            warn!("Operator resumed through the admin API");
+            state.paused.store(false, Ordering::Relaxed);
+            Ok(status(StatusCode::NO_CONTENT))
+        }
+        _ => Ok(status(StatusCode::NOT_FOUND)),
+    };
+    Ok(res.unwrap_or_else(|e| {
+        error!("Operator API request failed: {:?}", e);
+        status(StatusCode::INTERNAL_SERVER_ERROR)
+    }))
+}
+
+/// Keeps a partial signature gossiped by a peer, 404 when gossip is disabled.
+async fn receive_gossip(req: Request<Body>, state: &ApiState) -> eyre::Result<Response<Body>> {
+    let Some(gossip) = state.gossip.get() else {
+        return Ok(status(StatusCode::NOT_FOUND));
+    };
+    let body = read_body(req.into_body(), MAX_GOSSIP_BODY).await?;
+    gossip.receive(&body).await?;
+    Ok(status(StatusCode::NO_CONTENT))
+}
+
+/// Reads a request body, failing once it exceeds `limit` bytes rather than buffering it.
+pub async fn read_body(mut body: Body, limit: usize) -> eyre::Result<Bytes> {
+    let mut read = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if read.len() + chunk.len() > limit {
+            return Err(eyre!("request body larger than {} bytes", limit));
+        }
+        read.extend_from_slice(&chunk);
+    }
+    Ok(read.into())
+}
+
+fn encode_metrics() -> eyre::Result<Response<Body>> {
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+    encoder.encode(&metrics().registry.gather(), &mut buffer)?;
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, encoder.format_type())
+        .body(buffer.into())?)
+}
+
+/// Reports the contract circuit breakers, unhealthy while any circuit is open.
+fn health() -> eyre::Result<Response<Body>> {
+    let circuits = circuits();
+    let mut res = json(&circuits)?;
+    if circuits.iter().any(|c| c.state == CircuitState::Open) {
+        *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    }
+    Ok(res)
+}
+
+/// Serves the quorum snapshot of `block`, 404 when the node keeps no store or no task
+/// referenced that block.
+async fn quorum_snapshot(state: &ApiState, block: &str) -> eyre::Result<Response<Body>> {
+    let (Some(store), Ok(block)) = (state.store.get(), block.parse::<u32>()) else {
+        return Ok(status(StatusCode::NOT_FOUND));
+    };
+    match store.run(move |s| s.get_quorum_snapshot(block)).await? {
+        Some(snapshot) => json(&snapshot),
+        None => Ok(status(StatusCode::NOT_FOUND)),
+    }
+}
+
+fn json<T: serde::Serialize>(value: &T) -> eyre::Result<Response<Body>> {
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(value)?.into())?)
+}
+
+fn status(code: StatusCode) -> Response<Body> {
+    let mut res = Response::new(Body::empty());
+    *res.status_mut() = code;
+    res
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Fetches the JSON-RPC usage summary from an operator node serving its API on `addr`.
+pub async fn fetch_rpc_usage(cfg: &ApiArgs) -> eyre::Result<Vec<RpcUsage>> {
+    fetch_json(cfg, "/rpc-usage").await
+}
+
+/// Fetches the JSON served on `path` by the API of a running operator node.
+pub async fn fetch_json<T: serde::de::DeserializeOwned>(
+    cfg: &ApiArgs,
+    path: &str,
+) -> eyre::Result<T> {
+    let addr = cfg
+        .api_addr
+        .ok_or_eyre("--api-addr of the running operator is required")?;
+    if cfg.api_tls_cert.is_some() {
+        return Err(eyre!(
+            "querying a TLS enabled operator API is not supported"
+        ));
+    }
+
+    let mut req = reqwest::Client::new().get(format!("http://{}{}", addr, path));
+    if let Some(token) = &cfg.api_token {
+        req = req.bearer_auth(token);
+    }
+    let body = req.send().await?.error_for_status()?.text().await?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+#[test]
+fn test_constant_time_eq() {
+    assert!(constant_time_eq(b"secret", b"secret"));
+    assert!(!constant_time_eq(b"secret", b"secreT"));
+    assert!(!constant_time_eq(b"secret", b"secret2"));
+}
@@ -6,8 +6,10 @@ use tracing_subscriber::prelude::*;
 async fn main() -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(avs_finalizer::log_writer)
         .finish()
         .with(ErrorLayer::default())
+        .with(avs_finalizer::recent_errors_layer())
         .init();
 
     color_eyre::install()?;
@@ -0,0 +1,410 @@
+//! Soak test of a node against a local deployment, started as in the README with anvil and
+//! the aggregator running. Spawns the node given after `--`, creates tasks on the task manager
+//! at a steady rate for hours and samples the resident memory and open descriptors of the node
+//! process and its task latency from `/metrics`, to catch the leaks otherwise found in
+//! production after days. Exits non-zero when the node dies or when the last samples drifted
+//! from the ones taken after the warmup beyond the limits.
+//!
+//! ```bash
+//! cargo run --release --bin soak -- --duration-mins 480 -- \
+//!     target/release/main --ecdsa-ephemeral-key --bls-ephemeral-key --stake 100 \
+//!     --api-addr 127.0.0.1:9100
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bindings::{
+    mangata_service_manager::MangataServiceManager, mangata_task_manager::MangataTaskManager,
+};
+use clap::Parser;
+use color_eyre::eyre::{eyre, Result};
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::Address,
+};
+use serde::Serialize;
+use tokio::process::{Child, Command};
+use tracing::{error, info, warn};
+
+/// Threshold and quorums of the tasks, as created by the aggregator.
+const QUORUM_THRESHOLD_PERCENTAGE: u32 = 66;
+const QUORUM_NUMBERS: [u8; 1] = [0];
+/// Samples of the end of the run compared with the baseline, the lowest resident memory of
+/// them is used as the allocator keeps freed memory for a while.
+const FINAL_SAMPLES: usize = 5;
+
+#[derive(Parser, Debug)]
+#[command(about = "Runs a node under synthetic task load and checks it for leaks")]
+struct SoakArgs {
+    /// Ethereum node of the local deployment
+    #[arg(long, env, default_value = "http://localhost:8545")]
+    eth_rpc_url: String,
+    #[arg(
+        long,
+        env,
+        default_value = "0x9E545E3C0baAB3E08CdfD552C960A1050f373042"
+    )]
+    avs_service_manager_addr: Address,
+    /// Key of the task generator, the anvil account of `tests/keys/aggregator.ecdsa.key.json`
+    #[arg(
+        long,
+        env,
+        default_value = "0x2a871d0798f97d79848a013d4936a73bf4cc922c825d33c1cf7073dff6d409c6"
+    )]
+    task_generator_key: String,
+    /// Metrics of the node, served with `--api-addr`
+    #[arg(long, env, default_value = "http://127.0.0.1:9100/metrics")]
+    metrics_url: String,
+    /// Rollup block verified by the first task, the next tasks cycle through `blocks` blocks
+    #[arg(long, env, default_value_t = 1)]
+    first_block: u32,
+    #[arg(long, env, default_value_t = 100)]
+    blocks: u32,
+    #[arg(long, env, default_value_t = 2_000)]
+    task_interval_ms: u64,
+    #[arg(long, env, default_value_t = 360)]
+    duration_mins: u64,
+    /// Time for the node to fill its caches before the baseline sample
+    #[arg(long, env, default_value_t = 10)]
+    warmup_mins: u64,
+    #[arg(long, env, default_value_t = 60)]
+    sample_interval_secs: u64,
+    /// Growth of the resident memory over the baseline failing the run
+    #[arg(long, env, default_value_t = 25.0)]
+    max_rss_growth_pct: f64,
+    /// Descriptors opened over the baseline failing the run
+    #[arg(long, env, default_value_t = 32)]
+    max_fd_growth: u64,
+    /// Growth of the mean task latency over the baseline failing the run
+    #[arg(long, env, default_value_t = 50.0)]
+    max_latency_drift_pct: f64,
+    /// File the node logs are written to
+    #[arg(long, env, default_value = "soak-node.log")]
+    node_log: PathBuf,
+    /// File the samples and the verdict are written to as JSON
+    #[arg(long, env, default_value = "soak-report.json")]
+    report: PathBuf,
+    /// Command running the node
+    #[arg(last = true, required = true)]
+    node: Vec<String>,
+}
+
+/// State of the node at one point of the run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+struct Sample {
+    elapsed_secs: u64,
+    tasks_sent: u64,
+    rss_bytes: u64,
+    open_fds: u64,
+    /// Mean time from the task event to the response of the tasks processed since the
+    /// previous sample, none when no task was
+    latency_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    samples: Vec<Sample>,
+    baseline: Option<Sample>,
+    violations: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "soak=info".into()),
+        )
+        .init();
+    color_eyre::install()?;
+
+    let args = SoakArgs::parse();
+    let provider = Provider::<Http>::try_from(args.eth_rpc_url.as_str())?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let wallet = args
+        .task_generator_key
+        .parse::<LocalWallet>()?
+        .with_chain_id(chain_id);
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+    let service_manager = MangataServiceManager::new(args.avs_service_manager_addr, client.clone());
+    let task_manager =
+        MangataTaskManager::new(service_manager.task_manager().call().await?, client);
+
+    let mut node = spawn_node(&args.node, &args.node_log)?;
+    let pid = node.id().ok_or_else(|| eyre!("node exited on start"))?;
+    info!(
+        "Node {} started, logging to {}",
+        pid,
+        args.node_log.display()
+    );
+
+    let started = Instant::now();
+    let duration = Duration::from_secs(args.duration_mins * 60);
+    let mut tasks = tokio::time::interval(Duration::from_millis(args.task_interval_ms));
+    let mut sampling = tokio::time::interval(Duration::from_secs(args.sample_interval_secs));
+    let http = reqwest::Client::new();
+    let (mut tasks_sent, mut failed_sends) = (0u64, 0u64);
+    let mut latency = LatencyCounter::default();
+    let mut samples = vec![];
+
+    let exited = loop {
+        if started.elapsed() >= duration {
+            break None;
+        }
+        tokio::select! {
+            _ = tasks.tick() => {
+                let block = args.first_block + (tasks_sent % args.blocks.max(1) as u64) as u32;
+                let call = task_manager.create_new_task(
+                    block.into(),
+                    QUORUM_THRESHOLD_PERCENTAGE,
+                    QUORUM_NUMBERS.to_vec().into(),
+                );
+                let sent = call.send().await.map(drop);
+                match sent {
+                    Ok(()) => tasks_sent += 1,
+                    Err(e) => {
+                        failed_sends += 1;
+                        warn!("Cannot create the task of block {}: {}", block, e);
+                    }
+                }
+            }
+            _ = sampling.tick() => {
+                let metrics = match fetch_metrics(&http, &args.metrics_url).await {
+                    Ok(metrics) => metrics,
+                    Err(e) => {
+                        warn!("Cannot scrape the node metrics: {}", e);
+                        continue;
+                    }
+                };
+                let sample = Sample {
+                    elapsed_secs: started.elapsed().as_secs(),
+                    tasks_sent,
+                    rss_bytes: rss_bytes(pid)?,
+                    open_fds: open_fds(pid)?,
+                    latency_ms: latency.observe(&metrics),
+                };
+                info!(
+                    "{}s: {} tasks sent ({} failed), rss {} MiB, {} fds, latency {}",
+                    sample.elapsed_secs,
+                    sample.tasks_sent,
+                    failed_sends,
+                    sample.rss_bytes >> 20,
+                    sample.open_fds,
+                    sample.latency_ms.map_or("-".to_owned(), |ms| format!("{:.0}ms", ms)),
+                );
+                samples.push(sample);
+            }
+            status = node.wait() => break Some(status?),
+        }
+    };
+
+    let warmup = Duration::from_secs(args.warmup_mins * 60);
+    let baseline = samples
+        .iter()
+        .find(|s| s.elapsed_secs >= warmup.as_secs())
+        .copied();
+    let mut violations = match exited {
+        Some(status) => vec![format!("node exited with {}", status)],
+        None => {
+            node.kill().await?;
+            check_drift(&samples, baseline, &args)
+        }
+    };
+    if baseline.is_none() {
+        violations.push("no sample after the warmup, run longer".into());
+    }
+    if tasks_sent == 0 {
+        violations.push("no task could be created".into());
+    }
+
+    let report = Report {
+        samples,
+        baseline,
+        violations,
+    };
+    std::fs::write(&args.report, serde_json::to_string_pretty(&report)?)?;
+    if report.violations.is_empty() {
+        info!("Soak test passed, report in {}", args.report.display());
+        return Ok(());
+    }
+    for violation in &report.violations {
+        error!("{}", violation);
+    }
+    Err(eyre!(
+        "soak test failed, report in {}",
+        args.report.display()
+    ))
+}
+
+fn spawn_node(command: &[String], log: &Path) -> Result<Child> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| eyre!("missing node command"))?;
+    let log = std::fs::File::create(log)?;
+    Ok(Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .kill_on_drop(true)
+        .spawn()?)
+}
+
+fn rss_bytes(pid: u32) -> Result<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid))?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|kb| kb.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+        .ok_or_else(|| eyre!("no VmRSS in /proc/{}/status", pid))
+}
+
+fn open_fds(pid: u32) -> Result<u64> {
+    Ok(std::fs::read_dir(format!("/proc/{}/fd", pid))?.count() as u64)
+}
+
+async fn fetch_metrics(client: &reqwest::Client, url: &str) -> Result<String> {
+    let res = client
+        .get(url)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?;
+    if !res.status().is_success() {
+        return Err(eyre!("replied {}", res.status()));
+    }
+    Ok(res.text().await?)
+}
+
+/// Totals of `task_stage_seconds`, turned into the mean latency of the tasks processed
+/// between two scrapes.
+#[derive(Debug, Default)]
+struct LatencyCounter {
+    seconds: f64,
+    tasks: f64,
+}
+
+impl LatencyCounter {
+    fn observe(&mut self, metrics: &str) -> Option<f64> {
+        let (mut seconds, mut tasks) = (0.0, 0.0);
+        for line in metrics.lines() {
+            let Some((name, value)) = line.rsplit_once(' ') else {
+                continue;
+            };
+            let Ok(value) = value.parse::<f64>() else {
+                continue;
+            };
+            // the confirmation waits for the aggregator, not the node
+            if name.starts_with("task_stage_seconds_sum{") && !name.contains("stage=\"confirm\"") {
+                seconds += value;
+            } else if name == "task_stage_seconds_count{stage=\"queue\"}" {
+                tasks += value;
+            }
+        }
+        let processed = tasks - self.tasks;
+        let mean = (processed > 0.0).then(|| (seconds - self.seconds) / processed * 1e3);
+        (self.seconds, self.tasks) = (seconds, tasks);
+        mean
+    }
+}
+
+/// Compares the end of the run with the baseline.
+fn check_drift(samples: &[Sample], baseline: Option<Sample>, args: &SoakArgs) -> Vec<String> {
+    let Some(baseline) = baseline else {
+        return vec![];
+    };
+    let last = &samples[samples.len().saturating_sub(FINAL_SAMPLES)..];
+    let growth_pct = |base: f64, end: f64| (end - base) / base * 100.0;
+    let mut violations = vec![];
+
+    if let Some(rss) = last.iter().map(|s| s.rss_bytes).min() {
+        let growth = growth_pct(baseline.rss_bytes as f64, rss as f64);
+        if growth > args.max_rss_growth_pct {
+            violations.push(format!(
+                "resident memory grew {:.1}% from {} to {} MiB",
+                growth,
+                baseline.rss_bytes >> 20,
+                rss >> 20
+            ));
+        }
+    }
+    if let Some(fds) = last.iter().map(|s| s.open_fds).min() {
+        if fds > baseline.open_fds + args.max_fd_growth {
+            violations.push(format!(
+                "open descriptors grew from {} to {}",
+                baseline.open_fds, fds
+            ));
+        }
+    }
+    let latencies = |samples: &[Sample]| {
+        let latencies: Vec<f64> = samples.iter().filter_map(|s| s.latency_ms).collect();
+        (!latencies.is_empty()).then(|| latencies.iter().sum::<f64>() / latencies.len() as f64)
+    };
+    let start = samples
+        .iter()
+        .position(|s| *s == baseline)
+        .map(|i| &samples[i..(i + FINAL_SAMPLES).min(samples.len())]);
+    if let (Some(base), Some(end)) = (start.and_then(latencies), latencies(last)) {
+        let drift = growth_pct(base, end);
+        if drift > args.max_latency_drift_pct {
+            violations.push(format!(
+                "task latency drifted {:.1}% from {:.0}ms to {:.0}ms",
+                drift, base, end
+            ));
+        }
+    }
+    violations
+}
+
+#[test]
+fn test_latency_counter() {
+    let scrape = |queue: u64, execute: f64, confirm: f64| {
+        format!(
+            "# TYPE task_stage_seconds histogram\n\
+             task_stage_seconds_bucket{{stage=\"queue\",le=\"0.05\"}} {queue}\n\
+             task_stage_seconds_sum{{stage=\"queue\"}} {queue_secs}\n\
+             task_stage_seconds_count{{stage=\"queue\"}} {queue}\n\
+             task_stage_seconds_sum{{stage=\"execute\"}} {execute}\n\
+             task_stage_seconds_count{{stage=\"execute\"}} {queue}\n\
+             task_stage_seconds_sum{{stage=\"confirm\"}} {confirm}\n",
+            queue_secs = queue as f64 * 0.01,
+        )
+    };
+    let mut latency = LatencyCounter::default();
+    assert_eq!(latency.observe(&scrape(0, 0.0, 0.0)), None);
+    let mean = latency.observe(&scrape(10, 2.0, 30.0)).unwrap();
+    assert!((mean - 210.0).abs() < 1e-6);
+    assert_eq!(latency.observe(&scrape(10, 2.0, 60.0)), None);
+}
+
+#[test]
+fn test_check_drift() {
+    let args = SoakArgs::parse_from(["soak", "--", "node"]);
+    let sample = |elapsed_secs, rss_mib: u64, open_fds, latency_ms| Sample {
+        elapsed_secs,
+        tasks_sent: elapsed_secs / 2,
+        rss_bytes: rss_mib << 20,
+        open_fds,
+        latency_ms: Some(latency_ms),
+    };
+    let steady: Vec<Sample> = (0..20)
+        .map(|i| sample(i * 60, 100 + i % 3, 40, 200.0))
+        .collect();
+    assert!(check_drift(&steady, Some(steady[2]), &args).is_empty());
+
+    // a spike in the last samples is not a leak, a raised floor is
+    let mut spike = steady.clone();
+    spike[19].rss_bytes = 400 << 20;
+    assert!(check_drift(&spike, Some(spike[2]), &args).is_empty());
+    let leaking: Vec<Sample> = (0..20)
+        .map(|i| sample(i * 60, 100 + i * 5, 40 + i * 4, 200.0 + i as f64 * 20.0))
+        .collect();
+    assert_eq!(check_drift(&leaking, Some(leaking[2]), &args).len(), 3);
+}
@@ -0,0 +1,109 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, Signature},
+};
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::{crypto::bn254::OperatorId, features};
+
+/// What identifies the build of the running node, embedded at compile time by `build.rs`.
+/// Rebuilding the commit with the same lock file, toolchain, target and profile is expected
+/// to produce the same binary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub version: String,
+    /// `AVS_GIT_COMMIT` given to the build, absent when it was not set
+    pub git_commit: Option<String>,
+    /// SHA-256 of the `Cargo.lock` the dependencies were resolved from
+    pub cargo_lock_sha256: Option<String>,
+    pub rustc: Option<String>,
+    pub target: Option<String>,
+    pub profile: Option<String>,
+    /// Optional features compiled in, see `features.rs`
+    pub features: Vec<String>,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            git_commit: option_env!("AVS_GIT_COMMIT").map(Into::into),
+            cargo_lock_sha256: option_env!("AVS_CARGO_LOCK_SHA256").map(Into::into),
+            rustc: option_env!("AVS_RUSTC_VERSION").map(Into::into),
+            target: option_env!("AVS_BUILD_TARGET").map(Into::into),
+            profile: option_env!("AVS_BUILD_PROFILE").map(Into::into),
+            features: features::compiled().into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Build of an operator node as attested by its ECDSA key.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttestedBuild {
+    pub eth_address: Address,
+    pub operator_id: OperatorId,
+    pub issued_at: u64,
+    pub build: BuildInfo,
+}
+
+/// Published attestation, `attestation` holds the exact JSON that was EIP-191 signed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildAttestation {
+    pub attestation: String,
+    pub signature: Signature,
+}
+
+pub async fn attest(
+    build: BuildInfo,
+    operator_id: OperatorId,
+    wallet: &LocalWallet,
+) -> eyre::Result<BuildAttestation> {
+    let attestation = serde_json::to_string(&AttestedBuild {
+        eth_address: wallet.address(),
+        operator_id,
+        issued_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        build,
+    })?;
+    let signature = wallet.sign_message(&attestation).await?;
+    Ok(BuildAttestation {
+        attestation,
+        signature,
+    })
+}
+
+/// Checks that the attestation was signed by the address it names. Whether that address
+/// is a registered operator must be checked on-chain, and whether the build is reproducible
+/// by rebuilding it.
+pub fn verify(attestation: &BuildAttestation) -> eyre::Result<AttestedBuild> {
+    let attested: AttestedBuild = serde_json::from_str(&attestation.attestation)?;
+    attestation
+        .signature
+        .verify(attestation.attestation.as_str(), attested.eth_address)
+        .map_err(|e| eyre!("invalid build attestation signature: {}", e))?;
+    Ok(attested)
+}
+
+#[tokio::test]
+async fn test_attest_build() {
+    let build = BuildInfo::current();
+    assert_eq!(build.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(build.cargo_lock_sha256.as_ref().map(String::len), Some(64));
+    assert!(build
+        .rustc
+        .as_ref()
+        .is_some_and(|r| r.starts_with("rustc ")));
+
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+    let mut signed = attest(build.clone(), OperatorId::random(), &wallet)
+        .await
+        .unwrap();
+    let attested = verify(&signed).unwrap();
+    assert_eq!(attested.eth_address, wallet.address());
+    assert_eq!(attested.build, build);
+
+    signed.attestation = signed.attestation.replace(&build.version, "0.0.0-forged");
+    assert!(verify(&signed).is_err());
+}
@@ -1,79 +1,545 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    sync::{Arc, Mutex, RwLock},
+};
 
 use bindings::{
-    bls_registry_coordinator_with_indices::BLSRegistryCoordinatorWithIndices,
-    mangata_service_manager::MangataServiceManager,
-    mangata_task_manager::{MangataTaskManager, NewTaskCreatedFilter},
-    shared_types::Operator,
+    bls_registry_coordinator_with_indices::{
+        BLSRegistryCoordinatorWithIndices, EjectOperatorFromCoordinatorCall,
+        OperatorDeregisteredFilter, BLSREGISTRYCOORDINATORWITHINDICES_ABI,
+    },
+    mangata_task_manager::{
+        CheckSignaturesIndices, MangataTaskManager, NewTaskCreatedFilter, RespondToTaskCall,
+        TaskRespondedFilter, MANGATATASKMANAGER_ABI,
+    },
+    shared_types::{Operator, OperatorSetParam, StrategyAndWeightingMultiplier},
+    stake_registry::{StakeRegistry, StakeUpdateFilter, STAKEREGISTRY_ABI},
 };
 use ethers::{
-    contract::Event,
-    providers::{Provider, Ws},
-    types::{Address, TransactionReceipt, H256},
+    abi::{parse_abi, Abi, AbiDecode, Detokenize, RawLog},
+    contract::{builders::ContractCall, Contract, EthCall, LogMeta},
+    providers::{Middleware, PubsubClient},
+    types::{Address, BlockNumber, Filter, TransactionReceipt, H256, U256},
 };
-use eyre::{Ok, OptionExt};
+use eyre::{eyre, Ok, OptionExt};
+use futures::{stream::BoxStream, StreamExt};
+use serde::Serialize;
+use tracing::info;
 
 use crate::{
+    addresses::AddressBook,
     cli::CliArgs,
-    crypto::{bn254::BlsKeypair, EthConvert},
+    constants::ChainConstants,
+    crypto::{
+        bn254::{BlsKeypair, PublicKey},
+        EthConvert,
+    },
+    store::{QuorumMembers, QuorumSnapshot},
+};
+
+use super::{
+    breaker::{CircuitBreaker, Guarded},
+    build_ws_provider,
+    events::{read_abis, AnyLog, EventRegistry},
+    logs::{query_chunked, query_chunked_with_meta},
+    poll::{poll_logs, PollSchedule},
+    service_manager::{MangataServiceManagerClient, ServiceManagerClient},
+    Client, WsProvider,
 };
 
-use super::Client;
+#[derive(Debug, Serialize)]
+pub struct QuorumStatus {
+    pub quorum_number: u8,
+    pub total_stake: u128,
+    pub operator_count: usize,
+    pub top10_stake: u128,
+    /// Share of the total stake held by the 10 largest operators, in percent
+    pub top10_concentration_pct: f64,
+    pub minimum_stake: u128,
+    pub max_operator_count: u32,
+    pub kick_bips_of_operator_stake: u16,
+    pub kick_bips_of_total_stake: u16,
+    pub own_stake: u128,
+    pub own_share_pct: f64,
+}
+
+/// Denominator of the kick thresholds of the registry coordinator.
+const BIPS_DENOMINATOR: u128 = 10_000;
+
+/// Whether and how the operator can join a quorum, see [`advise_quorum`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "advice", rename_all = "kebab-case")]
+pub enum JoinAdvice {
+    /// The operator is already a member
+    Member,
+    /// The quorum has room and the operator has the minimum stake
+    JoinNow,
+    /// The quorum is full, registering with churn can kick out one of `candidates`, smallest
+    /// stake first. Churning needs the approval of the churn approver.
+    Churn { candidates: Vec<ChurnCandidate> },
+    /// `shortfall` more stake is needed, to kick out `churn` when the quorum is full
+    Shortfall {
+        shortfall: u128,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        churn: Option<ChurnCandidate>,
+    },
+    /// The quorum is full and its kick thresholds prevent churning out anyone
+    Closed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChurnCandidate {
+    pub operator_id: H256,
+    pub stake: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuorumAdvice {
+    pub quorum_number: u8,
+    /// Stake the operator has or would have in the quorum
+    pub own_stake: u128,
+    pub minimum_stake: u128,
+    pub operator_count: usize,
+    pub max_operator_count: u32,
+    #[serde(flatten)]
+    pub advice: JoinAdvice,
+}
+
+/// Advises joining a quorum of `members` with `own_stake`, following the rules the registry
+/// coordinator applies: the minimum stake, then when the quorum is full, a churned out
+/// operator must have less than `kickBIPsOfOperatorStake` of the new stake and less than
+/// `kickBIPsOfTotalStake` of the total stake including the new one.
+pub fn advise_quorum(
+    own_id: Option<H256>,
+    own_stake: u128,
+    minimum_stake: u128,
+    params: &OperatorSetParam,
+    members: &[(H256, u128)],
+) -> JoinAdvice {
+    if own_id.is_some_and(|id| members.iter().any(|(member, _)| *member == id)) {
+        return JoinAdvice::Member;
+    }
+    if members.len() < params.max_operator_count as usize {
+        return match minimum_stake.checked_sub(own_stake) {
+            Some(shortfall) if shortfall > 0 => JoinAdvice::Shortfall {
+                shortfall,
+                churn: None,
+            },
+            _ => JoinAdvice::JoinNow,
+        };
+    }
+
+    let total: u128 = members.iter().map(|(_, stake)| stake).sum();
+    let operator_bips = params.kick_bi_ps_of_operator_stake as u128;
+    let total_bips = params.kick_bi_ps_of_total_stake as u128;
+    // least stake kicking out `stake`, the new stake also counting towards the total
+    let stake_to_kick = |stake: u128| {
+        if total_bips == 0 {
+            return None;
+        }
+        let over_operator = stake.saturating_mul(operator_bips) / BIPS_DENOMINATOR + 1;
+        let over_total = (stake + 1)
+            .saturating_mul(BIPS_DENOMINATOR)
+            .div_ceil(total_bips)
+            .saturating_sub(total);
+        Some(over_operator.max(over_total))
+    };
+    let mut members: Vec<ChurnCandidate> = members
+        .iter()
+        .map(|(operator_id, stake)| ChurnCandidate {
+            operator_id: *operator_id,
+            stake: *stake,
+        })
+        .collect();
+    members.sort_by_key(|m| m.stake);
+
+    let candidates: Vec<ChurnCandidate> = members
+        .iter()
+        .filter(|m| stake_to_kick(m.stake).is_some_and(|needed| own_stake >= needed))
+        .cloned()
+        .collect();
+    if own_stake >= minimum_stake && !candidates.is_empty() {
+        return JoinAdvice::Churn { candidates };
+    }
+    match members
+        .into_iter()
+        .filter_map(|m| Some((stake_to_kick(m.stake)?, m)))
+        .min_by_key(|(needed, _)| *needed)
+    {
+        Some((needed, churn)) => JoinAdvice::Shortfall {
+            shortfall: needed.max(minimum_stake).saturating_sub(own_stake),
+            churn: Some(churn),
+        },
+        None => JoinAdvice::Closed,
+    }
+}
+
+/// How the stake registry weighs the shares delegated to an operator in a quorum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuorumWeights {
+    pub multipliers: Vec<StrategyAndWeightingMultiplier>,
+    pub divisor: U256,
+    pub minimum_stake: u128,
+}
+
+impl QuorumWeights {
+    /// Stake of `shares` in `strategies`, summed over the strategies of the quorum as
+    /// `weightOfOperatorForQuorum` does.
+    pub fn weigh(&self, strategies: &[Address], shares: &[U256]) -> u128 {
+        if self.divisor.is_zero() {
+            return 0;
+        }
+        let weight = strategies
+            .iter()
+            .zip(shares)
+            .filter_map(|(strategy, shares)| {
+                let multiplier = self
+                    .multipliers
+                    .iter()
+                    .find(|m| m.strategy == *strategy)?
+                    .multiplier;
+                Some(shares.saturating_mul(multiplier.into()) / self.divisor)
+            })
+            .fold(U256::zero(), U256::saturating_add);
+        u128::try_from(weight).unwrap_or(u128::MAX)
+    }
+}
 
 pub struct AvsContracts {
-    service_manager: MangataServiceManager<Client>,
-    task_manager: MangataTaskManager<Client>,
-    task_manager_sub: MangataTaskManager<Provider<Ws>>,
-    registry: BLSRegistryCoordinatorWithIndices<Client>,
+    service_manager: Arc<dyn ServiceManagerClient>,
+    task_manager: Guarded<MangataTaskManager<Client>>,
+    /// Subscriptions to contract events, polled over HTTP without a websocket endpoint
+    ws: Option<WsProvider>,
+    poll: PollSchedule,
+    registry: Guarded<BLSRegistryCoordinatorWithIndices<Client>>,
+    stake_registry: Guarded<StakeRegistry<Client>>,
+    new_task_events: EventRegistry<NewTaskCreatedFilter>,
+    task_responded_events: EventRegistry<TaskRespondedFilter>,
+    constants: ChainConstants,
+    stakes: RwLock<StakeCache>,
+    /// Quorum snapshots by reference block and quorum numbers, the oldest dropped first
+    snapshots: Mutex<BTreeMap<(u32, Vec<u8>), QuorumSnapshot>>,
     client: Arc<Client>,
 }
 
+/// Quorum snapshots kept in memory, for the recent tasks aggregated from the gossip.
+const SNAPSHOT_CACHE_SIZE: usize = 64;
+
+/// Latest stake of every operator in every quorum, replayed from the `StakeUpdate` events.
+#[derive(Debug, Default)]
+struct StakeCache {
+    stakes: HashMap<u8, HashMap<[u8; 32], u128>>,
+    /// Last block whose events were applied
+    synced_to: Option<u64>,
+}
+
 impl Debug for AvsContracts {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AvsContracts")
             .field("service_manager", &self.service_manager.address())
             .field("task_manager", &self.task_manager.address())
-            .field("task_manager_sub", &self.task_manager_sub.address())
+            .field(
+                "event_source",
+                &if self.ws.is_some() { "ws" } else { "poll" },
+            )
             .field("registry", &self.registry.address())
+            .field("stake_registry", &self.stake_registry.address())
             .finish()
     }
 }
 
 impl AvsContracts {
-    const QUORUM: [u8; 1] = [0_u8; 1];
-
-    pub async fn build(config: &CliArgs, client: Arc<Client>) -> eyre::Result<Self> {
-        let ws = Arc::new(Provider::connect(config.eth_ws_url.to_owned()).await?);
+    pub async fn build(
+        config: &CliArgs,
+        constants: &ChainConstants,
+        client: Arc<Client>,
+    ) -> eyre::Result<Self> {
+        let service_manager = Arc::new(MangataServiceManagerClient::new(
+            AddressBook::resolve(config)?.service_manager,
+            client.clone(),
+            CircuitBreaker::new("service_manager", &config.contract_calls),
+        ));
+        Self::with_service_manager(config, constants, client, service_manager).await
+    }
 
-        let service_manager =
-            MangataServiceManager::new(config.avs_service_manager_addr, client.clone());
+    /// Contracts of the AVS found through `service_manager`.
+    pub async fn with_service_manager(
+        config: &CliArgs,
+        constants: &ChainConstants,
+        client: Arc<Client>,
+        service_manager: Arc<dyn ServiceManagerClient>,
+    ) -> eyre::Result<Self> {
+        let ws = match &config.eth_ws_url {
+            Some(url) => Some(build_ws_provider(url).await?),
+            None => {
+                info!("No websocket endpoint, polling contract events over HTTP");
+                None
+            }
+        };
 
+        let calls = &config.contract_calls;
         let task_manager_addr = service_manager.task_manager().await?;
-        let task_manager = MangataTaskManager::new(task_manager_addr, client.clone());
-        let task_manager_sub = MangataTaskManager::new(task_manager_addr, ws);
+        let task_manager = Guarded::new(
+            MangataTaskManager::new(task_manager_addr, client.clone()),
+            CircuitBreaker::new("task_manager", calls),
+        );
 
         let registry_addr = service_manager.registry_coordinator().await?;
-        let registry = BLSRegistryCoordinatorWithIndices::new(registry_addr, client.clone());
+        let registry = Guarded::new(
+            BLSRegistryCoordinatorWithIndices::new(registry_addr, client.clone()),
+            CircuitBreaker::new("registry_coordinator", calls),
+        );
 
+        let stake_registry_addr = service_manager.stake_registry().await?;
+        let stake_registry = Guarded::new(
+            StakeRegistry::new(stake_registry_addr, client.clone()),
+            CircuitBreaker::new("stake_registry", calls),
+        );
+
+        let versions = read_abis(&config.event_abis)?;
         Ok(Self {
             service_manager,
             task_manager,
-            task_manager_sub,
+            ws,
+            poll: PollSchedule::new(&config.poll),
             registry,
+            stake_registry,
+            new_task_events: EventRegistry::new(&MANGATATASKMANAGER_ABI, &versions)?,
+            task_responded_events: EventRegistry::new(&MANGATATASKMANAGER_ABI, &versions)?,
+            constants: constants.clone(),
+            stakes: RwLock::default(),
+            snapshots: Mutex::default(),
             client,
         })
     }
 
-    pub fn new_task_stream(&self) -> Event<Arc<Provider<Ws>>, Provider<Ws>, NewTaskCreatedFilter> {
-        self.task_manager_sub.new_task_created_filter()
+    /// Logs of all known shapes of `NewTaskCreated`, to decode with [`Self::decode_new_task`].
+    pub async fn new_task_stream(&self) -> eyre::Result<BoxStream<'_, (AnyLog, LogMeta)>> {
+        self.task_manager_logs(self.new_task_events.filter()).await
+    }
+
+    /// Logs of all known shapes of `TaskResponded`, to decode with
+    /// [`Self::decode_task_responded`].
+    pub async fn task_responded_stream(&self) -> eyre::Result<BoxStream<'_, (AnyLog, LogMeta)>> {
+        self.task_manager_logs(self.task_responded_events.filter())
+            .await
+    }
+
+    /// Task manager logs matching `filter` from the next block, subscribed to over the
+    /// websocket or polled over HTTP.
+    async fn task_manager_logs(
+        &self,
+        filter: Filter,
+    ) -> eyre::Result<BoxStream<'_, (AnyLog, LogMeta)>> {
+        let filter = filter.address(self.task_manager.address());
+        let logs = match &self.ws {
+            Some(ws) => subscribe_logs(ws, &filter).await?,
+            None => poll_logs(self.client.as_ref(), filter, self.poll.clone()).boxed(),
+        };
+        Ok(logs
+            .map(|(log, meta)| {
+                let log = RawLog::from(log);
+                #[cfg(feature = "fault-injection")]
+                let log = crate::faults::corrupt_event(log);
+                (AnyLog(log), meta)
+            })
+            .boxed())
+    }
+
+    pub fn decode_new_task(&self, log: &AnyLog) -> Option<NewTaskCreatedFilter> {
+        self.new_task_events.decode_or_warn(&log.0)
+    }
+
+    pub fn decode_task_responded(&self, log: &AnyLog) -> Option<TaskRespondedFilter> {
+        self.task_responded_events.decode_or_warn(&log.0)
+    }
+
+    /// Returns the tasks created since `from_block`, in creation order.
+    pub async fn tasks_created_since(
+        &self,
+        from_block: u64,
+    ) -> eyre::Result<Vec<NewTaskCreatedFilter>> {
+        let latest = self.client.get_block_number().await?.as_u64();
+        let logs = query_chunked(
+            self.task_manager
+                .event_with_filter::<AnyLog>(self.new_task_events.filter()),
+            from_block,
+            latest,
+        )
+        .await?;
+        Ok(logs
+            .iter()
+            .filter_map(|log| self.decode_new_task(log))
+            .collect())
+    }
+
+    /// Returns the task responses submitted since `from_block` with the transactions that
+    /// submitted them, in submission order.
+    pub async fn task_responses_since(
+        &self,
+        from_block: u64,
+    ) -> eyre::Result<Vec<(TaskRespondedFilter, LogMeta)>> {
+        let latest = self.client.get_block_number().await?.as_u64();
+        let logs = query_chunked_with_meta(
+            self.task_manager
+                .event_with_filter::<AnyLog>(self.task_responded_events.filter()),
+            from_block,
+            latest,
+        )
+        .await?;
+        Ok(logs
+            .into_iter()
+            .filter_map(|(log, meta)| Some((self.decode_task_responded(&log)?, meta)))
+            .collect())
+    }
+
+    /// Deregistrations from the AVS between `from_block` and `to_block`, only those of
+    /// `operator` when given.
+    pub async fn deregistrations(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        operator: Option<Address>,
+    ) -> eyre::Result<Vec<(OperatorDeregisteredFilter, LogMeta)>> {
+        let mut event = self.registry.operator_deregistered_filter();
+        if let Some(operator) = operator {
+            event = event.topic1(operator);
+        }
+        query_chunked_with_meta(event, from_block, to_block).await
+    }
+
+    /// Whether the deregistration sent by `tx_hash` is an ejection by the ejector rather
+    /// than an operator opting out.
+    pub async fn is_ejection(&self, tx_hash: H256) -> eyre::Result<bool> {
+        let tx = self
+            .client
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or_eyre("deregistration transaction not found")?;
+        Ok(tx
+            .input
+            .starts_with(&EjectOperatorFromCoordinatorCall::selector()))
+    }
+
+    /// Accounts allowed to respond to and to create tasks.
+    pub async fn aggregator_and_generator(&self) -> eyre::Result<(Address, Address)> {
+        Ok((
+            self.task_manager.view(|c| c.aggregator()).await?,
+            self.task_manager.view(|c| c.generator()).await?,
+        ))
+    }
+
+    pub async fn task_response_window(&self) -> eyre::Result<u32> {
+        if let Some(window) = self.constants.task_response_window_blocks {
+            return Ok(window);
+        }
+        self.task_manager
+            .view(|c| c.task_response_window_block())
+            .await
+    }
+
+    /// Number of tasks created, the index of the next one.
+    pub async fn task_count(&self) -> eyre::Result<u32> {
+        self.task_manager.view(|c| c.latest_task_num()).await
+    }
+
+    pub async fn is_task_responded(&self, task_index: u32) -> eyre::Result<bool> {
+        let response = self
+            .task_manager
+            .view(|c| c.all_task_responses(task_index))
+            .await?;
+        Ok(response != [0_u8; 32])
+    }
+
+    /// Hash of a `respondToTask` transaction for `task_index` in the pending block of the
+    /// node, a response about to be confirmed.
+    pub async fn pending_response(&self, task_index: u32) -> eyre::Result<Option<H256>> {
+        let Some(block) = self.client.get_block_with_txs(BlockNumber::Pending).await? else {
+            return Ok(None);
+        };
+        Ok(block
+            .transactions
+            .iter()
+            .filter(|tx| tx.to == Some(self.task_manager.address()))
+            .find(|tx| {
+                RespondToTaskCall::decode(&tx.input)
+                    .is_ok_and(|call| call.task_response.reference_task_index == task_index)
+            })
+            .map(|tx| tx.hash))
+    }
+
+    /// Whether `operator_id` signed the response submitted by the `respondToTask`
+    /// transaction `tx_hash`, i.e. is not one of its non-signers.
+    pub async fn signed_response(&self, tx_hash: H256, operator_id: H256) -> eyre::Result<bool> {
+        let tx = self
+            .client
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or_eyre("respondToTask transaction not found")?;
+        let call = RespondToTaskCall::decode(&tx.input)
+            .map_err(|e| eyre!("{:?} is not a respondToTask call: {}", tx_hash, e))?;
+        Ok(!call
+            .non_signer_stakes_and_signature
+            .non_signer_pubkeys
+            .iter()
+            .filter_map(EthConvert::from_g1)
+            .any(|pubkey| BlsKeypair::operator_id_of(pubkey) == operator_id))
+    }
+
+    /// Upgradeable AVS contracts by name, with the ABIs of their bindings.
+    pub fn contracts(&self) -> Vec<(&'static str, Address, &'static Abi)> {
+        vec![
+            (
+                "service_manager",
+                self.service_manager.address(),
+                self.service_manager.abi(),
+            ),
+            (
+                "task_manager",
+                self.task_manager.address(),
+                &MANGATATASKMANAGER_ABI,
+            ),
+            (
+                "registry_coordinator",
+                self.registry.address(),
+                &BLSREGISTRYCOORDINATORWITHINDICES_ABI,
+            ),
+            (
+                "stake_registry",
+                self.stake_registry.address(),
+                &STAKEREGISTRY_ABI,
+            ),
+        ]
+    }
+
+    /// Owner of the service manager, administering the AVS contracts.
+    pub async fn owner(&self) -> eyre::Result<Address> {
+        self.service_manager.owner().await
+    }
+
+    /// Whether `account` may pause the AVS, per the pauser registry of the task manager.
+    pub async fn is_pauser(&self, account: Address) -> eyre::Result<bool> {
+        let registry_addr = self.task_manager.view(|c| c.pauser_registry()).await?;
+        let abi = parse_abi(&["function isPauser(address) external view returns (bool)"])?;
+        let registry = Contract::new(registry_addr, abi, self.client.clone());
+        Ok(registry
+            .method::<_, bool>("isPauser", account)?
+            .call()
+            .await?)
     }
 
     pub async fn slasher_address(&self) -> eyre::Result<Address> {
-        Ok(self.service_manager.slasher().await?)
+        self.service_manager.slasher().await
     }
 
     pub async fn operator_id(&self) -> eyre::Result<Option<H256>> {
-        let status: Operator = self.registry.get_operator(self.client.address()).await?;
+        self.operator_id_of(self.client.address()).await
+    }
+
+    /// Operator id of `operator`, `None` unless it is registered with the AVS.
+    pub async fn operator_id_of(&self, operator: Address) -> eyre::Result<Option<H256>> {
+        let status: Operator = self.registry.view(|c| c.get_operator(operator)).await?;
         let id: H256 = status.operator_id.into();
         if id.is_zero() || status.status != 1_u8 {
             Ok(None)
@@ -82,37 +548,547 @@ impl AvsContracts {
         }
     }
 
-    pub async fn register_with_avs(
+    pub async fn operator_stake(&self) -> eyre::Result<u128> {
+        self.stake_registry
+            .view(|c| {
+                c.weight_of_operator_for_quorum(
+                    self.constants.stake_quorum(),
+                    self.client.address(),
+                )
+            })
+            .await
+    }
+
+    pub async fn minimum_stake(&self) -> eyre::Result<u128> {
+        self.stake_registry
+            .view(|c| c.minimum_stake_for_quorum(self.constants.stake_quorum().into()))
+            .await
+    }
+
+    pub async fn quorum_strategy(&self) -> eyre::Result<StrategyAndWeightingMultiplier> {
+        self.stake_registry
+            .view(|c| {
+                c.strategy_and_weighting_multiplier_for_quorum_by_index(
+                    self.constants.stake_quorum(),
+                    0.into(),
+                )
+            })
+            .await
+    }
+
+    /// Strategy multipliers and minimum stake of `quorum_number`.
+    pub async fn quorum_weights(&self, quorum_number: u8) -> eyre::Result<QuorumWeights> {
+        let count = self
+            .stake_registry
+            .view(|c| c.strategies_considered_and_multipliers_length(quorum_number))
+            .await?
+            .as_usize();
+        let mut multipliers = Vec::with_capacity(count);
+        for index in 0..count {
+            multipliers.push(
+                self.stake_registry
+                    .view(|c| {
+                        c.strategy_and_weighting_multiplier_for_quorum_by_index(
+                            quorum_number,
+                            index.into(),
+                        )
+                    })
+                    .await?,
+            );
+        }
+        Ok(QuorumWeights {
+            multipliers,
+            divisor: self.stake_registry.view(|c| c.weighting_divisor()).await?,
+            minimum_stake: self
+                .stake_registry
+                .view(|c| c.minimum_stake_for_quorum(quorum_number.into()))
+                .await?,
+        })
+    }
+
+    /// Returns the operator stake and the total stake of every quorum.
+    pub async fn stake_shares(&self) -> eyre::Result<Vec<(u8, u128, u128)>> {
+        let own_id = self.operator_id().await?;
+        let mut shares = vec![];
+        for quorum_number in 0..self.stake_registry.view(|c| c.quorum_count()).await? as u8 {
+            let stake = match own_id {
+                Some(id) => {
+                    self.stake_registry
+                        .view(|c| c.get_current_operator_stake_for_quorum(id.into(), quorum_number))
+                        .await?
+                }
+                None => 0,
+            };
+            let total = self
+                .stake_registry
+                .view(|c| c.get_current_total_stake_for_quorum(quorum_number))
+                .await?;
+            shares.push((quorum_number, stake, total));
+        }
+        Ok(shares)
+    }
+
+    /// Members of `quorums` and their stake at `reference_block`, read from the stake
+    /// histories of the registries so no archive node is needed however old the block. The
+    /// history of a past block does not change, the snapshots are cached.
+    pub async fn quorum_snapshot(
+        &self,
+        reference_block: u32,
+        quorums: &[u8],
+    ) -> eyre::Result<QuorumSnapshot> {
+        let key = (reference_block, quorums.to_vec());
+        if let Some(snapshot) = self.snapshots.lock().expect("poisoned lock").get(&key) {
+            return Ok(snapshot.clone());
+        }
+        let registry = self.registry.address();
+        let members = self
+            .task_manager
+            .view(|c| c.get_operator_state(registry, quorums.to_vec().into(), reference_block))
+            .await?;
+        let snapshot = QuorumSnapshot {
+            reference_block,
+            quorums: quorums
+                .iter()
+                .zip(members)
+                .map(|(quorum_number, operators)| QuorumMembers {
+                    quorum_number: *quorum_number,
+                    total_stake: operators.iter().map(|o| o.stake).sum(),
+                    operators: operators
+                        .into_iter()
+                        .map(|o| (o.operator_id.into(), o.stake))
+                        .collect(),
+                })
+                .collect(),
+        };
+        let mut snapshots = self.snapshots.lock().expect("poisoned lock");
+        snapshots.insert(key, snapshot.clone());
+        while snapshots.len() > SNAPSHOT_CACHE_SIZE {
+            snapshots.pop_first();
+        }
+        Ok(snapshot)
+    }
+
+    /// Indices into the registry histories at `reference_block` which `checkSignatures` needs
+    /// to look up the stakes of `non_signers` and the totals of `quorums` at that block.
+    pub async fn check_signatures_indices(
         &self,
-        keypair: &BlsKeypair,
-    ) -> eyre::Result<TransactionReceipt> {
-        let op_address =
-            EthConvert::to_g1(keypair.public).ok_or_eyre("cannot convert G1 public")?;
+        reference_block: u32,
+        quorums: &[u8],
+        non_signers: &[H256],
+    ) -> eyre::Result<CheckSignaturesIndices> {
+        let registry = self.registry.address();
+        let ids: Vec<[u8; 32]> = non_signers.iter().map(|id| id.0).collect();
+        self.task_manager
+            .view(|c| {
+                c.get_check_signatures_indices(
+                    registry,
+                    reference_block,
+                    quorums.to_vec().into(),
+                    ids,
+                )
+            })
+            .await
+    }
+
+    /// Account `operator_id` was registered by, which stays assigned to it after it
+    /// deregisters.
+    pub async fn operator_address_of_id(&self, operator_id: H256) -> eyre::Result<Address> {
+        self.registry
+            .view(|c| c.get_operator_from_id(operator_id.0))
+            .await
+    }
+
+    /// Summarizes stake distribution and threshold parameters of every quorum.
+    pub async fn quorum_status(&self) -> eyre::Result<Vec<QuorumStatus>> {
+        let own_id = self.operator_id().await?;
+        let latest = self.client.get_block_number().await?.as_u64();
+        self.sync_stakes(latest).await?;
+        let mut stakes = self.stakes.read().expect("poisoned lock").stakes.clone();
+
+        let mut status = vec![];
+        for quorum_number in 0..self.stake_registry.view(|c| c.quorum_count()).await? as u8 {
+            let params: OperatorSetParam = self
+                .registry
+                .view(|c| c.get_operator_set_params(quorum_number))
+                .await?;
+            let quorum_stakes = stakes.remove(&quorum_number).unwrap_or_default();
+            let own_stake = own_id
+                .and_then(|id| quorum_stakes.get(id.as_fixed_bytes()).copied())
+                .unwrap_or_default();
+            let mut weights: Vec<u128> = quorum_stakes.into_values().filter(|s| *s > 0).collect();
+            weights.sort_unstable_by(|a, b| b.cmp(a));
+
+            let total_stake = self
+                .stake_registry
+                .view(|c| c.get_current_total_stake_for_quorum(quorum_number))
+                .await?;
+            let top10_stake = weights.iter().take(10).sum();
+            status.push(QuorumStatus {
+                quorum_number,
+                total_stake,
+                operator_count: weights.len(),
+                top10_stake,
+                top10_concentration_pct: share_pct(top10_stake, total_stake),
+                minimum_stake: self
+                    .stake_registry
+                    .view(|c| c.minimum_stake_for_quorum(quorum_number.into()))
+                    .await?,
+                max_operator_count: params.max_operator_count,
+                kick_bips_of_operator_stake: params.kick_bi_ps_of_operator_stake,
+                kick_bips_of_total_stake: params.kick_bi_ps_of_total_stake,
+                own_stake,
+                own_share_pct: share_pct(own_stake, total_stake),
+            });
+        }
+        Ok(status)
+    }
+
+    /// Advises on joining every quorum from the stake the operator has in it, the members
+    /// at the chain head and the churn parameters.
+    pub async fn advise_quorums(&self) -> eyre::Result<Vec<QuorumAdvice>> {
+        let own_id = self.operator_id().await?;
+        let head = self.client.get_block_number().await?.as_u32();
+        let quorums: Vec<u8> =
+            (0..self.stake_registry.view(|c| c.quorum_count()).await? as u8).collect();
+        let snapshot = self.quorum_snapshot(head, &quorums).await?;
+
+        let mut advice = vec![];
+        for members in snapshot.quorums {
+            let quorum_number = members.quorum_number;
+            let params: OperatorSetParam = self
+                .registry
+                .view(|c| c.get_operator_set_params(quorum_number))
+                .await?;
+            let own_stake = self
+                .stake_registry
+                .view(|c| c.weight_of_operator_for_quorum(quorum_number, self.client.address()))
+                .await?;
+            let minimum_stake = self
+                .stake_registry
+                .view(|c| c.minimum_stake_for_quorum(quorum_number.into()))
+                .await?;
+            advice.push(QuorumAdvice {
+                quorum_number,
+                own_stake,
+                minimum_stake,
+                operator_count: members.operators.len(),
+                max_operator_count: params.max_operator_count,
+                advice: advise_quorum(
+                    own_id,
+                    own_stake,
+                    minimum_stake,
+                    &params,
+                    &members.operators,
+                ),
+            });
+        }
+        Ok(advice)
+    }
+
+    /// Catches up with the `StakeUpdate` events up to `to_block` since the previous sync, the
+    /// first sync replays them from genesis unless seeded by [`Self::seed_stakes`].
+    pub async fn sync_stakes(&self, to_block: u64) -> eyre::Result<()> {
+        let from = self
+            .stakes
+            .read()
+            .expect("poisoned lock")
+            .synced_to
+            .map_or(0, |block| block + 1);
+        if from > to_block {
+            return Ok(());
+        }
+        let updates: Vec<StakeUpdateFilter> =
+            query_chunked(self.stake_registry.stake_update_filter(), from, to_block).await?;
+
+        let mut cache = self.stakes.write().expect("poisoned lock");
+        // the last StakeUpdate of an operator in a quorum holds its current stake
+        for update in updates {
+            cache
+                .stakes
+                .entry(update.quorum_number)
+                .or_default()
+                .insert(update.operator_id, update.stake);
+        }
+        cache.synced_to = Some(to_block);
+        Ok(())
+    }
+
+    /// Starts the stake cache from the stakes replayed up to `block` by another node.
+    pub fn seed_stakes(&self, block: u64, quorums: &[QuorumMembers]) {
+        let mut cache = self.stakes.write().expect("poisoned lock");
+        cache.stakes = quorums
+            .iter()
+            .map(|quorum| {
+                let stakes = quorum
+                    .operators
+                    .iter()
+                    .map(|(id, stake)| (id.to_fixed_bytes(), *stake))
+                    .collect();
+                (quorum.quorum_number, stakes)
+            })
+            .collect();
+        cache.synced_to = Some(block);
+    }
+
+    /// Stakes of every quorum as of the last [`Self::sync_stakes`].
+    pub fn cached_stakes(&self) -> Vec<QuorumMembers> {
+        let cache = self.stakes.read().expect("poisoned lock");
+        let mut quorums: Vec<QuorumMembers> = cache
+            .stakes
+            .iter()
+            .map(|(quorum_number, stakes)| {
+                let mut operators: Vec<(H256, u128)> = stakes
+                    .iter()
+                    .map(|(id, stake)| (H256::from(*id), *stake))
+                    .collect();
+                operators.sort_unstable();
+                QuorumMembers {
+                    quorum_number: *quorum_number,
+                    total_stake: operators.iter().map(|(_, stake)| stake).sum(),
+                    operators,
+                }
+            })
+            .collect();
+        quorums.sort_unstable_by_key(|quorum| quorum.quorum_number);
+        quorums
+    }
+
+    pub async fn register_with_avs(&self, public: PublicKey) -> eyre::Result<TransactionReceipt> {
+        self.ensure_not_paused(
+            self.constants.pause_indexes.register_operator,
+            "registration",
+        )
+        .await?;
+        let op_address = EthConvert::to_g1(public).ok_or_eyre("cannot convert G1 public")?;
         let trx = self.registry.register_operator_with_coordinator_1(
-            AvsContracts::QUORUM.into(),
+            self.constants.quorums.clone().into(),
             op_address,
             String::new(),
         );
 
+        simulate(&trx, "register_with_avs").await?;
         let pending = trx.send().await?;
         let receipt = pending.await?;
 
         receipt.ok_or_eyre("register_with_avs trx failed")
     }
 
-    pub async fn deregister_with_avs(
-        &self,
-        keypair: &BlsKeypair,
-    ) -> eyre::Result<TransactionReceipt> {
-        let op_address =
-            EthConvert::to_g1(keypair.public).ok_or_eyre("cannot convert G1 public")?;
-        let trx = self
-            .registry
-            .deregister_operator_with_coordinator(AvsContracts::QUORUM.into(), op_address);
+    pub async fn deregister_with_avs(&self, public: PublicKey) -> eyre::Result<TransactionReceipt> {
+        self.ensure_not_paused(
+            self.constants.pause_indexes.deregister_operator,
+            "deregistration",
+        )
+        .await?;
+        let op_address = EthConvert::to_g1(public).ok_or_eyre("cannot convert G1 public")?;
+        let trx = self.registry.deregister_operator_with_coordinator(
+            self.constants.quorums.clone().into(),
+            op_address,
+        );
 
+        simulate(&trx, "deregister_with_avs").await?;
         let pending = trx.send().await?;
         let receipt = pending.await?;
 
         receipt.ok_or_eyre("register_with_avs trx failed")
     }
+
+    async fn ensure_not_paused(&self, index: u8, action: &str) -> eyre::Result<()> {
+        if self.registry.view(|c| c.paused_with_index(index)).await? {
+            return Err(eyre!(
+                "operator {} is paused on the registry coordinator (pause index {})",
+                action,
+                index
+            ));
+        }
+        Ok(())
+    }
+}
+
+async fn subscribe_logs<'a, P: PubsubClient>(
+    provider: &'a ethers::providers::Provider<P>,
+    filter: &Filter,
+) -> eyre::Result<BoxStream<'a, (ethers::types::Log, LogMeta)>> {
+    Ok(provider
+        .subscribe_logs(filter)
+        .await?
+        .map(|log| {
+            let meta = LogMeta::from(&log);
+            (log, meta)
+        })
+        .boxed())
+}
+
+/// Registry revert reasons mapped to what the operator can do about them.
+const REVERT_HINTS: &[(&str, &str)] = &[
+    (
+        "quorum is overfilled",
+        "the quorum is full, registering requires churning out an operator with less stake",
+    ),
+    (
+        "does not meet minimum stake",
+        "insufficient stake, deposit or get delegated more stake in the quorum strategy",
+    ),
+    (
+        "operator already registered",
+        "the operator is already registered with the AVS",
+    ),
+    (
+        "operator is not registered",
+        "the operator is not registered with the AVS",
+    ),
+    (
+        "does not own pubkey",
+        "the BLS public key is not registered to this operator in the pubkey compendium",
+    ),
+    (
+        "opted into slashing",
+        "the operator did not opt into slashing by the AVS service manager",
+    ),
+    (
+        "index is paused",
+        "registration is currently paused by the AVS",
+    ),
+];
+
+/// Simulates `call` with `eth_call` so registry reverts are reported before sending it.
+async fn simulate<D: Detokenize>(call: &ContractCall<Client, D>, action: &str) -> eyre::Result<()> {
+    match call.call().await {
+        std::result::Result::Ok(_) => Ok(()),
+        Err(e) => Err(match e.decode_revert::<String>() {
+            Some(reason) => eyre!("{} would revert: {}", action, explain_revert(&reason)),
+            None => eyre!("{} simulation failed: {}", action, e),
+        }),
+    }
+}
+
+fn explain_revert(reason: &str) -> String {
+    match REVERT_HINTS
+        .iter()
+        .find(|(needle, _)| reason.contains(needle))
+    {
+        Some((_, hint)) => format!("{} ({})", hint, reason),
+        None => reason.to_owned(),
+    }
+}
+
+pub(crate) fn share_pct(part: u128, total: u128) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    part as f64 * 100.0 / total as f64
+}
+
+#[test]
+fn test_share_pct() {
+    assert_eq!(share_pct(0, 0), 0.0);
+    assert_eq!(share_pct(25, 100), 25.0);
+    assert_eq!(share_pct(u128::MAX, u128::MAX), 100.0);
+}
+
+#[test]
+fn test_explain_revert() {
+    let reason = "StakeRegistry._registerOperator: Operator does not meet minimum stake requirement for quorum";
+    assert!(explain_revert(reason).starts_with("insufficient stake"));
+    assert_eq!(explain_revert("unknown"), "unknown");
+}
+
+#[test]
+fn test_quorum_weights() {
+    let weights = QuorumWeights {
+        multipliers: vec![
+            StrategyAndWeightingMultiplier {
+                strategy: Address::repeat_byte(1),
+                multiplier: 2_000,
+            },
+            StrategyAndWeightingMultiplier {
+                strategy: Address::repeat_byte(2),
+                multiplier: 500,
+            },
+        ],
+        divisor: 1_000.into(),
+        minimum_stake: 0,
+    };
+    let strategies = [
+        Address::repeat_byte(1),
+        Address::repeat_byte(2),
+        Address::repeat_byte(3),
+    ];
+    assert_eq!(
+        weights.weigh(&strategies, &[10.into(), 10.into(), 1_000.into()]),
+        25
+    );
+    assert_eq!(weights.weigh(&strategies[2..], &[1_000.into()]), 0);
+}
+
+#[test]
+fn test_advise_quorum() {
+    let id = H256::repeat_byte;
+    let params = OperatorSetParam {
+        max_operator_count: 3,
+        kick_bi_ps_of_operator_stake: 15_000,
+        kick_bi_ps_of_total_stake: 1_000,
+    };
+    let members = [(id(1), 100), (id(2), 1_000), (id(3), 2_000)];
+    assert_eq!(
+        advise_quorum(Some(id(2)), 0, 10, &params, &members),
+        JoinAdvice::Member
+    );
+    assert_eq!(
+        advise_quorum(None, 50, 10, &params, &members[..2]),
+        JoinAdvice::JoinNow
+    );
+    assert_eq!(
+        advise_quorum(None, 4, 10, &params, &members[..2]),
+        JoinAdvice::Shortfall {
+            shortfall: 6,
+            churn: None
+        }
+    );
+
+    // full: 100 is kicked out by over 150, and is under 10% of 3_100 plus the new stake
+    let churn = ChurnCandidate {
+        operator_id: id(1),
+        stake: 100,
+    };
+    assert_eq!(
+        advise_quorum(None, 151, 10, &params, &members),
+        JoinAdvice::Churn {
+            candidates: vec![churn.clone()]
+        }
+    );
+    assert_eq!(
+        advise_quorum(None, 150, 10, &params, &members),
+        JoinAdvice::Shortfall {
+            shortfall: 1,
+            churn: Some(churn.clone())
+        }
+    );
+    assert_eq!(
+        advise_quorum(None, 150, 200, &params, &members),
+        JoinAdvice::Shortfall {
+            shortfall: 50,
+            churn: Some(churn)
+        }
+    );
+
+    // 1_000 is over 10% of the total, unless the new stake raises it past 10_010
+    let members = [(id(2), 1_000), (id(3), 2_000), (id(4), 3_000)];
+    assert_eq!(
+        advise_quorum(None, 1_501, 10, &params, &members),
+        JoinAdvice::Shortfall {
+            shortfall: 2_509,
+            churn: Some(ChurnCandidate {
+                operator_id: id(2),
+                stake: 1_000
+            })
+        }
+    );
+    let closed = OperatorSetParam {
+        kick_bi_ps_of_total_stake: 0,
+        ..params
+    };
+    assert_eq!(
+        advise_quorum(None, u128::MAX, 10, &closed, &members),
+        JoinAdvice::Closed
+    );
 }
@@ -0,0 +1,266 @@
+use ethers::types::{Address, Bytes, H256};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use tracing::instrument;
+
+use crate::metrics::metrics;
+
+/// Prefix of the withdrawal credentials of a validator withdrawing to an execution address,
+/// followed by 11 zero bytes and the address.
+const ETH1_WITHDRAWAL_PREFIX: u8 = 0x01;
+
+/// Client of the standard beacon node REST API, reading the validators natively restaked
+/// through EigenPods.
+#[derive(Debug)]
+pub struct BeaconClient {
+    client: ClientWithMiddleware,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response<T> {
+    data: T,
+}
+
+/// Signed block header, the block `root` is what EigenPod proofs are checked against.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BeaconHeader {
+    pub root: H256,
+    pub header: SignedHeader,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SignedHeader {
+    pub message: HeaderMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HeaderMessage {
+    #[serde(deserialize_with = "quoted_u64")]
+    pub slot: u64,
+    pub state_root: H256,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BeaconValidator {
+    #[serde(deserialize_with = "quoted_u64")]
+    pub index: u64,
+    /// Balance in gwei
+    #[serde(deserialize_with = "quoted_u64")]
+    pub balance: u64,
+    pub status: String,
+    pub validator: ValidatorRecord,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ValidatorRecord {
+    pub pubkey: Bytes,
+    pub withdrawal_credentials: H256,
+    /// Effective balance in gwei
+    #[serde(deserialize_with = "quoted_u64")]
+    pub effective_balance: u64,
+    pub slashed: bool,
+}
+
+/// The beacon API encodes integers as decimal strings.
+fn quoted_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+impl BeaconClient {
+    pub fn new(url: &str) -> Self {
+        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+        Self {
+            client,
+            url: url.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, endpoint: &str, path: &str) -> eyre::Result<T> {
+        metrics().record_rpc_call("beacon", endpoint);
+        let res = self
+            .client
+            .get(format!("{}{}", self.url, path))
+            .send()
+            .await?;
+        let status = res.status();
+        let body = res.text().await?;
+        if !status.is_success() {
+            eyre::bail!("beacon node replied {} to {}: {}", status, path, body);
+        }
+        Ok(serde_json::from_str::<Response<T>>(&body)?.data)
+    }
+
+    /// Header of the block `block_id`: `head`, `finalized`, a slot or a block root.
+    #[instrument(skip(self))]
+    pub async fn header(&self, block_id: &str) -> eyre::Result<BeaconHeader> {
+        self.get(
+            "beacon_headers",
+            &format!("/eth/v1/beacon/headers/{}", block_id),
+        )
+        .await
+    }
+
+    /// Validators of the state `state_id` by index or pubkey, unknown ones are omitted.
+    #[instrument(skip(self, ids))]
+    pub async fn validators(
+        &self,
+        state_id: &str,
+        ids: &[String],
+    ) -> eyre::Result<Vec<BeaconValidator>> {
+        self.get(
+            "beacon_validators",
+            &format!(
+                "/eth/v1/beacon/states/{}/validators?id={}",
+                state_id,
+                ids.join(",")
+            ),
+        )
+        .await
+    }
+}
+
+/// Withdrawal credentials of the validators restaked through the EigenPod `pod`.
+pub fn pod_withdrawal_credentials(pod: Address) -> H256 {
+    let mut credentials = H256::zero();
+    credentials.0[0] = ETH1_WITHDRAWAL_PREFIX;
+    credentials.0[12..].copy_from_slice(pod.as_bytes());
+    credentials
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PodValidator {
+    pub index: u64,
+    pub pubkey: Bytes,
+    pub status: String,
+    pub balance_gwei: u64,
+    pub effective_balance_gwei: u64,
+    pub slashed: bool,
+    /// Whether the validator withdraws to the EigenPod, it is not restaked otherwise
+    pub withdraws_to_pod: bool,
+}
+
+/// Validators of an EigenPod at a finalized beacon block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NativeRestaking {
+    pub eigen_pod: Address,
+    pub slot: u64,
+    pub block_root: H256,
+    pub state_root: H256,
+    /// Sum of the effective balances of the active, unslashed validators withdrawing to the
+    /// pod, the stake EigenLayer credits at most
+    pub restaked_gwei: u64,
+    pub validators: Vec<PodValidator>,
+    /// Requested validators unknown to the beacon node
+    pub missing: Vec<String>,
+}
+
+impl NativeRestaking {
+    pub fn new(
+        eigen_pod: Address,
+        header: &BeaconHeader,
+        requested: &[String],
+        validators: Vec<BeaconValidator>,
+    ) -> Self {
+        let credentials = pod_withdrawal_credentials(eigen_pod);
+        let validators: Vec<PodValidator> = validators
+            .into_iter()
+            .map(|v| PodValidator {
+                index: v.index,
+                withdraws_to_pod: v.validator.withdrawal_credentials == credentials,
+                pubkey: v.validator.pubkey,
+                status: v.status,
+                balance_gwei: v.balance,
+                effective_balance_gwei: v.validator.effective_balance,
+                slashed: v.validator.slashed,
+            })
+            .collect();
+        let missing = requested
+            .iter()
+            .filter(|id| {
+                !validators.iter().any(|v| {
+                    **id == v.index.to_string() || id.eq_ignore_ascii_case(&v.pubkey.to_string())
+                })
+            })
+            .cloned()
+            .collect();
+        Self {
+            eigen_pod,
+            slot: header.header.message.slot,
+            block_root: header.root,
+            state_root: header.header.message.state_root,
+            restaked_gwei: validators
+                .iter()
+                .filter(|v| v.withdraws_to_pod && !v.slashed && v.status.starts_with("active"))
+                .map(|v| v.effective_balance_gwei)
+                .sum(),
+            validators,
+            missing,
+        }
+    }
+}
+
+#[test]
+fn test_native_restaking() {
+    let pod = Address::repeat_byte(0xab);
+    let credentials = pod_withdrawal_credentials(pod);
+    assert_eq!(
+        format!("{:?}", credentials),
+        format!("0x010000000000000000000000{}", "ab".repeat(20))
+    );
+
+    let header: BeaconHeader = serde_json::from_value::<Response<_>>(serde_json::json!({
+        "execution_optimistic": false,
+        "finalized": true,
+        "data": {
+            "root": format!("0x{}", "11".repeat(32)),
+            "canonical": true,
+            "header": {
+                "message": {
+                    "slot": "8000",
+                    "proposer_index": "4",
+                    "parent_root": format!("0x{}", "22".repeat(32)),
+                    "state_root": format!("0x{}", "33".repeat(32)),
+                    "body_root": format!("0x{}", "44".repeat(32)),
+                },
+                "signature": "0x00",
+            }
+        }
+    }))
+    .unwrap()
+    .data;
+    let validator = |index: u64, credentials: H256, status: &str| {
+        serde_json::json!({
+            "index": index.to_string(),
+            "balance": "32000001000",
+            "status": status,
+            "validator": {
+                "pubkey": format!("0x{:096x}", index),
+                "withdrawal_credentials": credentials,
+                "effective_balance": "32000000000",
+                "slashed": false,
+                "activation_epoch": "0",
+            }
+        })
+    };
+    let validators: Vec<BeaconValidator> = serde_json::from_value(serde_json::json!([
+        validator(1, credentials, "active_ongoing"),
+        validator(2, H256::zero(), "active_ongoing"),
+        validator(3, credentials, "withdrawal_done"),
+    ]))
+    .unwrap();
+
+    let requested = ["1", "2", "3", "9"].map(String::from);
+    let report = NativeRestaking::new(pod, &header, &requested, validators);
+    assert_eq!(report.slot, 8000);
+    assert_eq!(report.state_root, H256::repeat_byte(0x33));
+    assert_eq!(report.restaked_gwei, 32_000_000_000);
+    assert!(!report.validators[1].withdraws_to_pod);
+    assert_eq!(report.missing, vec!["9".to_owned()]);
+}
@@ -0,0 +1,233 @@
+use std::{
+    future::Future,
+    ops::Deref,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use ethers::{abi::Detokenize, contract::builders::ContractCall};
+use eyre::eyre;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::{cli::ContractCallArgs, metrics::metrics};
+
+use super::Client;
+
+static BREAKERS: OnceLock<Mutex<Vec<Arc<CircuitBreaker>>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    /// Cooldown elapsed, the next call probes whether the contract recovered
+    HalfOpen,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CircuitStatus {
+    pub contract: &'static str,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Bounds the duration of calls to a contract and stops calling it for `cooldown` once
+/// `failure_threshold` consecutive calls failed or timed out.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    contract: &'static str,
+    timeout: Duration,
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+/// States of all the circuit breakers created by the process.
+pub fn circuits() -> Vec<CircuitStatus> {
+    BREAKERS
+        .get_or_init(Mutex::default)
+        .lock()
+        .expect("poisoned lock")
+        .iter()
+        .map(|b| b.status())
+        .collect()
+}
+
+impl CircuitBreaker {
+    pub fn new(contract: &'static str, cfg: &ContractCallArgs) -> Arc<Self> {
+        let breaker = Arc::new(Self::with_limits(
+            contract,
+            Duration::from_millis(cfg.timeout_ms(contract)),
+            cfg.circuit_failure_threshold,
+            Duration::from_secs(cfg.circuit_cooldown_secs),
+        ));
+        BREAKERS
+            .get_or_init(Mutex::default)
+            .lock()
+            .expect("poisoned lock")
+            .push(breaker.clone());
+        breaker
+    }
+
+    fn with_limits(
+        contract: &'static str,
+        timeout: Duration,
+        failure_threshold: u32,
+        cooldown: Duration,
+    ) -> Self {
+        Self {
+            contract,
+            timeout,
+            failure_threshold,
+            cooldown,
+            state: Mutex::new(BreakerState {
+                failures: 0,
+                opened_at: None,
+            }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock().expect("poisoned lock");
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(at) if at.elapsed() < self.cooldown => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    pub fn status(&self) -> CircuitStatus {
+        CircuitStatus {
+            contract: self.contract,
+            state: self.state(),
+            consecutive_failures: self.state.lock().expect("poisoned lock").failures,
+            timeout_ms: self.timeout.as_millis() as u64,
+        }
+    }
+
+    /// Runs `fut` within the call timeout, `is_failure` tells which errors count towards
+    /// opening the circuit.
+    pub async fn run<T, E, F>(&self, fut: F, is_failure: impl Fn(&E) -> bool) -> eyre::Result<T>
+    where
+        F: Future<Output = Result<T, E>>,
+        E: Into<eyre::Report>,
+    {
+        if self.state() == CircuitState::Open {
+            return Err(eyre!("circuit open for {} calls", self.contract));
+        }
+        match tokio::time::timeout(self.timeout, fut).await {
+            Err(_) => {
+                self.record(false);
+                Err(eyre!(
+                    "{} call timed out after {:?}",
+                    self.contract,
+                    self.timeout
+                ))
+            }
+            Ok(Err(e)) => {
+                self.record(!is_failure(&e));
+                Err(e.into())
+            }
+            Ok(Ok(v)) => {
+                self.record(true);
+                Ok(v)
+            }
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let mut state = self.state.lock().expect("poisoned lock");
+        if success {
+            if state.opened_at.take().is_some() {
+                info!("Circuit of {} calls closed", self.contract);
+                metrics()
+                    .contract_circuit_open
+                    .with_label_values(&[self.contract])
+                    .set(0);
+            }
+            state.failures = 0;
+            return;
+        }
+        state.failures += 1;
+        let half_open = state
+            .opened_at
+            .is_some_and(|at| at.elapsed() >= self.cooldown);
+        if state.failures >= self.failure_threshold && (state.opened_at.is_none() || half_open) {
+            warn!(
+                "Circuit of {} calls opened after {} consecutive failures",
+                self.contract, state.failures
+            );
+            state.opened_at = Some(Instant::now());
+            metrics()
+                .contract_circuit_open
+                .with_label_values(&[self.contract])
+                .set(1);
+        }
+    }
+}
+
+/// Contract binding whose view calls go through a [`CircuitBreaker`].
+#[derive(Debug)]
+pub struct Guarded<C> {
+    contract: C,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl<C> Guarded<C> {
+    pub fn new(contract: C, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { contract, breaker }
+    }
+
+    /// Sends the `eth_call` built by `call`, reverts do not count as failures of the contract.
+    pub async fn view<D: Detokenize>(
+        &self,
+        call: impl FnOnce(&C) -> ContractCall<Client, D>,
+    ) -> eyre::Result<D> {
+        let call = call(&self.contract);
+        self.breaker.run(call.call(), |e| !e.is_revert()).await
+    }
+}
+
+impl<C> Deref for Guarded<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.contract
+    }
+}
+
+#[tokio::test]
+async fn test_circuit_breaker() {
+    let breaker = CircuitBreaker::with_limits("test", Duration::from_millis(10), 2, Duration::ZERO);
+    let fail = || async { Err::<(), _>(eyre!("unreachable")) };
+
+    assert!(breaker.run(fail(), |_| true).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    // errors which are not failures of the contract reset the count
+    assert!(breaker.run(fail(), |_| false).await.is_err());
+    assert!(breaker.run(fail(), |_| true).await.is_err());
+    assert_eq!(breaker.status().consecutive_failures, 1);
+
+    let hang = async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok::<_, eyre::Report>(())
+    };
+    let res = breaker.run(hang, |_| true).await;
+    assert!(res.unwrap_err().to_string().contains("timed out"));
+    // zero cooldown, the circuit is half open right after opening
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    breaker
+        .run(async { Ok::<_, eyre::Report>(()) }, |_| true)
+        .await
+        .unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
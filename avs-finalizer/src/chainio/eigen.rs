@@ -1,30 +1,176 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    sync::{Arc, RwLock},
+};
 
 use bindings::{
-    bls_public_key_compendium::BLSPublicKeyCompendium, delegation_manager::DelegationManager,
-    shared_types::OperatorDetails, slasher::Slasher,
+    bls_public_key_compendium::BLSPublicKeyCompendium,
+    delegation_manager::{
+        DelegationManager, DelegationManagerEvents, WithdrawalCompletedFilter,
+        WithdrawalQueuedFilter,
+    },
+    erc20_mock::ERC20Mock,
+    i_strategy::IStrategy,
+    shared_types::{OperatorDetails, Withdrawal},
+    slasher::{FrozenStatusResetFilter, OperatorFrozenFilter, Slasher, SlasherEvents},
+    strategy_manager::{
+        StrategyAddedToDepositWhitelistFilter, StrategyManager, StrategyManagerEvents,
+        StrategyRemovedFromDepositWhitelistFilter,
+    },
+};
+use ethers::{
+    contract::{EthEvent, LogMeta, Multicall},
+    types::{Address, TransactionReceipt, H256, U256},
 };
-use ethers::types::{Address, TransactionReceipt};
-use eyre::{Ok, OptionExt};
+use eyre::{eyre, Ok, OptionExt};
+use futures::future::try_join_all;
+use serde::Serialize;
+use tracing::debug;
 
 use crate::{
+    addresses::AddressBook,
     cli::CliArgs,
+    constants::ChainConstants,
     crypto::{bn254::BlsKeypair, EthConvert},
+    units::{self, TokenAmount},
 };
 
-use super::Client;
+use super::{
+    breaker::{CircuitBreaker, Guarded},
+    logs::{query_chunked, query_chunked_with_meta},
+    Client,
+};
 
 pub struct ElContracts {
-    delegation: DelegationManager<Client>,
-    bls_pub_key: BLSPublicKeyCompendium<Client>,
+    delegation: Guarded<DelegationManager<Client>>,
+    bls_pub_key: Guarded<BLSPublicKeyCompendium<Client>>,
+    strategy_manager: Guarded<StrategyManager<Client>>,
+    slasher: Guarded<Slasher<Client>>,
+    deposits_pause_index: u8,
+    whitelist: RwLock<StrategyWhitelist>,
+    withdrawals: RwLock<WithdrawalQueue>,
+    /// Underlying token and its decimals by strategy, which never change
+    tokens: RwLock<BTreeMap<Address, (Address, u8)>>,
     client: Arc<Client>,
 }
 
+/// Strategies whitelisted for deposit, replayed from the strategy manager events.
+#[derive(Debug, Default)]
+struct StrategyWhitelist {
+    strategies: BTreeSet<Address>,
+    /// Last block whose events were applied
+    synced_to: Option<u64>,
+}
+
+/// A strategy added to or removed from the deposit whitelist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyChange {
+    Added(Address),
+    Removed(Address),
+}
+
+impl StrategyWhitelist {
+    /// Applies whitelist events in log order, returning the changes to the tracked strategies.
+    fn apply(&mut self, events: Vec<StrategyManagerEvents>) -> Vec<StrategyChange> {
+        let mut changes = vec![];
+        for event in events {
+            match event {
+                StrategyManagerEvents::StrategyAddedToDepositWhitelistFilter(e)
+                    if self.strategies.insert(e.strategy) =>
+                {
+                    changes.push(StrategyChange::Added(e.strategy))
+                }
+                StrategyManagerEvents::StrategyRemovedFromDepositWhitelistFilter(e)
+                    if self.strategies.remove(&e.strategy) =>
+                {
+                    changes.push(StrategyChange::Removed(e.strategy))
+                }
+                _ => {}
+            }
+        }
+        changes
+    }
+}
+
+/// Withdrawals queued by the stakers delegated to an operator and not completed yet, replayed
+/// from the delegation manager events.
+#[derive(Debug, Default)]
+struct WithdrawalQueue {
+    pending: BTreeMap<[u8; 32], Withdrawal>,
+    /// Last block whose events were applied
+    synced_to: Option<u64>,
+}
+
+impl WithdrawalQueue {
+    /// Applies withdrawal events in log order, keeping the withdrawals from `operator`.
+    /// Migrated withdrawals are queued again under their new root.
+    fn apply(&mut self, operator: Address, events: Vec<DelegationManagerEvents>) {
+        for event in events {
+            match event {
+                DelegationManagerEvents::WithdrawalQueuedFilter(e)
+                    if e.withdrawal.delegated_to == operator =>
+                {
+                    self.pending.insert(e.withdrawal_root, e.withdrawal);
+                }
+                DelegationManagerEvents::WithdrawalCompletedFilter(e) => {
+                    self.pending.remove(&e.withdrawal_root);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StakerDeposits {
+    pub staker: Address,
+    /// (strategy, shares) pairs
+    pub deposits: Vec<(Address, U256)>,
+}
+
+/// Delegation parameters of an operator on the `DelegationManager`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperatorSettings {
+    /// Receives the operator earnings
+    pub earnings_receiver: Address,
+    /// Must approve delegations to the operator, anyone can delegate when zero
+    pub delegation_approver: Address,
+    /// Blocks stakers have to undelegate before an operator change affects them
+    pub staker_opt_out_window_blocks: u32,
+    pub max_staker_opt_out_window_blocks: u32,
+}
+
+impl OperatorSettings {
+    /// Checks `details` against the `DelegationManager` rules before sending them.
+    pub fn validate(&self, details: &OperatorDetails) -> eyre::Result<()> {
+        if details.earnings_receiver.is_zero() {
+            return Err(eyre!("the earnings receiver cannot be the zero address"));
+        }
+        if details.staker_opt_out_window_blocks > self.max_staker_opt_out_window_blocks {
+            return Err(eyre!(
+                "staker opt-out window of {} blocks exceeds the maximum of {} blocks",
+                details.staker_opt_out_window_blocks,
+                self.max_staker_opt_out_window_blocks
+            ));
+        }
+        if details.staker_opt_out_window_blocks < self.staker_opt_out_window_blocks {
+            return Err(eyre!(
+                "the staker opt-out window cannot decrease from {} to {} blocks",
+                self.staker_opt_out_window_blocks,
+                details.staker_opt_out_window_blocks
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl Debug for ElContracts {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ElContracts")
             .field("delegation", &self.delegation.address())
             .field("bls_pub_key", &self.bls_pub_key.address())
+            .field("strategy_manager", &self.strategy_manager.address())
             .finish()
     }
 }
@@ -32,35 +178,75 @@ impl Debug for ElContracts {
 impl ElContracts {
     pub async fn build(
         cfg: &CliArgs,
+        constants: &ChainConstants,
         slasher_addr: Address,
         client: Arc<Client>,
     ) -> eyre::Result<Self> {
+        let calls = &cfg.contract_calls;
         let slasher = Slasher::new(slasher_addr, client.clone());
         let delegation_addr = slasher.delegation().await?;
-        let delegation = DelegationManager::new(delegation_addr, client.clone());
+        let delegation = Guarded::new(
+            DelegationManager::new(delegation_addr, client.clone()),
+            CircuitBreaker::new("delegation_manager", calls),
+        );
+        let strategy_manager_addr = slasher.strategy_manager().await?;
+        let strategy_manager = Guarded::new(
+            StrategyManager::new(strategy_manager_addr, client.clone()),
+            CircuitBreaker::new("strategy_manager", calls),
+        );
+
+        let slasher = Guarded::new(slasher, CircuitBreaker::new("slasher", calls));
 
-        let bls_pubkey_compendium =
-            BLSPublicKeyCompendium::new(cfg.bls_compendium_addr, client.clone());
+        let bls_pubkey_compendium = Guarded::new(
+            BLSPublicKeyCompendium::new(
+                AddressBook::resolve(cfg)?.bls_pubkey_compendium,
+                client.clone(),
+            ),
+            CircuitBreaker::new("bls_pubkey_compendium", calls),
+        );
 
         Ok(Self {
             delegation,
             bls_pub_key: bls_pubkey_compendium,
+            strategy_manager,
+            slasher,
+            deposits_pause_index: constants.pause_indexes.deposits,
+            whitelist: Default::default(),
+            withdrawals: Default::default(),
+            tokens: Default::default(),
             client,
         })
     }
 
     pub async fn is_operator_registered(&self, operator_address: Address) -> eyre::Result<bool> {
-        Ok(self.delegation.is_operator(operator_address).await?)
+        self.delegation
+            .view(|c| c.is_operator(operator_address))
+            .await
+    }
+
+    /// Account allowed to change the strategies whitelisted for deposit.
+    pub async fn strategy_whitelister(&self) -> eyre::Result<Address> {
+        self.strategy_manager
+            .view(|c| c.strategy_whitelister())
+            .await
     }
 
     pub async fn has_operator_pubkey(&self, operator_address: Address) -> eyre::Result<bool> {
         Ok(!self
             .bls_pub_key
-            .operator_to_pubkey_hash(operator_address)
+            .view(|c| c.operator_to_pubkey_hash(operator_address))
             .await?
             .is_empty())
     }
 
+    pub async fn operator_pubkey_hash(&self, operator_address: Address) -> eyre::Result<H256> {
+        Ok(self
+            .bls_pub_key
+            .view(|c| c.operator_to_pubkey_hash(operator_address))
+            .await?
+            .into())
+    }
+
     pub async fn register_as_operator_with_el(
         &self,
         operator_address: Address,
@@ -79,6 +265,47 @@ impl ElContracts {
         receipt.ok_or_eyre("register_as_operator_with_el trx failed")
     }
 
+    pub async fn operator_settings(&self, operator: Address) -> eyre::Result<OperatorSettings> {
+        let details = self
+            .delegation
+            .view(|c| c.operator_details(operator))
+            .await?;
+        let max = self
+            .delegation
+            .view(|c| c.max_staker_opt_out_window_blocks())
+            .await?;
+        Ok(OperatorSettings {
+            earnings_receiver: details.earnings_receiver,
+            delegation_approver: details.delegation_approver,
+            staker_opt_out_window_blocks: details.staker_opt_out_window_blocks,
+            max_staker_opt_out_window_blocks: max.try_into().unwrap_or(u32::MAX),
+        })
+    }
+
+    pub async fn modify_operator_details(
+        &self,
+        details: OperatorDetails,
+    ) -> eyre::Result<TransactionReceipt> {
+        self.delegation
+            .modify_operator_details(details)
+            .send()
+            .await?
+            .await?
+            .ok_or_eyre("modify_operator_details trx failed")
+    }
+
+    pub async fn update_operator_metadata_uri(
+        &self,
+        uri: String,
+    ) -> eyre::Result<TransactionReceipt> {
+        self.delegation
+            .update_operator_metadata_uri(uri)
+            .send()
+            .await?
+            .await?
+            .ok_or_eyre("update_operator_metadata_uri trx failed")
+    }
+
     pub async fn register_bls_pub_key(
         &self,
         keypair: &BlsKeypair,
@@ -101,4 +328,330 @@ impl ElContracts {
 
         receipt.ok_or_eyre("register_bls_pub_key trx failed")
     }
+
+    /// Catches up with the deposit whitelist events up to `to_block` since the previous sync,
+    /// the first sync replays them from genesis unless seeded by [`Self::seed_strategies`].
+    /// Returns the strategies added or removed since.
+    pub async fn sync_strategies(&self, to_block: u64) -> eyre::Result<Vec<StrategyChange>> {
+        let from = self
+            .whitelist
+            .read()
+            .expect("poisoned lock")
+            .synced_to
+            .map_or(0, |block| block + 1);
+        if from > to_block {
+            return Ok(vec![]);
+        }
+        let mut events = self.strategy_manager.events();
+        events.filter = events.filter.topic0(vec![
+            StrategyAddedToDepositWhitelistFilter::signature(),
+            StrategyRemovedFromDepositWhitelistFilter::signature(),
+        ]);
+        let events = query_chunked(events, from, to_block).await?;
+
+        let mut whitelist = self.whitelist.write().expect("poisoned lock");
+        whitelist.synced_to = Some(to_block);
+        Ok(whitelist.apply(events))
+    }
+
+    /// Starts the whitelist from the strategies replayed up to `block` by another node.
+    pub fn seed_strategies(&self, block: u64, strategies: &[Address]) {
+        let mut whitelist = self.whitelist.write().expect("poisoned lock");
+        whitelist.strategies = strategies.iter().copied().collect();
+        whitelist.synced_to = Some(block);
+    }
+
+    /// Catches up with the withdrawal events up to `to_block` since the previous sync, the
+    /// first sync replays them from genesis. Returns the withdrawals from `operator` queued
+    /// and not completed.
+    pub async fn sync_withdrawals(
+        &self,
+        operator: Address,
+        to_block: u64,
+    ) -> eyre::Result<Vec<Withdrawal>> {
+        let from = self
+            .withdrawals
+            .read()
+            .expect("poisoned lock")
+            .synced_to
+            .map_or(0, |block| block + 1);
+        if from <= to_block {
+            let mut events = self.delegation.events();
+            events.filter = events.filter.topic0(vec![
+                WithdrawalQueuedFilter::signature(),
+                WithdrawalCompletedFilter::signature(),
+            ]);
+            let events = query_chunked(events, from, to_block).await?;
+            let mut queue = self.withdrawals.write().expect("poisoned lock");
+            queue.synced_to = Some(to_block);
+            queue.apply(operator, events);
+        }
+        let queue = self.withdrawals.read().expect("poisoned lock");
+        Ok(queue.pending.values().cloned().collect())
+    }
+
+    /// Freezes of operators and resets of their frozen status by the slasher between
+    /// `from_block` and `to_block`, only those of `operator` when given.
+    pub async fn slasher_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        operator: Option<Address>,
+    ) -> eyre::Result<Vec<(SlasherEvents, LogMeta)>> {
+        let mut events = self.slasher.events();
+        events.filter = events.filter.topic0(vec![
+            OperatorFrozenFilter::signature(),
+            FrozenStatusResetFilter::signature(),
+        ]);
+        if let Some(operator) = operator {
+            events.filter = events.filter.topic1(operator);
+        }
+        query_chunked_with_meta(events, from_block, to_block).await
+    }
+
+    /// Strategies whitelisted for deposit as of the last [`Self::sync_strategies`].
+    pub fn whitelisted_strategies(&self) -> Vec<Address> {
+        let whitelist = self.whitelist.read().expect("poisoned lock");
+        whitelist.strategies.iter().copied().collect()
+    }
+
+    /// Shares delegated to `operator` in each of `strategies`.
+    pub async fn operator_shares(
+        &self,
+        operator: Address,
+        strategies: &[Address],
+    ) -> eyre::Result<Vec<(Address, U256)>> {
+        try_join_all(strategies.iter().map(|strategy| async move {
+            let shares = self
+                .delegation
+                .view(|c| c.operator_shares(operator, *strategy))
+                .await?;
+            Ok((*strategy, shares))
+        }))
+        .await
+    }
+
+    /// Underlying token of `strategy` and its decimals.
+    pub async fn strategy_token(&self, strategy: Address) -> eyre::Result<(Address, u8)> {
+        if let Some(token) = self.tokens.read().expect("poisoned lock").get(&strategy) {
+            return Ok(*token);
+        }
+        let token = IStrategy::new(strategy, self.client.clone())
+            .underlying_token()
+            .await?;
+        let decimals = units::token_decimals(self.client.as_ref(), token).await?;
+        self.tokens
+            .write()
+            .expect("poisoned lock")
+            .insert(strategy, (token, decimals));
+        Ok((token, decimals))
+    }
+
+    /// Underlying tokens `shares` of `strategy` are worth at the current exchange rate.
+    pub async fn shares_to_underlying(
+        &self,
+        strategy: Address,
+        shares: U256,
+    ) -> eyre::Result<TokenAmount> {
+        let (token, decimals) = self.strategy_token(strategy).await?;
+        let raw = IStrategy::new(strategy, self.client.clone())
+            .shares_to_underlying_view(shares)
+            .await?;
+        Ok(TokenAmount {
+            token,
+            raw,
+            decimals,
+        })
+    }
+
+    /// Pulls up to `amount` of the strategy underlying token, in its smallest unit, from
+    /// `treasury` (bounded by the allowance it granted to the operator) and deposits it into
+    /// `strategy`. Returns the deposited amount, zero if there was no allowance left.
+    pub async fn top_up_from_treasury(
+        &self,
+        treasury: Address,
+        strategy: Address,
+        amount: U256,
+    ) -> eyre::Result<TokenAmount> {
+        let index = self.deposits_pause_index;
+        if self
+            .strategy_manager
+            .view(|c| c.paused_with_index(index))
+            .await?
+        {
+            return Err(eyre!(
+                "deposits are paused on the strategy manager (pause index {})",
+                index
+            ));
+        }
+        let (token_addr, decimals) = self.strategy_token(strategy).await?;
+        let token = ERC20Mock::new(token_addr, self.client.clone());
+
+        let allowance = token.allowance(treasury, self.client.address()).await?;
+        let amount = amount.min(allowance);
+        let deposited = TokenAmount {
+            token: token_addr,
+            raw: amount,
+            decimals,
+        };
+        if amount.is_zero() {
+            return Ok(deposited);
+        }
+
+        token
+            .transfer_from(treasury, self.client.address(), amount)
+            .send()
+            .await?
+            .await?
+            .ok_or_eyre("treasury transfer_from trx failed")?;
+        token
+            .approve(self.strategy_manager.address(), amount)
+            .send()
+            .await?
+            .await?
+            .ok_or_eyre("strategy manager approve trx failed")?;
+        self.strategy_manager
+            .deposit_into_strategy(strategy, token_addr, amount)
+            .send()
+            .await?
+            .await?
+            .ok_or_eyre("deposit_into_strategy trx failed")?;
+
+        Ok(deposited)
+    }
+
+    /// Queries strategies and shares of many stakers, `page_size` stakers per multicall.
+    /// Falls back to concurrent calls when no Multicall3 contract is known for the chain.
+    pub async fn get_deposits_batch(
+        &self,
+        stakers: &[Address],
+        page_size: usize,
+    ) -> eyre::Result<Vec<StakerDeposits>> {
+        let mut multicall = match Multicall::new(self.client.clone(), None).await {
+            std::result::Result::Ok(multicall) => Some(multicall),
+            Err(e) => {
+                debug!("Multicall unavailable, querying stakers one by one: {}", e);
+                None
+            }
+        };
+
+        let mut all = Vec::with_capacity(stakers.len());
+        for page in stakers.chunks(page_size.max(1)) {
+            let results: Vec<(Vec<Address>, Vec<U256>)> = match multicall.as_mut() {
+                Some(multicall) => {
+                    multicall.clear_calls();
+                    for staker in page {
+                        multicall.add_call(self.strategy_manager.get_deposits(*staker), false);
+                    }
+                    multicall.call_array().await?
+                }
+                None => {
+                    try_join_all(page.iter().map(|staker| async move {
+                        self.strategy_manager
+                            .view(|c| c.get_deposits(*staker))
+                            .await
+                    }))
+                    .await?
+                }
+            };
+            all.extend(
+                page.iter()
+                    .zip(results)
+                    .map(|(staker, (strategies, shares))| StakerDeposits {
+                        staker: *staker,
+                        deposits: strategies.into_iter().zip(shares).collect(),
+                    }),
+            );
+        }
+
+        Ok(all)
+    }
+}
+
+#[test]
+fn test_validate_operator_details() {
+    let current = OperatorSettings {
+        earnings_receiver: Address::repeat_byte(1),
+        delegation_approver: Address::zero(),
+        staker_opt_out_window_blocks: 10,
+        max_staker_opt_out_window_blocks: 100,
+    };
+    let details = |receiver: Address, window| OperatorDetails {
+        earnings_receiver: receiver,
+        delegation_approver: Address::repeat_byte(2),
+        staker_opt_out_window_blocks: window,
+    };
+    assert!(current
+        .validate(&details(Address::repeat_byte(3), 50))
+        .is_ok());
+    assert!(current.validate(&details(Address::zero(), 50)).is_err());
+    assert!(current
+        .validate(&details(Address::repeat_byte(3), 101))
+        .is_err());
+    assert!(current
+        .validate(&details(Address::repeat_byte(3), 9))
+        .is_err());
+}
+
+#[test]
+fn test_strategy_whitelist_changes() {
+    let added = |n: u64| {
+        StrategyManagerEvents::StrategyAddedToDepositWhitelistFilter(
+            StrategyAddedToDepositWhitelistFilter {
+                strategy: Address::from_low_u64_be(n),
+            },
+        )
+    };
+    let removed = |n: u64| {
+        StrategyManagerEvents::StrategyRemovedFromDepositWhitelistFilter(
+            StrategyRemovedFromDepositWhitelistFilter {
+                strategy: Address::from_low_u64_be(n),
+            },
+        )
+    };
+    let mut whitelist = StrategyWhitelist::default();
+    assert_eq!(
+        whitelist.apply(vec![added(1), added(2), removed(2), added(1)]),
+        vec![
+            StrategyChange::Added(Address::from_low_u64_be(1)),
+            StrategyChange::Added(Address::from_low_u64_be(2)),
+            StrategyChange::Removed(Address::from_low_u64_be(2)),
+        ]
+    );
+    assert_eq!(
+        whitelist.strategies,
+        BTreeSet::from([Address::from_low_u64_be(1)])
+    );
+}
+
+#[test]
+fn test_withdrawal_queue() {
+    let operator = Address::repeat_byte(1);
+    let queued = |root: u8, delegated_to: Address| {
+        DelegationManagerEvents::WithdrawalQueuedFilter(WithdrawalQueuedFilter {
+            withdrawal_root: [root; 32],
+            withdrawal: Withdrawal {
+                delegated_to,
+                start_block: root.into(),
+                ..Default::default()
+            },
+        })
+    };
+    let completed = |root: u8| {
+        DelegationManagerEvents::WithdrawalCompletedFilter(WithdrawalCompletedFilter {
+            withdrawal_root: [root; 32],
+        })
+    };
+    let mut queue = WithdrawalQueue::default();
+    queue.apply(
+        operator,
+        vec![
+            queued(1, operator),
+            queued(2, Address::repeat_byte(2)),
+            queued(3, operator),
+            completed(1),
+            completed(2),
+        ],
+    );
+    assert_eq!(queue.pending.keys().collect::<Vec<_>>(), vec![&[3; 32]]);
 }
@@ -0,0 +1,258 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    marker::PhantomData,
+    path::Path,
+    sync::Mutex,
+};
+
+use ethers::{
+    abi::{self, Abi, RawLog, Token},
+    contract::{EthEvent, EthLogDecode},
+    types::{Filter, H256},
+    utils::keccak256,
+};
+use eyre::{eyre, OptionExt};
+use tracing::warn;
+
+use crate::metrics::metrics;
+
+/// Undecoded log, to subscribe to several shapes of an event and decode them with an
+/// [`EventRegistry`].
+#[derive(Debug, Clone)]
+pub struct AnyLog(pub RawLog);
+
+impl EthLogDecode for AnyLog {
+    fn decode_log(log: &RawLog) -> Result<Self, abi::Error> {
+        Ok(Self(log.clone()))
+    }
+}
+
+/// Logs decoded by an [`EventRegistry`] kept for reuse, the same task log is decoded by the
+/// subscription, the catch-up backfill and the divergence monitor.
+const DECODE_CACHE_SIZE: usize = 256;
+
+/// Known shapes of the event `D` across contract versions. Logs of the compiled shape are
+/// decoded by the bindings, logs of another known shape are projected onto the compiled one
+/// by parameter name, so a newer event adding parameters still decodes.
+#[derive(Debug)]
+pub struct EventRegistry<D> {
+    compiled: abi::Event,
+    compiled_topic: H256,
+    /// Other known shapes and their topics, computed once as hashing a signature is costly
+    versions: Vec<(H256, abi::Event)>,
+    cache: Mutex<DecodeCache<D>>,
+    event: PhantomData<D>,
+}
+
+/// Recently decoded logs by hash, evicted oldest first.
+#[derive(Debug)]
+struct DecodeCache<D> {
+    decoded: HashMap<H256, D>,
+    order: VecDeque<H256>,
+}
+
+impl<D: Clone> DecodeCache<D> {
+    fn key(log: &RawLog) -> H256 {
+        let mut bytes = Vec::with_capacity(log.topics.len() * 32 + log.data.len());
+        for topic in &log.topics {
+            bytes.extend_from_slice(topic.as_bytes());
+        }
+        bytes.extend_from_slice(&log.data);
+        keccak256(bytes).into()
+    }
+
+    fn get(&self, key: &H256) -> Option<D> {
+        self.decoded.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: H256, event: D) {
+        if self.decoded.insert(key, event).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > DECODE_CACHE_SIZE {
+            if let Some(oldest) = self.order.pop_front() {
+                self.decoded.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl<D: EthEvent + Clone> EventRegistry<D> {
+    /// Registers the shape of `D` in the compiled `abi` and in the other contract `versions`.
+    pub fn new(abi: &Abi, versions: &[Abi]) -> eyre::Result<Self> {
+        let name = D::name();
+        let compiled = abi.event(&name)?.clone();
+        let compiled_topic = compiled.signature();
+        let mut known: Vec<(H256, abi::Event)> = vec![];
+        for events in versions
+            .iter()
+            .flat_map(|abi| abi.events_by_name(&name).ok())
+        {
+            for event in events {
+                let topic = event.signature();
+                if topic != compiled_topic && known.iter().all(|(t, _)| *t != topic) {
+                    known.push((topic, event.clone()));
+                }
+            }
+        }
+        Ok(Self {
+            compiled,
+            compiled_topic,
+            versions: known,
+            cache: Mutex::new(DecodeCache {
+                decoded: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+            event: PhantomData,
+        })
+    }
+
+    /// Topic filter matching every known shape of the event.
+    pub fn filter(&self) -> Filter {
+        let topics: Vec<H256> = std::iter::once(self.compiled_topic)
+            .chain(self.versions.iter().map(|(topic, _)| *topic))
+            .collect();
+        Filter::new().topic0(topics)
+    }
+
+    pub fn decode(&self, log: &RawLog) -> eyre::Result<D> {
+        let key = DecodeCache::<D>::key(log);
+        if let Some(event) = self.cache.lock().expect("poisoned lock").get(&key) {
+            return Ok(event);
+        }
+        let event = self.decode_uncached(log)?;
+        self.cache
+            .lock()
+            .expect("poisoned lock")
+            .insert(key, event.clone());
+        Ok(event)
+    }
+
+    fn decode_uncached(&self, log: &RawLog) -> eyre::Result<D> {
+        let topic = log.topics.first().ok_or_eyre("anonymous log")?;
+        if *topic == self.compiled_topic {
+            return Ok(D::decode_log(log)?);
+        }
+        let (version_topic, version) = self
+            .versions
+            .iter()
+            .find(|(t, _)| t == topic)
+            .ok_or_else(|| eyre!("unknown {} event topic {:x}", D::name(), topic))?;
+        let parsed = version.parse_log(log.clone())?;
+
+        let mut projected = RawLog {
+            topics: vec![self.compiled_topic],
+            data: vec![],
+        };
+        let mut data = vec![];
+        for param in &self.compiled.inputs {
+            let token = parsed
+                .params
+                .iter()
+                .find(|p| p.name == param.name)
+                .map(|p| &p.value)
+                .filter(|token| token.type_check(&param.kind))
+                .ok_or_else(|| {
+                    eyre!(
+                        "{} event version {} has no {} parameter of type {}",
+                        D::name(),
+                        version_topic,
+                        param.name,
+                        param.kind
+                    )
+                })?;
+            if param.indexed {
+                projected.topics.push(indexed_topic(token)?);
+            } else {
+                data.push(token.clone());
+            }
+        }
+        projected.data = abi::encode(&data);
+        Ok(D::decode_log(&projected)?)
+    }
+
+    /// Decodes `log`, logging and counting the logs which cannot be decoded instead of
+    /// dropping them silently.
+    pub fn decode_or_warn(&self, log: &RawLog) -> Option<D> {
+        match self.decode(log) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                warn!("Skipping undecodable {} log {:?}: {:?}", D::name(), log, e);
+                metrics()
+                    .undecodable_events
+                    .with_label_values(&[D::name().as_ref()])
+                    .inc();
+                None
+            }
+        }
+    }
+}
+
+/// Topic of an indexed value type parameter. Indexed dynamic parameters are only stored
+/// hashed and are already topics.
+fn indexed_topic(token: &Token) -> eyre::Result<H256> {
+    match token {
+        Token::FixedBytes(bytes) if bytes.len() == 32 => Ok(H256::from_slice(bytes)),
+        Token::Address(_)
+        | Token::FixedBytes(_)
+        | Token::Int(_)
+        | Token::Uint(_)
+        | Token::Bool(_) => Ok(H256::from_slice(&abi::encode(std::slice::from_ref(token)))),
+        _ => Err(eyre!("cannot index parameter {:?}", token)),
+    }
+}
+
+/// Reads the ABIs of other deployed contract versions, as plain JSON ABI files.
+pub fn read_abis(paths: &[impl AsRef<Path>]) -> eyre::Result<Vec<Abi>> {
+    paths
+        .iter()
+        .map(|path| {
+            let path = path.as_ref();
+            serde_json::from_slice(&std::fs::read(path)?)
+                .map_err(|e| eyre!("invalid ABI file {}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+#[test]
+fn test_decode_newer_event() {
+    use bindings::mangata_task_manager::{NewTaskCreatedFilter, MANGATATASKMANAGER_ABI};
+    use ethers::abi::{EventParam, ParamType};
+
+    let compiled = MANGATATASKMANAGER_ABI.event("NewTaskCreated").unwrap();
+    let mut newer = compiled.clone();
+    newer.inputs.push(EventParam {
+        name: "deadline".into(),
+        kind: ParamType::Uint(32),
+        indexed: false,
+    });
+    let newer_abi = Abi {
+        events: [("NewTaskCreated".to_owned(), vec![newer.clone()])].into(),
+        ..Default::default()
+    };
+    let registry =
+        EventRegistry::<NewTaskCreatedFilter>::new(&MANGATATASKMANAGER_ABI, &[newer_abi]).unwrap();
+
+    let task = Token::Tuple(vec![
+        Token::Uint(100.into()),
+        Token::Uint(7.into()),
+        Token::Bytes(vec![0]),
+        Token::Uint(66.into()),
+    ]);
+    let log = RawLog {
+        topics: vec![newer.signature(), H256::from_low_u64_be(3)],
+        data: abi::encode(&[task, Token::Uint(50.into())]),
+    };
+    let event = registry.decode(&log).unwrap();
+    assert_eq!(registry.decode(&log).unwrap(), event);
+    assert_eq!(registry.cache.lock().unwrap().order.len(), 1);
+    assert_eq!(event.task_index, 3);
+    assert_eq!(event.task.block_number, 100.into());
+    assert_eq!(event.task.quorum_numbers.to_vec(), [0]);
+
+    let unknown = RawLog {
+        topics: vec![H256::repeat_byte(1)],
+        data: vec![],
+    };
+    assert!(registry.decode(&unknown).is_err());
+}
@@ -0,0 +1,170 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use bindings::{
+    bls_public_key_compendium::{BLSPublicKeyCompendium, RegisterBLSPublicKeyCall},
+    bls_registry_coordinator_with_indices::{
+        BLSRegistryCoordinatorWithIndices, RegisterOperatorWithCoordinator1Call,
+    },
+    delegation_manager::{DelegationManager, QueueWithdrawalsCall, RegisterAsOperatorCall},
+    mangata_task_manager::{MangataTaskManager, RespondToTaskCall},
+    shared_types::{
+        G1Point, G2Point, NonSignerStakesAndSignature, OperatorDetails, QueuedWithdrawalParams,
+        Task, TaskResponse,
+    },
+};
+use ethers::{
+    abi::{AbiEncode, Detokenize},
+    contract::builders::ContractCall,
+    providers::{Http, Provider},
+    types::{Address, Bytes, U256},
+    utils::parse_ether,
+};
+
+const FIXTURES: &str = include_str!("../../../tests/fixtures/calldata.json");
+
+type M = Provider<Http>;
+
+/// Never connected to, calldata is encoded locally.
+fn provider() -> Arc<M> {
+    Arc::new(Provider::try_from("http://localhost:8545").unwrap())
+}
+
+fn g1(x: u64, y: u64) -> G1Point {
+    G1Point {
+        x: x.into(),
+        y: y.into(),
+    }
+}
+
+/// Asserts that both the contract call the node sends and the call type the events and
+/// traces are decoded with encode to the fixture `name`.
+fn assert_golden<D: Detokenize>(name: &str, call: ContractCall<M, D>, typed: impl AbiEncode) {
+    let fixtures: BTreeMap<String, Bytes> = serde_json::from_str(FIXTURES).unwrap();
+    let expected = fixtures.get(name).expect("missing fixture");
+    assert_eq!(
+        call.calldata().as_ref(),
+        Some(expected),
+        "{} calldata",
+        name
+    );
+    assert_eq!(&Bytes::from(typed.encode()), expected, "{} call type", name);
+}
+
+#[test]
+fn test_registration_calldata() {
+    let operator: Address = "0x1111111111111111111111111111111111111111"
+        .parse()
+        .unwrap();
+    let client = provider();
+
+    let registry = BLSRegistryCoordinatorWithIndices::new(Address::zero(), client.clone());
+    let quorum_numbers = Bytes::from(vec![0]);
+    assert_golden(
+        "registerOperatorWithCoordinator",
+        registry.register_operator_with_coordinator_1(
+            quorum_numbers.clone(),
+            g1(1, 2),
+            String::new(),
+        ),
+        RegisterOperatorWithCoordinator1Call {
+            quorum_numbers,
+            pubkey: g1(1, 2),
+            socket: String::new(),
+        },
+    );
+
+    let delegation = DelegationManager::new(Address::zero(), client.clone());
+    let details = OperatorDetails {
+        earnings_receiver: operator,
+        ..Default::default()
+    };
+    assert_golden(
+        "registerAsOperator",
+        delegation.register_as_operator(details.clone(), String::new()),
+        RegisterAsOperatorCall {
+            registering_operator_details: details,
+            metadata_uri: String::new(),
+        },
+    );
+
+    let compendium = BLSPublicKeyCompendium::new(Address::zero(), client);
+    let g2 = G2Point {
+        x: [5.into(), 6.into()],
+        y: [7.into(), 8.into()],
+    };
+    assert_golden(
+        "registerBLSPublicKey",
+        compendium.register_bls_public_key(g1(3, 4), g1(1, 2), g2.clone()),
+        RegisterBLSPublicKeyCall {
+            signed_message_hash: g1(3, 4),
+            pubkey_g1: g1(1, 2),
+            pubkey_g2: g2,
+        },
+    );
+}
+
+#[test]
+fn test_response_calldata() {
+    let task = Task {
+        block_number: 42.into(),
+        task_created_block: 100,
+        quorum_numbers: vec![0].into(),
+        quorum_threshold_percentage: 67,
+    };
+    let task_response = TaskResponse {
+        reference_task_index: 7,
+        block_hash: [0xaa; 32],
+        storage_proof_hash: [0xbb; 32],
+    };
+    let signature = NonSignerStakesAndSignature {
+        non_signer_quorum_bitmap_indices: vec![1],
+        non_signer_pubkeys: vec![g1(9, 10)],
+        quorum_apks: vec![g1(11, 12)],
+        apk_g2: G2Point {
+            x: [13.into(), 14.into()],
+            y: [15.into(), 16.into()],
+        },
+        sigma: g1(17, 18),
+        quorum_apk_indices: vec![2],
+        total_stake_indices: vec![3],
+        non_signer_stake_indices: vec![vec![4]],
+    };
+
+    let task_manager = MangataTaskManager::new(Address::zero(), provider());
+    assert_golden(
+        "respondToTask",
+        task_manager.respond_to_task(task.clone(), task_response.clone(), signature.clone()),
+        RespondToTaskCall {
+            task,
+            task_response,
+            non_signer_stakes_and_signature: signature,
+        },
+    );
+}
+
+#[test]
+fn test_withdrawal_calldata() {
+    let params = vec![QueuedWithdrawalParams {
+        strategies: vec![
+            "0x2222222222222222222222222222222222222222"
+                .parse()
+                .unwrap(),
+            "0x3333333333333333333333333333333333333333"
+                .parse()
+                .unwrap(),
+        ],
+        shares: vec![parse_ether(1).unwrap(), U256::MAX],
+        withdrawer: "0x1111111111111111111111111111111111111111"
+            .parse()
+            .unwrap(),
+    }];
+
+    let delegation = DelegationManager::new(Address::zero(), provider());
+    assert_golden(
+        "queueWithdrawals",
+        delegation.queue_withdrawals(params.clone()),
+        QueueWithdrawalsCall {
+            queued_withdrawal_params: params,
+        },
+    );
+}
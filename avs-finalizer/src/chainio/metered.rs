@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use ethers::{
+    providers::{JsonRpcClient, PubsubClient},
+    types::U256,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+use crate::metrics::metrics;
+
+/// JSON-RPC transport wrapper recording per method call counts and estimated compute units
+/// for the `provider` it is labeled with.
+#[derive(Debug, Clone)]
+pub struct Metered<T> {
+    inner: T,
+    provider: &'static str,
+}
+
+impl<T> Metered<T> {
+    pub fn new(inner: T, provider: &'static str) -> Self {
+        Self { inner, provider }
+    }
+}
+
+#[async_trait]
+impl<T: JsonRpcClient> JsonRpcClient for Metered<T> {
+    type Error = T::Error;
+
+    async fn request<P, R>(&self, method: &str, params: P) -> Result<R, Self::Error>
+    where
+        P: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        metrics().record_rpc_call(self.provider, method);
+        #[cfg(feature = "fault-injection")]
+        let method = crate::faults::rpc_method(self.provider, method).await;
+        self.inner.request(method, params).await
+    }
+}
+
+impl<T: PubsubClient> PubsubClient for Metered<T> {
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe<I: Into<U256>>(&self, id: I) -> Result<Self::NotificationStream, Self::Error> {
+        self.inner.subscribe(id)
+    }
+
+    fn unsubscribe<I: Into<U256>>(&self, id: I) -> Result<(), Self::Error> {
+        self.inner.unsubscribe(id)
+    }
+}
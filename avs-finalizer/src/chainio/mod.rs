@@ -1,31 +1,77 @@
 use std::{str::FromStr, sync::Arc};
 
 use bindings::{
-    erc20_mock::ERC20Mock, i_strategy::IStrategy, mangata_service_manager::MangataServiceManager,
+    delegation_manager::DelegationManager, erc20_mock::ERC20Mock, i_strategy::IStrategy,
+    mangata_service_manager::MangataServiceManager, shared_types::SignatureWithExpiry,
     stake_registry::StakeRegistry, strategy_manager::StrategyManager,
 };
 use ethers::{
     middleware::{MiddlewareBuilder, NonceManagerMiddleware, SignerMiddleware},
-    providers::{Http, Middleware, Provider},
+    providers::{Authorization, ConnectionDetails, Http, Middleware, Provider, Ws},
     signers::{LocalWallet, Signer},
     types::{Address, Chain, TransactionRequest},
     utils::parse_ether,
 };
 use tracing::{debug, info, instrument};
 
-use crate::cli::CliArgs;
+use crate::{
+    cli::CliArgs,
+    headers::{authorization_for, headers_for},
+};
+use metered::Metered;
+use transport::EthTransport;
+
+pub use avs_operator_sdk::chain::{logs, transport};
 
 pub mod avs;
+pub mod beacon;
+pub mod breaker;
 pub mod eigen;
+pub mod events;
+/// Calldata of the calls sent to the contracts, compared byte for byte with the fixtures in
+/// `tests/fixtures/calldata.json` generated by the aggregator's reference encoding, so that the
+/// ABI the clients encode with cannot drift apart unnoticed.
+#[cfg(test)]
+mod golden;
+pub mod metered;
+pub mod poll;
+pub mod service_manager;
+pub mod upgrades;
 
-type MW = Provider<Http>;
+type MW = Provider<Metered<EthTransport>>;
+pub type WsProvider = Provider<Metered<Ws>>;
 pub type Client = SignerMiddleware<NonceManagerMiddleware<MW>, LocalWallet>;
 
+pub(crate) async fn build_eth_provider(url: &str) -> eyre::Result<MW> {
+    let headers = headers_for(url);
+    // headers only travel over http(s), an ipc endpoint never has any
+    let transport = match headers.is_empty() || !url.starts_with("http") {
+        true => EthTransport::connect(url).await?,
+        false => {
+            let client = reqwest::Client::builder()
+                .default_headers(headers)
+                .build()?;
+            EthTransport::Http(Http::new_with_client(reqwest::Url::parse(url)?, client))
+        }
+    };
+    let label = match transport {
+        EthTransport::Http(_) => "eth_http",
+        EthTransport::Ipc(_) => "eth_ipc",
+    };
+    Ok(Provider::new(Metered::new(transport, label)))
+}
+
+pub(crate) async fn build_ws_provider(url: &str) -> eyre::Result<WsProvider> {
+    let auth = authorization_for(url)?.map(Authorization::raw);
+    let ws = Ws::connect(ConnectionDetails::new(url, auth)).await?;
+    Ok(Provider::new(Metered::new(ws, "eth_ws")))
+}
+
 #[instrument(skip_all)]
 pub(crate) async fn build_eth_client(cfg: &CliArgs) -> eyre::Result<Client> {
-    let provider: Provider<Http> = MW::try_from(cfg.eth_rpc_url.clone())?;
+    let provider = build_eth_provider(&cfg.eth_rpc_url).await?;
     info!("Eth Wallet decryting...");
-    let wallet = cfg.get_ecdsa_keystore()?.into_wallet()?;
+    let wallet = cfg.get_ecdsa_wallet().await?;
     info!("Eth Wallet decrytped with address {:x}", wallet.address());
     let nonce = NonceManagerMiddleware::new(provider, wallet.address());
     let client = Client::new_with_provider_chain(nonce, wallet.with_chain_id(cfg.chain_id)).await?;
@@ -33,29 +79,38 @@ pub(crate) async fn build_eth_client(cfg: &CliArgs) -> eyre::Result<Client> {
     Ok(client)
 }
 
+/// Funds `staker` and deposits `stake` into the quorum strategy from it. A staker other than
+/// the operator then delegates its stake to `operator`.
 #[instrument(skip_all)]
+/// Sends ether for gas to each of `accounts` from a prefunded anvil account.
+pub(crate) async fn fund_accounts(eth_rpc_url: &str, accounts: &[Address]) -> eyre::Result<()> {
+    let provider = build_eth_provider(eth_rpc_url).await?;
+    let anvil = LocalWallet::from_str(
+        "0x2a871d0798f97d79848a013d4936a73bf4cc922c825d33c1cf7073dff6d409c6",
+    )?
+    .with_chain_id(Chain::AnvilHardhat as u64);
+    let client = provider.with_signer(anvil);
+    for account in accounts {
+        let transfer = TransactionRequest::pay(*account, parse_ether(100).unwrap());
+        client.send_transaction(transfer, None).await?.await?;
+        debug!("sent some ether to {:x}", account);
+    }
+    Ok(())
+}
+
+/// Deposits `stake` of the quorum 0 strategy token minted to `staker`, delegating it to
+/// `operator` when the staker is another account. The staker must be funded for gas.
 pub(crate) async fn setup_deposits(
     eth_rpc_url: String,
     svc_manager_address: Address,
     stake: u32,
-    operator: LocalWallet,
+    staker: LocalWallet,
+    operator: Address,
 ) -> eyre::Result<()> {
-    let provider: Provider<Http> = MW::try_from(eth_rpc_url)?;
-    let anvil = LocalWallet::from_str(
-        "0x2a871d0798f97d79848a013d4936a73bf4cc922c825d33c1cf7073dff6d409c6",
-    )?
-    .with_chain_id(Chain::AnvilHardhat as u64);
-    let op_address = operator.address();
-    let transfer = TransactionRequest::pay(op_address, parse_ether(100).unwrap());
-    provider
-        .clone()
-        .with_signer(anvil)
-        .send_transaction(transfer, None)
-        .await?
-        .await?;
-    debug!("sent some ether to operator");
+    let provider = build_eth_provider(&eth_rpc_url).await?;
+    let op_address = staker.address();
 
-    let client = Arc::new(provider.with_signer(operator));
+    let client = Arc::new(provider.with_signer(staker));
     let svc = MangataServiceManager::new(svc_manager_address, client.clone());
     let stake_registry_address = svc.stake_registry().await?;
     let stake_reg = StakeRegistry::new(stake_registry_address, client.clone());
@@ -83,5 +138,18 @@ pub(crate) async fn setup_deposits(
         .await?
         .await?;
     debug!("deposited into startegy manager for erc20 for operator");
+    if op_address != operator {
+        let delegation =
+            DelegationManager::new(strategy_manager.delegation().call().await?, client.clone());
+        delegation
+            .delegate_to(operator, SignatureWithExpiry::default(), [0; 32])
+            .send()
+            .await?
+            .await?;
+        debug!(
+            "delegated staker {:x} to operator {:x}",
+            op_address, operator
+        );
+    }
     Ok(())
 }
@@ -0,0 +1,161 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use ethers::{
+    contract::LogMeta,
+    providers::Middleware,
+    types::{Filter, Log},
+};
+use futures::{stream, Stream};
+use tracing::{debug, warn};
+
+use crate::cli::PollArgs;
+
+/// Weight of the latest gap between events in the expected gap.
+const GAP_SMOOTHING: f64 = 0.3;
+
+/// Interval between polls for logs over HTTP. Contract events such as task creations are
+/// emitted on a cadence, polling is fast from shortly before the next event is expected
+/// until it arrives or is long overdue, and slow otherwise.
+#[derive(Debug, Clone)]
+pub struct PollSchedule {
+    min: Duration,
+    max: Duration,
+    last_event: Option<Instant>,
+    expected_gap: Option<Duration>,
+}
+
+impl PollSchedule {
+    pub fn new(args: &PollArgs) -> Self {
+        let min = Duration::from_millis(args.eth_poll_min_interval_ms);
+        Self {
+            min,
+            max: Duration::from_millis(args.eth_poll_max_interval_ms).max(min),
+            last_event: None,
+            expected_gap: None,
+        }
+    }
+
+    pub fn record_event(&mut self, at: Instant) {
+        if let Some(last) = self.last_event {
+            let gap = at.duration_since(last);
+            // events of the same block are polled together and carry no cadence
+            if gap >= self.min {
+                self.expected_gap = Some(match self.expected_gap {
+                    Some(expected) => {
+                        expected.mul_f64(1.0 - GAP_SMOOTHING) + gap.mul_f64(GAP_SMOOTHING)
+                    }
+                    None => gap,
+                });
+            }
+        }
+        self.last_event = Some(at);
+    }
+
+    pub fn interval(&self, now: Instant) -> Duration {
+        let (Some(last), Some(gap)) = (self.last_event, self.expected_gap) else {
+            return self.max;
+        };
+        let expected = last + gap;
+        let fast_from = expected.checked_sub(self.max).unwrap_or(last).max(last);
+        let fast_until = expected + gap;
+        if now >= fast_from && now < fast_until {
+            self.min
+        } else if now < fast_from {
+            // wake up in time for the fast window
+            (fast_from - now).clamp(self.min, self.max)
+        } else {
+            self.max
+        }
+    }
+}
+
+/// Logs matching `filter` from the block following the current head, polled with
+/// `eth_getLogs` on the adaptive `schedule`. Failed polls are retried on the next interval.
+pub fn poll_logs<M: Middleware>(
+    client: &M,
+    filter: Filter,
+    schedule: PollSchedule,
+) -> impl Stream<Item = (Log, LogMeta)> + '_ {
+    struct State {
+        filter: Filter,
+        schedule: PollSchedule,
+        next_block: Option<u64>,
+        pending: VecDeque<Log>,
+    }
+
+    let state = State {
+        filter,
+        schedule,
+        next_block: None,
+        pending: VecDeque::new(),
+    };
+    stream::unfold(state, move |mut state| async move {
+        loop {
+            if let Some(log) = state.pending.pop_front() {
+                let meta = LogMeta::from(&log);
+                return Some(((log, meta), state));
+            }
+            let head = match client.get_block_number().await {
+                Ok(head) => head.as_u64(),
+                Err(e) => {
+                    warn!("Cannot poll the head block: {:?}", e);
+                    tokio::time::sleep(state.schedule.interval(Instant::now())).await;
+                    continue;
+                }
+            };
+            let from = match state.next_block {
+                Some(from) => from,
+                None => {
+                    // like a subscription, starts with the logs of the next block
+                    state.next_block = Some(head + 1);
+                    continue;
+                }
+            };
+            if head >= from {
+                let filter = state.filter.clone().from_block(from).to_block(head);
+                match client.get_logs(&filter).await {
+                    Ok(logs) => {
+                        debug!("Polled {} logs in blocks {}..={}", logs.len(), from, head);
+                        if !logs.is_empty() {
+                            state.schedule.record_event(Instant::now());
+                        }
+                        state.pending.extend(logs);
+                        state.next_block = Some(head + 1);
+                        continue;
+                    }
+                    Err(e) => warn!("Cannot poll logs of blocks {}..={}: {:?}", from, head, e),
+                }
+            }
+            tokio::time::sleep(state.schedule.interval(Instant::now())).await;
+        }
+    })
+}
+
+#[test]
+fn test_poll_schedule() {
+    let mut schedule = PollSchedule::new(&PollArgs {
+        eth_poll_min_interval_ms: 1_000,
+        eth_poll_max_interval_ms: 10_000,
+    });
+    let start = Instant::now();
+    let secs = |s| start + Duration::from_secs(s);
+    assert_eq!(schedule.interval(start), Duration::from_secs(10));
+
+    // tasks every 60s
+    schedule.record_event(start);
+    schedule.record_event(secs(60));
+    assert_eq!(schedule.interval(secs(61)), Duration::from_secs(10));
+    // wakes up for the window starting 10s before the next task
+    assert_eq!(schedule.interval(secs(105)), Duration::from_secs(5));
+    assert_eq!(schedule.interval(secs(110)), Duration::from_secs(1));
+    assert_eq!(schedule.interval(secs(150)), Duration::from_secs(1));
+    // long overdue
+    assert_eq!(schedule.interval(secs(190)), Duration::from_secs(10));
+
+    schedule.record_event(secs(190));
+    let expected = schedule.expected_gap.unwrap().as_secs_f64();
+    assert_eq!(expected.round(), 81.0);
+}
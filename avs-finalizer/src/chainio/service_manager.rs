@@ -0,0 +1,196 @@
+use std::{fmt::Debug, sync::Arc};
+
+use async_trait::async_trait;
+use bindings::mangata_service_manager::{MangataServiceManager, MANGATASERVICEMANAGER_ABI};
+use ethers::{abi::Abi, types::Address};
+
+use super::{
+    breaker::{CircuitBreaker, Guarded},
+    Client,
+};
+
+/// Service manager of the AVS, the entry point the node finds the other AVS contracts and
+/// their owner through. Only the discovery of the contracts depends on this trait, an AVS
+/// whose service manager has another ABI implements it next to
+/// [`MangataServiceManagerClient`]. The task pipeline still talks to a `MangataTaskManager`:
+/// its events, `respondToTask` calls and `TaskResponse` are not abstracted.
+#[async_trait]
+pub trait ServiceManagerClient: Debug + Send + Sync {
+    fn address(&self) -> Address;
+
+    /// ABI of the contract, its code is checked for upgrades against it
+    fn abi(&self) -> &'static Abi;
+
+    async fn task_manager(&self) -> eyre::Result<Address>;
+
+    async fn registry_coordinator(&self) -> eyre::Result<Address>;
+
+    async fn stake_registry(&self) -> eyre::Result<Address>;
+
+    async fn slasher(&self) -> eyre::Result<Address>;
+
+    /// Account administering the AVS contracts
+    async fn owner(&self) -> eyre::Result<Address>;
+}
+
+/// The `MangataServiceManager` contract, called through a circuit breaker.
+#[derive(Debug)]
+pub struct MangataServiceManagerClient {
+    contract: Guarded<MangataServiceManager<Client>>,
+}
+
+impl MangataServiceManagerClient {
+    pub fn new(address: Address, client: Arc<Client>, breaker: Arc<CircuitBreaker>) -> Self {
+        Self {
+            contract: Guarded::new(MangataServiceManager::new(address, client), breaker),
+        }
+    }
+}
+
+#[async_trait]
+impl ServiceManagerClient for MangataServiceManagerClient {
+    fn address(&self) -> Address {
+        self.contract.address()
+    }
+
+    fn abi(&self) -> &'static Abi {
+        &MANGATASERVICEMANAGER_ABI
+    }
+
+    async fn task_manager(&self) -> eyre::Result<Address> {
+        self.contract.view(|c| c.task_manager()).await
+    }
+
+    async fn registry_coordinator(&self) -> eyre::Result<Address> {
+        self.contract.view(|c| c.registry_coordinator()).await
+    }
+
+    async fn stake_registry(&self) -> eyre::Result<Address> {
+        self.contract.view(|c| c.stake_registry()).await
+    }
+
+    async fn slasher(&self) -> eyre::Result<Address> {
+        self.contract.view(|c| c.slasher()).await
+    }
+
+    async fn owner(&self) -> eyre::Result<Address> {
+        self.contract.view(|c| c.owner()).await
+    }
+}
+
+/// Service manager answering fixed addresses, to find the AVS contracts without a chain. It
+/// cannot drive tasks, which are read from the task manager on chain.
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct MockServiceManager {
+    pub address: Address,
+    pub task_manager: Address,
+    pub registry_coordinator: Address,
+    pub stake_registry: Address,
+    pub slasher: Address,
+    pub owner: Address,
+}
+
+#[cfg(test)]
+impl Default for MockServiceManager {
+    fn default() -> Self {
+        Self {
+            address: Address::repeat_byte(1),
+            task_manager: Address::repeat_byte(2),
+            registry_coordinator: Address::repeat_byte(3),
+            stake_registry: Address::repeat_byte(4),
+            slasher: Address::repeat_byte(5),
+            owner: Address::repeat_byte(6),
+        }
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl ServiceManagerClient for MockServiceManager {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn abi(&self) -> &'static Abi {
+        &MANGATASERVICEMANAGER_ABI
+    }
+
+    async fn task_manager(&self) -> eyre::Result<Address> {
+        Ok(self.task_manager)
+    }
+
+    async fn registry_coordinator(&self) -> eyre::Result<Address> {
+        Ok(self.registry_coordinator)
+    }
+
+    async fn stake_registry(&self) -> eyre::Result<Address> {
+        Ok(self.stake_registry)
+    }
+
+    async fn slasher(&self) -> eyre::Result<Address> {
+        Ok(self.slasher)
+    }
+
+    async fn owner(&self) -> eyre::Result<Address> {
+        Ok(self.owner)
+    }
+}
+
+#[tokio::test]
+async fn test_mock_service_manager() {
+    use clap::Parser;
+    use ethers::{
+        middleware::{NonceManagerMiddleware, SignerMiddleware},
+        providers::{Http, Provider},
+        signers::{LocalWallet, Signer},
+    };
+
+    use super::{avs::AvsContracts, metered::Metered, transport::EthTransport};
+    use crate::{cli::CliArgs, constants::ChainConstants};
+
+    // nothing listens there, the contracts must be found without the chain
+    let http: Http = "http://127.0.0.1:1".parse().unwrap();
+    let provider = Provider::new(Metered::new(EthTransport::Http(http), "eth_http"));
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+    let client = Arc::new(SignerMiddleware::new(
+        NonceManagerMiddleware::new(provider, wallet.address()),
+        wallet,
+    ));
+    let cfg = CliArgs::try_parse_from([
+        "avs-finalizer",
+        "--chain-id=31337",
+        "--ecdsa-ephemeral-key",
+        "--bls-ephemeral-key",
+        "--eth-rpc-url=http://127.0.0.1:1",
+        "--substrate-rpc-url=ws://127.0.0.1:1",
+        "--avs-rpc-url=http://127.0.0.1:1",
+    ])
+    .unwrap();
+    let mock = MockServiceManager::default();
+    let contracts = AvsContracts::with_service_manager(
+        &cfg,
+        &ChainConstants::load(None).unwrap(),
+        client,
+        Arc::new(mock.clone()),
+    )
+    .await
+    .unwrap();
+
+    let addresses: Vec<(&str, Address)> = contracts
+        .contracts()
+        .into_iter()
+        .map(|(name, address, _)| (name, address))
+        .collect();
+    assert_eq!(
+        addresses,
+        vec![
+            ("service_manager", mock.address),
+            ("task_manager", mock.task_manager),
+            ("registry_coordinator", mock.registry_coordinator),
+            ("stake_registry", mock.stake_registry),
+        ]
+    );
+    assert_eq!(contracts.owner().await.unwrap(), mock.owner);
+    assert_eq!(contracts.slasher_address().await.unwrap(), mock.slasher);
+}
@@ -0,0 +1,227 @@
+use std::collections::BTreeSet;
+
+use ethers::{
+    abi::Abi,
+    providers::Middleware,
+    types::{Address, BlockNumber, Filter, H256},
+    utils::keccak256,
+};
+use serde::Serialize;
+
+/// EIP-1967 storage slot of the implementation behind a proxy,
+/// `keccak256("eip1967.proxy.implementation") - 1`.
+const IMPLEMENTATION_SLOT: H256 = H256([
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+]);
+
+const PUSH1: u8 = 0x60;
+const PUSH3: u8 = 0x62;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+const DUP1: u8 = 0x80;
+const DUP16: u8 = 0x8f;
+const EQ: u8 = 0x14;
+
+/// Code deployed at a contract address, through its EIP-1967 proxy if it is one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deployment {
+    /// `None` unless the address is a proxy
+    pub implementation: Option<Address>,
+    /// Hash of the code executing calls, the implementation's behind a proxy
+    pub code_hash: H256,
+    pub selectors: BTreeSet<[u8; 4]>,
+}
+
+impl Deployment {
+    pub async fn read<M: Middleware>(client: &M, address: Address) -> eyre::Result<Self>
+    where
+        M::Error: 'static,
+    {
+        let slot = client
+            .get_storage_at(address, IMPLEMENTATION_SLOT, None)
+            .await?;
+        let implementation = (!slot.is_zero()).then(|| Address::from(slot));
+        let code = client
+            .get_code(implementation.unwrap_or(address), None)
+            .await?;
+        Ok(Self {
+            implementation,
+            code_hash: keccak256(&code).into(),
+            selectors: dispatched_selectors(&code),
+        })
+    }
+}
+
+/// Function selectors the dispatcher of `code` compares the calldata against. Solidity
+/// pushes each selector and compares it with `EQ`, possibly after a `DUP`, shortened to 3
+/// bytes when it starts with a zero byte. This is a heuristic, a constant compared the same
+/// way elsewhere in the code shows up as a selector.
+pub fn dispatched_selectors(code: &[u8]) -> BTreeSet<[u8; 4]> {
+    let mut selectors = BTreeSet::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        if !(PUSH1..=PUSH32).contains(&op) {
+            pc += 1;
+            continue;
+        }
+        let len = (op - PUSH1 + 1) as usize;
+        let data = pc + 1;
+        let next = data + len;
+        if matches!(op, PUSH3 | PUSH4) && next < code.len() {
+            let compared = match code[next] {
+                EQ => true,
+                DUP1..=DUP16 => code.get(next + 1) == Some(&EQ),
+                _ => false,
+            };
+            if compared {
+                let mut selector = [0_u8; 4];
+                selector[4 - len..].copy_from_slice(&code[data..next]);
+                selectors.insert(selector);
+            }
+        }
+        pc = next;
+    }
+    selectors
+}
+
+/// Functions of the compiled bindings against the selectors of the deployed code.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SelectorDiff {
+    /// Functions of the bindings the deployed code no longer dispatches, calls to them revert
+    pub missing: Vec<String>,
+    /// Selectors dispatched by the deployed code without a function in the bindings
+    pub unknown: Vec<String>,
+}
+
+impl SelectorDiff {
+    pub fn new(bindings: &Abi, deployed: &BTreeSet<[u8; 4]>) -> Self {
+        let compiled: BTreeSet<_> = bindings.functions().map(|f| f.short_signature()).collect();
+        let mut missing: Vec<String> = bindings
+            .functions()
+            .filter(|f| !deployed.contains(&f.short_signature()))
+            .map(|f| f.signature())
+            .collect();
+        missing.sort();
+        Self {
+            missing,
+            unknown: deployed
+                .difference(&compiled)
+                .map(|selector| format!("0x{}", hex::encode(selector)))
+                .collect(),
+        }
+    }
+}
+
+/// Blocks between `from_block` and `to_block` in which the proxy `address` emitted
+/// `Upgraded(address)`.
+pub async fn upgraded_at<M: Middleware>(
+    client: &M,
+    address: Address,
+    from_block: u64,
+    to_block: u64,
+) -> eyre::Result<Vec<u64>>
+where
+    M::Error: 'static,
+{
+    let filter = Filter::new()
+        .address(address)
+        .topic0(H256(keccak256("Upgraded(address)")))
+        .from_block(BlockNumber::Number(from_block.into()))
+        .to_block(BlockNumber::Number(to_block.into()));
+    Ok(client
+        .get_logs(&filter)
+        .await?
+        .iter()
+        .filter_map(|log| log.block_number.map(|n| n.as_u64()))
+        .collect())
+}
+
+/// Change of the code behind an AVS contract, logged as an operator-facing changelog entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeNotice {
+    pub contract: &'static str,
+    pub address: Address,
+    pub previous_implementation: Option<Address>,
+    pub implementation: Option<Address>,
+    pub previous_code_hash: H256,
+    pub code_hash: H256,
+    /// Blocks of the `Upgraded` events since the previous check, empty when the code changed
+    /// without one
+    pub upgraded_at: Vec<u64>,
+    /// Selectors of the new code against the bindings this release was compiled with
+    pub selectors: SelectorDiff,
+}
+
+impl UpgradeNotice {
+    /// Notice of the change from `previous` to `current`, `None` if the code did not change.
+    pub fn new(
+        contract: &'static str,
+        address: Address,
+        bindings: &Abi,
+        previous: &Deployment,
+        current: &Deployment,
+        upgraded_at: Vec<u64>,
+    ) -> Option<Self> {
+        if previous.implementation == current.implementation
+            && previous.code_hash == current.code_hash
+            && upgraded_at.is_empty()
+        {
+            return None;
+        }
+        Some(Self {
+            contract,
+            address,
+            previous_implementation: previous.implementation,
+            implementation: current.implementation,
+            previous_code_hash: previous.code_hash,
+            code_hash: current.code_hash,
+            upgraded_at,
+            selectors: SelectorDiff::new(bindings, &current.selectors),
+        })
+    }
+}
+
+#[test]
+fn test_selector_diff() {
+    let abi = ethers::abi::parse_abi(&[
+        "function owner() external view returns (address)",
+        "function pause(uint256) external",
+    ])
+    .unwrap();
+    let owner = abi.function("owner").unwrap().short_signature();
+    let pause = abi.function("pause").unwrap().short_signature();
+
+    let mut code = vec![DUP1, PUSH4];
+    code.extend_from_slice(&owner);
+    code.extend_from_slice(&[EQ, 0x61, 0x00, 0x10, 0x57]);
+    // a selector-like pattern inside push data is not an instruction
+    code.push(PUSH32);
+    code.extend_from_slice(&[PUSH4, 0xaa, 0xbb, 0xcc, 0xdd, EQ]);
+    code.extend_from_slice(&[0; 26]);
+    // selectors starting with a zero byte are pushed with 3 bytes
+    code.extend_from_slice(&[PUSH3, 0x0a, 0x0b, 0x0c, DUP1 + 1, EQ]);
+
+    let deployed = dispatched_selectors(&code);
+    assert_eq!(deployed, [owner, [0, 0x0a, 0x0b, 0x0c]].into());
+
+    let diff = SelectorDiff::new(&abi, &deployed);
+    assert_eq!(diff.missing, vec!["pause(uint256)".to_owned()]);
+    assert_eq!(diff.unknown, vec!["0x000a0b0c".to_owned()]);
+
+    let previous = Deployment {
+        implementation: Some(Address::repeat_byte(1)),
+        code_hash: H256::repeat_byte(1),
+        selectors: [owner, pause].into(),
+    };
+    assert!(UpgradeNotice::new("x", Address::zero(), &abi, &previous, &previous, vec![]).is_none());
+    let current = Deployment {
+        implementation: Some(Address::repeat_byte(2)),
+        code_hash: H256::repeat_byte(2),
+        selectors: deployed,
+    };
+    let notice =
+        UpgradeNotice::new("x", Address::zero(), &abi, &previous, &current, vec![7]).unwrap();
+    assert_eq!(notice.selectors, diff);
+}
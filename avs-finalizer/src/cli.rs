@@ -1,51 +1,310 @@
-use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand};
-use ethers::types::{Address, Chain};
-use eyre::Ok;
+use clap::{error::ErrorKind, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use ethers::{
+    signers::{coins_bip39::English, LocalWallet, MnemonicBuilder},
+    types::{Address, Chain},
+};
+use eyre::{eyre, Ok};
 use serde::Serialize;
-use std::{fmt::Debug, path::PathBuf};
+use std::{
+    convert::Infallible,
+    fmt::Debug,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 use tracing::warn;
 
-use crate::crypto::keystore::EncodedKeystore;
+use crate::{
+    crypto::{keystore::EncodedKeystore, vault},
+    headers::RpcHeader,
+    hooks::Hook,
+    queue::DropPolicy,
+    service::{self, ServicePlatform},
+    store::{Backend, PostgresBackend, SledBackend, StoreKey},
+    verifier::Switchover,
+};
 
 #[derive(Parser, Serialize)]
 #[command(author, version, about, long_about = None)]
 pub struct CliArgs {
+    /// `KEY=value` file setting the environment variables of the node, loaded before the
+    /// arguments are parsed. Variables already set in the environment take precedence
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<PathBuf>,
+    /// JSON file of the contract addresses of each network by chain id, in place of the built
+    /// in ones, see `addresses.rs` for the format
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_book: Option<PathBuf>,
+    /// Service manager in place of the one of the address book
     #[arg(long, env)]
-    pub avs_service_manager_addr: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avs_service_manager_addr: Option<Address>,
+    /// BLS public key compendium in place of the one of the address book
     #[arg(long, env)]
-    pub bls_compendium_addr: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bls_compendium_addr: Option<Address>,
+    /// BLS operator state retriever in place of the one of the address book
     #[arg(long, env)]
-    pub bls_operator_state_retriever_addr: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bls_operator_state_retriever_addr: Option<Address>,
 
     #[arg(long, env)]
     pub substrate_rpc_url: String,
+    /// Additional substrate nodes cross-checking the finalized block hash of each task
+    #[arg(long, env, value_delimiter = ',')]
+    pub substrate_witness_rpc_urls: Vec<String>,
+    /// Number of substrate nodes (including `substrate_rpc_url`) which must agree on the block
+    /// hash, defaults to a majority
+    #[arg(long, env, requires("substrate_witness_rpc_urls"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub substrate_quorum: Option<usize>,
+    /// HTTP(S) url, or `ipc:///path/geth.ipc` for the IPC socket of a local execution client
     #[arg(long, env)]
     pub eth_rpc_url: String,
+    /// Websocket url subscribed to for contract events, polled over `eth_rpc_url` when unset
     #[arg(long, env)]
-    pub eth_ws_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eth_ws_url: Option<String>,
+    /// Subscribe to contract events at the host and path of `eth_rpc_url` over ws(s), for
+    /// providers serving both on the same url
+    #[arg(long, env, default_value_t = false, conflicts_with = "eth_ws_url")]
+    pub eth_ws_from_http: bool,
+    /// Beacon node REST API, read for the validators natively restaked through EigenPods
+    #[arg(long, env)]
+    #[serde(skip)]
+    pub beacon_api_url: Option<String>,
+    #[command(flatten)]
+    pub rpc_headers: RpcHeaderArgs,
+    #[command(flatten)]
+    pub poll: PollArgs,
     #[arg(long, env)]
     pub avs_rpc_url: String,
 
     #[arg(long, env)]
     pub chain_id: u64,
 
+    #[command(flatten)]
+    pub api: ApiArgs,
+
     #[command(flatten)]
     pub ecdsa_key: EcdsaKey,
     #[arg(long, env)]
     #[serde(skip)]
     pub ecdsa_key_password: Option<String>,
+    /// BIP-32 path of the key derived from `--ecdsa-mnemonic`
+    #[arg(
+        long,
+        env,
+        requires = "ecdsa_mnemonic",
+        conflicts_with = "ecdsa_account_index"
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecdsa_derivation_path: Option<String>,
+    /// Index of the account derived from `--ecdsa-mnemonic` on the `m/44'/60'/0'/0/{index}`
+    /// path, when no derivation path is given
+    #[arg(long, env, requires = "ecdsa_mnemonic", default_value_t = 0)]
+    pub ecdsa_account_index: u32,
+
+    #[command(flatten)]
+    pub vault: VaultArgs,
 
     #[command(flatten)]
     pub bls_key: BlsKey,
+    #[command(flatten)]
+    pub threshold: ThresholdArgs,
     #[arg(long, env)]
     #[serde(skip)]
     pub bls_key_password: Option<String>,
 
+    /// Latency budget of a task, from receiving the event to sending the response. Past it
+    /// the response is sent without gossiping it, the substrate cross-check is never skipped
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_budget_ms: Option<u64>,
+    /// Ethereum blocks to wait after the task was created before signing its response, for
+    /// operators wary of reorgs. Shortened when needed so the response is still signed a few
+    /// blocks before its response window closes
+    #[arg(long, env, default_value_t = 0)]
+    pub min_confirmation_delay_blocks: u32,
+    /// Quarantine a task once its verification failed this many times in a row with the same
+    /// error, it is then skipped until retried with `retry-quarantined`. Requires a store, 0
+    /// never quarantines
+    #[arg(long, env, default_value_t = 3)]
+    pub quarantine_after: u32,
+
+    /// Execute finalized substrate blocks whose number is a multiple of this period ahead of their
+    /// task, so responses can be sent as soon as tasks arrive. Only a guess of the next tasks:
+    /// it mirrors the rule of the aggregator, which creates a task for the blocks that are a
+    /// multiple of its `--avs-block-validation-period`, and must be set to the same period.
+    /// Blocks prepared for no task are dropped unused
+    #[arg(long, env, value_parser = clap::value_parser!(u32).range(1..))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prepare_block_period: Option<u32>,
+
+    /// Idle once the Ethereum head or the substrate finalized head did not advance for this
+    /// long, e.g. during planned maintenance, until it does again. Tasks expiring or failing
+    /// meanwhile are not counted against the node. The heads are read from the configured
+    /// endpoints only, a stuck endpoint looks like a halted chain and would hide the outage of
+    /// the node, so the detection is disabled by default and with 0
+    #[arg(long, env, default_value_t = 0)]
+    pub chain_halt_secs: u64,
+
+    /// Alert when a substrate block is bridged, i.e. a response to its task accepted on
+    /// Ethereum, later than this after its finalization, or is still not bridged by then
+    #[arg(long, env, default_value_t = 1800)]
+    pub max_finality_lag_secs: u64,
+
+    /// Verify blocks in this many worker processes instead of the node process, isolating
+    /// crashes of the block execution and spreading it over more cores. Workers hold no keys
+    #[arg(long, env, default_value_t = 0)]
+    pub verify_workers: u16,
+
+    /// Number of missed tasks processed in parallel while catching up after downtime
+    #[arg(long, env, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    pub catch_up_concurrency: u16,
+
+    /// Versions of the verifiers of the tasks created from a block on, as
+    /// `<task-type>:<version>@<block>`, tasks are verified with version 1 before any switchover
+    #[arg(long, env, value_delimiter = ',')]
+    pub verifier_switchovers: Vec<Switchover>,
+
+    /// Directory of the local persistent store, state is kept in memory only if neither this
+    /// nor `--db-url` is set
+    #[arg(long, env, group = "db")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<PathBuf>,
+    /// Postgres connection string of a store shared by several instances, in place of the
+    /// local store. The connection is always encrypted
+    #[arg(long, env, group = "db")]
+    #[serde(skip)]
+    pub db_url: Option<String>,
+    /// PEM CA certificates the `--db-url` server certificate is verified against, in place of
+    /// the webpki roots
+    #[arg(long, env, requires("db_url"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db_ca_cert: Option<PathBuf>,
+    /// Report the store schema version and pending migrations, then exit without migrating
+    #[arg(long, env, default_value_t = false, requires("db"))]
+    pub db_check: bool,
+    /// Encrypt the store at rest with a key derived from this passphrase, only possible on a
+    /// new store
+    #[arg(long, env, requires("db"), conflicts_with("db_key_vault_path"))]
+    #[serde(skip)]
+    pub db_passphrase: Option<String>,
+    /// Encrypt the store at rest with the hex encoded 32 bytes `key` field of this Vault secret
+    #[arg(long, env, requires("db"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db_key_vault_path: Option<String>,
+    /// Signed registry checkpoint written by `export-sync-checkpoint`, seeding the stake and
+    /// strategy caches so only the registry events after it are replayed
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync_checkpoint: Option<PathBuf>,
+    /// Addresses trusted to sign `--sync-checkpoint`, besides the operator itself
+    #[arg(long, env, value_delimiter = ',', requires("sync_checkpoint"))]
+    pub sync_checkpoint_signers: Vec<Address>,
+    /// JSON ABI files of other deployed versions of the task manager, used to decode events
+    /// whose shape differs from the compiled bindings
+    #[arg(long, env, value_delimiter = ',')]
+    pub event_abis: Vec<PathBuf>,
+    /// JSON file overriding the built in chain constants (quorums, response window, pause
+    /// indexes), for emergencies only
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_constants: Option<PathBuf>,
+    /// Append every signing decision and its inputs to this write-ahead log, see `replay-wal`
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wal_path: Option<PathBuf>,
+    /// File keeping the node identity across restarts, generated on the first start
+    #[arg(long, env, default_value = "node-identity.json")]
+    pub identity_file: PathBuf,
+    /// Write the stored response and WAL decisions of tasks diverging from the accepted
+    /// response to this directory, for on-call to dispute them
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evidence_dir: Option<PathBuf>,
+    /// Executables reviewing every task result before it is signed, see `plugin.rs` for the
+    /// protocol. A task is skipped unless all of them accept it
+    #[arg(long, env, value_delimiter = ',')]
+    pub plugins: Vec<PathBuf>,
+    /// Time a plugin has to answer before the task is skipped and the plugin restarted
+    #[arg(long, env, default_value_t = 2000)]
+    pub plugin_timeout_ms: u64,
+
     #[arg(long, env, default_value_t = false)]
     pub testnet: bool,
 
     #[arg(long, env, default_value_t = 100, requires("testnet"))]
     pub stake: u32,
+    /// Stake from a new staker account delegating to the operator instead of self-staking,
+    /// and fail when the operator shares its account with the aggregator or task generator
+    #[arg(long, env, default_value_t = false, requires("testnet"))]
+    pub testnet_distinct_roles: bool,
+
+    #[command(flatten)]
+    pub stake_top_up: StakeTopUp,
+
+    #[command(flatten)]
+    pub pressure: PressureArgs,
+
+    #[command(flatten)]
+    pub substrate_quota: SubstrateQuotaArgs,
+
+    #[command(flatten)]
+    pub update: UpdateArgs,
+
+    #[command(flatten)]
+    pub contract_calls: ContractCallArgs,
+
+    #[command(flatten)]
+    pub balance: BalanceArgs,
+
+    #[command(flatten)]
+    pub relayer: RelayerArgs,
+
+    #[command(flatten)]
+    pub gossip: GossipArgs,
+
+    #[command(flatten)]
+    pub hooks: HookArgs,
+
+    #[command(flatten)]
+    pub archive: ArchiveArgs,
+
+    /// Interval between samples of the operator stake share per quorum, 0 disables sampling
+    #[arg(long, env, default_value_t = 600)]
+    pub stake_share_interval_secs: u64,
+    /// Warn when the operator stake share of a quorum falls below this percentage
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stake_share_alert_pct: Option<f64>,
+    /// Interval between syncs of the strategies whitelisted for deposit, whose delegated
+    /// shares are exported as metrics, 0 disables tracking
+    #[arg(long, env, default_value_t = 60)]
+    pub strategy_sync_interval_secs: u64,
+    /// Interval between checks of the withdrawals queued by the stakers delegated to the
+    /// operator, which warn when they will drop its stake below a quorum minimum, 0 disables
+    /// the checks
+    #[arg(long, env, default_value_t = 600)]
+    pub withdrawal_check_interval_secs: u64,
+    /// Interval between checks of the code behind the AVS contracts, which logs a notice
+    /// when one is upgraded, 0 disables the checks
+    #[arg(long, env, default_value_t = 300)]
+    pub upgrade_check_interval_secs: u64,
+
+    /// Verify and sign tasks without sending responses, reporting diffs with the on-chain
+    /// responses signed by this operator, e.g. to burn in a new version
+    #[arg(long, env, conflicts_with = "testnet")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow_of: Option<Address>,
+
+    /// Rolling window of received tasks the reputation score is computed over
+    #[arg(long, env, default_value_t = 7 * 24 * 3600)]
+    pub reputation_window_secs: u64,
 
     #[command(subcommand)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -55,7 +314,7 @@ pub struct CliArgs {
 #[derive(Args, Serialize, Debug)]
 #[group(required = true, multiple = false)]
 pub struct EcdsaKey {
-    #[arg(long, env)]
+    #[arg(long, env, value_parser = keystore_path)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ecdsa_key_file: Option<PathBuf>,
     #[arg(long, env)]
@@ -63,12 +322,25 @@ pub struct EcdsaKey {
     pub ecdsa_key_json: Option<String>,
     #[arg(long, env)]
     pub ecdsa_ephemeral_key: bool,
+    /// Vault KV v2 secret path holding the ECDSA keystore
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecdsa_key_vault_path: Option<String>,
+    /// BIP-39 mnemonic the ECDSA key is derived from, see `--ecdsa-derivation-path`
+    #[arg(long, env)]
+    #[serde(skip)]
+    pub ecdsa_mnemonic: Option<String>,
+    /// Address of an operator key kept on an air-gapped machine, for the `offline-sign`
+    /// commands preparing and broadcasting the transactions it signs
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecdsa_cold_address: Option<Address>,
 }
 
 #[derive(Args, Serialize, Debug)]
 #[group(required = true, multiple = false)]
 pub struct BlsKey {
-    #[arg(long, env)]
+    #[arg(long, env, value_parser = keystore_path)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bls_key_file: Option<PathBuf>,
     #[arg(long, env)]
@@ -76,18 +348,611 @@ pub struct BlsKey {
     pub bls_key_json: Option<String>,
     #[arg(long, env)]
     pub bls_ephemeral_key: bool,
+    /// Vault KV v2 secret path holding the BLS keystore
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bls_key_vault_path: Option<String>,
+    /// Public key written by `split-bls-key`, signing with `--threshold-signer-urls` instead of
+    /// a local BLS key
+    #[arg(long, env, requires("threshold_signer_urls"), value_parser = keystore_path)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bls_threshold_key: Option<PathBuf>,
+}
+
+#[derive(Args, Serialize, Debug)]
+pub struct ThresholdArgs {
+    /// Signer daemons (`serve-bls-share`) holding the shares of the threshold BLS key
+    #[arg(long, env, value_delimiter = ',')]
+    pub threshold_signer_urls: Vec<String>,
+    /// Bearer tokens of the signer daemons, one per `--threshold-signer-urls` in the same
+    /// order, as set with `serve-bls-share --token`
+    #[arg(long, env, value_delimiter = ',')]
+    #[serde(skip)]
+    pub threshold_signer_tokens: Vec<String>,
+    #[arg(long, env, default_value_t = 2_000)]
+    pub threshold_signer_timeout_ms: u64,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct UpdateArgs {
+    /// URL of the signed release manifest, update checks are disabled if unset
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_manifest_url: Option<String>,
+    /// Release signing address, overrides the one embedded at build time
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_signer: Option<Address>,
+    /// Seconds between checks of the release manifest
+    #[arg(long, env, default_value_t = 21600, value_parser = clap::value_parser!(u64).range(1..))]
+    pub update_check_interval_secs: u64,
+    /// Download critical updates for this platform into this directory
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_download_dir: Option<PathBuf>,
+}
+
+/// Resource limits above which the operator sheds load.
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct PressureArgs {
+    /// Resident memory limit of the process
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+    /// CPU usage limit, in percent of one core
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cpu_pct: Option<f64>,
+    /// Number of received tasks waiting to be processed
+    #[arg(long, env, default_value_t = 16, value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_task_backlog: u64,
+    /// Received tasks queued for processing before the drop policy applies
+    #[arg(long, env, default_value_t = 256, value_parser = clap::value_parser!(u64).range(1..))]
+    pub task_queue_capacity: u64,
+    /// What to do with tasks received while the queue is full
+    #[arg(long, env, value_enum, default_value_t = DropPolicy::DropOldest)]
+    pub task_queue_drop_policy: DropPolicy,
+    /// Time to clear the task backlog the autoscaling signal aims for, its load is 1 when the
+    /// backlog drains in this time
+    #[arg(long, env, default_value_t = 60, value_parser = clap::value_parser!(u64).range(1..))]
+    pub autoscale_target_drain_secs: u64,
+}
+
+/// Quotas of the substrate node provider, billing by requests or bandwidth.
+#[derive(Args, Serialize, Debug, Clone, Default)]
+pub struct SubstrateQuotaArgs {
+    /// Requests to the substrate nodes per hour
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub substrate_max_requests_per_hour: Option<u64>,
+    /// Bytes exchanged with the substrate nodes per hour, counting the requests sent and the
+    /// blocks fetched
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub substrate_max_bytes_per_hour: Option<u64>,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct BalanceArgs {
+    /// Interval between checks of the operator account ETH balance
+    #[arg(long, env, default_value_t = 60, value_parser = clap::value_parser!(u64).range(1..))]
+    pub balance_check_interval_secs: u64,
+    /// Alert when the operator account holds less ETH than this gas float
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_balance_eth: Option<f64>,
+    /// Stop sending transactions (e.g. stake top-ups) while the balance is below the float,
+    /// task responses go through the aggregator and are not affected
+    #[arg(long, env, default_value_t = false, requires("min_balance_eth"))]
+    pub halt_below_min_balance: bool,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct RpcHeaderArgs {
+    /// Header sent with every request to `eth_rpc_url` as `Name: value`, e.g.
+    /// `Authorization: Bearer <token>` for private RPC gateways, repeat for more
+    #[arg(long, env)]
+    #[serde(skip)]
+    pub eth_rpc_header: Vec<RpcHeader>,
+    /// Header sent when connecting to `eth_ws_url`, only `Authorization` is supported
+    #[arg(long, env)]
+    #[serde(skip)]
+    pub eth_ws_header: Vec<RpcHeader>,
+    /// Header sent when connecting to `substrate_rpc_url`, not to the witnesses
+    #[arg(long, env)]
+    #[serde(skip)]
+    pub substrate_rpc_header: Vec<RpcHeader>,
+}
+
+impl RpcHeaderArgs {
+    /// Values of all headers, redacted from diagnostics.
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.eth_rpc_header
+            .iter()
+            .chain(&self.eth_ws_header)
+            .chain(&self.substrate_rpc_header)
+            .map(RpcHeader::value)
+    }
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct RelayerArgs {
+    /// Relayer service the signed task responses are handed to, paying the gas of their
+    /// submission. Responses are sent to the aggregator directly when the relayer fails
+    #[arg(long, env)]
+    #[serde(skip)]
+    pub relayer_url: Option<String>,
+    /// Bearer token of the relayer API
+    #[arg(long, env, requires("relayer_url"))]
+    #[serde(skip)]
+    pub relayer_token: Option<String>,
+    #[arg(long, env, default_value_t = 5_000)]
+    pub relayer_timeout_ms: u64,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct HookArgs {
+    /// Script or URL run with a JSON description of the task at a point of its lifecycle, as
+    /// `point=target` with point one of on-task-received, on-signed, on-submitted or on-error.
+    /// Scripts read it on stdin and only get the path, home, locale and temporary directory
+    /// of the node environment, URLs get it POSTed, repeat for more
+    #[arg(long, env, value_delimiter = ',')]
+    #[serde(skip)]
+    pub hook: Vec<Hook>,
+    /// Time a hook has to finish before it is killed
+    #[arg(long, env, default_value_t = 5_000)]
+    pub hook_timeout_ms: u64,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct ArchiveArgs {
+    /// S3 compatible bucket the inputs, verification records and receipts of the responded
+    /// tasks are archived to, as a path-style URL, e.g.
+    /// `https://s3.eu-central-1.amazonaws.com/avs-archive`
+    #[arg(long, env, requires("archive_access_key_id"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_url: Option<String>,
+    #[arg(long, env, default_value = "us-east-1")]
+    pub archive_region: String,
+    #[arg(long, env, requires_all(["archive_url", "archive_secret_access_key"]))]
+    #[serde(skip)]
+    pub archive_access_key_id: Option<String>,
+    #[arg(long, env, requires("archive_access_key_id"))]
+    #[serde(skip)]
+    pub archive_secret_access_key: Option<String>,
+    /// Key prefix of the archived objects, followed by the node identity
+    #[arg(long, env, default_value = "avs-finalizer")]
+    pub archive_prefix: String,
+    /// Days the archived objects are kept before the node deletes them, 0 keeps them.
+    /// Deletion relies on the store remembering the objects
+    #[arg(long, env, default_value_t = 90)]
+    pub archive_retention_days: u64,
+    /// Days the records of archived tasks are kept in the store, 0 keeps them
+    #[arg(long, env, default_value_t = 7)]
+    pub archive_local_retention_days: u64,
+    #[arg(long, env, default_value_t = 10_000)]
+    pub archive_timeout_ms: u64,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct GossipArgs {
+    /// Operator APIs of other operators to exchange partial signatures with, aggregated
+    /// locally when the aggregator does not take a response. Messages are authenticated by
+    /// the operator ECDSA keys, use https URLs to encrypt them
+    #[arg(long, env, value_delimiter = ',')]
+    pub gossip_peers: Vec<String>,
+    #[arg(long, env, default_value_t = 2_000)]
+    pub gossip_timeout_ms: u64,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct PollArgs {
+    /// Interval between polls for contract events without `--eth-ws-url`, when an event is
+    /// expected
+    #[arg(long, env, default_value_t = 1_000, value_parser = clap::value_parser!(u64).range(1..))]
+    pub eth_poll_min_interval_ms: u64,
+    /// Interval between polls for contract events without `--eth-ws-url`, between the
+    /// expected events
+    #[arg(long, env, default_value_t = 12_000)]
+    pub eth_poll_max_interval_ms: u64,
+}
+
+/// Contracts whose calls are guarded by a circuit breaker.
+pub const GUARDED_CONTRACTS: &[&str] = &[
+    "service_manager",
+    "task_manager",
+    "registry_coordinator",
+    "stake_registry",
+    "delegation_manager",
+    "bls_pubkey_compendium",
+    "strategy_manager",
+];
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct ContractCallArgs {
+    /// Timeout of a contract `eth_call`
+    #[arg(long, env, default_value_t = 10_000)]
+    pub contract_call_timeout_ms: u64,
+    /// Timeouts overriding `--contract-call-timeout-ms` for some contracts, as `contract=ms`
+    #[arg(long, env, value_delimiter = ',')]
+    pub contract_timeouts: Vec<ContractTimeout>,
+    /// Consecutive failed or timed out calls after which calls to a contract are stopped
+    #[arg(long, env, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    pub circuit_failure_threshold: u32,
+    /// Time calls to a contract stay stopped before a call probes it again
+    #[arg(long, env, default_value_t = 30)]
+    pub circuit_cooldown_secs: u64,
+}
+
+impl ContractCallArgs {
+    pub fn timeout_ms(&self, contract: &str) -> u64 {
+        self.contract_timeouts
+            .iter()
+            .find(|t| t.contract == contract)
+            .map_or(self.contract_call_timeout_ms, |t| t.timeout_ms)
+    }
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct ContractTimeout {
+    pub contract: String,
+    pub timeout_ms: u64,
+}
+
+impl FromStr for ContractTimeout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (contract, timeout_ms) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected contract=ms, got {}", s))?;
+        let contract = contract.trim();
+        if !GUARDED_CONTRACTS.contains(&contract) {
+            return Err(format!(
+                "unknown contract {}, expected one of {}",
+                contract,
+                GUARDED_CONTRACTS.join(", ")
+            ));
+        }
+        Result::Ok(Self {
+            contract: contract.to_owned(),
+            timeout_ms: timeout_ms.trim().parse().map_err(|e| format!("{}", e))?,
+        })
+    }
+}
+
+/// Keystore paths are often given to services unexpanded by a shell.
+fn keystore_path(s: &str) -> Result<PathBuf, Infallible> {
+    Result::Ok(service::expand_path(Path::new(s)))
+}
+
+#[derive(Args, Serialize, Debug)]
+pub struct VaultArgs {
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_addr: Option<String>,
+    #[arg(long, env, conflicts_with = "vault_role_id")]
+    #[serde(skip)]
+    pub vault_token: Option<String>,
+    #[arg(long, env, requires("vault_secret_id"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_role_id: Option<String>,
+    #[arg(long, env, requires("vault_role_id"))]
+    #[serde(skip)]
+    pub vault_secret_id: Option<String>,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct ApiArgs {
+    /// Address to serve the operator API (prometheus metrics, rpc usage, health, admin) on
+    #[arg(long, env)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_addr: Option<SocketAddr>,
+    /// Bearer token required on every operator API request
+    #[arg(long, env, requires("api_addr"))]
+    #[serde(skip)]
+    pub api_token: Option<String>,
+    #[arg(long, env, requires_all(["api_addr", "api_tls_key"]))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_tls_cert: Option<PathBuf>,
+    #[arg(long, env, requires("api_tls_cert"))]
+    #[serde(skip)]
+    pub api_tls_key: Option<PathBuf>,
+    /// CA which must have signed operator API client certificates (mTLS)
+    #[arg(long, env, requires("api_tls_cert"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_client_ca: Option<PathBuf>,
+    /// Allowlist of client certificate SHA-256 fingerprints (hex)
+    #[arg(long, env, value_delimiter = ',', requires("api_client_ca"))]
+    pub api_client_cert_sha256: Vec<String>,
+}
+
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct StakeTopUp {
+    /// Treasury which granted the operator an ERC20 allowance used to top up stake
+    #[arg(long, env, requires("top_up_amount"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_up_treasury: Option<Address>,
+    /// Amount of the strategy underlying token pulled from the treasury per top-up, in the
+    /// smallest unit of the token
+    #[arg(long, env, requires("top_up_treasury"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_up_amount: Option<u128>,
+    /// Top up when stake falls below the quorum minimum plus this margin (in percent)
+    #[arg(long, env, default_value_t = 10)]
+    pub top_up_margin_pct: u32,
+    /// Seconds between checks of the stake against the quorum minimums
+    #[arg(long, env, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+    pub top_up_interval_secs: u64,
+}
+
+#[derive(Debug, Subcommand, Serialize)]
+pub enum ConfigCommand {
+    /// Print the JSON Schema of the configuration, to validate configuration files against
+    Schema {
+        /// Write the schema to this file instead of logging it
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand, Serialize)]
+pub enum OfflineSignCommand {
+    /// Write the unsigned transactions registering `--ecdsa-cold-address` with EigenLayer and
+    /// the AVS, the registrations already made being skipped. Needs the BLS key
+    Export {
+        #[arg(long)]
+        out: PathBuf,
+        /// Gas limit of the transactions which cannot be estimated before the previous ones
+        /// are mined
+        #[arg(long, default_value_t = 1_000_000)]
+        fallback_gas_limit: u64,
+    },
+    /// Sign the transactions written by `export` with the ECDSA key, without connecting to
+    /// any node
+    SignPayload {
+        payload: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Send the transactions signed by `sign-payload` in order, waiting for each to be mined
+    Broadcast { signed: PathBuf },
 }
 
 #[derive(Debug, Subcommand, Serialize)]
 pub enum Commands {
     OptInAvs,
-    OptOutAvs,
-    PrintStatus,
+    OptOutAvs {
+        /// Announce the exit to the aggregator and keep registered this long before
+        /// deregistering, so the aggregator does not eject the operator for the tasks it misses
+        /// meanwhile. At most an hour and once per operator, the aggregator rejects longer
+        /// grace periods and renewed intents
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u64).range(..=3600))]
+        exit_grace_secs: u64,
+    },
+    PrintStatus {
+        /// Write the status to this file in the Prometheus text format, for the node exporter
+        /// textfile collector
+        #[arg(long, value_name = "PATH")]
+        prom: Option<PathBuf>,
+        /// Refresh the status every this many seconds, printing the new tasks, stake and lag
+        /// changes since the previous refresh
+        #[arg(long, value_name = "SECS", conflicts_with = "prom", value_parser = clap::value_parser!(u64).range(1..))]
+        watch: Option<u64>,
+    },
+    /// Print JSON-RPC usage of the operator node serving its API on `--api-addr`
+    RpcUsage,
+    /// Print stake distribution and threshold parameters of every quorum
+    QuorumStatus,
+    /// Recommend the quorums to join now, by churning out another operator, or after adding
+    /// the printed stake shortfall
+    AdviseQuorums,
+    /// Print the on-chain roles (owner, operator, pauser, whitelister) of the ECDSA key
+    Capabilities,
+    /// Print the tasks quarantined after failing their verification repeatedly
+    Quarantined,
+    /// Release a quarantined task and verify it again, if still within its response window
+    RetryQuarantined {
+        task_index: u32,
+    },
+    /// Verify, sign and encode a synthetic task for a substrate block without sending it
+    SelfTest {
+        block_number: u32,
+    },
+    /// Answer newline-delimited JSON requests read on stdin (`status`, `sign_digest`,
+    /// `submit_response`) with one JSON line each on stdout, logging to stderr
+    Script,
+    /// Print the average gas cost of recent task responses per task type and quorums, and the
+    /// margin left from the expected reward
+    Economics(EconomicsArgs),
+    /// Report the freezes by the EigenLayer slasher and the ejections from the AVS of the
+    /// operator
+    SlashingHistory {
+        #[arg(long, default_value_t = 0)]
+        from_block: u64,
+        /// Report the events of every operator instead of this one only
+        #[arg(long)]
+        all_operators: bool,
+        /// Write the report to this file instead of logging it
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Replay the registry events and write a checkpoint of the stake and strategy caches,
+    /// signed by the operator, for the `--sync-checkpoint` of other nodes
+    ExportSyncCheckpoint {
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Print strategies and shares deposited by the given stakers
+    GetDeposits(GetDepositsArgs),
+    /// Print the delegation parameters of the operator on the DelegationManager
+    OperatorDetails,
+    /// Update the delegation parameters of the operator, unset ones are kept
+    SetOperatorDetails(SetOperatorDetailsArgs),
+    /// Sign a challenge with both the ECDSA and BLS keys to prove their custody
+    ProveOwnership {
+        challenge: String,
+        /// Write the proof to this file instead of logging it
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Verify a proof produced by `prove-ownership`
+    VerifyOwnership {
+        proof: PathBuf,
+    },
+    /// Print the reputation score of the operator over `--reputation-window-secs`, requires a
+    /// store (`--db-path` or `--db-url`)
+    Reputation {
+        /// Sign the score with the ECDSA key for publication
+        #[arg(long)]
+        attest: bool,
+        /// Write the score to this file instead of logging it
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Verify an attestation produced by `reputation --attest`
+    VerifyReputation {
+        attestation: PathBuf,
+    },
+    /// Print the build of the node: version, git commit, Cargo.lock hash and rustc version
+    Version {
+        /// Sign the build with the ECDSA key, for AVS governance to check which build
+        /// produced the signatures of the operator
+        #[arg(long)]
+        attest: bool,
+        /// Write the build to this file instead of logging it
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Verify an attestation produced by `version --attest`
+    VerifyVersion {
+        attestation: PathBuf,
+    },
+    /// Collect sanitized diagnostics of the node into a zip archive for support requests,
+    /// querying the running node API when `--api-addr` is set
+    Doctor {
+        /// Archive path, defaults to `doctor-{timestamp}.zip`
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Report the balances of EigenPod validators at the finalized beacon block and whether
+    /// they withdraw to the pod, requires `--beacon-api-url`
+    NativeRestaking {
+        /// EigenPod the validators should withdraw to
+        #[arg(long)]
+        eigen_pod: Address,
+        /// Validator indices or pubkeys
+        #[arg(long, value_delimiter = ',', required = true)]
+        validators: Vec<String>,
+        /// Write the report to this file instead of logging it
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Configuration tooling
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Register an operator whose ECDSA key never leaves an air-gapped machine: `export` the
+    /// transactions online, `sign-payload` them offline and `broadcast` the signed ones
+    OfflineSign {
+        #[command(subcommand)]
+        command: OfflineSignCommand,
+    },
+    /// Print the OpenAPI description of the operator API, also served on `/openapi.json`
+    Openapi {
+        /// Write the description to this file instead of logging it
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Re-derive the signing decisions recorded in a write-ahead log and compare them with the
+    /// recorded ones
+    ReplayWal {
+        wal: PathBuf,
+        /// Only replay the decisions on this task
+        #[arg(long)]
+        task_index: Option<u32>,
+    },
+    /// Split the BLS key into `shares` encrypted keystores, any `threshold` of which sign for it
+    SplitBlsKey {
+        #[arg(long)]
+        threshold: u32,
+        #[arg(long)]
+        shares: u32,
+        /// Directory receiving the `bls-share-{index}.json` keystores and `threshold-key.json`
+        #[arg(long)]
+        out_dir: PathBuf,
+    },
+    /// Serve partial signatures of a key share written by `split-bls-key`, the share being
+    /// the configured BLS key
+    ServeBlsShare {
+        #[arg(long)]
+        index: u32,
+        #[arg(long, default_value = "127.0.0.1:9100")]
+        listen: SocketAddr,
+        /// Bearer token required from the finalizer, distinct for every daemon
+        #[arg(long, env = "BLS_SHARE_TOKEN")]
+        token: Option<String>,
+    },
+    /// Print a service definition running the node with the arguments preceding this command,
+    /// to install it as a systemd, launchd or Windows service
+    ServiceDefinition {
+        #[arg(long, value_enum, default_value_t = ServicePlatform::current())]
+        platform: ServicePlatform,
+        #[arg(long, default_value = "avs-finalizer")]
+        name: String,
+        /// `KEY=value` file with the secrets to keep out of the service arguments
+        #[arg(long)]
+        env_file: Option<PathBuf>,
+        /// Write the definition to this file instead of printing it
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug, Serialize)]
+pub struct GetDepositsArgs {
+    #[arg(required = true, value_delimiter = ',')]
+    pub stakers: Vec<Address>,
+    #[arg(long, default_value_t = 100)]
+    pub page_size: usize,
+}
+
+#[derive(Args, Debug, Serialize)]
+pub struct EconomicsArgs {
+    /// Blocks to look back for task responses, about a day of Ethereum blocks by default
+    #[arg(long, default_value_t = 7200)]
+    pub lookback_blocks: u64,
+    /// Most recent responses to sample
+    #[arg(long, default_value_t = 50)]
+    pub samples: usize,
+    /// Expected reward per task in ETH, the margin is only reported when set
+    #[arg(long)]
+    pub expected_reward_eth: Option<f64>,
+    /// Write the report to this file instead of logging it
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Serialize)]
+#[group(required = true, multiple = true)]
+pub struct SetOperatorDetailsArgs {
+    #[arg(long)]
+    pub earnings_receiver: Option<Address>,
+    /// Approver of delegations to the operator, the zero address lets anyone delegate
+    #[arg(long)]
+    pub delegation_approver: Option<Address>,
+    /// Can only increase, up to the DelegationManager maximum
+    #[arg(long)]
+    pub staker_opt_out_window_blocks: Option<u32>,
+    /// URL of the operator metadata JSON
+    #[arg(long)]
+    pub metadata_uri: Option<String>,
 }
 
 impl CliArgs {
     pub fn build() -> Self {
-        let args = CliArgs::parse();
+        let mut args = CliArgs::parse();
         if args.chain_id != Chain::AnvilHardhat as u64 {
             let mut cmd = CliArgs::command();
             if args.testnet {
@@ -101,10 +966,177 @@ impl CliArgs {
                 warn!("!!! Runing operator with epehemeral keys !!!")
             }
         }
+        if let Err(e) = args.check_endpoints() {
+            e.exit();
+        }
         args
     }
 
-    pub fn get_ecdsa_keystore(&self) -> eyre::Result<EncodedKeystore> {
+    /// The `config` command of `args`, if any, parsed without requiring the node arguments,
+    /// which it does not use, so that it is dispatched without keys nor RPC urls.
+    pub fn config_command<I, T>(args: I) -> Option<ConfigCommand>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let matches = CliArgs::command()
+            .ignore_errors(true)
+            .try_get_matches_from(args)
+            .ok()?;
+        match matches.subcommand() {
+            Some(("config", config)) => ConfigCommand::from_arg_matches(config).ok(),
+            _ => None,
+        }
+    }
+
+    /// Checks the endpoints of each RPC group together, once the websocket endpoint is
+    /// derived with `--eth-ws-from-http`, so that a mismatched combination fails before the
+    /// node connects. Urls are left out of the errors as they often embed an API key.
+    fn check_endpoints(&mut self) -> Result<(), clap::Error> {
+        let error = |kind, msg: String| CliArgs::command().error(kind, msg);
+        let scheme_error = |arg: &str, url: &str, expected: &[&str]| match reqwest::Url::parse(url)
+        {
+            Err(e) => Some(format!("{} is not a valid url: {}", arg, e)),
+            std::result::Result::Ok(url) if !expected.contains(&url.scheme()) => Some(format!(
+                "{} must be a {} url, not {}",
+                arg,
+                expected.join(" or "),
+                url.scheme()
+            )),
+            std::result::Result::Ok(_) => None,
+        };
+
+        // Ethereum: HTTP or IPC endpoint, events over websocket or polled
+        if let Some(e) = scheme_error("eth-rpc-url", &self.eth_rpc_url, &["http", "https", "ipc"]) {
+            return Err(error(ErrorKind::InvalidValue, e));
+        }
+        if !self.eth_rpc_url.starts_with("http") && !self.rpc_headers.eth_rpc_header.is_empty() {
+            return Err(error(
+                ErrorKind::ArgumentConflict,
+                "eth-rpc-header needs an http(s) eth-rpc-url, ipc carries no headers".into(),
+            ));
+        }
+        if self.eth_ws_from_http {
+            let Some(rest) = self.eth_rpc_url.strip_prefix("http") else {
+                return Err(error(
+                    ErrorKind::ArgumentConflict,
+                    "eth-ws-from-http needs an http(s) eth-rpc-url".into(),
+                ));
+            };
+            self.eth_ws_url = Some(format!("ws{}", rest));
+        }
+        match &self.eth_ws_url {
+            Some(url) => {
+                if let Some(e) = scheme_error("eth-ws-url", url, &["ws", "wss"]) {
+                    return Err(error(ErrorKind::InvalidValue, e));
+                }
+            }
+            None if !self.rpc_headers.eth_ws_header.is_empty() => {
+                return Err(error(
+                    ErrorKind::MissingRequiredArgument,
+                    "eth-ws-header needs a websocket endpoint, set eth-ws-url or eth-ws-from-http"
+                        .into(),
+                ));
+            }
+            None => {}
+        }
+
+        // Substrate: websocket endpoints, the witnesses cross-checking the primary one
+        if let Some(e) = scheme_error("substrate-rpc-url", &self.substrate_rpc_url, &["ws", "wss"])
+        {
+            return Err(error(ErrorKind::InvalidValue, e));
+        }
+        for (i, url) in self.substrate_witness_rpc_urls.iter().enumerate() {
+            if let Some(e) = scheme_error("substrate-witness-rpc-urls", url, &["ws", "wss"]) {
+                return Err(error(ErrorKind::InvalidValue, e));
+            }
+            if *url == self.substrate_rpc_url || self.substrate_witness_rpc_urls[..i].contains(url)
+            {
+                return Err(error(
+                    ErrorKind::ArgumentConflict,
+                    format!(
+                        "substrate witness {} repeats another substrate endpoint, it would count twice towards the quorum",
+                        i + 1
+                    ),
+                ));
+            }
+        }
+        if let Some((uris, quorum)) = self.substrate_consensus() {
+            if quorum == 0 || quorum > uris.len() {
+                return Err(error(
+                    ErrorKind::InvalidValue,
+                    format!("substrate-quorum must be between 1 and {}", uris.len()),
+                ));
+            }
+        }
+
+        // Aggregator
+        if let Some(e) = scheme_error("avs-rpc-url", &self.avs_rpc_url, &["http", "https"]) {
+            return Err(error(ErrorKind::InvalidValue, e));
+        }
+        if let Some(url) = &self.archive.archive_url {
+            if let Some(e) = scheme_error("archive-url", url, &["http", "https"]) {
+                return Err(error(ErrorKind::InvalidValue, e));
+            }
+        }
+        std::result::Result::Ok(())
+    }
+
+    /// All substrate endpoints cross-checking blocks with the quorum they must reach,
+    /// `None` when no witnesses are configured.
+    pub fn substrate_consensus(&self) -> Option<(Vec<String>, usize)> {
+        if self.substrate_witness_rpc_urls.is_empty() {
+            return None;
+        }
+        let uris: Vec<String> = std::iter::once(self.substrate_rpc_url.clone())
+            .chain(self.substrate_witness_rpc_urls.iter().cloned())
+            .collect();
+        let quorum = self.substrate_quorum.unwrap_or(uris.len() / 2 + 1);
+        Some((uris, quorum))
+    }
+
+    /// Backend of the store configured with `--db-path` or `--db-url`, if any.
+    pub async fn store_backend(&self) -> eyre::Result<Option<Arc<dyn Backend>>> {
+        Ok(match (&self.db_path, &self.db_url) {
+            (Some(path), _) => Some(Arc::new(SledBackend::open(path)?)),
+            (None, Some(url)) => Some(Arc::new(
+                PostgresBackend::connect(url, self.db_ca_cert.as_deref()).await?,
+            )),
+            (None, None) => None,
+        })
+    }
+
+    pub async fn store_key(&self) -> eyre::Result<Option<StoreKey>> {
+        if let Some(path) = &self.db_key_vault_path {
+            return Ok(Some(vault::fetch_store_key(&self.vault, path).await?));
+        }
+        Ok(self.db_passphrase.clone().map(StoreKey::Passphrase))
+    }
+
+    /// Signer of the operator ECDSA key, derived from `--ecdsa-mnemonic` when given and
+    /// decrypted from its keystore otherwise.
+    pub async fn get_ecdsa_wallet(&self) -> eyre::Result<LocalWallet> {
+        if let Some(address) = self.ecdsa_key.ecdsa_cold_address {
+            return Err(eyre!(
+                "the key of {:?} is offline, only the offline-sign commands run without it",
+                address
+            ));
+        }
+        let Some(mnemonic) = &self.ecdsa_key.ecdsa_mnemonic else {
+            return self.get_ecdsa_keystore().await?.into_wallet();
+        };
+        let builder = MnemonicBuilder::<English>::default().phrase(mnemonic.as_str());
+        let builder = match &self.ecdsa_derivation_path {
+            Some(path) => builder.derivation_path(path)?,
+            None => builder.index(self.ecdsa_account_index)?,
+        };
+        Ok(builder.build()?)
+    }
+
+    pub async fn get_ecdsa_keystore(&self) -> eyre::Result<EncodedKeystore> {
+        if let Some(path) = &self.ecdsa_key.ecdsa_key_vault_path {
+            return vault::fetch_keystore(&self.vault, path, self.ecdsa_key_password.clone()).await;
+        }
         get_keystore(
             &self.ecdsa_key.ecdsa_key_file,
             &self.ecdsa_key.ecdsa_key_json,
@@ -112,7 +1144,10 @@ impl CliArgs {
             &self.ecdsa_key_password,
         )
     }
-    pub fn get_bls_keystore(&self) -> eyre::Result<EncodedKeystore> {
+    pub async fn get_bls_keystore(&self) -> eyre::Result<EncodedKeystore> {
+        if let Some(path) = &self.bls_key.bls_key_vault_path {
+            return vault::fetch_keystore(&self.vault, path, self.bls_key_password.clone()).await;
+        }
         get_keystore(
             &self.bls_key.bls_key_file,
             &self.bls_key.bls_key_json,
@@ -136,3 +1171,87 @@ fn get_keystore(
     }?;
     Ok(keystore)
 }
+
+#[test]
+fn test_check_endpoints() {
+    let parse = |extra: &[&str]| {
+        let mut args = vec![
+            "avs-finalizer",
+            "--chain-id=31337",
+            "--ecdsa-ephemeral-key",
+            "--bls-ephemeral-key",
+            "--eth-rpc-url=https://eth.example/v3/key",
+            "--substrate-rpc-url=wss://substrate.example",
+            "--avs-rpc-url=http://aggregator:8090",
+        ];
+        args.extend_from_slice(extra);
+        let mut cli = CliArgs::try_parse_from(args).unwrap();
+        cli.check_endpoints()
+            .map(|_| cli.eth_ws_url)
+            .map_err(|e| e.kind())
+    };
+
+    assert_eq!(parse(&[]).unwrap(), None);
+    assert_eq!(
+        parse(&["--eth-ws-from-http"]).unwrap().as_deref(),
+        Some("wss://eth.example/v3/key")
+    );
+
+    assert_eq!(
+        parse(&["--eth-ws-url=https://eth.example"]).unwrap_err(),
+        ErrorKind::InvalidValue
+    );
+    assert_eq!(
+        parse(&["--eth-ws-header=Authorization: Bearer x"]).unwrap_err(),
+        ErrorKind::MissingRequiredArgument
+    );
+    assert!(parse(&[
+        "--eth-ws-from-http",
+        "--eth-ws-header=Authorization: Bearer x"
+    ])
+    .is_ok());
+    assert_eq!(
+        parse(&["--substrate-witness-rpc-urls=ws://a,wss://substrate.example"]).unwrap_err(),
+        ErrorKind::ArgumentConflict
+    );
+    assert_eq!(
+        parse(&[
+            "--substrate-witness-rpc-urls=ws://a,ws://b",
+            "--substrate-quorum=4"
+        ])
+        .unwrap_err(),
+        ErrorKind::InvalidValue
+    );
+    assert!(parse(&["--substrate-witness-rpc-urls=ws://a,ws://b"]).is_ok());
+    assert!(parse(&["--eth-rpc-header=Authorization: Bearer x"]).is_ok());
+
+    let mut ipc = CliArgs::try_parse_from([
+        "avs-finalizer",
+        "--chain-id=31337",
+        "--ecdsa-ephemeral-key",
+        "--bls-ephemeral-key",
+        "--eth-rpc-url=ipc:///var/run/geth.ipc",
+        "--eth-rpc-header=Authorization: Bearer x",
+        "--substrate-rpc-url=wss://substrate.example",
+        "--avs-rpc-url=http://aggregator:8090",
+    ])
+    .unwrap();
+    assert_eq!(
+        ipc.check_endpoints().unwrap_err().kind(),
+        ErrorKind::ArgumentConflict
+    );
+}
+
+#[test]
+fn test_config_command() {
+    let schema = CliArgs::config_command(["avs-finalizer", "config", "schema", "--out=s.json"]);
+    assert!(matches!(
+        schema,
+        Some(ConfigCommand::Schema { out: Some(out) }) if out == Path::new("s.json")
+    ));
+    assert!(
+        CliArgs::config_command(["avs-finalizer", "--chain-id=1", "config", "schema"]).is_some()
+    );
+    assert!(CliArgs::config_command(["avs-finalizer", "doctor"]).is_none());
+    assert!(CliArgs::config_command(["avs-finalizer"]).is_none());
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+/// Version of [`ChainConstants`], bumped whenever a constant is added, removed or changes
+/// meaning so an override file written for another version is rejected.
+pub const CONSTANTS_VERSION: u32 = 1;
+
+/// Constants of the deployed contracts the operator depends on. Built in values match the
+/// contracts the bindings are generated from, an override file can replace them in an
+/// emergency, e.g. after a contract upgrade, without a new release. The file must state its
+/// `version`, the other constants default to their built in value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainConstants {
+    pub version: u32,
+    /// Quorums the operator registers in, its stake is tracked in the first one
+    #[serde(default = "default_quorums")]
+    pub quorums: Vec<u8>,
+    /// Blocks a task stays open for, read from the task manager when unset
+    #[serde(default)]
+    pub task_response_window_blocks: Option<u32>,
+    #[serde(default)]
+    pub pause_indexes: PauseIndexes,
+}
+
+/// Indexes of the `Pausable` flags checked before acting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PauseIndexes {
+    /// `PAUSED_REGISTER_OPERATOR` of the registry coordinator
+    pub register_operator: u8,
+    /// `PAUSED_DEREGISTER_OPERATOR` of the registry coordinator
+    pub deregister_operator: u8,
+    /// `PAUSED_DEPOSITS` of the strategy manager
+    pub deposits: u8,
+}
+
+impl Default for ChainConstants {
+    fn default() -> Self {
+        Self {
+            version: CONSTANTS_VERSION,
+            quorums: default_quorums(),
+            task_response_window_blocks: None,
+            pause_indexes: PauseIndexes::default(),
+        }
+    }
+}
+
+fn default_quorums() -> Vec<u8> {
+    vec![0]
+}
+
+impl Default for PauseIndexes {
+    fn default() -> Self {
+        Self {
+            register_operator: 0,
+            deregister_operator: 1,
+            deposits: 0,
+        }
+    }
+}
+
+impl ChainConstants {
+    /// Built in constants, or the ones of the override file at `path`. Constants missing from
+    /// the file keep their built in value.
+    pub fn load(path: Option<&Path>) -> eyre::Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let constants: Self = serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|e| eyre!("invalid chain constants file {}: {}", path.display(), e))?;
+        constants.validate()?;
+        Ok(constants)
+    }
+
+    fn validate(&self) -> eyre::Result<()> {
+        if self.version != CONSTANTS_VERSION {
+            return Err(eyre!(
+                "chain constants version {} does not match version {} of this build",
+                self.version,
+                CONSTANTS_VERSION
+            ));
+        }
+        if self.quorums.is_empty() {
+            return Err(eyre!("at least one quorum is required"));
+        }
+        if self.task_response_window_blocks == Some(0) {
+            return Err(eyre!("the task response window cannot be empty"));
+        }
+        Ok(())
+    }
+
+    /// Quorum the operator stake is tracked in.
+    pub fn stake_quorum(&self) -> u8 {
+        self.quorums[0]
+    }
+}
+
+#[test]
+fn test_override_constants() {
+    let parse = |json: &str| {
+        serde_json::from_str::<ChainConstants>(json)
+            .map_err(eyre::Report::from)
+            .and_then(|c| c.validate().map(|_| c))
+    };
+    let constants = parse(r#"{"version": 1, "task_response_window_blocks": 50}"#).unwrap();
+    assert_eq!(constants.task_response_window_blocks, Some(50));
+    assert_eq!(constants.quorums, ChainConstants::default().quorums);
+    assert_eq!(constants.pause_indexes, PauseIndexes::default());
+
+    assert!(parse(r#"{"version": 2}"#).is_err());
+    assert!(parse(r#"{"task_response_window_blocks": 50}"#).is_err());
+    assert!(parse(r#"{"version": 1, "quorums": []}"#).is_err());
+    assert!(parse(r#"{"version": 1, "response_window": 50}"#).is_err());
+}
@@ -0,0 +1,220 @@
+use std::{path::Path, time::Duration};
+
+use bindings::shared_types::TaskResponse;
+use ethers::{
+    abi::{AbiDecode, AbiEncode},
+    types::{Bytes, H256},
+    utils::keccak256,
+};
+use eyre::eyre;
+use futures::{stream::FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn};
+
+use crate::{cli::ThresholdArgs, rpc::task_response_digest, script::SIGN_DIGEST_DOMAIN};
+
+use super::bn254::{BlsKeypair, BlsSignature, OperatorId, PublicKey, PublicKeyG2};
+
+pub use avs_operator_sdk::crypto::threshold::*;
+
+/// Body of a partial signature request sent to a signer daemon. The daemon derives the
+/// signed message itself, so it can only sign for a task response or a domain separated
+/// digest, never an arbitrary message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SignRequest {
+    /// ABI encoded task response, its [`task_response_digest`] is signed
+    TaskResponse { task_response: Bytes },
+    /// Digest signed as `keccak256(SIGN_DIGEST_DOMAIN ++ digest)`
+    Digest { digest: H256 },
+}
+
+impl SignRequest {
+    pub fn task_response(task_response: &TaskResponse) -> Self {
+        Self::TaskResponse {
+            task_response: task_response.clone().encode().into(),
+        }
+    }
+
+    /// Message signed for this request.
+    pub fn message(&self) -> eyre::Result<H256> {
+        match self {
+            Self::TaskResponse { task_response } => {
+                Ok(task_response_digest(&TaskResponse::decode(task_response)?))
+            }
+            Self::Digest { digest } => Ok(H256::from(keccak256(
+                [SIGN_DIGEST_DOMAIN, digest.as_bytes()].concat(),
+            ))),
+        }
+    }
+}
+
+/// BLS key of the operator, held locally or split across threshold signer daemons.
+#[derive(Debug)]
+pub enum OperatorBlsKey {
+    Local(BlsKeypair),
+    Threshold(Box<ThresholdSigner>),
+}
+
+impl OperatorBlsKey {
+    pub fn public(&self) -> PublicKey {
+        match self {
+            Self::Local(keypair) => keypair.public,
+            Self::Threshold(signer) => signer.public,
+        }
+    }
+
+    pub fn public_g2(&self) -> PublicKeyG2 {
+        match self {
+            Self::Local(keypair) => keypair.public_g2(),
+            Self::Threshold(signer) => signer.public_g2,
+        }
+    }
+
+    pub fn operator_id(&self) -> OperatorId {
+        BlsKeypair::operator_id_of(self.public())
+    }
+
+    /// The local keypair, for operations which cannot go through the signer daemons.
+    pub fn keypair(&self) -> eyre::Result<&BlsKeypair> {
+        match self {
+            Self::Local(keypair) => Ok(keypair),
+            Self::Threshold(_) => Err(eyre!(
+                "this operation requires the BLS private key, unavailable with threshold signing"
+            )),
+        }
+    }
+
+    pub async fn sign(&self, request: &SignRequest) -> eyre::Result<BlsSignature> {
+        match self {
+            Self::Local(keypair) => keypair.sign(request.message()?.as_bytes()),
+            Self::Threshold(signer) => signer.sign(request).await,
+        }
+    }
+}
+
+/// Collects partial signatures from the signer daemons holding the key shares.
+#[derive(Debug)]
+pub struct ThresholdSigner {
+    key: ThresholdPublicKey,
+    public: PublicKey,
+    public_g2: PublicKeyG2,
+    /// URL of each signer daemon with its own bearer token
+    signers: Vec<(String, Option<String>)>,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl ThresholdSigner {
+    pub fn from_path(path: &Path, cfg: &ThresholdArgs) -> eyre::Result<Self> {
+        let key: ThresholdPublicKey = serde_json::from_slice(&std::fs::read(path)?)?;
+        if cfg.threshold_signer_urls.len() < key.threshold as usize {
+            return Err(eyre!(
+                "{} threshold signers configured, {} required",
+                cfg.threshold_signer_urls.len(),
+                key.threshold
+            ));
+        }
+        let tokens = &cfg.threshold_signer_tokens;
+        if !tokens.is_empty() && tokens.len() != cfg.threshold_signer_urls.len() {
+            return Err(eyre!(
+                "{} threshold signer tokens configured for {} signers, one per signer required",
+                tokens.len(),
+                cfg.threshold_signer_urls.len()
+            ));
+        }
+        Ok(Self {
+            public: key.public()?,
+            public_g2: key.public_g2()?,
+            key,
+            signers: cfg
+                .threshold_signer_urls
+                .iter()
+                .enumerate()
+                .map(|(i, url)| (url.trim_end_matches('/').to_owned(), tokens.get(i).cloned()))
+                .collect(),
+            timeout: Duration::from_millis(cfg.threshold_signer_timeout_ms),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Requests partial signatures for `request` from all signers, combining the first
+    /// `threshold` valid ones. Fails if too few signers answer within the timeout.
+    #[instrument(skip_all)]
+    pub async fn sign(&self, request: &SignRequest) -> eyre::Result<BlsSignature> {
+        let message = request.message()?;
+        let msg = message.as_bytes();
+        let mut requests: FuturesUnordered<_> = self
+            .signers
+            .iter()
+            .map(|(url, token)| async move {
+                (
+                    url,
+                    self.request_partial(url, token.as_deref(), request).await,
+                )
+            })
+            .collect();
+
+        let mut partials = vec![];
+        while let Some((url, res)) = requests.next().await {
+            match res.and_then(|partial| {
+                let signature = self.key.verify_partial(msg, &partial)?;
+                Ok((partial.index, signature))
+            }) {
+                Ok(partial) if partials.iter().all(|(i, _)| *i != partial.0) => {
+                    partials.push(partial)
+                }
+                Ok((index, _)) => warn!("Duplicate key share {} from signer {}", index, url),
+                Err(e) => warn!("No partial signature from signer {}: {:?}", url, e),
+            }
+            if partials.len() == self.key.threshold as usize {
+                break;
+            }
+        }
+
+        let signature = self.key.combine(&partials)?;
+        if !BlsKeypair::verify(self.public_g2, msg, signature)? {
+            return Err(eyre!("combined threshold signature does not verify"));
+        }
+        Ok(signature)
+    }
+
+    async fn request_partial(
+        &self,
+        url: &str,
+        token: Option<&str>,
+        request: &SignRequest,
+    ) -> eyre::Result<PartialSignature> {
+        let mut req = self
+            .client
+            .post(format!("{}/sign", url))
+            .timeout(self.timeout)
+            .body(serde_json::to_string(request)?);
+        if let Some(token) = token {
+            req = req.bearer_auth(token);
+        }
+        let body = req.send().await?.error_for_status()?.text().await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+}
+
+#[test]
+fn test_sign_request_message() {
+    let task = TaskResponse {
+        reference_task_index: 7,
+        block_hash: [1; 32],
+        storage_proof_hash: [2; 32],
+    };
+    let request: SignRequest =
+        serde_json::from_str(&serde_json::to_string(&SignRequest::task_response(&task)).unwrap())
+            .unwrap();
+    assert_eq!(request.message().unwrap(), task_response_digest(&task));
+
+    // a digest can never pass for a task response digest
+    let digest = task_response_digest(&task);
+    assert_ne!(SignRequest::Digest { digest }.message().unwrap(), digest);
+    let garbage = SignRequest::TaskResponse {
+        task_response: vec![1, 2, 3].into(),
+    };
+    assert!(garbage.message().is_err());
+}
@@ -0,0 +1,93 @@
+use eyre::{eyre, OptionExt};
+use serde_json::{json, Value};
+use tracing::{info, instrument};
+
+use crate::{cli::VaultArgs, store::StoreKey};
+
+use super::keystore::EncodedKeystore;
+
+/// Fetches an encrypted keystore stored in the `keystore` field of a Vault KV v2 secret at
+/// `path` (e.g. `secret/data/operator/bls`). An optional `password` field of the secret takes
+/// precedence over `password`. Key material is only ever kept in memory.
+#[instrument(skip(cfg, password))]
+pub async fn fetch_keystore(
+    cfg: &VaultArgs,
+    path: &str,
+    password: Option<String>,
+) -> eyre::Result<EncodedKeystore> {
+    info!("Fetching keystore from Vault");
+    let data = read_secret(cfg, path).await?;
+    let keystore = match &data["keystore"] {
+        Value::String(keystore) => keystore.clone(),
+        Value::Object(_) => data["keystore"].to_string(),
+        _ => return Err(eyre!("Vault secret {} has no keystore field", path)),
+    };
+    let password = data["password"].as_str().map(str::to_owned).or(password);
+
+    EncodedKeystore::from_string(keystore, password)
+}
+
+/// Fetches the store encryption key from the hex encoded `key` field of a Vault KV v2 secret
+/// at `path`.
+#[instrument(skip(cfg))]
+pub async fn fetch_store_key(cfg: &VaultArgs, path: &str) -> eyre::Result<StoreKey> {
+    info!("Fetching store key from Vault");
+    let data = read_secret(cfg, path).await?;
+    let key = data["key"]
+        .as_str()
+        .ok_or_else(|| eyre!("Vault secret {} has no key field", path))?;
+    StoreKey::from_hex(key)
+}
+
+/// Reads the data of the KV v2 secret at `path`.
+async fn read_secret(cfg: &VaultArgs, path: &str) -> eyre::Result<Value> {
+    let addr = cfg
+        .vault_addr
+        .as_deref()
+        .ok_or_eyre("--vault-addr is required to read keys from Vault")?
+        .trim_end_matches('/');
+    let client = reqwest::Client::new();
+    let token = match (&cfg.vault_token, &cfg.vault_role_id, &cfg.vault_secret_id) {
+        (Some(token), _, _) => token.clone(),
+        (None, Some(role_id), Some(secret_id)) => {
+            approle_login(&client, addr, role_id, secret_id).await?
+        }
+        _ => {
+            return Err(eyre!(
+                "either a Vault token or an AppRole role & secret id is required"
+            ))
+        }
+    };
+
+    let body = client
+        .get(format!("{}/v1/{}", addr, path.trim_start_matches('/')))
+        .header("X-Vault-Token", token)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let mut secret: Value = serde_json::from_str(&body)?;
+    Ok(secret["data"]["data"].take())
+}
+
+async fn approle_login(
+    client: &reqwest::Client,
+    addr: &str,
+    role_id: &str,
+    secret_id: &str,
+) -> eyre::Result<String> {
+    let body = client
+        .post(format!("{}/v1/auth/approle/login", addr))
+        .body(json!({ "role_id": role_id, "secret_id": secret_id }).to_string())
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let login: Value = serde_json::from_str(&body)?;
+    login["auth"]["client_token"]
+        .as_str()
+        .map(str::to_owned)
+        .ok_or_eyre("Vault AppRole login returned no client token")
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{task::TaskType, units};
+
+/// Gas paid by one `respondToTask` transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseCost {
+    pub task_type: TaskType,
+    pub quorum_numbers: Vec<u8>,
+    pub gas_used: u64,
+    /// Effective gas price in wei
+    pub gas_price: u128,
+}
+
+impl ResponseCost {
+    fn cost_wei(&self) -> u128 {
+        self.gas_used as u128 * self.gas_price
+    }
+}
+
+/// Average response cost of the tasks of one type and set of quorums, compared with the
+/// expected reward of a task.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskEconomics {
+    pub task_type: TaskType,
+    pub quorum_numbers: Vec<u8>,
+    pub samples: usize,
+    pub avg_gas_used: u64,
+    pub avg_gas_price_gwei: f64,
+    pub avg_cost_eth: f64,
+    pub expected_reward_eth: Option<f64>,
+    pub margin_eth: Option<f64>,
+    /// Margin relative to the expected reward
+    pub margin_pct: Option<f64>,
+}
+
+/// Groups `costs` by task type and quorums, ordered by quorums.
+pub fn summarize(costs: &[ResponseCost], expected_reward_eth: Option<f64>) -> Vec<TaskEconomics> {
+    let mut groups: HashMap<(TaskType, &[u8]), Vec<&ResponseCost>> = HashMap::new();
+    for cost in costs {
+        groups
+            .entry((cost.task_type, &cost.quorum_numbers))
+            .or_default()
+            .push(cost);
+    }
+
+    let mut summary: Vec<_> = groups
+        .into_iter()
+        .map(|((task_type, quorum_numbers), costs)| {
+            let samples = costs.len();
+            let gas_used: u64 = costs.iter().map(|c| c.gas_used).sum();
+            let cost_wei: u128 = costs.iter().map(|c| c.cost_wei()).sum();
+            let avg_cost_eth = units::wei_to_eth(cost_wei.into()) / samples as f64;
+            let avg_gas_price_gwei = match gas_used {
+                0 => 0.0,
+                gas => units::wei_to_gwei(cost_wei.into()) / gas as f64,
+            };
+            let margin_eth = expected_reward_eth.map(|reward| reward - avg_cost_eth);
+            let margin_pct = expected_reward_eth
+                .zip(margin_eth)
+                .filter(|(reward, _)| *reward > 0.0)
+                .map(|(reward, margin)| margin / reward * 100.0);
+            TaskEconomics {
+                task_type,
+                quorum_numbers: quorum_numbers.to_vec(),
+                samples,
+                avg_gas_used: gas_used / samples as u64,
+                avg_gas_price_gwei,
+                avg_cost_eth,
+                expected_reward_eth,
+                margin_eth,
+                margin_pct,
+            }
+        })
+        .collect();
+    summary.sort_by(|a, b| a.quorum_numbers.cmp(&b.quorum_numbers));
+    summary
+}
+
+#[test]
+fn test_summarize() {
+    let cost = |quorum_numbers: Vec<u8>, gas_used, gwei: u128| ResponseCost {
+        task_type: TaskType::ExecuteBlock,
+        quorum_numbers,
+        gas_used,
+        gas_price: gwei * 1_000_000_000,
+    };
+    let costs = [
+        cost(vec![1], 100_000, 10),
+        cost(vec![0], 200_000, 10),
+        cost(vec![0], 200_000, 30),
+    ];
+
+    let summary = summarize(&costs, Some(0.01));
+    assert_eq!(summary.len(), 2);
+    let quorum_0 = &summary[0];
+    assert_eq!(quorum_0.quorum_numbers, vec![0]);
+    assert_eq!(quorum_0.samples, 2);
+    assert_eq!(quorum_0.avg_gas_used, 200_000);
+    assert!((quorum_0.avg_gas_price_gwei - 20.0).abs() < 1e-9);
+    assert!((quorum_0.avg_cost_eth - 0.004).abs() < 1e-12);
+    assert!((quorum_0.margin_eth.unwrap() - 0.006).abs() < 1e-12);
+    assert!((quorum_0.margin_pct.unwrap() - 60.0).abs() < 1e-9);
+
+    assert_eq!(summarize(&costs, None)[1].margin_pct, None);
+}
@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use ethers::types::H256;
+use serde::Serialize;
+
+use crate::{
+    store::{Store, TaskRecord},
+    wal::{self, Wal, WalRecord},
+};
+
+/// Verification evidence of a task whose accepted response diverges from the local result.
+///
+/// The task manager has no challenge mechanism to submit a defense to, the evidence is
+/// gathered for on-call to dispute the response off-chain.
+#[derive(Debug, Serialize)]
+pub struct TaskEvidence {
+    pub task_index: u32,
+    /// Identity of the node which gathered the evidence
+    pub node_id: String,
+    /// Transaction of the accepted response
+    pub response_tx: H256,
+    pub accepted_block_hash: H256,
+    pub accepted_storage_proof_hash: H256,
+    pub local_block_hash: H256,
+    pub local_storage_proof_hash: H256,
+    /// Response recorded in the store, with its verification memo
+    pub record: Option<TaskRecord>,
+    /// Signing decisions recorded in the WAL and their inputs
+    pub decisions: Vec<WalRecord>,
+}
+
+impl TaskEvidence {
+    /// Collects what the store and the WAL recorded about `evidence.task_index`.
+    pub async fn gather(mut self, store: Option<&Store>, wal: Option<&Wal>) -> eyre::Result<Self> {
+        if let Some(store) = store {
+            let task_index = self.task_index;
+            self.record = store.run(move |s| s.get_task(task_index)).await?;
+        }
+        if let Some(wal) = wal {
+            self.decisions = wal::read(wal.path())?
+                .into_iter()
+                .filter(|r| r.inputs.event.task_index == self.task_index)
+                .collect();
+        }
+        Ok(self)
+    }
+
+    /// Writes the evidence as `task-<index>.json` in `dir`, returning its path.
+    pub fn write(&self, dir: &Path) -> eyre::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("task-{}.json", self.task_index));
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,101 @@
+use super::{rpc_err_handler, ws_client};
+use crate::quota::record_substrate_call;
+use eyre::{eyre, OptionExt};
+use futures::future::join_all;
+use node_primitives::BlockNumber;
+use serde_json::json;
+use sp_core::H256;
+use sp_rpc::{list::ListOrValue::Value, number::NumberOrHex::Number};
+use sp_runtime::{
+    traits::{Block as BlockT, Header},
+    DeserializeOwned,
+};
+use substrate_rpc_client::ChainApi;
+use tracing::{instrument, warn};
+
+/// Queries every node in `uris` for the hash of block `at`, counting only the nodes which have
+/// already finalized it, and returns the hash at least `quorum` of them agree on.
+#[instrument(skip(uris))]
+pub async fn agreed_block_hash<Block: BlockT>(
+    uris: &[String],
+    at: BlockNumber,
+    quorum: usize,
+) -> eyre::Result<H256>
+where
+    Block::Hash: Into<H256>,
+    Block::Header: DeserializeOwned,
+{
+    let votes = join_all(
+        uris.iter()
+            .map(|uri| finalized_block_hash::<Block>(uri, at)),
+    )
+    .await;
+
+    let mut tally: Vec<(H256, usize)> = vec![];
+    for (uri, vote) in uris.iter().zip(votes) {
+        match vote {
+            Ok(Some(hash)) => match tally.iter_mut().find(|(h, _)| *h == hash) {
+                Some((_, n)) => *n += 1,
+                None => tally.push((hash, 1)),
+            },
+            Ok(None) => warn!("{} has not finalized block {} yet", uri, at),
+            Err(e) => warn!("{} failed to provide block {}: {:?}", uri, at, e),
+        }
+    }
+
+    tally
+        .into_iter()
+        .find(|(_, n)| *n >= quorum)
+        .map(|(hash, _)| hash)
+        .ok_or_else(|| {
+            eyre!(
+                "less than {} of {} substrate nodes agree on finalized block {}",
+                quorum,
+                uris.len(),
+                at
+            )
+        })
+}
+
+async fn finalized_block_hash<Block: BlockT>(
+    uri: &str,
+    at: BlockNumber,
+) -> eyre::Result<Option<H256>>
+where
+    Block::Hash: Into<H256>,
+    Block::Header: DeserializeOwned,
+{
+    let rpc = ws_client(uri).await.map_err(|e| eyre!(e))?;
+
+    record_substrate_call("chain_getFinalizedHead", json!([]));
+    let finalized = ChainApi::<(), Block::Hash, Block::Header, ()>::finalized_head(&rpc)
+        .await
+        .map_err(rpc_err_handler)
+        .map_err(|e| eyre!(e))?;
+
+    record_substrate_call(
+        "chain_getHeader",
+        json!([format!("0x{}", hex::encode(finalized))]),
+    );
+    let header = ChainApi::<(), Block::Hash, Block::Header, ()>::header(&rpc, Some(finalized))
+        .await
+        .map_err(rpc_err_handler)
+        .map_err(|e| eyre!(e))?
+        .ok_or_eyre("finalized header not found")?;
+    if *header.number() < at.into() {
+        return Ok(None);
+    }
+
+    record_substrate_call("chain_getBlockHash", json!([at]));
+    match ChainApi::<(), Block::Hash, Block::Header, ()>::block_hash(
+        &rpc,
+        Some(Value(Number(at.into()))),
+    )
+    .await
+    .map_err(rpc_err_handler)
+    .map_err(|e| eyre!(e))?
+    {
+        Value(hash) => Ok(hash.map(Into::into)),
+        _ => Err(eyre!("expected a single block hash")),
+    }
+}
@@ -1,21 +1,31 @@
 use super::{
-    full_extensions, rpc_err_handler, setup::build_executor, state::State,
-    state_machine_call_with_proof,
+    full_extensions, keccak_of_encoded, proof::executed_runtime_version, rpc_err_handler,
+    setup::build_executor, state::State, state_machine_call_with_proof, ws_client,
 };
+use crate::quota::{record_runtime_version, record_substrate_call, record_substrate_response};
+use crate::task::{cancellable, Cancelled};
 use eyre::eyre;
 use node_primitives::BlockNumber;
 use sc_executor::sp_wasm_interface::HostFunctions;
+use serde_json::json;
 use sp_core::H256;
 use sp_runtime::{
     generic::SignedBlock,
-    traits::{Block as BlockT, Hash, Header as HeaderT, Keccak256, NumberFor},
+    traits::{Block as BlockT, Header as HeaderT, NumberFor},
 };
 use std::{fmt::Debug, str::FromStr};
-use substrate_rpc_client::{ws_client, ChainApi};
-use tracing::instrument;
+use substrate_rpc_client::ChainApi;
+use tokio_util::sync::CancellationToken;
+use tracing::{instrument, warn};
 
-#[instrument(skip(uri))]
-pub async fn execute_block<Block, HostFns>(uri: &str, at: BlockNumber) -> eyre::Result<(H256, H256)>
+/// Executes block `at` and returns its hash and the hash of its storage proof. The substrate
+/// queries stop and the proof is not built once `cancel` is triggered.
+#[instrument(skip(uri, cancel))]
+pub async fn execute_block<Block, HostFns>(
+    uri: &str,
+    at: BlockNumber,
+    cancel: &CancellationToken,
+) -> eyre::Result<(H256, H256)>
 where
     Block: BlockT + serde::de::DeserializeOwned,
     <Block::Hash as FromStr>::Err: Debug,
@@ -27,18 +37,21 @@ where
     let executor = build_executor::<HostFns>();
     let rpc = ws_client(uri).await.map_err(|e| eyre!(e))?;
 
-    let execute_at_state = State::for_block_number::<Block>(uri, at).await?;
+    let execute_at_state = cancellable(cancel, State::for_block_number::<Block>(uri, at)).await??;
     let execute_at = execute_at_state.at::<Block>()?;
-    let prev_block_state = execute_at_state.into_prev_block_state::<Block>().await?;
 
-    let ext = prev_block_state.to_ext::<Block>().await?;
-
-    // Execute the desired block on top of it
-    let block = ChainApi::<(), Block::Hash, Block::Header, SignedBlock<Block>>::block(
-        &rpc,
-        Some(execute_at),
+    record_substrate_call(
+        "chain_getBlock",
+        json!([format!("0x{}", hex::encode(execute_at))]),
+    );
+    let block = cancellable(
+        cancel,
+        ChainApi::<(), Block::Hash, Block::Header, SignedBlock<Block>>::block(
+            &rpc,
+            Some(execute_at),
+        ),
     )
-    .await
+    .await?
     .map_err(rpc_err_handler)
     .map_err(|e| eyre!(e))?
     .expect("header exists, block should also exist; qed")
@@ -47,11 +60,25 @@ where
     // A digest item gets added when the runtime is processing the block, so we need to pop
     // the last one to be consistent with what a gossiped block would contain.
     let (mut header, extrinsics) = block.deconstruct();
+    let parent = *header.parent_hash();
+    let state_root = *header.state_root();
     header.digest_mut().pop();
     let block = Block::new(header, extrinsics);
 
     // for now, hardcoded for the sake of simplicity. We might customize them one day.
     let payload = block.clone().encode();
+    // hex encoded in the JSON response
+    record_substrate_response(2 * payload.len());
+
+    // the block executes on top of the state of its parent, downloading it is the longest
+    // step of the verification
+    let prev_block_state = execute_at_state.into_parent_state::<Block>(parent);
+    let ext = cancellable(cancel, prev_block_state.to_ext::<Block>()).await??;
+
+    // the proof is built synchronously and cannot be interrupted once started
+    if cancel.is_cancelled() {
+        return Err(Cancelled.into());
+    }
 
     let (proof, _) = state_machine_call_with_proof::<Block, HostFns>(
         &ext,
@@ -62,7 +89,15 @@ where
         full_extensions(executor.clone()),
         None,
     )?;
-    let hash = Keccak256::hash_of(&proof);
+    let hash = keccak_of_encoded(&proof);
+
+    // the block runs the code stored in the state of its parent, so a runtime upgrade takes
+    // effect on the first block after it, which records it. Read with a proof against the
+    // block header rather than trusting the version reported by the node.
+    match executed_runtime_version::<Block>(&rpc, execute_at, state_root).await {
+        Ok(version) => record_runtime_version(version.spec_version, &version.spec_name, at),
+        Err(e) => warn!("Cannot read the runtime version of block {}: {}", at, e),
+    }
 
     Ok((block.hash().into(), hash))
 }
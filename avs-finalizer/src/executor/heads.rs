@@ -0,0 +1,45 @@
+use super::{rpc_err_handler, ws_client};
+use crate::quota::record_substrate_call;
+use eyre::eyre;
+use futures::{stream, Stream};
+use node_primitives::BlockNumber;
+use serde_json::json;
+use sp_runtime::{
+    traits::{Block as BlockT, Header, NumberFor},
+    DeserializeOwned,
+};
+use substrate_rpc_client::ChainApi;
+use tracing::instrument;
+
+/// Subscribes to the finalized heads of the node at `uri`, yielding their block numbers.
+#[instrument(skip(uri))]
+pub async fn finalized_heads<Block: BlockT>(
+    uri: &str,
+) -> eyre::Result<impl Stream<Item = eyre::Result<BlockNumber>>>
+where
+    Block::Header: DeserializeOwned,
+    NumberFor<Block>: TryInto<BlockNumber>,
+{
+    let rpc = ws_client(uri).await.map_err(|e| eyre!(e))?;
+
+    record_substrate_call("chain_subscribeFinalizedHeads", json!([]));
+    let subscription =
+        ChainApi::<(), Block::Hash, Block::Header, ()>::subscribe_finalized_heads(&rpc)
+            .await
+            .map_err(rpc_err_handler)
+            .map_err(|e| eyre!(e))?;
+
+    // the client is moved along so the subscription outlives this function
+    Ok(stream::unfold(
+        (rpc, subscription),
+        |(rpc, mut subscription)| async move {
+            let header = subscription.next().await?;
+            let number = header.map_err(|e| eyre!(e)).and_then(|header| {
+                (*header.number())
+                    .try_into()
+                    .map_err(|_| eyre!("block number overflows u32"))
+            });
+            Some((number, (rpc, subscription)))
+        },
+    ))
+}
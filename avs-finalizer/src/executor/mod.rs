@@ -1,3 +1,4 @@
+use avs_operator_sdk::proofs::KeccakWriter;
 use sc_executor::{sp_wasm_interface::HostFunctions, WasmExecutor};
 use sp_core::{
     offchain::{
@@ -5,16 +6,26 @@ use sp_core::{
         OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
     },
     traits::{CallContext, ReadRuntimeVersionExt},
+    H256,
 };
 use sp_externalities::Extensions;
 use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
-use sp_runtime::traits::{Block as BlockT, HashingFor};
+use sp_runtime::{
+    codec::{Encode, Output},
+    traits::{Block as BlockT, HashingFor},
+};
 use sp_state_machine::{
     OverlayedChanges, StateMachine, StorageProof, TestExternalities, TrieBackendBuilder,
 };
-use std::{fmt::Debug, path::PathBuf, str::FromStr};
+use std::{fmt::Debug, path::PathBuf, str::FromStr, time::Duration};
+use substrate_rpc_client::{WsClient, WsClientBuilder};
+
+use crate::headers::headers_for;
 
+pub mod consensus;
 pub mod execute;
+pub mod heads;
+mod proof;
 mod setup;
 mod state;
 
@@ -114,11 +125,39 @@ fn storage_proof_to_raw_json(storage_proof: &sp_state_machine::StorageProof) ->
     serde_json::to_string_pretty(&obj).unwrap()
 }
 
+/// Keccak-256 of the SCALE encoding of `value`, same as `Keccak256::hash_of` but hashed while
+/// encoding so large storage proofs are never buffered whole.
+pub(crate) fn keccak_of_encoded<T: Encode>(value: &T) -> H256 {
+    struct Hashing(KeccakWriter);
+    impl Output for Hashing {
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.update(bytes);
+        }
+    }
+
+    let mut out = Hashing(KeccakWriter::default());
+    value.encode_to(&mut out);
+    out.0.finalize()
+}
+
 pub(crate) fn rpc_err_handler(error: impl Debug) -> &'static str {
     log::error!(target: LOG_TARGET, "rpc error: {:?}", error);
     "rpc error."
 }
 
+/// Connects to the substrate node at `uri` as [`substrate_rpc_client::ws_client`] does, with
+/// the headers configured for `uri`.
+pub(crate) async fn ws_client(uri: &str) -> Result<WsClient, String> {
+    WsClientBuilder::default()
+        .max_request_size(u32::MAX)
+        .max_response_size(u32::MAX)
+        .request_timeout(Duration::from_secs(60 * 10))
+        .set_headers(headers_for(uri))
+        .build(uri)
+        .await
+        .map_err(|e| format!("`WsClientBuilder` failed to build: {:?}", e))
+}
+
 /// Build all extensions that we typically use.
 pub(crate) fn full_extensions<H: HostFunctions>(wasm_executor: WasmExecutor<H>) -> Extensions {
     let mut extensions = Extensions::default();
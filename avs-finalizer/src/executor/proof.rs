@@ -0,0 +1,89 @@
+use super::rpc_err_handler;
+use crate::quota::{record_substrate_call, record_substrate_response};
+use eyre::eyre;
+use serde_json::json;
+use sp_core::storage::StorageKey;
+use sp_runtime::{
+    codec::{Compact, Decode},
+    traits::{Block as BlockT, HashingFor},
+};
+use sp_state_machine::{read_proof_check, StorageProof};
+use std::collections::HashMap;
+use substrate_rpc_client::{StateApi, WsClient};
+
+/// Storage key of `System::LastRuntimeUpgrade`, the runtime version which last ran its
+/// upgrade, so the one executing the blocks from the upgrade on.
+pub(crate) fn last_runtime_upgrade_key() -> Vec<u8> {
+    [
+        sp_core::twox_128(b"System"),
+        sp_core::twox_128(b"LastRuntimeUpgrade"),
+    ]
+    .concat()
+}
+
+/// `frame_system::LastRuntimeUpgradeInfo`
+#[derive(Debug)]
+pub(crate) struct LastRuntimeUpgrade {
+    pub spec_version: u32,
+    pub spec_name: String,
+}
+
+impl LastRuntimeUpgrade {
+    fn decode(mut encoded: &[u8]) -> eyre::Result<Self> {
+        Ok(Self {
+            spec_version: Compact::<u32>::decode(&mut encoded)?.0,
+            spec_name: String::decode(&mut encoded)?,
+        })
+    }
+}
+
+/// Reads `keys` from the state of block `at` with `state_getReadProof` and checks the proof
+/// against `state_root`, taken from the header of that block. Only the trie nodes on the
+/// path to the keys are downloaded, and a node cannot answer with values the block does not
+/// commit to.
+pub(crate) async fn read_verified<Block: BlockT>(
+    rpc: &WsClient,
+    at: Block::Hash,
+    state_root: Block::Hash,
+    keys: &[Vec<u8>],
+) -> eyre::Result<HashMap<Vec<u8>, Option<Vec<u8>>>> {
+    record_substrate_call(
+        "state_getReadProof",
+        json!([
+            keys.iter()
+                .map(|key| format!("0x{}", hex::encode(key)))
+                .collect::<Vec<_>>(),
+            format!("0x{}", hex::encode(at))
+        ]),
+    );
+    let read = StateApi::<Block::Hash>::read_proof(
+        rpc,
+        keys.iter().cloned().map(StorageKey).collect(),
+        Some(at),
+    )
+    .await
+    .map_err(rpc_err_handler)
+    .map_err(|e| eyre!(e))?;
+    // hex encoded in the JSON response
+    record_substrate_response(read.proof.iter().map(|node| 2 * node.len()).sum());
+
+    let proof = StorageProof::new(read.proof.into_iter().map(|node| node.0));
+    read_proof_check::<HashingFor<Block>, _>(state_root, proof, keys)
+        .map_err(|e| eyre!("invalid read proof at block {:?}: {}", at, e))
+}
+
+/// Version of the runtime which executed block `at`, read with a proof against its
+/// `state_root`.
+pub(crate) async fn executed_runtime_version<Block: BlockT>(
+    rpc: &WsClient,
+    at: Block::Hash,
+    state_root: Block::Hash,
+) -> eyre::Result<LastRuntimeUpgrade> {
+    let key = last_runtime_upgrade_key();
+    let mut values = read_verified::<Block>(rpc, at, state_root, &[key.clone()]).await?;
+    let encoded = values
+        .remove(&key)
+        .flatten()
+        .ok_or_else(|| eyre!("no runtime upgrade recorded at block {:?}", at))?;
+    LastRuntimeUpgrade::decode(&encoded)
+}
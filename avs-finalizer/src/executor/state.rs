@@ -1,15 +1,14 @@
-use super::{hash_of, rpc_err_handler};
+use super::{hash_of, rpc_err_handler, ws_client};
+use crate::quota::record_substrate_call;
 use frame_remote_externalities::{Builder, Mode, OnlineConfig, RemoteExternalities};
 use node_primitives::BlockNumber;
+use serde_json::json;
 
 use sp_core::{storage::well_known_keys, twox_128};
 use sp_rpc::{list::ListOrValue::Value, number::NumberOrHex::Number};
-use sp_runtime::{
-    traits::{Block as BlockT, Header},
-    DeserializeOwned,
-};
-use std::{fmt::Debug, str::FromStr};
-use substrate_rpc_client::{ws_client, ChainApi};
+use sp_runtime::{traits::Block as BlockT, DeserializeOwned};
+use std::{fmt::Debug, str::FromStr, sync::Arc};
+use substrate_rpc_client::ChainApi;
 
 /// The source of runtime *state* to use.
 #[derive(Debug, Clone)]
@@ -26,6 +25,7 @@ impl State {
     {
         let rpc = ws_client(uri).await?;
 
+        record_substrate_call("chain_getBlockHash", json!([at]));
         let hash = ChainApi::<(), Block::Hash, Block::Header, ()>::block_hash(
             &rpc,
             Some(Value(Number(at.into()))),
@@ -54,29 +54,13 @@ impl State {
         hash_of::<Block>(self.at.as_str())
     }
 
-    pub async fn into_prev_block_state<Block: BlockT>(self) -> sc_cli::Result<State>
-    where
-        <Block::Hash as FromStr>::Err: Debug,
-    {
-        // We want to execute the block `at`, therefore need the state of the block *before* it.
-        let at = self.at::<Block>()?;
-
-        // Get the block number requested by the user, or the current block number if they
-        // didn't specify one.
-        let rpc = ws_client(&self.uri).await?;
-        let previous_hash = ChainApi::<(), Block::Hash, Block::Header, ()>::header(&rpc, Some(at))
-            .await
-            .map_err(rpc_err_handler)
-            .and_then(|maybe_header| {
-                maybe_header
-                    .ok_or("header_not_found")
-                    .map(|h| *h.parent_hash())
-            })?;
-
-        Ok(State {
-            at: hex::encode(previous_hash),
+    /// State of `parent`, the block executed on top of it being the one of this state. The
+    /// parent hash is read from the fetched block rather than from a separate header request.
+    pub fn into_parent_state<Block: BlockT>(self, parent: Block::Hash) -> State {
+        State {
+            at: hex::encode(parent),
             ..self
-        })
+        }
     }
 
     /// Create the [`RemoteExternalities`].
@@ -90,10 +74,12 @@ impl State {
         Block::Header: DeserializeOwned,
         <Block::Hash as FromStr>::Err: Debug,
     {
+        // connected here rather than by the builder so that the configured headers are sent
+        let rpc = ws_client(&self.uri).await?;
         // get all keys
         let builder = Builder::<Block>::new().mode(Mode::Online(OnlineConfig {
             at: Some(self.at::<Block>()?),
-            transport: self.uri.to_owned().into(),
+            transport: Arc::new(rpc).into(),
             state_snapshot: None,
             pallets: vec![],
             child_trie: false,
@@ -0,0 +1,71 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::bn254::OperatorId;
+
+/// Announcement that the operator deregisters from the AVS at `exit_at`. The aggregator
+/// stops expecting its signatures and does not eject it meanwhile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitIntent {
+    pub eth_address: Address,
+    pub operator_id: OperatorId,
+    pub chain_id: u64,
+    pub issued_at: u64,
+    /// Unix time after which the operator deregisters
+    pub exit_at: u64,
+}
+
+/// Broadcast intent, `intent` holds the exact JSON that was EIP-191 signed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignedExitIntent {
+    pub intent: String,
+    pub signature: Bytes,
+}
+
+pub async fn sign(
+    operator_id: OperatorId,
+    chain_id: u64,
+    grace: Duration,
+    wallet: &LocalWallet,
+) -> eyre::Result<SignedExitIntent> {
+    let issued_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let intent = serde_json::to_string(&ExitIntent {
+        eth_address: wallet.address(),
+        operator_id,
+        chain_id,
+        issued_at,
+        exit_at: issued_at + grace.as_secs(),
+    })?;
+    let signature = wallet.sign_message(&intent).await?;
+    Ok(SignedExitIntent {
+        intent,
+        signature: signature.to_vec().into(),
+    })
+}
+
+#[tokio::test]
+async fn test_sign_verify() {
+    use ethers::types::Signature;
+
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+    let mut signed = sign(OperatorId::random(), 1, Duration::from_secs(60), &wallet)
+        .await
+        .unwrap();
+    let signature = Signature::try_from(signed.signature.as_ref()).unwrap();
+    let intent: ExitIntent = serde_json::from_str(&signed.intent).unwrap();
+    assert_eq!(intent.exit_at - intent.issued_at, 60);
+    signature
+        .verify(signed.intent.as_str(), wallet.address())
+        .unwrap();
+
+    // the aggregator checks the chain id, which is covered by the signature
+    signed.intent = signed.intent.replace("\"chain_id\":1", "\"chain_id\":2");
+    assert!(signature
+        .verify(signed.intent.as_str(), wallet.address())
+        .is_err());
+}
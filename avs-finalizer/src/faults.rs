@@ -0,0 +1,236 @@
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::Duration,
+};
+
+use ethers::abi::RawLog;
+use eyre::eyre;
+use tracing::{error, warn};
+
+/// Environment variable holding the fault rules, `;` separated.
+pub const FAULTS_ENV: &str = "AVS_FAULTS";
+
+/// JSON-RPC method sent instead of a dropped call, nodes answer it with a "method not found"
+/// error which the node handles like any failed call.
+const DROPPED_METHOD: &str = "fault_injected_drop";
+
+static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+
+/// Chaos scenarios of the resilience tests, which set [`FAULTS_ENV`] to the scenario name
+/// and check that the node recovers.
+const SCENARIOS: &[(&str, &str)] = &[
+    // every fifth call fails, retries and circuit breakers absorb it
+    ("flaky-rpc", "drop-rpc:*:every=5"),
+    // the task subscription lags, tasks are still answered within their window
+    ("slow-ws", "delay-ws:15000:every=1"),
+    // undecodable or altered task events are skipped or caught by the divergence monitor
+    ("corrupt-events", "corrupt-event:every=3"),
+    // the restarted node resends the response it signed instead of signing again
+    ("crash-before-send", "crash:sign:nth=1"),
+    // the restarted node catches up with the task it was executing
+    ("crash-mid-execution", "crash:execute:nth=1"),
+];
+
+/// Fault injected into the node for resilience testing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// Fails the JSON-RPC calls of a method, of any method for `*`
+    DropRpc(String),
+    /// Delays the requests over the Ethereum websocket
+    DelayWs(Duration),
+    /// Flips the last data byte of task manager logs
+    CorruptEvent,
+    /// Aborts the process once a task pipeline stage completes, see `TaskTimer`
+    Crash(String),
+}
+
+/// Hits of its injection point a fault applies to, counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    Nth(u64),
+    Every(u64),
+}
+
+impl Schedule {
+    fn applies(&self, hit: u64) -> bool {
+        match *self {
+            Schedule::Nth(n) => hit == n,
+            Schedule::Every(n) => hit.is_multiple_of(n),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, n) = s
+            .split_once('=')
+            .ok_or_else(|| eyre!("expected `nth=N` or `every=N`, got {:?}", s))?;
+        let n: u64 = n.parse()?;
+        match kind {
+            _ if n == 0 => Err(eyre!("schedules count hits from 1")),
+            "nth" => Ok(Schedule::Nth(n)),
+            "every" => Ok(Schedule::Every(n)),
+            _ => Err(eyre!("unknown schedule {:?}", kind)),
+        }
+    }
+}
+
+/// A fault and when to inject it, written `kind[:target]:schedule`:
+/// - `drop-rpc:eth_getLogs:every=3` fails every third `eth_getLogs` call
+/// - `delay-ws:5000:nth=2` delays the second websocket request by 5 seconds
+/// - `corrupt-event:every=2` corrupts every other task manager log
+/// - `crash:sign:nth=1` aborts the node once the first task is signed, before it is sent
+#[derive(Debug)]
+pub struct Rule {
+    fault: Fault,
+    schedule: Schedule,
+    hits: AtomicU64,
+}
+
+impl Rule {
+    /// Counts a hit of the injection point of the rule, returns whether the fault applies.
+    fn hit(&self) -> bool {
+        let hit = self.hits.fetch_add(1, Ordering::Relaxed) + 1;
+        self.schedule.applies(hit)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().split(':').collect();
+        let (fault, schedule) = match parts[..] {
+            ["drop-rpc", method, schedule] => (Fault::DropRpc(method.to_owned()), schedule),
+            ["delay-ws", ms, schedule] => {
+                (Fault::DelayWs(Duration::from_millis(ms.parse()?)), schedule)
+            }
+            ["corrupt-event", schedule] => (Fault::CorruptEvent, schedule),
+            ["crash", stage, schedule] => (Fault::Crash(stage.to_owned()), schedule),
+            _ => return Err(eyre!("invalid fault rule {:?}", s)),
+        };
+        Ok(Self {
+            fault,
+            schedule: schedule.parse()?,
+            hits: AtomicU64::new(0),
+        })
+    }
+}
+
+/// Parses `;` separated fault rules, or the name of one of the [`SCENARIOS`].
+pub fn parse(spec: &str) -> eyre::Result<Vec<Rule>> {
+    let spec = SCENARIOS
+        .iter()
+        .find(|(name, _)| *name == spec.trim())
+        .map_or(spec, |(_, rules)| rules);
+    spec.split(';')
+        .filter(|rule| !rule.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Reads the fault rules from [`FAULTS_ENV`], returns how many were set up.
+pub fn configure() -> eyre::Result<usize> {
+    let rules = match std::env::var(FAULTS_ENV) {
+        Ok(spec) => parse(&spec)?,
+        Err(_) => vec![],
+    };
+    let count = rules.len();
+    if count > 0 {
+        warn!(
+            "Fault injection enabled with {} rules: {:?}",
+            count,
+            rules.iter().map(|r| &r.fault).collect::<Vec<_>>()
+        );
+    }
+    let _ = RULES.set(rules);
+    Ok(count)
+}
+
+fn rules() -> &'static [Rule] {
+    RULES.get().map_or(&[], Vec::as_slice)
+}
+
+/// JSON-RPC method to send for a call of `method` to `provider`, [`DROPPED_METHOD`] when
+/// the call is to be dropped. Websocket requests are delayed first.
+pub async fn rpc_method<'a>(provider: &str, method: &'a str) -> &'a str {
+    let mut dropped = false;
+    for rule in rules() {
+        match &rule.fault {
+            Fault::DelayWs(delay) if provider == "eth_ws" && rule.hit() => {
+                warn!("Injected fault: delaying {} by {:?}", method, delay);
+                tokio::time::sleep(*delay).await;
+            }
+            Fault::DropRpc(target) if (target == "*" || target == method) && rule.hit() => {
+                warn!("Injected fault: dropping {} to {}", method, provider);
+                dropped = true;
+            }
+            _ => {}
+        }
+    }
+    if dropped {
+        DROPPED_METHOD
+    } else {
+        method
+    }
+}
+
+/// `log` with its last data byte flipped when scheduled.
+pub fn corrupt_event(mut log: RawLog) -> RawLog {
+    for rule in rules() {
+        if rule.fault == Fault::CorruptEvent && rule.hit() {
+            if let Some(byte) = log.data.last_mut() {
+                warn!("Injected fault: corrupting a task manager log");
+                *byte ^= 0xff;
+            }
+        }
+    }
+    log
+}
+
+/// Aborts the process when a crash is scheduled once `stage` completes.
+pub fn crash_point(stage: &str) {
+    for rule in rules() {
+        if matches!(&rule.fault, Fault::Crash(target) if target == stage) && rule.hit() {
+            error!("Injected fault: crashing after the {} stage", stage);
+            std::process::abort();
+        }
+    }
+}
+
+#[test]
+fn test_parse_rules() {
+    let rules =
+        parse("drop-rpc:*:every=3; delay-ws:250:nth=2;corrupt-event:nth=1;crash:sign:nth=4")
+            .unwrap();
+    assert_eq!(rules[0].fault, Fault::DropRpc("*".into()));
+    assert_eq!(rules[0].schedule, Schedule::Every(3));
+    assert_eq!(rules[1].fault, Fault::DelayWs(Duration::from_millis(250)));
+    assert_eq!(rules[2].fault, Fault::CorruptEvent);
+    assert_eq!(rules[3].fault, Fault::Crash("sign".into()));
+    assert_eq!(rules[3].schedule, Schedule::Nth(4));
+
+    assert!(parse("drop-rpc:every=3").is_err());
+    assert!(parse("crash:sign:every=0").is_err());
+    assert!(parse("flood:nth=1").is_err());
+    assert!(parse("").unwrap().is_empty());
+    for (name, _) in SCENARIOS {
+        assert!(!parse(name).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn test_schedule() {
+    let rule: Rule = "drop-rpc:eth_call:every=2".parse().unwrap();
+    let hits: Vec<bool> = (0..4).map(|_| rule.hit()).collect();
+    assert_eq!(hits, [false, true, false, true]);
+    let rule: Rule = "crash:sign:nth=2".parse().unwrap();
+    let hits: Vec<bool> = (0..3).map(|_| rule.hit()).collect();
+    assert_eq!(hits, [false, true, false]);
+}
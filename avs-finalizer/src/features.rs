@@ -0,0 +1,103 @@
+use serde::Serialize;
+
+use crate::cli::CliArgs;
+
+/// Optional features and whether this build includes them. Light client verification and
+/// private transaction submission are not part of this code base yet and listed as missing,
+/// so that their absence shows in the report rather than being guessed from the version.
+const COMPILED: &[(&str, bool)] = &[
+    ("kms", true),
+    ("light-client", false),
+    ("aggregator-client", true),
+    ("private-tx", false),
+    ("relayer", true),
+    ("gossip", true),
+    ("threshold-bls", true),
+    ("postgres-store", true),
+    ("verify-workers", true),
+    ("fault-injection", cfg!(feature = "fault-injection")),
+];
+
+/// Names of the optional features compiled into this build.
+pub fn compiled() -> Vec<&'static str> {
+    COMPILED
+        .iter()
+        .filter(|(_, compiled)| *compiled)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Optional feature of the node, compiled into this build and turned on by its configuration.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Feature {
+    pub name: &'static str,
+    pub compiled: bool,
+    pub enabled: bool,
+}
+
+/// Optional features of this build with whether `cfg` enables them, for support to see what
+/// a deployment can do.
+pub fn report(cfg: &CliArgs) -> Vec<Feature> {
+    COMPILED
+        .iter()
+        .map(|&(name, compiled)| Feature {
+            name,
+            compiled,
+            enabled: compiled && enabled(name, cfg),
+        })
+        .collect()
+}
+
+fn enabled(name: &str, cfg: &CliArgs) -> bool {
+    match name {
+        // keys and the store key fetched from Vault
+        "kms" => {
+            cfg.ecdsa_key.ecdsa_key_vault_path.is_some()
+                || cfg.bls_key.bls_key_vault_path.is_some()
+                || cfg.db_key_vault_path.is_some()
+        }
+        // shadow nodes never send their responses
+        "aggregator-client" => cfg.shadow_of.is_none(),
+        "relayer" => cfg.relayer.relayer_url.is_some(),
+        "gossip" => !cfg.gossip.gossip_peers.is_empty(),
+        "threshold-bls" => cfg.bls_key.bls_threshold_key.is_some(),
+        "postgres-store" => cfg.db_url.is_some(),
+        "verify-workers" => cfg.verify_workers > 0,
+        // test builds only, the rules are read from the environment
+        "fault-injection" => std::env::var_os("AVS_FAULTS").is_some(),
+        _ => false,
+    }
+}
+
+/// One line summary of `features`, `+` for enabled and `-` for compiled but disabled ones,
+/// missing ones are left out.
+pub fn summary(features: &[Feature]) -> String {
+    features
+        .iter()
+        .filter(|f| f.compiled)
+        .map(|f| format!("{}{}", if f.enabled { '+' } else { '-' }, f.name))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn test_summary() {
+    let features = [
+        Feature {
+            name: "kms",
+            compiled: true,
+            enabled: true,
+        },
+        Feature {
+            name: "light-client",
+            compiled: false,
+            enabled: false,
+        },
+        Feature {
+            name: "relayer",
+            compiled: true,
+            enabled: false,
+        },
+    ];
+    assert_eq!(summary(&features), "+kms -relayer");
+}
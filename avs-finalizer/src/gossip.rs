@@ -0,0 +1,523 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bindings::{
+    mangata_task_manager::CheckSignaturesIndices,
+    shared_types::{G1Point, G2Point, Task, TaskResponse},
+};
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, Signature, H256},
+};
+use eyre::{eyre, OptionExt};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    chainio::avs::AvsContracts,
+    cli::GossipArgs,
+    crypto::{
+        bn254::{BlsKeypair, BlsSignature, OperatorId, PublicKey, PublicKeyG2},
+        EthConvert,
+    },
+    metrics::metrics,
+    rpc::{decode_bls_task_response, task_response_digest},
+};
+
+/// Tasks whose partial signatures are kept, older ones are dropped first.
+const GOSSIP_TASKS: usize = 64;
+/// Partial signatures kept per task, more than the operators of any quorum.
+const PARTIALS_PER_TASK: usize = 256;
+/// Tasks a peer may gossip beyond the latest this node knows of, when it saw the task event
+/// first.
+const TASK_INDEX_LEAD: u32 = 2;
+/// How long registration lookups of gossiping accounts are cached.
+const REGISTRATION_TTL: Duration = Duration::from_secs(10 * 60);
+/// Accounts whose registration is cached, the cache is cleared past it.
+const REGISTRATION_CACHE_SIZE: usize = 4096;
+
+/// Operators registered with the AVS, only their partial signatures are kept.
+#[async_trait]
+pub trait OperatorRegistry: Send + Sync {
+    /// Operator id of `operator`, `None` unless it is registered.
+    async fn operator_id_of(&self, operator: Address) -> eyre::Result<Option<OperatorId>>;
+}
+
+#[async_trait]
+impl OperatorRegistry for AvsContracts {
+    async fn operator_id_of(&self, operator: Address) -> eyre::Result<Option<OperatorId>> {
+        AvsContracts::operator_id_of(self, operator).await
+    }
+}
+
+/// Partial signature of a task response gossiped between operators, with the BLS keys it
+/// was signed with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GossipMessage {
+    pub chain_id: u64,
+    /// ECDSA account of the operator, which signs the message
+    pub eth_address: Address,
+    pub public_g1: G1Point,
+    pub public_g2: G2Point,
+    /// Signed task response, as sent to the aggregator
+    pub response: String,
+}
+
+/// Gossiped message, `message` holds the exact JSON that was EIP-191 signed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignedGossip {
+    pub message: String,
+    pub signature: Bytes,
+}
+
+/// Partial signature whose ECDSA and BLS signatures were checked. Whether `eth_address` is
+/// registered as `operator_id` is left to the caller, as it needs the chain.
+#[derive(Debug, Clone)]
+pub struct VerifiedPartial {
+    pub eth_address: Address,
+    pub operator_id: OperatorId,
+    pub task: TaskResponse,
+    pub digest: H256,
+    pub signature: BlsSignature,
+    pub public_g2: PublicKeyG2,
+}
+
+/// Aggregate of the partial signatures gossiped for a task response, for a backup aggregator
+/// to submit while the aggregator is down.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GossipAggregate {
+    pub task_index: u32,
+    pub digest: H256,
+    pub signers: Vec<OperatorId>,
+    pub signature: G1Point,
+    /// Sum of the G2 public keys of the signers, the aggregate signature verifies against it
+    pub apk_g2: G2Point,
+    pub non_signers: NonSigners,
+}
+
+/// Members of the task quorums at its reference block which did not sign, with the indices
+/// `checkSignatures` looks their stakes up at. They are read at the reference block rather
+/// than from the current registries, whose members may have changed since.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NonSigners {
+    /// Block the task was created at, the stakes are checked at
+    pub reference_block: u32,
+    pub quorum_numbers: Bytes,
+    /// Ids of the non-signers, ascending
+    pub operator_ids: Vec<OperatorId>,
+    #[serde(flatten)]
+    pub indices: CheckSignaturesIndices,
+}
+
+/// Exchanges partial signatures with the operators at `--gossip-peers`, over their operator
+/// API. Messages are authenticated by the ECDSA key of a registered operator and encrypted in
+/// transit when the peers serve their API over TLS.
+pub struct Gossip {
+    peers: Vec<String>,
+    timeout: Duration,
+    chain_id: u64,
+    client: reqwest::Client,
+    registry: Arc<dyn OperatorRegistry>,
+    /// Operator ids of the gossiping accounts and when they were looked up
+    registrations: Mutex<HashMap<Address, (Option<OperatorId>, Instant)>>,
+    /// Latest task index seen by this node, later tasks are not accepted
+    latest_task: Mutex<Option<u32>>,
+    /// Partial signatures received per task index
+    inbox: Mutex<BTreeMap<u32, Vec<VerifiedPartial>>>,
+    /// Tasks the aggregator failed to take the response of, aggregated from the inbox
+    fallback: Mutex<BTreeMap<u32, Task>>,
+    aggregates: Mutex<BTreeMap<u32, GossipAggregate>>,
+}
+
+impl std::fmt::Debug for Gossip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gossip")
+            .field("peers", &self.peers)
+            .field("chain_id", &self.chain_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Gossip {
+    /// The gossip layer, `None` without `--gossip-peers`.
+    pub fn from_cli(
+        cfg: &GossipArgs,
+        chain_id: u64,
+        registry: Arc<dyn OperatorRegistry>,
+    ) -> Option<Self> {
+        if cfg.gossip_peers.is_empty() {
+            return None;
+        }
+        Some(Self {
+            peers: cfg
+                .gossip_peers
+                .iter()
+                .map(|url| url.trim_end_matches('/').to_owned())
+                .collect(),
+            timeout: Duration::from_millis(cfg.gossip_timeout_ms),
+            chain_id,
+            client: reqwest::Client::new(),
+            registry,
+            registrations: Mutex::default(),
+            latest_task: Mutex::default(),
+            inbox: Mutex::default(),
+            fallback: Mutex::default(),
+            aggregates: Mutex::default(),
+        })
+    }
+
+    /// Signs the BLS signed `response` with the operator `wallet` and sends it to every peer,
+    /// keeping it for the local aggregate as well.
+    #[instrument(skip_all)]
+    pub async fn publish(
+        &self,
+        wallet: &LocalWallet,
+        public: (PublicKey, PublicKeyG2),
+        response: &str,
+    ) -> eyre::Result<()> {
+        let message = serde_json::to_string(&GossipMessage {
+            chain_id: self.chain_id,
+            eth_address: wallet.address(),
+            public_g1: EthConvert::to_g1(public.0).ok_or_eyre("BLS public key at infinity")?,
+            public_g2: EthConvert::to_g2(public.1).ok_or_eyre("BLS public key at infinity")?,
+            response: response.to_owned(),
+        })?;
+        let signature = wallet.sign_message(&message).await?;
+        let signed = SignedGossip {
+            message,
+            signature: signature.to_vec().into(),
+        };
+        self.keep(verify(&signed, self.chain_id)?);
+
+        let body = serde_json::to_string(&signed)?;
+        let sent = join_all(self.peers.iter().map(|peer| {
+            self.client
+                .post(format!("{}/gossip", peer))
+                .timeout(self.timeout)
+                .body(body.clone())
+                .send()
+        }))
+        .await;
+        for (peer, res) in self.peers.iter().zip(sent) {
+            if let Err(e) = res.and_then(|res| res.error_for_status()) {
+                warn!("Cannot gossip to {}: {}", peer, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that task `task_index` was created, peers may gossip it from now on.
+    pub fn task_created(&self, task_index: u32) {
+        let mut latest = self.latest_task.lock().expect("poisoned lock");
+        *latest = Some(latest.map_or(task_index, |latest| latest.max(task_index)));
+    }
+
+    /// Checks and keeps a message received from a peer.
+    pub async fn receive(&self, body: &[u8]) -> eyre::Result<()> {
+        let partial = self.admit(body).await;
+        let outcome = if partial.is_ok() {
+            "accepted"
+        } else {
+            "rejected"
+        };
+        metrics()
+            .gossip_messages
+            .with_label_values(&[outcome])
+            .inc();
+        let partial = partial?;
+        debug!(
+            "Partial signature of task {} gossiped by {:?}",
+            partial.task.reference_task_index, partial.eth_address
+        );
+        self.keep(partial);
+        Ok(())
+    }
+
+    /// Verifies a message, for a task this node knows of and from a registered operator.
+    async fn admit(&self, body: &[u8]) -> eyre::Result<VerifiedPartial> {
+        let signed: SignedGossip = serde_json::from_slice(body)?;
+        let partial = verify(&signed, self.chain_id)?;
+        let task_index = partial.task.reference_task_index;
+        match *self.latest_task.lock().expect("poisoned lock") {
+            Some(latest) if task_index <= latest.saturating_add(TASK_INDEX_LEAD) => {}
+            latest => {
+                return Err(eyre!(
+                    "partial signature of task {}, the latest known is {:?}",
+                    task_index,
+                    latest
+                ))
+            }
+        }
+        let operator_id = self.registered_id(partial.eth_address).await?;
+        if operator_id != Some(partial.operator_id) {
+            return Err(eyre!(
+                "{:?} is not registered as operator {:?}",
+                partial.eth_address,
+                partial.operator_id
+            ));
+        }
+        Ok(partial)
+    }
+
+    /// Operator id `eth_address` is registered as, cached for a while.
+    async fn registered_id(&self, eth_address: Address) -> eyre::Result<Option<OperatorId>> {
+        let cached = self
+            .registrations
+            .lock()
+            .expect("poisoned lock")
+            .get(&eth_address)
+            .copied();
+        if let Some((operator_id, at)) = cached {
+            if at.elapsed() < REGISTRATION_TTL {
+                return Ok(operator_id);
+            }
+        }
+        let operator_id = self.registry.operator_id_of(eth_address).await?;
+        let mut registrations = self.registrations.lock().expect("poisoned lock");
+        if registrations.len() >= REGISTRATION_CACHE_SIZE {
+            registrations.retain(|_, (_, at)| at.elapsed() < REGISTRATION_TTL);
+            if registrations.len() >= REGISTRATION_CACHE_SIZE {
+                registrations.clear();
+            }
+        }
+        registrations.insert(eth_address, (operator_id, Instant::now()));
+        Ok(operator_id)
+    }
+
+    fn keep(&self, partial: VerifiedPartial) {
+        let mut inbox = self.inbox.lock().expect("poisoned lock");
+        let partials = inbox.entry(partial.task.reference_task_index).or_default();
+        // the latest message of an operator replaces its previous one
+        partials.retain(|p| p.operator_id != partial.operator_id);
+        if partials.len() >= PARTIALS_PER_TASK {
+            warn!(
+                "Already {} partial signatures of task {}, dropping that of {:?}",
+                partials.len(),
+                partial.task.reference_task_index,
+                partial.eth_address
+            );
+            return;
+        }
+        partials.push(partial);
+        while inbox.len() > GOSSIP_TASKS {
+            inbox.pop_first();
+        }
+    }
+
+    /// Aggregates the partial signatures of `task_index` from now on, once the aggregator
+    /// failed to take its response.
+    pub fn aggregate_later(&self, task_index: u32, task: Task) {
+        let mut fallback = self.fallback.lock().expect("poisoned lock");
+        fallback.insert(task_index, task);
+        while fallback.len() > GOSSIP_TASKS {
+            fallback.pop_first();
+        }
+    }
+
+    /// Partial signatures received for the tasks to aggregate, by task index.
+    pub fn fallback_partials(&self) -> Vec<(u32, Task, Vec<VerifiedPartial>)> {
+        let fallback = self.fallback.lock().expect("poisoned lock");
+        let inbox = self.inbox.lock().expect("poisoned lock");
+        fallback
+            .iter()
+            .filter_map(|(task_index, task)| {
+                Some((*task_index, task.clone(), inbox.get(task_index)?.clone()))
+            })
+            .collect()
+    }
+
+    /// Keeps `aggregate`, returns whether it has more signers than the previous one.
+    pub fn set_aggregate(&self, aggregate: GossipAggregate) -> bool {
+        let mut aggregates = self.aggregates.lock().expect("poisoned lock");
+        let grew = aggregates
+            .get(&aggregate.task_index)
+            .is_none_or(|previous| previous.signers.len() < aggregate.signers.len());
+        aggregates.insert(aggregate.task_index, aggregate);
+        while aggregates.len() > GOSSIP_TASKS {
+            aggregates.pop_first();
+        }
+        grew
+    }
+
+    pub fn aggregates(&self) -> Vec<GossipAggregate> {
+        self.aggregates
+            .lock()
+            .expect("poisoned lock")
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Checks the ECDSA signature, the chain and the BLS partial signature of a message against
+/// the keys it carries.
+fn verify(signed: &SignedGossip, chain_id: u64) -> eyre::Result<VerifiedPartial> {
+    let message: GossipMessage = serde_json::from_str(&signed.message)?;
+    Signature::try_from(signed.signature.as_ref())?
+        .verify(signed.message.as_str(), message.eth_address)
+        .map_err(|e| eyre!("invalid gossip signature: {}", e))?;
+    if message.chain_id != chain_id {
+        return Err(eyre!(
+            "partial signature for chain {}, expected {}",
+            message.chain_id,
+            chain_id
+        ));
+    }
+
+    let public_g1 = EthConvert::from_g1(&message.public_g1).ok_or_eyre("invalid G1 public key")?;
+    let public_g2 = EthConvert::from_g2(&message.public_g2).ok_or_eyre("invalid G2 public key")?;
+    let (task, signature, operator_id) = decode_bls_task_response(&message.response)?;
+    if BlsKeypair::operator_id_of(public_g1) != operator_id {
+        return Err(eyre!(
+            "operator id {:?} is not of the G1 public key",
+            operator_id
+        ));
+    }
+    if !BlsKeypair::is_same_key(public_g1, public_g2) {
+        return Err(eyre!("G1 and G2 public keys do not match"));
+    }
+    let digest = task_response_digest(&task);
+    if !BlsKeypair::verify(public_g2, digest.as_bytes(), signature)? {
+        return Err(eyre!("partial signature does not verify"));
+    }
+    Ok(VerifiedPartial {
+        eth_address: message.eth_address,
+        operator_id,
+        task,
+        digest,
+        signature,
+        public_g2,
+    })
+}
+
+/// Aggregates the partial signatures of `digest` among `partials`, `None` if there is none.
+/// The signers must not be among `non_signers`.
+pub fn aggregate(
+    task_index: u32,
+    digest: H256,
+    partials: &[VerifiedPartial],
+    non_signers: NonSigners,
+) -> Option<GossipAggregate> {
+    let signing: Vec<&VerifiedPartial> = partials.iter().filter(|p| p.digest == digest).collect();
+    if signing.is_empty() {
+        return None;
+    }
+    let signatures: Vec<BlsSignature> = signing.iter().map(|p| p.signature).collect();
+    let keys: Vec<PublicKeyG2> = signing.iter().map(|p| p.public_g2).collect();
+    Some(GossipAggregate {
+        task_index,
+        digest,
+        signers: signing.iter().map(|p| p.operator_id).collect(),
+        signature: EthConvert::to_g1(BlsKeypair::aggregate(&signatures))?,
+        apk_g2: EthConvert::to_g2(BlsKeypair::aggregate_public_g2(&keys))?,
+        non_signers,
+    })
+}
+
+#[tokio::test]
+async fn test_gossip_partials() {
+    use crate::crypto::keystore::EncodedKeystore;
+    use avs_operator_sdk::response::encode_task_response;
+
+    #[derive(Default)]
+    struct Registered(Mutex<HashMap<Address, OperatorId>>);
+
+    #[async_trait]
+    impl OperatorRegistry for Registered {
+        async fn operator_id_of(&self, operator: Address) -> eyre::Result<Option<OperatorId>> {
+            Ok(self.0.lock().unwrap().get(&operator).copied())
+        }
+    }
+
+    let registry = Arc::new(Registered::default());
+    let gossip = Gossip::from_cli(
+        &GossipArgs {
+            gossip_peers: vec!["http://127.0.0.1:1".into()],
+            gossip_timeout_ms: 100,
+        },
+        1,
+        registry.clone(),
+    )
+    .unwrap();
+    let task = TaskResponse {
+        reference_task_index: 7,
+        block_hash: [1; 32],
+        storage_proof_hash: [2; 32],
+    };
+    let digest = task_response_digest(&task);
+
+    let mut messages = vec![];
+    for _ in 0..2 {
+        let wallet = EncodedKeystore::random().unwrap().into_wallet().unwrap();
+        let keypair = EncodedKeystore::random()
+            .unwrap()
+            .into_bls_keypair()
+            .unwrap();
+        let response = encode_task_response(task.clone(), &keypair).unwrap();
+        let message = serde_json::to_string(&GossipMessage {
+            chain_id: 1,
+            eth_address: wallet.address(),
+            public_g1: EthConvert::to_g1(keypair.public).unwrap(),
+            public_g2: EthConvert::to_g2(keypair.public_g2()).unwrap(),
+            response,
+        })
+        .unwrap();
+        let signature = wallet.sign_message(&message).await.unwrap();
+        messages.push(SignedGossip {
+            message,
+            signature: signature.to_vec().into(),
+        });
+        registry
+            .0
+            .lock()
+            .unwrap()
+            .insert(wallet.address(), BlsKeypair::operator_id_of(keypair.public));
+    }
+    let body = |signed: &SignedGossip| serde_json::to_vec(signed).unwrap();
+    // partial signatures of tasks this node has not seen yet are rejected
+    gossip.task_created(4);
+    assert!(gossip.receive(&body(&messages[0])).await.is_err());
+    gossip.task_created(5);
+    for signed in &messages {
+        gossip.receive(&body(signed)).await.unwrap();
+    }
+    // as those of unregistered accounts, even with valid signatures
+    registry.0.lock().unwrap().clear();
+    gossip.registrations.lock().unwrap().clear();
+    assert!(gossip.receive(&body(&messages[0])).await.is_err());
+    // a message signed by another account or for another chain is rejected
+    let forged = SignedGossip {
+        message: messages[0].message.clone(),
+        signature: messages[1].signature.clone(),
+    };
+    assert!(verify(&forged, 1).is_err());
+    assert!(verify(&messages[0], 2).is_err());
+
+    assert!(gossip.fallback_partials().is_empty());
+    gossip.aggregate_later(7, Task::default());
+    let (task_index, _, partials) = gossip.fallback_partials().remove(0);
+    assert_eq!((task_index, partials.len()), (7, 2));
+
+    let non_signers = NonSigners {
+        reference_block: 10,
+        quorum_numbers: vec![0].into(),
+        operator_ids: vec![H256::repeat_byte(3)],
+        indices: Default::default(),
+    };
+    let combined = aggregate(7, digest, &partials, non_signers.clone()).unwrap();
+    assert_eq!(combined.signers.len(), 2);
+    assert!(BlsKeypair::verify(
+        EthConvert::from_g2(&combined.apk_g2).unwrap(),
+        digest.as_bytes(),
+        EthConvert::from_g1(&combined.signature).unwrap()
+    )
+    .unwrap());
+    assert!(gossip.set_aggregate(combined.clone()));
+    assert!(!gossip.set_aggregate(combined));
+    assert!(aggregate(7, H256::zero(), &partials, non_signers).is_none());
+}
@@ -0,0 +1,161 @@
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+
+use crate::metrics::metrics;
+
+/// Chains whose block production the node depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FollowedChain {
+    Ethereum,
+    /// Followed by its finalized heads
+    Substrate,
+}
+
+impl FollowedChain {
+    fn label(&self) -> &'static str {
+        match self {
+            FollowedChain::Ethereum => "ethereum",
+            FollowedChain::Substrate => "substrate",
+        }
+    }
+}
+
+/// Last head of a chain and when it was first seen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Progress {
+    head: u64,
+    since: Instant,
+    halted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Transition {
+    Halted { head: u64, for_: Duration },
+    Resumed { head: u64, after: Duration },
+}
+
+impl Progress {
+    /// Follows the chain to `head` at `now`, reporting when it halted or resumed.
+    fn update(&mut self, head: u64, now: Instant, threshold: Duration) -> Option<Transition> {
+        let stalled = now.saturating_duration_since(self.since);
+        if head != self.head {
+            let resumed = self.halted;
+            *self = Self {
+                head,
+                since: now,
+                halted: false,
+            };
+            return resumed.then_some(Transition::Resumed {
+                head,
+                after: stalled,
+            });
+        }
+        if !self.halted && stalled > threshold {
+            self.halted = true;
+            return Some(Transition::Halted {
+                head,
+                for_: stalled,
+            });
+        }
+        None
+    }
+}
+
+/// Detects the followed chains stopping to produce blocks for longer than a threshold, e.g.
+/// during planned maintenance. While any is halted the node is idle: tasks expiring or
+/// failing then are expected and not counted against it, and the `chain_halted` gauge lets
+/// alerting rules inhibit the alerts on timeouts. A stuck RPC endpoint cannot be told apart
+/// from a halted chain, which is why the detection is opt-in.
+#[derive(Debug)]
+pub struct ChainHalts {
+    threshold: Duration,
+    chains: Mutex<BTreeMap<FollowedChain, Progress>>,
+}
+
+impl ChainHalts {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            chains: Mutex::default(),
+        }
+    }
+
+    /// Records `head` as the current head of `chain`, its first head only starts the clock.
+    pub fn observe(&self, chain: FollowedChain, head: u64) {
+        let now = Instant::now();
+        let mut chains = self.chains.lock().expect("poisoned lock");
+        let progress = chains.entry(chain).or_insert(Progress {
+            head,
+            since: now,
+            halted: false,
+        });
+        let transition = progress.update(head, now, self.threshold);
+        let gauge = metrics().chain_halted.with_label_values(&[chain.label()]);
+        match transition {
+            Some(Transition::Halted { head, for_ }) => {
+                warn!(
+                    "No new {} block for {:?} since block {}, idling until it resumes",
+                    chain.label(),
+                    for_,
+                    head
+                );
+                gauge.set(1);
+            }
+            Some(Transition::Resumed { head, after }) => {
+                info!(
+                    "{} produces blocks again at block {} after {:?}, resuming",
+                    chain.label(),
+                    head,
+                    after
+                );
+                gauge.set(0);
+            }
+            None => {}
+        }
+    }
+
+    /// Whether a followed chain is halted.
+    pub fn idle(&self) -> bool {
+        self.chains
+            .lock()
+            .expect("poisoned lock")
+            .values()
+            .any(|p| p.halted)
+    }
+}
+
+#[test]
+fn test_progress_update() {
+    let start = Instant::now();
+    let threshold = Duration::from_secs(60);
+    let mut progress = Progress {
+        head: 10,
+        since: start,
+        halted: false,
+    };
+    let at = |secs| start + Duration::from_secs(secs);
+    assert_eq!(progress.update(10, at(30), threshold), None);
+    assert_eq!(
+        progress.update(10, at(61), threshold),
+        Some(Transition::Halted {
+            head: 10,
+            for_: Duration::from_secs(61)
+        })
+    );
+    // reported once
+    assert_eq!(progress.update(10, at(90), threshold), None);
+    assert_eq!(
+        progress.update(11, at(300), threshold),
+        Some(Transition::Resumed {
+            head: 11,
+            after: Duration::from_secs(300)
+        })
+    );
+    assert_eq!(progress.update(12, at(310), threshold), None);
+    assert!(!progress.halted);
+}
@@ -0,0 +1,137 @@
+use std::{collections::BTreeMap, fmt, str::FromStr, sync::RwLock};
+
+use eyre::eyre;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::cli::CliArgs;
+
+/// Headers sent to each RPC url, set up with [`configure`].
+static HEADERS: RwLock<BTreeMap<String, Vec<RpcHeader>>> = RwLock::new(BTreeMap::new());
+
+/// HTTP header given as `Name: value`, e.g. `Authorization: Bearer <token>` for private RPC
+/// gateways.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcHeader {
+    name: HeaderName,
+    value: HeaderValue,
+}
+
+impl RpcHeader {
+    /// The header value, a secret more often than not.
+    pub fn value(&self) -> &str {
+        self.value.to_str().unwrap_or_default()
+    }
+}
+
+impl FromStr for RpcHeader {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s
+            .split_once(':')
+            .ok_or_else(|| eyre!("expected `Name: value`"))?;
+        let mut value = HeaderValue::from_str(value.trim())?;
+        value.set_sensitive(true);
+        Ok(Self {
+            name: HeaderName::from_str(name.trim())?,
+            value,
+        })
+    }
+}
+
+impl fmt::Display for RpcHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.value())
+    }
+}
+
+impl Serialize for RpcHeader {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RpcHeader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Sends `headers` along every connection to `url`.
+pub fn set_headers(url: &str, headers: &[RpcHeader]) {
+    HEADERS
+        .write()
+        .expect("poisoned lock")
+        .insert(url.to_owned(), headers.to_vec());
+}
+
+/// Headers configured for `url`, handed over to the verification workers.
+pub fn configured(url: &str) -> Vec<RpcHeader> {
+    HEADERS
+        .read()
+        .expect("poisoned lock")
+        .get(url)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Sets up the headers of the Ethereum and substrate RPC urls of `cfg`.
+pub fn configure(cfg: &CliArgs) {
+    set_headers(&cfg.eth_rpc_url, &cfg.rpc_headers.eth_rpc_header);
+    if let Some(url) = &cfg.eth_ws_url {
+        set_headers(url, &cfg.rpc_headers.eth_ws_header);
+    }
+    set_headers(
+        &cfg.substrate_rpc_url,
+        &cfg.rpc_headers.substrate_rpc_header,
+    );
+}
+
+/// Headers to send to `url`, none unless configured.
+pub fn headers_for(url: &str) -> HeaderMap {
+    configured(url)
+        .into_iter()
+        .map(|h| (h.name, h.value))
+        .collect()
+}
+
+/// Value of the `Authorization` header of `url`, the only header websocket connections to
+/// Ethereum can carry. Fails if other headers are configured for `url`.
+pub fn authorization_for(url: &str) -> eyre::Result<Option<String>> {
+    let headers = headers_for(url);
+    if let Some(name) = headers.keys().find(|name| **name != AUTHORIZATION) {
+        return Err(eyre!(
+            "only the Authorization header can be sent over the Ethereum websocket, not {}",
+            name
+        ));
+    }
+    Ok(headers
+        .get(AUTHORIZATION)
+        .map(|value| value.to_str().unwrap_or_default().to_owned()))
+}
+
+#[test]
+fn test_rpc_header() {
+    let header: RpcHeader = "Authorization: Bearer abc ".parse().unwrap();
+    assert_eq!(header.name, AUTHORIZATION);
+    assert_eq!(header.value(), "Bearer abc");
+    assert_eq!(header.to_string(), "authorization: Bearer abc");
+    assert_eq!(
+        serde_json::from_value::<RpcHeader>(serde_json::to_value(&header).unwrap()).unwrap(),
+        header
+    );
+    assert!("Authorization".parse::<RpcHeader>().is_err());
+    assert!("Bad Name: x".parse::<RpcHeader>().is_err());
+
+    set_headers("wss://gateway.test", &[header]);
+    assert_eq!(
+        authorization_for("wss://gateway.test").unwrap().as_deref(),
+        Some("Bearer abc")
+    );
+    set_headers("wss://other.test", &["X-Api-Key: k".parse().unwrap()]);
+    assert!(authorization_for("wss://other.test").is_err());
+    assert!(headers_for("https://unknown.test").is_empty());
+}
@@ -0,0 +1,345 @@
+use std::{
+    fmt,
+    path::PathBuf,
+    process::Stdio,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bindings::mangata_task_manager::NewTaskCreatedFilter;
+use eyre::eyre;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio::{io::AsyncWriteExt, process::Command};
+use tracing::{debug, warn};
+
+use crate::{cli::HookArgs, metrics::metrics};
+
+/// Environment passed on to hook scripts, the rest of the node environment holds secrets
+/// such as the key passwords.
+const SCRIPT_ENV: [&str; 6] = ["PATH", "HOME", "LANG", "LC_ALL", "TZ", "TMPDIR"];
+
+/// Point of the task lifecycle a hook runs at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum HookPoint {
+    /// The task event was received, before it is verified
+    #[serde(rename = "on-task-received")]
+    TaskReceived,
+    /// The response was signed, before it is sent
+    #[serde(rename = "on-signed")]
+    Signed,
+    /// The response was sent to the aggregator or relayer
+    #[serde(rename = "on-submitted")]
+    Submitted,
+    /// The task failed or was abandoned
+    #[serde(rename = "on-error")]
+    Error,
+}
+
+impl HookPoint {
+    const ALL: [HookPoint; 4] = [
+        HookPoint::TaskReceived,
+        HookPoint::Signed,
+        HookPoint::Submitted,
+        HookPoint::Error,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            HookPoint::TaskReceived => "on-task-received",
+            HookPoint::Signed => "on-signed",
+            HookPoint::Submitted => "on-submitted",
+            HookPoint::Error => "on-error",
+        }
+    }
+}
+
+/// What a hook runs, an executable reading the payload on stdin or a URL the payload is
+/// POSTed to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookTarget {
+    Script(PathBuf),
+    Http(String),
+}
+
+/// Hook given as `point=target`, e.g. `on-error=/usr/local/bin/page.sh` or
+/// `on-submitted=https://automation.internal/avs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hook {
+    point: HookPoint,
+    target: HookTarget,
+}
+
+impl FromStr for Hook {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (point, target) = s
+            .split_once('=')
+            .ok_or_else(|| eyre!("expected `point=script` or `point=url`"))?;
+        let point = HookPoint::ALL
+            .into_iter()
+            .find(|p| p.as_str() == point.trim())
+            .ok_or_else(|| eyre!("unknown hook point {:?}", point))?;
+        let target = match target.trim() {
+            "" => return Err(eyre!("missing hook target")),
+            url if url.starts_with("http://") || url.starts_with("https://") => {
+                HookTarget::Http(url.to_owned())
+            }
+            path => HookTarget::Script(path.into()),
+        };
+        Ok(Self { point, target })
+    }
+}
+
+impl fmt::Display for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            HookTarget::Script(path) => write!(f, "{}={}", self.point.as_str(), path.display()),
+            HookTarget::Http(url) => write!(f, "{}={}", self.point.as_str(), url),
+        }
+    }
+}
+
+impl Serialize for Hook {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hook {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// JSON document handed to the hooks of a task.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookPayload {
+    pub hook: HookPoint,
+    pub task_index: u32,
+    pub block_number: u32,
+    /// Seconds since the epoch when the hook point was reached
+    pub timestamp: u64,
+    /// Signed response as encoded for the aggregator
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HookPayload {
+    pub fn new(hook: HookPoint, event: &NewTaskCreatedFilter) -> Self {
+        Self {
+            hook,
+            task_index: event.task_index,
+            block_number: event.task.block_number.as_u32(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            response: None,
+            accepted: None,
+            error: None,
+        }
+    }
+}
+
+/// User automation run at the points of the task lifecycle, e.g. to page on errors or to
+/// archive signed responses. Hooks run in the background and are killed after the timeout,
+/// their failures are logged and counted but never affect the task.
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    hooks: Arc<Vec<Hook>>,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl Hooks {
+    pub fn from_cli(cfg: &HookArgs) -> Self {
+        Self {
+            hooks: Arc::new(cfg.hook.clone()),
+            timeout: Duration::from_millis(cfg.hook_timeout_ms),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Starts the hooks of `payload.hook`, without waiting for them.
+    pub fn fire(&self, payload: HookPayload) {
+        if !self.hooks.iter().any(|h| h.point == payload.hook) {
+            return;
+        }
+        let hooks = self.clone();
+        tokio::spawn(async move { hooks.run(&payload).await });
+    }
+
+    async fn run(&self, payload: &HookPayload) {
+        let body = match serde_json::to_vec(payload) {
+            Ok(body) => body,
+            Err(e) => {
+                return warn!(
+                    "Cannot encode the {} hook payload: {}",
+                    payload.hook.as_str(),
+                    e
+                )
+            }
+        };
+        for hook in self.hooks.iter().filter(|h| h.point == payload.hook) {
+            let res = tokio::time::timeout(self.timeout, self.call(&hook.target, &body)).await;
+            let outcome = match res {
+                Ok(Ok(())) => {
+                    debug!("Hook {} of task {} ran", hook, payload.task_index);
+                    "ok"
+                }
+                Ok(Err(e)) => {
+                    warn!("Hook {} of task {} failed: {}", hook, payload.task_index, e);
+                    "failed"
+                }
+                Err(_) => {
+                    warn!(
+                        "Hook {} of task {} timed out after {:?}",
+                        hook, payload.task_index, self.timeout
+                    );
+                    "timeout"
+                }
+            };
+            metrics()
+                .hook_runs
+                .with_label_values(&[payload.hook.as_str(), outcome])
+                .inc();
+        }
+    }
+
+    async fn call(&self, target: &HookTarget, body: &[u8]) -> eyre::Result<()> {
+        match target {
+            HookTarget::Http(url) => {
+                let res = self
+                    .client
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body.to_vec())
+                    .send()
+                    .await?;
+                if !res.status().is_success() {
+                    return Err(eyre!("replied {}", res.status()));
+                }
+                Ok(())
+            }
+            HookTarget::Script(path) => {
+                // killed when the timeout drops the wait
+                let mut child = Command::new(path)
+                    .env_clear()
+                    .envs(
+                        SCRIPT_ENV
+                            .iter()
+                            .filter_map(|var| Some((var, std::env::var_os(var)?))),
+                    )
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::piped())
+                    .kill_on_drop(true)
+                    .spawn()?;
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(body).await?;
+                }
+                let output = child.wait_with_output().await?;
+                if !output.status.success() {
+                    return Err(eyre!(
+                        "exited with {}: {}",
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_hooks() {
+    let hook: Hook = "on-error=/usr/local/bin/page.sh".parse().unwrap();
+    assert_eq!(hook.point, HookPoint::Error);
+    assert_eq!(
+        hook.target,
+        HookTarget::Script("/usr/local/bin/page.sh".into())
+    );
+    let hook: Hook = "on-submitted = https://automation.internal/avs"
+        .parse()
+        .unwrap();
+    assert_eq!(hook.point, HookPoint::Submitted);
+    assert_eq!(
+        hook.target,
+        HookTarget::Http("https://automation.internal/avs".into())
+    );
+    assert_eq!(
+        hook.to_string(),
+        "on-submitted=https://automation.internal/avs"
+    );
+
+    assert!("on-signed".parse::<Hook>().is_err());
+    assert!("on-signed=".parse::<Hook>().is_err());
+    assert!("on-finalized=/bin/true".parse::<Hook>().is_err());
+}
+
+#[tokio::test]
+async fn test_script_hook() {
+    let dir = std::env::temp_dir().join(format!("avs-hooks-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let out = dir.join("payload.json");
+    let env = dir.join("env");
+    let script = dir.join("hook.sh");
+    let slow = dir.join("slow.sh");
+    for (path, body) in [
+        (
+            &script,
+            format!(
+                "#!/bin/sh\ncat > {}\nenv > {}\n",
+                out.display(),
+                env.display()
+            ),
+        ),
+        (&slow, "#!/bin/sh\nsleep 10\n".to_owned()),
+    ] {
+        std::fs::write(path, body).unwrap();
+        std::fs::set_permissions(path, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+    }
+
+    std::env::set_var("AVS_HOOK_TEST_SECRET", "hunter2");
+    let hooks = Hooks::from_cli(&HookArgs {
+        hook: vec![format!("on-signed={}", script.display()).parse().unwrap()],
+        hook_timeout_ms: 5_000,
+    });
+    let payload = HookPayload {
+        hook: HookPoint::Signed,
+        task_index: 7,
+        block_number: 42,
+        timestamp: 1,
+        response: Some(serde_json::json!({ "signature": "0x01" })),
+        accepted: None,
+        error: None,
+    };
+    hooks.run(&payload).await;
+    let written: serde_json::Value = serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+    assert_eq!(written["hook"], "on-signed");
+    assert_eq!(written["task_index"], 7);
+    assert_eq!(written["response"]["signature"], "0x01");
+    assert!(written.get("error").is_none());
+    // the node environment holds the key passwords
+    let env = std::fs::read_to_string(&env).unwrap();
+    assert!(!env.contains("AVS_HOOK_TEST_SECRET"));
+
+    let hooks = Hooks::from_cli(&HookArgs {
+        hook: vec![format!("on-signed={}", slow.display()).parse().unwrap()],
+        hook_timeout_ms: 50,
+    });
+    let started = std::time::Instant::now();
+    hooks.run(&payload).await;
+    assert!(started.elapsed() < Duration::from_secs(1));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,140 @@
+use std::{
+    fmt::Write as _,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ethers::{
+    core::rand::random,
+    types::{Address, H256},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Keys a node ran with, the ECDSA address and the BLS operator id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyFingerprints {
+    pub ecdsa_address: Address,
+    pub bls_operator_id: H256,
+    /// Seconds since the epoch when the node first started with these keys
+    pub since: u64,
+}
+
+impl KeyFingerprints {
+    fn same_keys(&self, other: &KeyFingerprints) -> bool {
+        self.ecdsa_address == other.ecdsa_address && self.bls_operator_id == other.bls_operator_id
+    }
+}
+
+/// Identity of a node installation, kept across restarts and key rotations so the logs,
+/// metrics and reports of a fleet can be told apart when operator addresses are reused.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeIdentity {
+    /// Random UUID generated on the first start
+    pub id: String,
+    pub first_started_at: u64,
+    /// Version of the node which generated the identity
+    pub created_by: String,
+    pub keys: KeyFingerprints,
+    /// Keys the node ran with before, oldest first
+    #[serde(default)]
+    pub previous_keys: Vec<KeyFingerprints>,
+}
+
+impl NodeIdentity {
+    fn generate(keys: KeyFingerprints) -> Self {
+        Self {
+            id: uuid_v4(random()),
+            first_started_at: keys.since,
+            created_by: env!("CARGO_PKG_VERSION").to_owned(),
+            keys,
+            previous_keys: vec![],
+        }
+    }
+
+    /// Reads the identity at `path`, generating it on the first start and recording a change
+    /// of keys. The identity is used unsaved when it cannot be written.
+    pub fn load(path: &Path, ecdsa_address: Address, bls_operator_id: H256) -> eyre::Result<Self> {
+        let keys = KeyFingerprints {
+            ecdsa_address,
+            bls_operator_id,
+            since: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+        let identity = match std::fs::read(path) {
+            Ok(json) => {
+                let mut identity: NodeIdentity = serde_json::from_slice(&json)?;
+                if identity.keys.same_keys(&keys) {
+                    return Ok(identity);
+                }
+                warn!(
+                    "Node {} now runs with ECDSA address {:?} and BLS operator id {:x}, was {:?} and {:x}",
+                    identity.id,
+                    keys.ecdsa_address,
+                    keys.bls_operator_id,
+                    identity.keys.ecdsa_address,
+                    identity.keys.bls_operator_id
+                );
+                let previous = std::mem::replace(&mut identity.keys, keys);
+                identity.previous_keys.push(previous);
+                identity
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let identity = Self::generate(keys);
+                info!("Generated node identity {}", identity.id);
+                identity
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if let Err(e) = identity.save(path) {
+            warn!(
+                "Cannot save the node identity to {}, it changes on restart: {}",
+                path.display(),
+                e
+            );
+        }
+        Ok(identity)
+    }
+
+    fn save(&self, path: &Path) -> eyre::Result<()> {
+        // written aside and renamed so a crash cannot leave a truncated identity
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Formats random bytes as a version 4 UUID.
+fn uuid_v4(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let mut uuid = String::with_capacity(36);
+    for (i, byte) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            uuid.push('-');
+        }
+        let _ = write!(uuid, "{:02x}", byte);
+    }
+    uuid
+}
+
+#[test]
+fn test_node_identity() {
+    assert_eq!(uuid_v4([0xff; 16]), "ffffffff-ffff-4fff-bfff-ffffffffffff");
+
+    let dir = std::env::temp_dir().join(format!("avs-identity-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("identity.json");
+    let (address, id) = (Address::repeat_byte(1), H256::repeat_byte(2));
+
+    let identity = NodeIdentity::load(&path, address, id).unwrap();
+    assert_eq!(identity.id.len(), 36);
+    assert_eq!(NodeIdentity::load(&path, address, id).unwrap(), identity);
+
+    let rotated = NodeIdentity::load(&path, address, H256::repeat_byte(3)).unwrap();
+    assert_eq!(rotated.id, identity.id);
+    assert_eq!(rotated.first_started_at, identity.first_started_at);
+    assert_eq!(rotated.keys.bls_operator_id, H256::repeat_byte(3));
+    assert_eq!(rotated.previous_keys, vec![identity.keys]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    ops::RangeInclusive,
+    sync::Mutex,
+    time::Duration,
+};
+
+use node_primitives::BlockNumber;
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::metrics::metrics;
+
+/// Finalized blocks and received tasks remembered until their task is accepted.
+const TRACKED: usize = 1024;
+/// Latest lag samples listed on the operator API.
+const SAMPLES: usize = 100;
+
+/// Time from the finalization of a substrate block to the acceptance of its task response
+/// on Ethereum, both in unix seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LagSample {
+    pub task_index: u32,
+    pub block_number: BlockNumber,
+    pub finalized_at: u64,
+    pub accepted_at: u64,
+    pub lag_secs: u64,
+}
+
+/// Latest finality lag samples and the lag the rollup bridge assumes at most.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LagReport {
+    pub max_lag_secs: u64,
+    pub samples: Vec<LagSample>,
+}
+
+#[derive(Debug, Default)]
+struct Tracked {
+    finalized_at: BTreeMap<BlockNumber, u64>,
+    tasks: BTreeMap<u32, BlockNumber>,
+    samples: VecDeque<LagSample>,
+    /// Tasks alerted as overdue before their acceptance, counted once
+    overdue: BTreeSet<u32>,
+    /// First finalized block alerted as overdue without a task, until a task covers it
+    uncovered: Option<BlockNumber>,
+}
+
+/// Measures the end-to-end finality lag of the rollup: a substrate block is final for the
+/// bridge only once a response to its task is accepted by the task manager. Blocks count
+/// as finalized when the node first sees them finalized, so blocks finalized before it
+/// started are not measured. Blocks and tasks still waiting past the maximum are alerted by
+/// [`FinalityLag::check_overdue`], as the lag of a stalled bridge is otherwise never measured.
+#[derive(Debug)]
+pub struct FinalityLag {
+    max_lag: Duration,
+    tracked: Mutex<Tracked>,
+}
+
+impl FinalityLag {
+    pub fn new(max_lag: Duration) -> Self {
+        Self {
+            max_lag,
+            tracked: Mutex::default(),
+        }
+    }
+
+    /// Records `blocks` as finalized at `at`, several blocks are finalized at once when the
+    /// finalized head skips numbers.
+    pub fn finalized(&self, blocks: RangeInclusive<BlockNumber>, at: u64) {
+        let mut tracked = self.tracked.lock().expect("poisoned lock");
+        let start = (*blocks.start()).max(blocks.end().saturating_sub(TRACKED as BlockNumber));
+        for block in start..=*blocks.end() {
+            tracked.finalized_at.entry(block).or_insert(at);
+        }
+        while tracked.finalized_at.len() > TRACKED {
+            tracked.finalized_at.pop_first();
+        }
+    }
+
+    /// Records the substrate block task `task_index` is about.
+    pub fn task_created(&self, task_index: u32, block_number: BlockNumber) {
+        let mut tracked = self.tracked.lock().expect("poisoned lock");
+        tracked.tasks.insert(task_index, block_number);
+        while tracked.tasks.len() > TRACKED {
+            if let Some((task_index, _)) = tracked.tasks.pop_first() {
+                tracked.overdue.remove(&task_index);
+            }
+        }
+        if tracked.uncovered.is_some_and(|block| block <= block_number) {
+            tracked.uncovered = None;
+        }
+    }
+
+    /// Alerts once for each task not accepted `max_lag` after the finalization of its block,
+    /// and for the first finalized block no task was created for in `max_lag`. Returns the
+    /// number of alerts.
+    pub fn check_overdue(&self, now: u64) -> usize {
+        let max_lag = self.max_lag.as_secs();
+        let mut tracked = self.tracked.lock().expect("poisoned lock");
+        let tracked = &mut *tracked;
+        let mut alerts = vec![];
+        for (task_index, block_number) in &tracked.tasks {
+            let Some(finalized_at) = tracked.finalized_at.get(block_number) else {
+                continue;
+            };
+            if now.saturating_sub(*finalized_at) > max_lag && tracked.overdue.insert(*task_index) {
+                alerts.push(format!(
+                    "Task {} about block {} is not accepted {}s after the block finalization, over the {:?} the bridge assumes",
+                    task_index,
+                    block_number,
+                    now.saturating_sub(*finalized_at),
+                    self.max_lag
+                ));
+            }
+        }
+        let covered = tracked.tasks.values().max().copied();
+        if tracked.uncovered.is_none() {
+            let uncovered = tracked
+                .finalized_at
+                .iter()
+                .find(|(block, _)| covered.is_none_or(|covered| **block > covered));
+            if let Some((block_number, finalized_at)) = uncovered {
+                if now.saturating_sub(*finalized_at) > max_lag {
+                    tracked.uncovered = Some(*block_number);
+                    alerts.push(format!(
+                        "No task created for block {} {}s after its finalization, over the {:?} the bridge assumes",
+                        block_number,
+                        now.saturating_sub(*finalized_at),
+                        self.max_lag
+                    ));
+                }
+            }
+        }
+
+        for alert in &alerts {
+            metrics().finality_lag_exceeded.inc();
+            warn!("{}", alert);
+        }
+        alerts.len()
+    }
+
+    /// Measures the lag of task `task_index` accepted at `accepted_at`, alerting when it is
+    /// over the maximum. `None` if the task or the finalization of its block was not seen.
+    pub fn accepted(&self, task_index: u32, accepted_at: u64) -> Option<LagSample> {
+        let mut tracked = self.tracked.lock().expect("poisoned lock");
+        let block_number = tracked.tasks.remove(&task_index)?;
+        let alerted = tracked.overdue.remove(&task_index);
+        let finalized_at = *tracked.finalized_at.get(&block_number)?;
+        let sample = LagSample {
+            task_index,
+            block_number,
+            finalized_at,
+            accepted_at,
+            lag_secs: accepted_at.saturating_sub(finalized_at),
+        };
+        tracked.samples.push_back(sample);
+        while tracked.samples.len() > SAMPLES {
+            tracked.samples.pop_front();
+        }
+        drop(tracked);
+
+        let m = metrics();
+        m.finality_lag.observe(sample.lag_secs as f64);
+        m.finality_lag_last.set(sample.lag_secs as i64);
+        if sample.lag_secs > self.max_lag.as_secs() {
+            if !alerted {
+                m.finality_lag_exceeded.inc();
+            }
+            error!(
+                "Block {} was bridged {}s after its finalization by task {}, over the {:?} the bridge assumes",
+                block_number, sample.lag_secs, task_index, self.max_lag
+            );
+        } else {
+            info!(
+                task_index,
+                lag_secs = sample.lag_secs,
+                "Block {} bridged {}s after its finalization",
+                block_number,
+                sample.lag_secs
+            );
+        }
+        Some(sample)
+    }
+
+    pub fn report(&self) -> LagReport {
+        LagReport {
+            max_lag_secs: self.max_lag.as_secs(),
+            samples: self
+                .tracked
+                .lock()
+                .expect("poisoned lock")
+                .samples
+                .iter()
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+#[test]
+fn test_finality_lag() {
+    let lag = FinalityLag::new(Duration::from_secs(600));
+    lag.finalized(10..=12, 1_000);
+    lag.finalized(11..=13, 1_050);
+    lag.task_created(1, 12);
+    lag.task_created(2, 13);
+    lag.task_created(3, 20);
+
+    assert_eq!(
+        lag.accepted(1, 1_300),
+        Some(LagSample {
+            task_index: 1,
+            block_number: 12,
+            finalized_at: 1_000,
+            accepted_at: 1_300,
+            lag_secs: 300,
+        })
+    );
+    assert_eq!(lag.accepted(2, 1_900).map(|s| s.lag_secs), Some(850));
+    // finalization not seen
+    assert_eq!(lag.accepted(3, 1_900), None);
+    // accepted once
+    assert_eq!(lag.accepted(1, 2_000), None);
+    assert_eq!(lag.report().samples.len(), 2);
+}
+
+#[test]
+fn test_overdue_finality_lag() {
+    let lag = FinalityLag::new(Duration::from_secs(600));
+    lag.finalized(10..=12, 1_000);
+    lag.task_created(1, 11);
+    assert_eq!(lag.check_overdue(1_600), 0);
+    // task 1 is not accepted and no task covers block 12
+    assert_eq!(lag.check_overdue(1_601), 2);
+    assert_eq!(lag.check_overdue(1_700), 0);
+    // counted once
+    assert!(lag.accepted(1, 1_800).is_some());
+    lag.task_created(2, 12);
+    assert_eq!(lag.check_overdue(1_900), 1);
+    assert_eq!(lag.check_overdue(2_000), 0);
+    lag.finalized(13..=13, 2_000);
+    assert_eq!(lag.check_overdue(2_601), 1);
+}
@@ -1,45 +1,339 @@
-use chainio::setup_deposits;
+use avs_operator_sdk::ownership;
+use chainio::{fund_accounts, setup_deposits};
 use cli::CliArgs;
+use ethers::signers::{LocalWallet, Signer};
 use eyre::eyre;
 use operator::Operator;
-use tracing::{info, instrument};
+use std::{
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use tracing::{info, info_span, instrument, warn, Instrument};
 
+mod addresses;
+mod api;
+mod archive;
+mod build_info;
 mod chainio;
 mod cli;
+mod constants;
 mod crypto;
+mod doctor;
+mod economics;
+mod evidence;
 mod executor;
+mod exit;
+#[cfg(feature = "fault-injection")]
+mod faults;
+mod features;
+mod gossip;
+mod halt;
+mod headers;
+mod hooks;
+mod identity;
+mod lag;
+mod metrics;
+mod offline;
+mod openapi;
 mod operator;
+mod plugin;
+mod pressure;
+mod queue;
+mod quota;
+mod relayer;
+mod reputation;
+mod roles;
 mod rpc;
+mod schema;
+mod script;
+mod service;
+mod signer;
+mod slashing;
+mod store;
+mod sync;
+mod task;
+mod units;
+mod update;
+mod verifier;
+mod wal;
+mod worker;
+
+/// Logs go to stderr in `script` mode, whose replies own stdout.
+static LOG_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Tracing layer keeping the last warnings and errors, reported by the operator API and `doctor`.
+pub fn recent_errors_layer() -> doctor::RecentErrorsLayer {
+    doctor::RecentErrorsLayer
+}
+
+/// Writer of the logs, stdout unless the command writes its own output there.
+pub fn log_writer() -> Box<dyn std::io::Write> {
+    match LOG_TO_STDERR.load(Ordering::Relaxed) {
+        true => Box::new(std::io::stderr()),
+        false => Box::new(std::io::stdout()),
+    }
+}
 
 pub async fn start() -> eyre::Result<()> {
+    // workers are started without the node arguments, which hold the keys
+    if std::env::args().nth(1).as_deref() == Some(worker::WORKER_COMMAND) {
+        LOG_TO_STDERR.store(true, Ordering::Relaxed);
+        return worker::serve().await;
+    }
+    // the configuration tooling runs without the node arguments, e.g. in CI
+    if let Some(command) = CliArgs::config_command(std::env::args_os()) {
+        return config(&command);
+    }
+    service::load_env_file()?;
     let cli = CliArgs::build();
+    headers::configure(&cli);
+    doctor::redact_recent_errors(&cli);
+    #[cfg(feature = "fault-injection")]
+    faults::configure()?;
+    if matches!(cli.command, Some(cli::Commands::Script)) {
+        LOG_TO_STDERR.store(true, Ordering::Relaxed);
+    }
+    match &cli.command {
+        Some(cli::Commands::RpcUsage) => return print_rpc_usage(&cli).await,
+        Some(cli::Commands::VerifyOwnership { proof }) => return verify_ownership(proof),
+        Some(cli::Commands::VerifyReputation { attestation }) => {
+            return verify_reputation(attestation)
+        }
+        Some(cli::Commands::Version { attest: false, out }) => {
+            let json = serde_json::to_string_pretty(&build_info::BuildInfo::current())?;
+            match out {
+                Some(path) => std::fs::write(path, json)?,
+                None => info!("{}", json),
+            }
+            return Ok(());
+        }
+        Some(cli::Commands::VerifyVersion { attestation }) => return verify_version(attestation),
+        Some(cli::Commands::Doctor { out }) => return run_doctor(&cli, out.as_deref()).await,
+        Some(cli::Commands::NativeRestaking {
+            eigen_pod,
+            validators,
+            out,
+        }) => return native_restaking(&cli, *eigen_pod, validators, out.as_deref()).await,
+        Some(cli::Commands::ReplayWal { wal, task_index }) => return replay_wal(wal, *task_index),
+        Some(cli::Commands::Config { command }) => return config(command),
+        Some(cli::Commands::Openapi { out }) => {
+            let spec = serde_json::to_string_pretty(&openapi::spec())?;
+            match out {
+                Some(path) => std::fs::write(path, spec)?,
+                None => info!("{}", spec),
+            }
+            return Ok(());
+        }
+        Some(cli::Commands::ServiceDefinition {
+            platform,
+            name,
+            env_file,
+            out,
+        }) => return service_definition(*platform, name, env_file.as_deref(), out.as_deref()),
+        Some(cli::Commands::SplitBlsKey {
+            threshold,
+            shares,
+            out_dir,
+        }) => return split_bls_key(&cli, *threshold, *shares, out_dir).await,
+        Some(cli::Commands::OfflineSign { command }) => return offline_sign(&cli, command).await,
+        Some(cli::Commands::ServeBlsShare {
+            index,
+            listen,
+            token,
+        }) => {
+            let share = cli.get_bls_keystore().await?.into_bls_keypair()?;
+            return signer::serve(*listen, *index, share, token.clone()).await;
+        }
+        _ => {}
+    }
+    if let (true, Some(db)) = (cli.db_check, cli.store_backend().await?) {
+        let report = store::Store::check(db)?;
+        info!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    let api_state = api::ApiState::new(&cli.api);
+    if cli.api.api_addr.is_some() {
+        tokio::spawn(api::serve(cli.api.clone(), api_state.clone()));
+    }
+
+    info!(
+        "avs-finalizer v{} ({}), features: {}",
+        env!("CARGO_PKG_VERSION"),
+        option_env!("AVS_GIT_COMMIT").unwrap_or("unknown commit"),
+        features::summary(&features::report(&cli))
+    );
     info!(
         "Creating a new Operator from {}",
         serde_json::to_string_pretty(&cli)?
     );
-    let operator = Operator::from_cli(&cli).await?;
+    let operator = Operator::from_cli(&cli, api_state).await?;
 
     if let Some(cmd) = &cli.command {
         info!("Operator created with command '{:?}'", cmd);
+        if roles::required_role(cmd).is_some() {
+            operator.capabilities().await?.ensure_allowed(cmd)?;
+        }
         match cmd {
             cli::Commands::OptInAvs => operator.opt_in_avs().await?,
-            cli::Commands::OptOutAvs => operator.opt_out_avs().await?,
-            cli::Commands::PrintStatus => print_status(&operator).await?,
+            cli::Commands::OptOutAvs { exit_grace_secs } => {
+                operator
+                    .opt_out_avs(Duration::from_secs(*exit_grace_secs))
+                    .await?
+            }
+            cli::Commands::PrintStatus { prom, watch } => match watch {
+                Some(secs) => watch_status(&operator, Duration::from_secs(*secs)).await?,
+                None => print_status(&operator, prom.as_deref()).await?,
+            },
+            cli::Commands::RpcUsage
+            | cli::Commands::VerifyOwnership { .. }
+            | cli::Commands::VerifyReputation { .. }
+            | cli::Commands::Version { attest: false, .. }
+            | cli::Commands::VerifyVersion { .. }
+            | cli::Commands::ReplayWal { .. }
+            | cli::Commands::Openapi { .. }
+            | cli::Commands::Config { .. }
+            | cli::Commands::ServiceDefinition { .. }
+            | cli::Commands::Doctor { .. }
+            | cli::Commands::NativeRestaking { .. }
+            | cli::Commands::SplitBlsKey { .. }
+            | cli::Commands::OfflineSign { .. }
+            | cli::Commands::ServeBlsShare { .. } => {
+                unreachable!("handled before creating the operator")
+            }
+            cli::Commands::SelfTest { block_number } => self_test(&operator, *block_number).await?,
+            cli::Commands::Script => script::run(&operator).await?,
+            cli::Commands::Quarantined => {
+                let quarantined = operator.quarantined_tasks().await?;
+                info!("{}", serde_json::to_string_pretty(&quarantined)?);
+            }
+            cli::Commands::RetryQuarantined { task_index } => {
+                let accepted = operator.retry_quarantined(*task_index).await?;
+                info!(
+                    "Task {} retried, response accepted: {}",
+                    task_index, accepted
+                );
+            }
+            cli::Commands::QuorumStatus => {
+                let status = operator.quorum_status().await?;
+                info!("{}", serde_json::to_string_pretty(&status)?);
+            }
+            cli::Commands::AdviseQuorums => {
+                let advice = operator.advise_quorums().await?;
+                info!("{}", serde_json::to_string_pretty(&advice)?);
+            }
+            cli::Commands::Capabilities => {
+                let capabilities = operator.capabilities().await?;
+                info!("{}", serde_json::to_string_pretty(&capabilities)?);
+            }
+            cli::Commands::Economics(args) => {
+                let economics = operator
+                    .economics(args.lookback_blocks, args.samples, args.expected_reward_eth)
+                    .await?;
+                let json = serde_json::to_string_pretty(&economics)?;
+                match &args.out {
+                    Some(path) => std::fs::write(path, json)?,
+                    None => info!("{}", json),
+                }
+            }
+            cli::Commands::SlashingHistory {
+                from_block,
+                all_operators,
+                out,
+            } => {
+                let history = operator
+                    .slashing_history(*from_block, *all_operators)
+                    .await?;
+                let json = serde_json::to_string_pretty(&history)?;
+                match out {
+                    Some(path) => std::fs::write(path, json)?,
+                    None => info!("{}", json),
+                }
+            }
+            cli::Commands::ExportSyncCheckpoint { out } => {
+                operator.export_sync_checkpoint(out).await?;
+            }
+            cli::Commands::OperatorDetails => {
+                let settings = operator.operator_settings().await?;
+                info!("{}", serde_json::to_string_pretty(&settings)?);
+            }
+            cli::Commands::SetOperatorDetails(args) => {
+                let settings = operator.set_operator_details(args).await?;
+                info!("{}", serde_json::to_string_pretty(&settings)?);
+            }
+            cli::Commands::GetDeposits(args) => {
+                let deposits = operator.get_deposits(&args.stakers, args.page_size).await?;
+                info!("{}", serde_json::to_string_pretty(&deposits)?);
+            }
+            cli::Commands::ProveOwnership { challenge, out } => {
+                let proof = operator.prove_ownership(challenge).await?;
+                let json = serde_json::to_string_pretty(&proof)?;
+                match out {
+                    Some(path) => std::fs::write(path, json)?,
+                    None => info!("{}", json),
+                }
+            }
+            cli::Commands::Reputation { attest, out } => {
+                let reputation = operator.reputation().await?;
+                let json = if *attest {
+                    serde_json::to_string_pretty(&operator.attest_reputation(reputation).await?)?
+                } else {
+                    serde_json::to_string_pretty(&reputation)?
+                };
+                match out {
+                    Some(path) => std::fs::write(path, json)?,
+                    None => info!("{}", json),
+                }
+            }
+            cli::Commands::Version { attest: true, out } => {
+                let json = serde_json::to_string_pretty(&operator.attest_build().await?)?;
+                match out {
+                    Some(path) => std::fs::write(path, json)?,
+                    None => info!("{}", json),
+                }
+            }
         }
     } else if cli.testnet {
         info!("Operator created and starting testnet setup");
         ephemeral_testnet(&operator, cli.stake, &cli).await?;
     } else {
         info!("Operator created and starting AVS verification");
-        run_node(operator).await?;
+        if cli.update.update_manifest_url.is_some() {
+            tokio::spawn(update::watch(cli.update.clone()));
+        }
+        // tags the logs of the node so those of a fleet can be told apart
+        let span = info_span!("node", id = %operator.identity().id);
+        run_node(operator).instrument(span).await?;
     }
 
     Ok(())
 }
 
 pub async fn run_node(operator: Operator) -> eyre::Result<()> {
-    check_registration(&operator).await?;
-    operator.watch_new_tasks().await?;
+    match operator.shadow_of() {
+        Some(address) => info!(
+            "Shadow mode, comparing responses with operator {:?} without sending any",
+            address
+        ),
+        None => check_registration(&operator).await?,
+    }
+    tokio::select! {
+        res = operator.watch_new_tasks() => res?,
+        res = operator.watch_stake() => res?,
+        res = operator.watch_stake_share() => res?,
+        res = operator.watch_archive() => res?,
+        res = operator.watch_strategies() => res?,
+        res = operator.watch_withdrawals() => res?,
+        res = operator.watch_upgrades() => res?,
+        res = operator.watch_gossip() => res?,
+        res = operator.watch_chain_halts() => res?,
+        res = operator.watch_finalized_blocks() => res?,
+        res = operator.watch_balance() => res?,
+        res = operator.watch_divergence() => res?,
+        res = operator.watch_pressure() => res?,
+        res = operator.watch_substrate_blocks() => res?,
+        res = service::shutdown_signal() => info!("Received {}, shutting down", res?),
+    }
 
     Ok(())
 }
@@ -68,9 +362,299 @@ pub(crate) async fn check_registration(operator: &Operator) -> eyre::Result<()>
 }
 
 #[instrument(skip_all)]
-pub(crate) async fn print_status(operator: &Operator) -> eyre::Result<()> {
+pub(crate) async fn print_status(operator: &Operator, prom: Option<&Path>) -> eyre::Result<()> {
     let status = operator.get_status().await?;
-    info!("{:#?}", status);
+    match prom {
+        Some(path) => {
+            // the collector may read the file at any time, replace it in one step
+            let tmp = path.with_extension("prom.tmp");
+            std::fs::write(&tmp, status.to_prometheus()?)?;
+            std::fs::rename(tmp, path)?;
+            info!("Status written to {}", path.display());
+        }
+        None => info!("{:#?}", status),
+    }
+    Ok(())
+}
+
+/// Prints the status, then a summary line every `interval` with the changes since the
+/// previous one, until interrupted.
+#[instrument(skip_all)]
+pub(crate) async fn watch_status(operator: &Operator, interval: Duration) -> eyre::Result<()> {
+    print_status(operator, None).await?;
+    let mut interval = tokio::time::interval(interval);
+    let mut previous = None;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            res = service::shutdown_signal() => {
+                info!("Received {}, stopping", res?);
+                return Ok(());
+            }
+        }
+        match operator.status_sample().await {
+            Ok(sample) => {
+                info!("{}", sample.describe(previous.as_ref()));
+                previous = Some(sample);
+            }
+            Err(e) => warn!("Cannot refresh the status: {:?}", e),
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub(crate) async fn self_test(operator: &Operator, block_number: u32) -> eyre::Result<()> {
+    let report = operator.self_test(block_number).await?;
+    info!("{}", serde_json::to_string_pretty(&report)?);
+    match (report.signature_valid, report.pubkey_registered) {
+        (false, _) => Err(eyre!(
+            "Self-test failed: task response signature does not verify"
+        )),
+        (true, false) => Err(eyre!(
+            "Self-test failed: BLS public key is not registered for this operator"
+        )),
+        (true, true) => {
+            info!("Self-test passed");
+            Ok(())
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub(crate) async fn print_rpc_usage(cfg: &CliArgs) -> eyre::Result<()> {
+    let usage = api::fetch_rpc_usage(&cfg.api).await?;
+    info!("{}", serde_json::to_string_pretty(&usage)?);
+    Ok(())
+}
+
+#[instrument(skip(cfg, out))]
+pub(crate) async fn native_restaking(
+    cfg: &CliArgs,
+    eigen_pod: ethers::types::Address,
+    validators: &[String],
+    out: Option<&Path>,
+) -> eyre::Result<()> {
+    let url = cfg
+        .beacon_api_url
+        .as_deref()
+        .ok_or_else(|| eyre!("native-restaking requires --beacon-api-url"))?;
+    let beacon = chainio::beacon::BeaconClient::new(url);
+    let header = beacon.header("finalized").await?;
+    // read the validators at the state of that exact block, proofs are made against its root
+    let state_id = format!("{:?}", header.header.message.state_root);
+    let found = beacon.validators(&state_id, validators).await?;
+    let report = chainio::beacon::NativeRestaking::new(eigen_pod, &header, validators, found);
+    if report.validators.iter().any(|v| !v.withdraws_to_pod) {
+        warn!(
+            "Some validators do not withdraw to EigenPod {:?}",
+            eigen_pod
+        );
+    }
+    let json = serde_json::to_string_pretty(&report)?;
+    match out {
+        Some(path) => std::fs::write(path, json)?,
+        None => info!("{}", json),
+    }
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub(crate) fn verify_ownership(path: &Path) -> eyre::Result<()> {
+    let proof: ownership::OwnershipProof = serde_json::from_slice(&std::fs::read(path)?)?;
+    ownership::verify(&proof)?;
+    info!(
+        "Valid ownership proof of address {:x} and operator id {:x} for challenge {:?}",
+        proof.eth_address, proof.operator_id, proof.challenge
+    );
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub(crate) fn verify_reputation(path: &Path) -> eyre::Result<()> {
+    let attestation: reputation::ReputationAttestation =
+        serde_json::from_slice(&std::fs::read(path)?)?;
+    let attested = reputation::verify(&attestation)?;
+    info!(
+        "Valid reputation attestation of address {:x} and operator id {:x}",
+        attested.eth_address, attested.operator_id
+    );
+    info!("{}", serde_json::to_string_pretty(&attested)?);
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub(crate) fn verify_version(path: &Path) -> eyre::Result<()> {
+    let attestation: build_info::BuildAttestation = serde_json::from_slice(&std::fs::read(path)?)?;
+    let attested = build_info::verify(&attestation)?;
+    info!(
+        "Valid build attestation of address {:x} and operator id {:x}",
+        attested.eth_address, attested.operator_id
+    );
+    info!("{}", serde_json::to_string_pretty(&attested)?);
+    Ok(())
+}
+
+pub(crate) async fn run_doctor(cli: &CliArgs, out: Option<&Path>) -> eyre::Result<()> {
+    let default_out = std::path::PathBuf::from(format!(
+        "doctor-{}.zip",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs()
+    ));
+    let report = doctor::doctor(cli, out.unwrap_or(&default_out)).await?;
+    info!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+pub(crate) fn replay_wal(path: &Path, task_index: Option<u32>) -> eyre::Result<()> {
+    let replayed = wal::replay(path, task_index)?;
+    info!("{}", serde_json::to_string_pretty(&replayed)?);
+    let differ = replayed.iter().filter(|r| !r.matches).count();
+    if differ > 0 {
+        return Err(eyre!(
+            "{} of {} replayed decisions differ from the recorded ones",
+            differ,
+            replayed.len()
+        ));
+    }
+    info!("{} decisions replayed identically", replayed.len());
+    Ok(())
+}
+
+fn config(command: &cli::ConfigCommand) -> eyre::Result<()> {
+    match command {
+        cli::ConfigCommand::Schema { out } => {
+            let schema = serde_json::to_string_pretty(&schema::config_schema())?;
+            match out {
+                Some(path) => std::fs::write(path, schema)?,
+                None => info!("{}", schema),
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn service_definition(
+    platform: service::ServicePlatform,
+    name: &str,
+    env_file: Option<&Path>,
+    out: Option<&Path>,
+) -> eyre::Result<()> {
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .take_while(|arg| arg != "service-definition")
+        .collect();
+    if args.iter().any(|arg| {
+        ["password", "passphrase", "token", "secret"]
+            .iter()
+            .any(|s| arg.contains(s))
+    }) {
+        warn!("Secrets passed as arguments end up in the service definition, use --env-file");
+    }
+    let exe = std::env::current_exe()?;
+    let working_dir = std::env::current_dir()?;
+    let env_file = env_file.map(std::path::absolute).transpose()?;
+    let definition = service::definition(
+        platform,
+        &service::ServiceSpec {
+            name,
+            exe: &exe,
+            args: &args,
+            working_dir: &working_dir,
+            env_file: env_file.as_deref(),
+        },
+    );
+    match out {
+        Some(path) => std::fs::write(path, definition)?,
+        None => info!("{}", definition),
+    }
+    Ok(())
+}
+
+#[instrument(skip(cli))]
+#[instrument(skip_all)]
+pub(crate) async fn offline_sign(
+    cli: &CliArgs,
+    command: &cli::OfflineSignCommand,
+) -> eyre::Result<()> {
+    match command {
+        cli::OfflineSignCommand::Export {
+            out,
+            fallback_gas_limit,
+        } => {
+            let operator = cli
+                .ecdsa_key
+                .ecdsa_cold_address
+                .ok_or_else(|| eyre!("export needs the --ecdsa-cold-address of the operator"))?;
+            let payload = offline::export(cli, operator, *fallback_gas_limit).await?;
+            std::fs::write(out, serde_json::to_string_pretty(&payload)?)?;
+            for tx in &payload.transactions {
+                info!("To sign: {}", tx.description);
+            }
+            info!(
+                "{} transactions of {:?} written to {}",
+                payload.transactions.len(),
+                operator,
+                out.display()
+            );
+        }
+        cli::OfflineSignCommand::SignPayload { payload, out } => {
+            let payload: offline::UnsignedPayload =
+                serde_json::from_slice(&std::fs::read(payload)?)?;
+            for tx in &payload.transactions {
+                info!(
+                    "Signing for chain {}: {} (to {:?}, nonce {:?})",
+                    payload.chain_id,
+                    tx.description,
+                    tx.tx.to_addr(),
+                    tx.tx.nonce()
+                );
+            }
+            let wallet = cli.get_ecdsa_wallet().await?;
+            let signed = offline::sign(&payload, &wallet)?;
+            std::fs::write(out, serde_json::to_string_pretty(&signed)?)?;
+            info!(
+                "{} transactions signed by {:?} written to {}",
+                signed.transactions.len(),
+                signed.from,
+                out.display()
+            );
+        }
+        cli::OfflineSignCommand::Broadcast { signed } => {
+            let signed: offline::SignedPayload = serde_json::from_slice(&std::fs::read(signed)?)?;
+            let receipts = offline::broadcast(&cli.eth_rpc_url, &signed).await?;
+            info!("{} transactions of {:?} mined", receipts.len(), signed.from);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn split_bls_key(
+    cli: &CliArgs,
+    threshold: u32,
+    shares: u32,
+    out_dir: &Path,
+) -> eyre::Result<()> {
+    let keypair = cli.get_bls_keystore().await?.into_bls_keypair()?;
+    let (public, keys) = crypto::threshold::split(&keypair, threshold, shares)?;
+
+    std::fs::create_dir_all(out_dir)?;
+    let password = cli.bls_key_password.clone().unwrap_or_default();
+    for (index, share) in &keys {
+        let name = format!("bls-share-{}.json", index);
+        crypto::keystore::write_bls_keystore(out_dir, &name, share, &password)?;
+    }
+    std::fs::write(
+        out_dir.join("threshold-key.json"),
+        serde_json::to_string_pretty(&public)?,
+    )?;
+    info!(
+        "BLS key of operator id {:x} split into {} shares with threshold {} in {}",
+        keypair.operator_id(),
+        shares,
+        threshold,
+        out_dir.display()
+    );
     Ok(())
 }
 
@@ -79,17 +663,49 @@ pub(crate) async fn ephemeral_testnet(
     stake: u32,
     cfg: &CliArgs,
 ) -> eyre::Result<()> {
+    let operator_address = operator.client.address();
+    let staker = if cfg.testnet_distinct_roles {
+        LocalWallet::new(&mut ethers::core::rand::thread_rng()).with_chain_id(cfg.chain_id)
+    } else {
+        operator.client.signer().clone()
+    };
+    let mut roles = operator.role_accounts().await?;
+    roles.push(("staker", staker.address()));
+    let shared = operator::shared_role_accounts(&roles);
+    for conflict in &shared {
+        warn!(
+            "Testnet roles {}, authorization bugs between them go unnoticed",
+            conflict
+        );
+    }
+    if cfg.testnet_distinct_roles && !shared.is_empty() {
+        return Err(eyre!(
+            "--testnet-distinct-roles requires distinct accounts, {}",
+            shared.join(", ")
+        ));
+    }
+
+    let mut accounts: Vec<ethers::types::Address> =
+        roles.iter().map(|(_, account)| *account).collect();
+    accounts.sort();
+    accounts.dedup();
+    fund_accounts(&cfg.eth_rpc_url, &accounts).await?;
+    if cfg.testnet_distinct_roles {
+        // only a registered operator can be delegated to
+        operator.register().await?;
+    }
     setup_deposits(
         cfg.eth_rpc_url.clone(),
-        cfg.avs_service_manager_addr,
+        addresses::AddressBook::resolve(cfg)?.service_manager,
         stake,
-        operator.client.signer().clone(),
+        staker,
+        operator_address,
     )
     .await?;
 
     operator.register().await?;
     operator.opt_in_avs().await?;
-    print_status(operator).await?;
+    print_status(operator, None).await?;
 
     info!("Testnet setup sucessfully, starting AVS verification");
     operator.watch_new_tasks().await?;
@@ -0,0 +1,488 @@
+use std::sync::OnceLock;
+
+use prometheus::{
+    Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry,
+};
+use serde::{Deserialize, Serialize};
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Process wide prometheus registry and the metrics recorded by the operator.
+pub struct Metrics {
+    pub registry: Registry,
+    pub rpc_calls: IntCounterVec,
+    pub rpc_compute_units: IntCounterVec,
+    pub task_stage_seconds: HistogramVec,
+    pub task_budget_exceeded: IntCounter,
+    pub tasks_abandoned: IntCounter,
+    pub tasks_quarantined: IntCounter,
+    pub stake_share_pct: GaugeVec,
+    pub operator_strategy_shares: GaugeVec,
+    pub operator_strategy_underlying: GaugeVec,
+    pub projected_stake: GaugeVec,
+    pub task_divergence: IntCounter,
+    pub pressure_level: IntGauge,
+    pub task_backlog: IntGauge,
+    pub task_throughput: Gauge,
+    pub autoscaling_load: Gauge,
+    pub memory_bytes: IntGauge,
+    pub reputation_score: Gauge,
+    pub task_response_rate: Gauge,
+    pub contract_circuit_open: IntGaugeVec,
+    pub chain_halted: IntGaugeVec,
+    pub finality_lag: Histogram,
+    pub finality_lag_last: IntGauge,
+    pub finality_lag_exceeded: IntCounter,
+    pub shadow_diffs: IntCounter,
+    pub wallet_balance_eth: Gauge,
+    pub substrate_spec_version: IntGauge,
+    pub substrate_bytes_sent: IntCounter,
+    pub substrate_bytes_received: IntCounter,
+    pub substrate_quota_exhausted: IntGauge,
+    pub plugin_task_score: GaugeVec,
+    pub undecodable_events: IntCounterVec,
+    pub contract_upgrades: IntCounterVec,
+    pub relayed_responses: IntCounterVec,
+    pub gossip_messages: IntCounterVec,
+    pub restored_broadcasts: IntCounterVec,
+    pub worker_restarts: IntCounter,
+    pub queue_depth: IntGaugeVec,
+    pub queue_dropped: IntCounterVec,
+    pub hook_runs: IntCounterVec,
+    pub archive_operations: IntCounterVec,
+    pub node_info: IntGaugeVec,
+}
+
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(|| Metrics::new().expect("metrics should register"))
+}
+
+impl Metrics {
+    fn new() -> eyre::Result<Self> {
+        let registry = Registry::new_custom(Some("avs_finalizer".into()), None)?;
+
+        let rpc_calls = IntCounterVec::new(
+            Opts::new("rpc_calls_total", "JSON-RPC calls per provider and method"),
+            &["provider", "method"],
+        )?;
+        registry.register(Box::new(rpc_calls.clone()))?;
+
+        let rpc_compute_units = IntCounterVec::new(
+            Opts::new(
+                "rpc_compute_units_total",
+                "Estimated billable compute units per provider and method",
+            ),
+            &["provider", "method"],
+        )?;
+        registry.register(Box::new(rpc_compute_units.clone()))?;
+
+        let task_stage_seconds = HistogramVec::new(
+            HistogramOpts::new("task_stage_seconds", "Time spent per task pipeline stage")
+                .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]),
+            &["stage"],
+        )?;
+        registry.register(Box::new(task_stage_seconds.clone()))?;
+
+        let task_budget_exceeded = IntCounter::new(
+            "task_budget_exceeded_total",
+            "Tasks whose verification exceeded the latency budget",
+        )?;
+        registry.register(Box::new(task_budget_exceeded.clone()))?;
+
+        let tasks_abandoned = IntCounter::new(
+            "tasks_abandoned_total",
+            "Tasks abandoned mid-verification once their response window expired",
+        )?;
+        registry.register(Box::new(tasks_abandoned.clone()))?;
+
+        let tasks_quarantined = IntCounter::new(
+            "tasks_quarantined_total",
+            "Tasks quarantined after failing their verification repeatedly with the same error",
+        )?;
+        registry.register(Box::new(tasks_quarantined.clone()))?;
+
+        let stake_share_pct = GaugeVec::new(
+            Opts::new(
+                "stake_share_pct",
+                "Share of the quorum total stake held by the operator, in percent",
+            ),
+            &["quorum"],
+        )?;
+        registry.register(Box::new(stake_share_pct.clone()))?;
+
+        let operator_strategy_shares = GaugeVec::new(
+            Opts::new(
+                "operator_strategy_shares",
+                "Shares delegated to the operator per strategy whitelisted for deposit",
+            ),
+            &["strategy"],
+        )?;
+        registry.register(Box::new(operator_strategy_shares.clone()))?;
+
+        let operator_strategy_underlying = GaugeVec::new(
+            Opts::new(
+                "operator_strategy_underlying",
+                "Underlying tokens of the shares delegated to the operator per strategy whitelisted for deposit, in whole tokens per the token decimals",
+            ),
+            &["strategy"],
+        )?;
+        registry.register(Box::new(operator_strategy_underlying.clone()))?;
+
+        let projected_stake = GaugeVec::new(
+            Opts::new(
+                "projected_stake",
+                "Operator stake per quorum at the next stake update, from its delegated shares",
+            ),
+            &["quorum"],
+        )?;
+        registry.register(Box::new(projected_stake.clone()))?;
+
+        let task_divergence = IntCounter::new(
+            "task_divergence_total",
+            "Tasks whose accepted response differs from the locally computed result",
+        )?;
+        registry.register(Box::new(task_divergence.clone()))?;
+
+        let pressure_level = IntGauge::new(
+            "pressure_level",
+            "Resource pressure, 0 normal, 1 elevated, 2 critical",
+        )?;
+        registry.register(Box::new(pressure_level.clone()))?;
+
+        let task_backlog = IntGauge::new("task_backlog", "Received tasks waiting to be processed")?;
+        registry.register(Box::new(task_backlog.clone()))?;
+
+        let task_throughput = Gauge::new(
+            "task_throughput_per_minute",
+            "Tasks verified per minute over the last ten minutes",
+        )?;
+        registry.register(Box::new(task_throughput.clone()))?;
+
+        let autoscaling_load = Gauge::new(
+            "autoscaling_load",
+            "Time to verify the task backlog over the autoscaling target, scale out above 1",
+        )?;
+        registry.register(Box::new(autoscaling_load.clone()))?;
+
+        let memory_bytes = IntGauge::new("memory_bytes", "Resident memory of the process")?;
+        registry.register(Box::new(memory_bytes.clone()))?;
+
+        let reputation_score = Gauge::new(
+            "reputation_score",
+            "Reputation score over the reputation window, 0 to 100",
+        )?;
+        registry.register(Box::new(reputation_score.clone()))?;
+
+        let task_response_rate = Gauge::new(
+            "task_response_rate",
+            "Share of received tasks responded over the reputation window",
+        )?;
+        registry.register(Box::new(task_response_rate.clone()))?;
+
+        let contract_circuit_open = IntGaugeVec::new(
+            Opts::new(
+                "contract_circuit_open",
+                "Whether calls to a contract are stopped by its circuit breaker",
+            ),
+            &["contract"],
+        )?;
+        registry.register(Box::new(contract_circuit_open.clone()))?;
+
+        let chain_halted = IntGaugeVec::new(
+            Opts::new(
+                "chain_halted",
+                "Whether a chain stopped producing blocks, the node idles meanwhile",
+            ),
+            &["chain"],
+        )?;
+        registry.register(Box::new(chain_halted.clone()))?;
+
+        let finality_lag = Histogram::with_opts(
+            HistogramOpts::new(
+                "rollup_finality_lag_seconds",
+                "Time from the finalization of a substrate block to the acceptance of its task response",
+            )
+            .buckets(vec![
+                30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0,
+            ]),
+        )?;
+        registry.register(Box::new(finality_lag.clone()))?;
+
+        let finality_lag_last = IntGauge::new(
+            "rollup_finality_lag_last_seconds",
+            "Finality lag of the last accepted task response",
+        )?;
+        registry.register(Box::new(finality_lag_last.clone()))?;
+
+        let finality_lag_exceeded = IntCounter::new(
+            "rollup_finality_lag_exceeded_total",
+            "Blocks and tasks whose finality lag exceeded --max-finality-lag-secs, accepted or not",
+        )?;
+        registry.register(Box::new(finality_lag_exceeded.clone()))?;
+
+        let shadow_diffs = IntCounter::new(
+            "shadow_diffs_total",
+            "Tasks whose response signed by the shadowed operator differs from the local result",
+        )?;
+        registry.register(Box::new(shadow_diffs.clone()))?;
+
+        let wallet_balance_eth =
+            Gauge::new("wallet_balance_eth", "ETH balance of the operator account")?;
+        registry.register(Box::new(wallet_balance_eth.clone()))?;
+
+        let substrate_spec_version = IntGauge::new(
+            "substrate_spec_version",
+            "Spec version of the runtime which executed the last verified block",
+        )?;
+        registry.register(Box::new(substrate_spec_version.clone()))?;
+
+        let substrate_bytes_sent = IntCounter::new(
+            "substrate_bytes_sent_total",
+            "Estimated bytes of the requests sent to the substrate nodes",
+        )?;
+        registry.register(Box::new(substrate_bytes_sent.clone()))?;
+
+        let substrate_bytes_received = IntCounter::new(
+            "substrate_bytes_received_total",
+            "Bytes of the blocks fetched from the substrate nodes",
+        )?;
+        registry.register(Box::new(substrate_bytes_received.clone()))?;
+
+        let substrate_quota_exhausted = IntGauge::new(
+            "substrate_quota_exhausted",
+            "1 once the hourly substrate request or bandwidth quota is used up",
+        )?;
+        registry.register(Box::new(substrate_quota_exhausted.clone()))?;
+
+        let plugin_task_score = GaugeVec::new(
+            Opts::new(
+                "plugin_task_score",
+                "Score given by a plugin to the last task it reviewed",
+            ),
+            &["plugin"],
+        )?;
+        registry.register(Box::new(plugin_task_score.clone()))?;
+
+        let undecodable_events = IntCounterVec::new(
+            Opts::new(
+                "undecodable_events",
+                "Contract logs matching no known event shape",
+            ),
+            &["event"],
+        )?;
+        registry.register(Box::new(undecodable_events.clone()))?;
+
+        let contract_upgrades = IntCounterVec::new(
+            Opts::new(
+                "contract_upgrades_total",
+                "Changes of the code behind an AVS contract",
+            ),
+            &["contract"],
+        )?;
+        registry.register(Box::new(contract_upgrades.clone()))?;
+
+        let relayed_responses = IntCounterVec::new(
+            Opts::new(
+                "relayed_responses_total",
+                "Task responses handed to the relayer, by acknowledged or fallen back",
+            ),
+            &["outcome"],
+        )?;
+        registry.register(Box::new(relayed_responses.clone()))?;
+
+        let gossip_messages = IntCounterVec::new(
+            Opts::new(
+                "gossip_messages_total",
+                "Partial signatures gossiped by other operators, accepted or rejected",
+            ),
+            &["outcome"],
+        )?;
+        registry.register(Box::new(gossip_messages.clone()))?;
+
+        let restored_broadcasts = IntCounterVec::new(
+            Opts::new(
+                "restored_broadcasts_total",
+                "Responses sent before a restart, found landed or sent again unchanged",
+            ),
+            &["outcome"],
+        )?;
+        registry.register(Box::new(restored_broadcasts.clone()))?;
+
+        let worker_restarts = IntCounter::new(
+            "verify_worker_restarts_total",
+            "Verification workers lost while verifying, replaced on the next verification",
+        )?;
+        registry.register(Box::new(worker_restarts.clone()))?;
+
+        let queue_depth = IntGaugeVec::new(
+            Opts::new("queue_depth", "Items waiting in a bounded pipeline queue"),
+            &["queue"],
+        )?;
+        registry.register(Box::new(queue_depth.clone()))?;
+
+        let queue_dropped = IntCounterVec::new(
+            Opts::new(
+                "queue_dropped_total",
+                "Items dropped by a full bounded pipeline queue",
+            ),
+            &["queue"],
+        )?;
+        registry.register(Box::new(queue_dropped.clone()))?;
+
+        let hook_runs = IntCounterVec::new(
+            Opts::new(
+                "hook_runs_total",
+                "Lifecycle hooks run, by hook point and ok, failed or timeout",
+            ),
+            &["hook", "outcome"],
+        )?;
+        registry.register(Box::new(hook_runs.clone()))?;
+
+        let archive_operations = IntCounterVec::new(
+            Opts::new(
+                "archive_operations_total",
+                "Objects uploaded to or deleted from the archive, by operation and ok, failed or timeout",
+            ),
+            &["operation", "outcome"],
+        )?;
+        registry.register(Box::new(archive_operations.clone()))?;
+
+        let node_info = IntGaugeVec::new(
+            Opts::new(
+                "node_info",
+                "Always 1, labelled with the persistent node identity",
+            ),
+            &["node_id"],
+        )?;
+        registry.register(Box::new(node_info.clone()))?;
+
+        Ok(Self {
+            registry,
+            rpc_calls,
+            rpc_compute_units,
+            task_stage_seconds,
+            task_budget_exceeded,
+            tasks_abandoned,
+            tasks_quarantined,
+            stake_share_pct,
+            operator_strategy_shares,
+            operator_strategy_underlying,
+            projected_stake,
+            task_divergence,
+            pressure_level,
+            task_backlog,
+            task_throughput,
+            autoscaling_load,
+            memory_bytes,
+            reputation_score,
+            task_response_rate,
+            contract_circuit_open,
+            chain_halted,
+            finality_lag,
+            finality_lag_last,
+            finality_lag_exceeded,
+            shadow_diffs,
+            wallet_balance_eth,
+            substrate_spec_version,
+            substrate_bytes_sent,
+            substrate_bytes_received,
+            substrate_quota_exhausted,
+            plugin_task_score,
+            undecodable_events,
+            contract_upgrades,
+            relayed_responses,
+            gossip_messages,
+            restored_broadcasts,
+            worker_restarts,
+            queue_depth,
+            queue_dropped,
+            hook_runs,
+            archive_operations,
+            node_info,
+        })
+    }
+
+    pub fn record_rpc_call(&self, provider: &str, method: &str) {
+        self.record_rpc_calls(provider, method, 1);
+    }
+
+    pub fn record_rpc_calls(&self, provider: &str, method: &str, calls: u64) {
+        self.rpc_calls
+            .with_label_values(&[provider, method])
+            .inc_by(calls);
+        self.rpc_compute_units
+            .with_label_values(&[provider, method])
+            .inc_by(compute_units(method) * calls);
+    }
+
+    pub fn rpc_usage(&self) -> Vec<RpcUsage> {
+        let mut usage: Vec<RpcUsage> = vec![];
+        for family in self.registry.gather() {
+            let is_calls = family.get_name().ends_with("rpc_calls_total");
+            if !is_calls && !family.get_name().ends_with("rpc_compute_units_total") {
+                continue;
+            }
+            for metric in family.get_metric() {
+                let label = |name: &str| {
+                    metric
+                        .get_label()
+                        .iter()
+                        .find(|l| l.get_name() == name)
+                        .map(|l| l.get_value().to_owned())
+                        .unwrap_or_default()
+                };
+                let (provider, method) = (label("provider"), label("method"));
+                let value = metric.get_counter().get_value() as u64;
+
+                let entry = match usage
+                    .iter_mut()
+                    .position(|u| u.provider == provider && u.method == method)
+                {
+                    Some(i) => &mut usage[i],
+                    None => {
+                        usage.push(RpcUsage {
+                            provider,
+                            method,
+                            calls: 0,
+                            compute_units: 0,
+                        });
+                        usage.last_mut().expect("just pushed")
+                    }
+                };
+                if is_calls {
+                    entry.calls = value;
+                } else {
+                    entry.compute_units = value;
+                }
+            }
+        }
+        usage.sort_by_key(|u| std::cmp::Reverse(u.compute_units));
+        usage
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcUsage {
+    pub provider: String,
+    pub method: String,
+    pub calls: u64,
+    pub compute_units: u64,
+}
+
+/// Estimated compute units billed per call, based on the published Alchemy pricing which
+/// Infura credits follow closely enough for capacity planning.
+fn compute_units(method: &str) -> u64 {
+    match method {
+        "eth_chainId" | "net_version" => 0,
+        "eth_blockNumber" | "eth_feeHistory" | "eth_subscribe" | "eth_unsubscribe" => 10,
+        "eth_getTransactionReceipt" => 15,
+        "eth_getBlockByNumber" | "eth_getBlockByHash" => 16,
+        "eth_gasPrice" | "eth_getBalance" | "eth_maxPriorityFeePerGas" => 19,
+        "eth_call" | "eth_getTransactionCount" | "eth_getCode" => 26,
+        "eth_getLogs" | "eth_newFilter" => 75,
+        "eth_estimateGas" => 87,
+        "eth_sendRawTransaction" | "eth_sendTransaction" => 250,
+        _ => 20,
+    }
+}
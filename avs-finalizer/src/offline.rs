@@ -0,0 +1,303 @@
+use std::sync::Arc;
+
+use bindings::{
+    bls_public_key_compendium::BLSPublicKeyCompendium,
+    bls_registry_coordinator_with_indices::BLSRegistryCoordinatorWithIndices,
+    delegation_manager::DelegationManager, mangata_service_manager::MangataServiceManager,
+    shared_types::OperatorDetails, slasher::Slasher,
+};
+use ethers::{
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, Eip1559TransactionRequest,
+        TransactionReceipt, H256, U64,
+    },
+    utils::keccak256,
+};
+use eyre::{eyre, OptionExt};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    addresses::AddressBook,
+    chainio::build_eth_provider,
+    cli::CliArgs,
+    constants::ChainConstants,
+    crypto::{bn254::BlsKeypair, EthConvert},
+};
+
+/// Transaction of an offline key, described for its review before signing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadTx {
+    pub description: String,
+    pub tx: TypedTransaction,
+}
+
+/// Unsigned transactions of `from`, with their nonces and fees, written by `export` on an
+/// online machine. They are signed for `chain_id`, transactions serialize without theirs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedPayload {
+    pub chain_id: u64,
+    pub from: Address,
+    pub transactions: Vec<PayloadTx>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedTx {
+    pub description: String,
+    pub hash: H256,
+    pub raw: Bytes,
+}
+
+/// Transactions of an [`UnsignedPayload`] signed on the air-gapped machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedPayload {
+    pub chain_id: u64,
+    pub from: Address,
+    pub transactions: Vec<SignedTx>,
+}
+
+/// Prepares the transactions registering `operator` with EigenLayer and the AVS as
+/// `opt-in-avs` would, skipping the registrations already made. The public key registration
+/// is signed with the BLS key, which is needed here, the ECDSA key is not. Transactions
+/// depending on earlier ones cannot be estimated and get `fallback_gas_limit`.
+pub async fn export(
+    cfg: &CliArgs,
+    operator: Address,
+    fallback_gas_limit: u64,
+) -> eyre::Result<UnsignedPayload> {
+    let provider = Arc::new(build_eth_provider(&cfg.eth_rpc_url).await?);
+    let chain_id = provider.get_chainid().await?.as_u64();
+    if chain_id != cfg.chain_id {
+        return Err(eyre!(
+            "{} serves chain {}, not {}",
+            cfg.eth_rpc_url,
+            chain_id,
+            cfg.chain_id
+        ));
+    }
+    let constants = ChainConstants::load(cfg.chain_constants.as_deref())?;
+    let addresses = AddressBook::resolve(cfg)?;
+    let keypair = cfg.get_bls_keystore().await?.into_bls_keypair()?;
+
+    let service_manager = MangataServiceManager::new(addresses.service_manager, provider.clone());
+    let registry = BLSRegistryCoordinatorWithIndices::new(
+        service_manager.registry_coordinator().call().await?,
+        provider.clone(),
+    );
+    let slasher = Slasher::new(service_manager.slasher().call().await?, provider.clone());
+    let delegation = DelegationManager::new(slasher.delegation().call().await?, provider.clone());
+    let compendium = BLSPublicKeyCompendium::new(addresses.bls_pubkey_compendium, provider.clone());
+
+    let mut calls = vec![];
+    if compendium.operator_to_pubkey_hash(operator).call().await? == [0; 32] {
+        calls.push((
+            "register the BLS public key with the BLSPublicKeyCompendium",
+            compendium.address(),
+            register_bls_public_key_data(&compendium, &keypair, operator, chain_id)?,
+        ));
+    }
+    if !delegation.is_operator(operator).call().await? {
+        let details = OperatorDetails {
+            earnings_receiver: operator,
+            ..Default::default()
+        };
+        let call = delegation.register_as_operator(details, String::new());
+        calls.push((
+            "register as operator with the EigenLayer DelegationManager",
+            delegation.address(),
+            call.calldata()
+                .ok_or_eyre("cannot encode registerAsOperator")?,
+        ));
+    }
+    if registry.get_operator(operator).call().await?.status != 1 {
+        let public = EthConvert::to_g1(keypair.public).ok_or_eyre("cannot convert G1 public")?;
+        let call = registry.register_operator_with_coordinator_1(
+            constants.quorums.clone().into(),
+            public,
+            String::new(),
+        );
+        calls.push((
+            "register with the AVS registry coordinator",
+            registry.address(),
+            call.calldata()
+                .ok_or_eyre("cannot encode registerOperatorWithCoordinator")?,
+        ));
+    }
+
+    let nonce = provider.get_transaction_count(operator, None).await?;
+    let (max_fee, priority_fee) = provider.estimate_eip1559_fees(None).await?;
+    let mut transactions = vec![];
+    for (i, (description, to, data)) in calls.into_iter().enumerate() {
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(operator)
+            .to(to)
+            .data(data)
+            .nonce(nonce + i)
+            .chain_id(chain_id)
+            .max_fee_per_gas(max_fee)
+            .max_priority_fee_per_gas(priority_fee)
+            .into();
+        let gas = match provider.estimate_gas(&tx, None).await {
+            Ok(gas) => gas,
+            Err(e) if i > 0 => {
+                warn!(
+                    "Cannot estimate the gas to {} before the previous transactions are mined, using {}: {}",
+                    description, fallback_gas_limit, e
+                );
+                fallback_gas_limit.into()
+            }
+            Err(e) => return Err(eyre!("{} would fail: {}", description, e)),
+        };
+        tx.set_gas(gas);
+        transactions.push(PayloadTx {
+            description: description.to_owned(),
+            tx,
+        });
+    }
+    if transactions.is_empty() {
+        info!("Operator {:?} is already registered", operator);
+    }
+    Ok(UnsignedPayload {
+        chain_id,
+        from: operator,
+        transactions,
+    })
+}
+
+fn register_bls_public_key_data<M: Middleware>(
+    compendium: &BLSPublicKeyCompendium<M>,
+    keypair: &BlsKeypair,
+    operator: Address,
+    chain_id: u64,
+) -> eyre::Result<Bytes> {
+    let signed_hash =
+        keypair.make_pubkey_registration_data(operator, compendium.address(), chain_id)?;
+    let call = compendium.register_bls_public_key(
+        EthConvert::to_g1(signed_hash).ok_or_eyre("cannot convert signed_hash")?,
+        EthConvert::to_g1(keypair.public).ok_or_eyre("cannot convert G1 public")?,
+        EthConvert::to_g2(keypair.public_g2()).ok_or_eyre("cannot convert G2 public")?,
+    );
+    call.calldata()
+        .ok_or_eyre("cannot encode registerBLSPublicKey")
+}
+
+/// Signs the transactions of `payload` for its chain with `wallet`, which must be the account
+/// they are sent from. Needs no connection.
+pub fn sign(payload: &UnsignedPayload, wallet: &LocalWallet) -> eyre::Result<SignedPayload> {
+    if wallet.address() != payload.from {
+        return Err(eyre!(
+            "payload of {:?} cannot be signed by {:?}",
+            payload.from,
+            wallet.address()
+        ));
+    }
+    let transactions = payload
+        .transactions
+        .iter()
+        .map(|PayloadTx { description, tx }| {
+            let mut tx = tx.clone();
+            tx.set_chain_id(payload.chain_id);
+            let signature = wallet.sign_transaction_sync(&tx)?;
+            let raw = tx.rlp_signed(&signature);
+            Ok(SignedTx {
+                description: description.clone(),
+                hash: keccak256(&raw).into(),
+                raw,
+            })
+        })
+        .collect::<eyre::Result<_>>()?;
+    Ok(SignedPayload {
+        chain_id: payload.chain_id,
+        from: payload.from,
+        transactions,
+    })
+}
+
+/// Sends the transactions of `signed` in order, waiting for each to be mined. Transactions
+/// already mined, e.g. by an interrupted broadcast, are skipped.
+pub async fn broadcast(
+    eth_rpc_url: &str,
+    signed: &SignedPayload,
+) -> eyre::Result<Vec<TransactionReceipt>> {
+    let provider = build_eth_provider(eth_rpc_url).await?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    if chain_id != signed.chain_id {
+        return Err(eyre!(
+            "transactions signed for chain {} cannot be sent to chain {}",
+            signed.chain_id,
+            chain_id
+        ));
+    }
+    let mut receipts = vec![];
+    for tx in &signed.transactions {
+        let receipt = match provider.get_transaction_receipt(tx.hash).await? {
+            Some(receipt) => {
+                info!("Already mined: {} in {:?}", tx.description, tx.hash);
+                receipt
+            }
+            None => provider
+                .send_raw_transaction(tx.raw.clone())
+                .await?
+                .await?
+                .ok_or_else(|| eyre!("{} was dropped", tx.description))?,
+        };
+        if receipt.status != Some(U64::one()) {
+            return Err(eyre!("{} reverted in {:?}", tx.description, tx.hash));
+        }
+        info!(
+            "Mined: {} in {:?} at block {:?}",
+            tx.description, tx.hash, receipt.block_number
+        );
+        receipts.push(receipt);
+    }
+    Ok(receipts)
+}
+
+#[test]
+fn test_sign_payload() {
+    use ethers::{types::Signature, utils::rlp::Rlp};
+
+    let wallet: LocalWallet = "0x2a871d0798f97d79848a013d4936a73bf4cc922c825d33c1cf7073dff6d409c6"
+        .parse()
+        .unwrap();
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .from(wallet.address())
+        .to(Address::repeat_byte(0x11))
+        .data(vec![1, 2, 3])
+        .nonce(7)
+        .gas(100_000)
+        .chain_id(17_000)
+        .max_fee_per_gas(2_000_000_000u64)
+        .max_priority_fee_per_gas(1_000_000_000u64)
+        .into();
+    let payload = UnsignedPayload {
+        chain_id: 17_000,
+        from: wallet.address(),
+        transactions: vec![PayloadTx {
+            description: "test".into(),
+            tx: tx.clone(),
+        }],
+    };
+    // the payload goes through files between the machines
+    let payload: UnsignedPayload =
+        serde_json::from_str(&serde_json::to_string(&payload).unwrap()).unwrap();
+
+    let signed = sign(&payload, &wallet).unwrap();
+    let raw = &signed.transactions[0].raw;
+    let (decoded, signature): (TypedTransaction, Signature) =
+        TypedTransaction::decode_signed(&Rlp::new(raw)).unwrap();
+    assert_eq!(decoded.nonce(), tx.nonce());
+    assert_eq!(decoded.chain_id(), Some(17_000.into()));
+    assert_eq!(
+        signature.recover(decoded.sighash()).unwrap(),
+        wallet.address()
+    );
+    assert_eq!(signed.transactions[0].hash, H256::from(keccak256(raw)));
+
+    let other: LocalWallet = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"
+        .parse()
+        .unwrap();
+    assert!(sign(&payload, &other).is_err());
+}
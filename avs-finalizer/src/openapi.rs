@@ -0,0 +1,421 @@
+use serde_json::{json, Value};
+
+/// OpenAPI 3 description of the operator HTTP API served by [`crate::api::serve`], served on
+/// `GET /openapi.json` and printed by the `openapi` command.
+pub fn spec() -> Value {
+    let ok_json = |description: &str, schema: Value| {
+        json!({
+            "description": description,
+            "content": { "application/json": { "schema": schema } }
+        })
+    };
+    let array_of = |name: &str| json!({ "type": "array", "items": schema_ref(name) });
+    let admin = |summary: &str| {
+        json!({
+            "post": {
+                "summary": summary,
+                "responses": {
+                    "204": { "description": "Done" },
+                    "403": { "description": "No token or client CA configured" }
+                }
+            }
+        })
+    };
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Mangata AVS operator API",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "security": [{ "bearer": [] }],
+        "paths": {
+            "/metrics": {
+                "get": {
+                    "summary": "Prometheus metrics",
+                    "responses": {
+                        "200": {
+                            "description": "Metrics in the Prometheus text format",
+                            "content": { "text/plain": { "schema": { "type": "string" } } }
+                        }
+                    }
+                }
+            },
+            "/rpc-usage": {
+                "get": {
+                    "summary": "JSON-RPC calls and estimated compute units per provider and method",
+                    "responses": { "200": ok_json("Usage since the node started", array_of("RpcUsage")) }
+                }
+            },
+            "/health": {
+                "get": {
+                    "summary": "Contract circuit breakers",
+                    "responses": {
+                        "200": ok_json("All circuits closed or half open", array_of("CircuitStatus")),
+                        "503": ok_json("A circuit is open", array_of("CircuitStatus"))
+                    }
+                }
+            },
+            "/recent-errors": {
+                "get": {
+                    "summary": "Last warnings and errors logged by the node, oldest first",
+                    "responses": { "200": ok_json("Recent errors", array_of("RecentError")) }
+                }
+            },
+            "/quorum-snapshots/{block}": {
+                "get": {
+                    "summary": "Members of the task quorums and their stake at a task reference block",
+                    "parameters": [{
+                        "name": "block",
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "integer", "minimum": 0 }
+                    }],
+                    "responses": {
+                        "200": ok_json("Quorum snapshot", schema_ref("QuorumSnapshot")),
+                        "404": { "description": "No store or no task referenced this block" }
+                    }
+                }
+            },
+            "/pending-tasks": {
+                "get": {
+                    "summary": "Tasks received but not signed yet, by task index",
+                    "responses": { "200": ok_json("Pending tasks", array_of("PendingTask")) }
+                }
+            },
+            "/autoscaling": {
+                "get": {
+                    "summary": "Task backlog and verification throughput, scale out while the load is above 1",
+                    "responses": { "200": ok_json("Autoscaling signal", schema_ref("AutoscaleSignal")) }
+                }
+            },
+            "/finality-lag": {
+                "get": {
+                    "summary": "Latest delays from the finalization of a substrate block to the acceptance of its task response",
+                    "responses": { "200": ok_json("Finality lag", schema_ref("LagReport")) }
+                }
+            },
+            "/identity": {
+                "get": {
+                    "summary": "Persistent identity of the node and the keys it ran with",
+                    "responses": { "200": ok_json("Node identity", schema_ref("NodeIdentity")) }
+                }
+            },
+            "/gossip": {
+                "post": {
+                    "summary": "Partial signature of a task response gossiped by another operator",
+                    "security": [],
+                    "responses": {
+                        "204": { "description": "Accepted" },
+                        "400": { "description": "Invalid message or signature" },
+                        "404": { "description": "Gossip disabled" }
+                    }
+                }
+            },
+            "/gossip/aggregates": {
+                "get": {
+                    "summary": "Aggregated gossiped signatures of the tasks the aggregator did not take the response of",
+                    "responses": {
+                        "200": ok_json("Aggregates by task index", array_of("GossipAggregate")),
+                        "404": { "description": "Gossip disabled" }
+                    }
+                }
+            },
+            "/openapi.json": {
+                "get": {
+                    "summary": "This document",
+                    "responses": { "200": ok_json("OpenAPI document", json!({ "type": "object" })) }
+                }
+            },
+            "/admin/pause": admin("Stop answering new tasks"),
+            "/admin/resume": admin("Resume answering new tasks")
+        },
+        "components": {
+            "securitySchemes": {
+                "bearer": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "Required when the node runs with `--api-token`"
+                }
+            },
+            "schemas": schemas()
+        }
+    })
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn object(properties: Value) -> Value {
+    let required: Vec<&String> = properties
+        .as_object()
+        .map_or(vec![], |p| p.keys().collect());
+    json!({ "type": "object", "required": required, "properties": properties })
+}
+
+fn schemas() -> Value {
+    let uint = json!({ "type": "integer", "format": "int64", "minimum": 0 });
+    json!({
+        "RpcUsage": object(json!({
+            "provider": { "type": "string" },
+            "method": { "type": "string" },
+            "calls": uint,
+            "compute_units": uint
+        })),
+        "CircuitStatus": object(json!({
+            "contract": { "type": "string" },
+            "state": { "type": "string", "enum": ["closed", "open", "half_open"] },
+            "consecutive_failures": uint,
+            "timeout_ms": uint
+        })),
+        "RecentError": object(json!({
+            "timestamp": uint,
+            "level": { "type": "string" },
+            "target": { "type": "string" },
+            "message": { "type": "string" }
+        })),
+        "PendingTask": object(json!({
+            "task_index": uint,
+            "block_number": uint,
+            "task_created_block": uint,
+            "expires_at_block": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Last block at which the task manager accepts a response"
+            },
+            "received_at": { "type": "integer", "format": "int64", "description": "Unix time" },
+            "state": {
+                "type": "string",
+                "enum": ["queued", "executing", "reviewing", "confirming", "signing"]
+            }
+        })),
+        "AutoscaleSignal": object(json!({
+            "task_backlog": uint,
+            "throughput_per_minute": { "type": "number" },
+            "drain_secs": { "type": "number", "nullable": true },
+            "load": { "type": "number" }
+        })),
+        "LagReport": object(json!({
+            "max_lag_secs": uint,
+            "samples": { "type": "array", "items": schema_ref("LagSample") }
+        })),
+        "LagSample": object(json!({
+            "task_index": uint,
+            "block_number": uint,
+            "finalized_at": { "type": "integer", "format": "int64", "description": "Unix time" },
+            "accepted_at": { "type": "integer", "format": "int64", "description": "Unix time" },
+            "lag_secs": uint
+        })),
+        "GossipAggregate": object(json!({
+            "task_index": uint,
+            "digest": { "type": "string", "description": "Task response digest, 0x prefixed" },
+            "signers": { "type": "array", "items": { "type": "string" } },
+            "signature": schema_ref("G1Point"),
+            "apk_g2": {
+                "type": "object",
+                "description": "Sum of the G2 public keys of the signers",
+                "properties": {
+                    "x": { "type": "array", "items": { "type": "string" } },
+                    "y": { "type": "array", "items": { "type": "string" } }
+                }
+            },
+            "non_signers": schema_ref("NonSigners")
+        })),
+        "NonSigners": object(json!({
+            "reference_block": { "type": "integer", "description": "Block the task was created at, the stakes are checked at" },
+            "quorum_numbers": { "type": "string", "description": "0x prefixed" },
+            "operator_ids": { "type": "array", "items": { "type": "string" }, "description": "Ascending" },
+            "non_signer_quorum_bitmap_indices": { "type": "array", "items": uint },
+            "quorum_apk_indices": { "type": "array", "items": uint },
+            "total_stake_indices": { "type": "array", "items": uint },
+            "non_signer_stake_indices": { "type": "array", "items": { "type": "array", "items": uint } }
+        })),
+        "G1Point": object(json!({
+            "x": { "type": "string", "description": "Hex encoded coordinate" },
+            "y": { "type": "string", "description": "Hex encoded coordinate" }
+        })),
+        "NodeIdentity": object(json!({
+            "id": { "type": "string", "format": "uuid" },
+            "first_started_at": { "type": "integer", "format": "int64", "description": "Unix time" },
+            "created_by": { "type": "string", "description": "Node version which generated the identity" },
+            "keys": schema_ref("KeyFingerprints"),
+            "previous_keys": { "type": "array", "items": schema_ref("KeyFingerprints") }
+        })),
+        "KeyFingerprints": object(json!({
+            "ecdsa_address": { "type": "string" },
+            "bls_operator_id": { "type": "string" },
+            "since": { "type": "integer", "format": "int64", "description": "Unix time" }
+        })),
+        "QuorumSnapshot": object(json!({
+            "reference_block": uint,
+            "quorums": { "type": "array", "items": schema_ref("QuorumMembers") }
+        })),
+        "QuorumMembers": object(json!({
+            "quorum_number": uint,
+            "total_stake": uint,
+            "operators": {
+                "type": "array",
+                "description": "[operator id, stake] pairs",
+                "items": {
+                    "type": "array",
+                    "items": {},
+                    "minItems": 2,
+                    "maxItems": 2
+                }
+            }
+        }))
+    })
+}
+
+#[test]
+fn test_schemas_match_responses() {
+    use crate::{
+        chainio::breaker::{CircuitState, CircuitStatus},
+        doctor::RecentError,
+        gossip::{GossipAggregate, NonSigners},
+        identity::{KeyFingerprints, NodeIdentity},
+        lag::{LagReport, LagSample},
+        metrics::RpcUsage,
+        pressure::AutoscaleSignal,
+        store::{QuorumMembers, QuorumSnapshot},
+        task::{PendingTask, VerificationState},
+    };
+    use bindings::shared_types::G1Point;
+    use ethers::types::H256;
+    use std::time::Duration;
+
+    let members = QuorumMembers {
+        quorum_number: 0,
+        total_stake: 1,
+        operators: vec![(H256::zero(), 1)],
+    };
+    let non_signers = NonSigners {
+        reference_block: 1,
+        quorum_numbers: vec![0].into(),
+        operator_ids: vec![H256::zero()],
+        indices: Default::default(),
+    };
+
+    let sample = LagSample {
+        task_index: 1,
+        block_number: 2,
+        finalized_at: 3,
+        accepted_at: 4,
+        lag_secs: 1,
+    };
+
+    let keys = KeyFingerprints {
+        ecdsa_address: Default::default(),
+        bls_operator_id: H256::zero(),
+        since: 1,
+    };
+
+    let examples = [
+        (
+            "RpcUsage",
+            serde_json::to_value(RpcUsage {
+                provider: "eth_http".into(),
+                method: "eth_call".into(),
+                calls: 1,
+                compute_units: 26,
+            }),
+        ),
+        (
+            "CircuitStatus",
+            serde_json::to_value(CircuitStatus {
+                contract: "task_manager",
+                state: CircuitState::HalfOpen,
+                consecutive_failures: 0,
+                timeout_ms: 10,
+            }),
+        ),
+        (
+            "RecentError",
+            serde_json::to_value(RecentError {
+                timestamp: 0,
+                level: "WARN".into(),
+                target: "avs_finalizer".into(),
+                message: "x".into(),
+            }),
+        ),
+        (
+            "QuorumSnapshot",
+            serde_json::to_value(QuorumSnapshot {
+                reference_block: 1,
+                quorums: vec![members.clone()],
+            }),
+        ),
+        ("QuorumMembers", serde_json::to_value(members)),
+        (
+            "GossipAggregate",
+            serde_json::to_value(GossipAggregate {
+                task_index: 1,
+                digest: H256::zero(),
+                signers: vec![H256::zero()],
+                signature: Default::default(),
+                apk_g2: Default::default(),
+                non_signers: non_signers.clone(),
+            }),
+        ),
+        ("NonSigners", serde_json::to_value(non_signers)),
+        ("G1Point", serde_json::to_value(G1Point::default())),
+        (
+            "NodeIdentity",
+            serde_json::to_value(NodeIdentity {
+                id: "00000000-0000-4000-8000-000000000000".into(),
+                first_started_at: 1,
+                created_by: "0.1.0".into(),
+                keys: keys.clone(),
+                previous_keys: vec![],
+            }),
+        ),
+        ("KeyFingerprints", serde_json::to_value(keys)),
+        (
+            "LagReport",
+            serde_json::to_value(LagReport {
+                max_lag_secs: 1800,
+                samples: vec![sample],
+            }),
+        ),
+        ("LagSample", serde_json::to_value(sample)),
+        (
+            "AutoscaleSignal",
+            serde_json::to_value(AutoscaleSignal::new(
+                4,
+                20,
+                Duration::from_secs(600),
+                Duration::from_secs(60),
+            )),
+        ),
+        (
+            "PendingTask",
+            serde_json::to_value(PendingTask {
+                task_index: 1,
+                block_number: 2,
+                task_created_block: 3,
+                expires_at_block: 4,
+                received_at: 0,
+                state: VerificationState::Reviewing,
+            }),
+        ),
+    ];
+    let schemas = schemas();
+    for (name, example) in examples {
+        let example = example.unwrap();
+        let schema = &schemas[name]["properties"];
+        let mut fields: Vec<&String> = example.as_object().unwrap().keys().collect();
+        let mut documented: Vec<&String> = schema.as_object().unwrap().keys().collect();
+        fields.sort();
+        documented.sort();
+        assert_eq!(fields, documented, "{} schema out of date", name);
+    }
+    assert_eq!(
+        schemas["CircuitStatus"]["properties"]["state"]["enum"][2],
+        serde_json::to_value(CircuitState::HalfOpen).unwrap()
+    );
+    assert_eq!(
+        schemas["PendingTask"]["properties"]["state"]["enum"][2],
+        serde_json::to_value(VerificationState::Reviewing).unwrap()
+    );
+}
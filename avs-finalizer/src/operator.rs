@@ -1,127 +1,2415 @@
-use crate::chainio::{avs::AvsContracts, build_eth_client, eigen::ElContracts, Client};
-use crate::cli::CliArgs;
-use crate::crypto::bn254::{BlsKeypair, OperatorId};
+use crate::addresses::AddressBook;
+use crate::api::ApiState;
+use crate::archive::{Archive, TaskArchive, TaskReceipt};
+use crate::build_info::{self, BuildAttestation, BuildInfo};
+use crate::chainio::{
+    avs::{share_pct, AvsContracts, QuorumAdvice, QuorumStatus},
+    build_eth_client,
+    eigen::{ElContracts, OperatorSettings, StakerDeposits, StrategyChange},
+    upgrades::{self, Deployment, SelectorDiff, UpgradeNotice},
+    Client,
+};
+use crate::cli::{BalanceArgs, CliArgs, SetOperatorDetailsArgs, StakeTopUp};
+use crate::constants::ChainConstants;
+use crate::crypto::bn254::OperatorId;
+use crate::crypto::threshold::{OperatorBlsKey, SignRequest, ThresholdSigner};
 use crate::crypto::EthConvert;
-use crate::executor::execute::execute_block;
-use crate::rpc::Rpc;
+use crate::economics::{self, ResponseCost, TaskEconomics};
+use crate::evidence::TaskEvidence;
+use crate::executor::{consensus::agreed_block_hash, heads::finalized_heads};
+use crate::exit;
+use crate::features::{self, Feature};
+use crate::gossip::{self, Gossip, NonSigners};
+use crate::halt::{ChainHalts, FollowedChain};
+use crate::hooks::{HookPayload, HookPoint, Hooks};
+use crate::identity::NodeIdentity;
+use crate::lag::FinalityLag;
+use crate::metrics::metrics;
+use crate::ownership::{self, OwnershipProof};
+use crate::plugin::{Plugins, TaskReview};
+use crate::pressure::{Pressure, PressureLevel};
+use crate::queue::BoundedQueue;
+use crate::quota::{quota, SubstrateQuota};
+use crate::relayer::Relayer;
+use crate::reputation::{self, Reputation, ReputationAttestation, RollingReputation};
+use crate::roles::{Capabilities, Role};
+use crate::rpc::{encode_bls_task_response, task_response_digest, verify_task_response, Rpc};
+use crate::script::DigestSignatures;
+use crate::slashing::{SlashingAction, SlashingEvent, SlashingHistory};
+use crate::store::{
+    BroadcastRecord, QuarantineRecord, RelayRecord, StakeShareRecord, Store, TaskMemo, TaskOutcome,
+    TaskRecord,
+};
+use crate::sync::{estimate_catch_up, RegistryCheckpoint, SignedCheckpoint, SyncPlan, SyncSource};
+use crate::task::{
+    cancellable, confirmation_target, observe_stage, progress_bar, Cancelled, PendingTask,
+    TaskTimer, TaskType, VerificationState,
+};
+use crate::units;
+use crate::verifier::{Proofs, Verifier, Verifiers};
+use crate::wal::{self, ConfigSnapshot, Decision, DecisionInputs, Wal, WalRecord};
+use crate::worker::WorkerPool;
+
+use bindings::{
+    mangata_task_manager::NewTaskCreatedFilter,
+    shared_types::{G1Point, G2Point, OperatorDetails, Task, TaskResponse},
+};
+use ethers::abi::Abi;
+use ethers::prelude::*;
+use node_primitives::BlockNumber;
+use prometheus::{Encoder, Gauge, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sp_runtime::traits::BlakeTwo256;
+use sp_runtime::{generic, OpaqueExtrinsic};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, instrument, warn};
+
+pub type Header = generic::HeaderVer<node_primitives::BlockNumber, BlakeTwo256>;
+pub type Block = generic::Block<Header, OpaqueExtrinsic>;
+
+/// Number of recent task results kept in memory for the divergence monitor.
+const RECENT_RESULTS: usize = 1024;
+/// Recent tasks whose verification time estimates the catch-up duration.
+const CATCH_UP_ESTIMATE_SAMPLES: usize = 100;
+/// Number of blocks executed ahead of their task kept in memory.
+const PREPARED_BLOCKS: usize = 64;
+/// Interval of the chain head checks abandoning tasks whose response window expired, about
+/// one Ethereum block
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(12);
+/// Interval of the chain head checks detecting halted chains.
+const HALT_CHECK_INTERVAL: Duration = Duration::from_secs(12);
+/// Interval of the chain head checks while waiting for the confirmations of a task.
+const CONFIRMATION_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// Interval between aggregations of the gossiped partial signatures, about a block.
+const GOSSIP_AGGREGATE_INTERVAL: Duration = Duration::from_secs(12);
+/// Interval of the checks alerting on the blocks and tasks past the maximum finality lag.
+const FINALITY_LAG_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Interval between deletions of the archived objects past their retention.
+const ARCHIVE_RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Commit the node was built from, embedded at build time from `AVS_GIT_COMMIT`.
+const GIT_COMMIT: Option<&str> = option_env!("AVS_GIT_COMMIT");
+
+#[derive(Debug, Serialize)]
+pub struct OperatorStatus {
+    /// Persistent identity of the node, see `--identity-file`
+    pub node_id: String,
+    pub eth_address: Address,
+    pub registered_with_eigen: bool,
+    pub bls_key_registered: bool,
+    pub bls_g1: G1Point,
+    pub bls_g2: G2Point,
+    pub registered_with_avs: bool,
+    pub operator_id: Option<OperatorId>,
+    pub stake: StakeStatus,
+    /// Only known when the node keeps a local store
+    pub reputation: Option<Reputation>,
+    /// Optional features of the build and whether they are enabled
+    pub features: Vec<Feature>,
+    // opted_in_salshing_by_avs: bool,
+    // frozen: bool,
+}
+
+impl OperatorStatus {
+    /// Encodes the status in the Prometheus text format, for the node exporter textfile
+    /// collector. Reputation gauges keep the names served on `/metrics`.
+    pub fn to_prometheus(&self) -> eyre::Result<String> {
+        let registry = Registry::new();
+        let info = IntGaugeVec::new(
+            Opts::new(
+                "operator_info",
+                "Addresses identifying the operator and its node",
+            ),
+            &["node_id", "eth_address", "operator_id"],
+        )?;
+        registry.register(Box::new(info.clone()))?;
+        let operator_id = self.operator_id.map(|id| format!("{:x}", id));
+        info.with_label_values(&[
+            &self.node_id,
+            &format!("{:?}", self.eth_address),
+            operator_id.as_deref().unwrap_or_default(),
+        ])
+        .set(1);
+
+        for (name, help, value) in [
+            (
+                "operator_registered_with_eigen",
+                "Whether the operator is registered with the EigenLayer DelegationManager",
+                self.registered_with_eigen,
+            ),
+            (
+                "operator_bls_key_registered",
+                "Whether the operator BLS public key is registered",
+                self.bls_key_registered,
+            ),
+            (
+                "operator_registered_with_avs",
+                "Whether the operator is registered with the AVS",
+                self.registered_with_avs,
+            ),
+        ] {
+            let gauge = IntGauge::new(name, help)?;
+            gauge.set(value.into());
+            registry.register(Box::new(gauge))?;
+        }
+
+        let features = IntGaugeVec::new(
+            Opts::new(
+                "operator_feature_enabled",
+                "Whether an optional feature is enabled, by whether it is compiled in",
+            ),
+            &["feature", "compiled"],
+        )?;
+        registry.register(Box::new(features.clone()))?;
+        for feature in &self.features {
+            features
+                .with_label_values(&[feature.name, &feature.compiled.to_string()])
+                .set(feature.enabled.into());
+        }
+
+        if let Some(reputation) = &self.reputation {
+            for (name, help, value) in [
+                (
+                    "reputation_score",
+                    "Reputation score of the operator over the reputation window",
+                    reputation.score,
+                ),
+                (
+                    "task_response_rate",
+                    "Share of the tasks of the reputation window the operator responded to",
+                    reputation.response_rate,
+                ),
+            ] {
+                let gauge = Gauge::new(name, help)?;
+                gauge.set(value);
+                registry.register(Box::new(gauge))?;
+            }
+        }
+
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+/// Stake of the operator in the stake quorum, debug printed in whole units.
+#[derive(Clone, Serialize)]
+pub struct StakeStatus {
+    /// Strategy of the stake quorum
+    pub strategy: Address,
+    /// Shares of the strategy delegated to the operator, counted with the decimals of its
+    /// underlying token
+    #[serde(serialize_with = "units::serialize_decimal")]
+    pub shares: U256,
+    pub underlying: units::TokenAmount,
+    /// Weight of the operator in the quorum, see [`units::STAKE_DECIMALS`]
+    pub weight: u128,
+}
+
+impl std::fmt::Debug for StakeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StakeStatus")
+            .field("strategy", &self.strategy)
+            .field(
+                "shares",
+                &format_args!(
+                    "{}",
+                    units::format_units(self.shares, self.underlying.decimals)
+                ),
+            )
+            .field(
+                "underlying",
+                &format_args!("{} of token {:?}", self.underlying, self.underlying.token),
+            )
+            .field("weight", &format_args!("{}", format_stake(self.weight)))
+            .finish()
+    }
+}
+
+/// Stake registry weight in whole units.
+fn format_stake(weight: u128) -> String {
+    units::format_units(weight.into(), units::STAKE_DECIMALS)
+}
+
+/// Values followed by `print-status --watch`, compared between refreshes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusSample {
+    pub eth_head: u64,
+    /// Tasks created by the task manager so far
+    pub task_count: u32,
+    pub operator_stake: u128,
+    pub registered_with_avs: bool,
+    /// Blocks between the chain head and the creation of the latest task handled, only known
+    /// when the node keeps a local store
+    pub lag_blocks: Option<u64>,
+}
+
+impl StatusSample {
+    /// One line summary, with the changes since `previous` next to the values.
+    pub fn describe(&self, previous: Option<&StatusSample>) -> String {
+        let delta = |now: i128, before: Option<i128>| match before {
+            Some(before) if before != now => format!(" ({:+})", now - before),
+            _ => String::new(),
+        };
+        let stake_delta = match previous.map(|p| p.operator_stake) {
+            Some(before) if before < self.operator_stake => {
+                format!(" (+{})", format_stake(self.operator_stake - before))
+            }
+            Some(before) if before > self.operator_stake => {
+                format!(" (-{})", format_stake(before - self.operator_stake))
+            }
+            _ => String::new(),
+        };
+        let mut line = format!(
+            "head {} | tasks {}{} | stake {}{}",
+            self.eth_head,
+            self.task_count,
+            delta(
+                self.task_count.into(),
+                previous.map(|p| p.task_count.into())
+            ),
+            format_stake(self.operator_stake),
+            stake_delta,
+        );
+        if let Some(lag) = self.lag_blocks {
+            line.push_str(&format!(
+                " | lag {} blocks{}",
+                lag,
+                delta(
+                    lag.into(),
+                    previous.and_then(|p| p.lag_blocks).map(i128::from)
+                )
+            ));
+        }
+        match previous {
+            Some(p) if p.registered_with_avs != self.registered_with_avs => {
+                line.push_str(if self.registered_with_avs {
+                    " | now registered with the AVS"
+                } else {
+                    " | no longer registered with the AVS"
+                })
+            }
+            _ if !self.registered_with_avs => line.push_str(" | not registered with the AVS"),
+            _ => {}
+        }
+        line
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelfTestReport {
+    pub block_number: BlockNumber,
+    pub block_hash: H256,
+    pub storage_proof_hash: H256,
+    pub pubkey_registered: bool,
+    pub signature_valid: bool,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug)]
+pub struct Operator {
+    pub client: Arc<Client>,
+    avs_contracts: Arc<AvsContracts>,
+    el_contracts: ElContracts,
+    bls_key: OperatorBlsKey,
+    substrate_client_uri: String,
+    substrate_consensus: Option<(Vec<String>, usize)>,
+    chain_id: u64,
+    rpc: Rpc,
+    relayer: Option<Relayer>,
+    gossip: Option<Arc<Gossip>>,
+    features: Vec<Feature>,
+    stake_top_up: StakeTopUp,
+    verifiers: Verifiers,
+    workers: Option<WorkerPool>,
+    /// `None` when halt detection is disabled
+    chain_halts: Option<ChainHalts>,
+    finality_lag: FinalityLag,
+    store: Option<Store>,
+    wal: Option<Wal>,
+    evidence_dir: Option<PathBuf>,
+    plugins: Plugins,
+    hooks: Hooks,
+    archive: Option<Archive>,
+    identity: NodeIdentity,
+    api_state: Arc<ApiState>,
+    latency_budget: Option<Duration>,
+    min_confirmation_delay: u32,
+    quarantine_after: u32,
+    catch_up_concurrency: usize,
+    stake_share_interval: Duration,
+    stake_share_alert_pct: Option<f64>,
+    strategy_sync_interval: Duration,
+    withdrawal_check_interval: Duration,
+    upgrade_check_interval: Duration,
+    reputation_window: Duration,
+    /// Reputation over the window, read from the store on first use
+    rolling_reputation: Mutex<Option<RollingReputation>>,
+    shadow_of: Option<Address>,
+    balance: BalanceArgs,
+    low_balance: AtomicBool,
+    recent_results: Mutex<BTreeMap<u32, (H256, H256)>>,
+    /// When the responses of recent tasks were sent, until their `TaskResponded` event
+    broadcast_at: Mutex<BTreeMap<u32, Instant>>,
+    pressure: Pressure,
+    prepare_block_period: Option<u32>,
+    /// Blocks executed ahead of their task, with the version of the verifier used
+    prepared: Mutex<BTreeMap<BlockNumber, (u32, Proofs)>>,
+}
+impl Operator {
+    #[instrument(name = "create_operator", skip_all)]
+    pub async fn from_cli(cfg: &CliArgs, api_state: Arc<ApiState>) -> eyre::Result<Self> {
+        let client = Arc::new(build_eth_client(cfg).await?);
+        let constants = ChainConstants::load(cfg.chain_constants.as_deref())?;
+        if constants != ChainConstants::default() {
+            warn!("Chain constants overridden: {:?}", constants);
+        }
+        let addresses = AddressBook::resolve(cfg)?;
+        addresses.validate(client.as_ref()).await?;
+        info!(
+            "Contract addresses of {}: {:?}",
+            addresses.network, addresses
+        );
+        let avs_contracts = Arc::new(AvsContracts::build(cfg, &constants, client.clone()).await?);
+        let slasher = avs_contracts.slasher_address().await?;
+        let el_contracts = ElContracts::build(cfg, &constants, slasher, client.clone()).await?;
+        if let Some(path) = &cfg.sync_checkpoint {
+            let mut trusted = cfg.sync_checkpoint_signers.clone();
+            trusted.push(client.address());
+            let (checkpoint, signer) =
+                SignedCheckpoint::read(path)?.verify(cfg.chain_id, &trusted)?;
+            let head = client.get_block_number().await?.as_u64();
+            if checkpoint.block > head {
+                return Err(eyre::eyre!(
+                    "sync checkpoint at block {} is ahead of the chain head {}",
+                    checkpoint.block,
+                    head
+                ));
+            }
+            el_contracts.seed_strategies(checkpoint.block, &checkpoint.strategies);
+            avs_contracts.seed_stakes(checkpoint.block, &checkpoint.stakes);
+            info!(
+                "Registry caches seeded from the checkpoint at block {} signed by {:?}",
+                checkpoint.block, signer
+            );
+        }
+
+        let bls_key = match &cfg.bls_key.bls_threshold_key {
+            Some(path) => {
+                info!("Loading threshold BLS key...");
+                OperatorBlsKey::Threshold(Box::new(ThresholdSigner::from_path(
+                    path,
+                    &cfg.threshold,
+                )?))
+            }
+            None => {
+                info!("Decrypting BLS keypair...");
+                OperatorBlsKey::Local(cfg.get_bls_keystore().await?.into_bls_keypair()?)
+            }
+        };
+        info!(
+            "Bls key loaded with operator id: {:x}",
+            bls_key.operator_id()
+        );
+
+        let identity =
+            NodeIdentity::load(&cfg.identity_file, client.address(), bls_key.operator_id())?;
+        info!("Node identity {}", identity.id);
+        metrics()
+            .node_info
+            .with_label_values(&[&identity.id])
+            .set(1);
+        api_state.set_identity(identity.clone());
+
+        let rpc = Rpc::build(cfg);
+        let store = match cfg.store_backend().await? {
+            Some(db) => Some(Store::open(db, cfg.store_key().await?.as_ref())?),
+            None => None,
+        };
+        if let Some(store) = &store {
+            api_state.set_store(store.clone());
+        }
+        let wal = cfg.wal_path.as_deref().map(Wal::open).transpose()?;
+        let archive = Archive::from_cli(&cfg.archive, &identity.id, store.clone())?;
+        let gossip =
+            Gossip::from_cli(&cfg.gossip, cfg.chain_id, avs_contracts.clone()).map(Arc::new);
+        if let Some(gossip) = &gossip {
+            api_state.set_gossip(gossip.clone());
+        }
+        SubstrateQuota::configure(cfg.substrate_quota.clone())?;
+
+        Ok(Self {
+            avs_contracts,
+            el_contracts,
+            substrate_client_uri: cfg.substrate_rpc_url.to_owned(),
+            substrate_consensus: cfg.substrate_consensus(),
+            client,
+            bls_key,
+            chain_id: cfg.chain_id,
+            rpc,
+            relayer: Relayer::from_cli(&cfg.relayer),
+            gossip,
+            features: features::report(cfg),
+            stake_top_up: cfg.stake_top_up.clone(),
+            verifiers: Verifiers::builtin(&cfg.verifier_switchovers)?,
+            workers: WorkerPool::new(cfg.verify_workers.into()),
+            chain_halts: (cfg.chain_halt_secs > 0)
+                .then(|| ChainHalts::new(Duration::from_secs(cfg.chain_halt_secs))),
+            finality_lag: FinalityLag::new(Duration::from_secs(cfg.max_finality_lag_secs)),
+            store,
+            wal,
+            evidence_dir: cfg.evidence_dir.clone(),
+            plugins: Plugins::load(&cfg.plugins, Duration::from_millis(cfg.plugin_timeout_ms)),
+            hooks: Hooks::from_cli(&cfg.hooks),
+            archive,
+            identity,
+            api_state,
+            latency_budget: cfg.latency_budget_ms.map(Duration::from_millis),
+            min_confirmation_delay: cfg.min_confirmation_delay_blocks,
+            quarantine_after: cfg.quarantine_after,
+            catch_up_concurrency: cfg.catch_up_concurrency.into(),
+            stake_share_interval: Duration::from_secs(cfg.stake_share_interval_secs),
+            stake_share_alert_pct: cfg.stake_share_alert_pct,
+            strategy_sync_interval: Duration::from_secs(cfg.strategy_sync_interval_secs),
+            withdrawal_check_interval: Duration::from_secs(cfg.withdrawal_check_interval_secs),
+            upgrade_check_interval: Duration::from_secs(cfg.upgrade_check_interval_secs),
+            reputation_window: Duration::from_secs(cfg.reputation_window_secs),
+            rolling_reputation: Mutex::default(),
+            shadow_of: cfg.shadow_of,
+            balance: cfg.balance.clone(),
+            low_balance: AtomicBool::new(false),
+            recent_results: Mutex::default(),
+            broadcast_at: Mutex::default(),
+            pressure: Pressure::new(cfg.pressure.clone()),
+            prepare_block_period: cfg.prepare_block_period,
+            prepared: Mutex::default(),
+        })
+    }
+
+    #[instrument(skip_all)]
+    pub async fn watch_new_tasks(&self) -> eyre::Result<()> {
+        let mut stream = self.avs_contracts.new_task_stream().await?;
+        let window = self.avs_contracts.task_response_window().await?;
+
+        // events are queued while catching up and processing, the queue length is the backlog
+        let queue = BoundedQueue::new(
+            "new_tasks",
+            self.pressure.limits().task_queue_capacity as usize,
+            self.pressure.limits().task_queue_drop_policy,
+        );
+        let receive = async {
+            while let Some((log, _)) = stream.next().await {
+                let Some(event) = self.avs_contracts.decode_new_task(&log) else {
+                    continue;
+                };
+                self.track_pending(&event, window);
+                self.finality_lag
+                    .task_created(event.task_index, event.task.block_number.as_u32());
+                if let Some(gossip) = &self.gossip {
+                    gossip.task_created(event.task_index);
+                }
+                match queue.push((event, Instant::now())).await {
+                    Some((dropped, _)) => {
+                        warn!("Task queue full, dropped task {}", dropped.task_index);
+                        self.api_state.untrack_task(dropped.task_index);
+                        self.record_missed(dropped.task_index).await;
+                    }
+                    None => self.pressure.task_queued(),
+                }
+            }
+            // exits rather than stalling, the restarted node catches up from its checkpoint
+            Err::<(), _>(eyre::eyre!("task stream ended"))
+        };
+
+        let process = async {
+            // subscribed before catching up so tasks created meanwhile are not missed
+            let caught_up = self.catch_up().await?;
+            info!("Switching to live mode");
+
+            loop {
+                let (event, received) = queue.pop().await;
+                self.pressure.task_dequeued();
+                if caught_up.is_some_and(|last| event.task_index <= last) {
+                    debug!("Task {} already handled during catch-up", event.task_index);
+                    self.api_state.untrack_task(event.task_index);
+                    continue;
+                }
+                if self.api_state.is_paused() {
+                    warn!("Operator paused, skipping task {}", event.task_index);
+                    self.api_state.untrack_task(event.task_index);
+                    self.record_missed(event.task_index).await;
+                    continue;
+                }
+                self.process_task(&event, received).await?;
+                self.advance_checkpoint(Some(event.task.task_created_block))
+                    .await?;
+            }
+        };
+
+        tokio::select! {
+            res = process => res,
+            res = receive => res,
+        }
+    }
+
+    /// Executes finalized substrate blocks expected to become tasks ahead of the task event,
+    /// pending forever if disabled or once the subscription fails, tasks are then executed
+    /// when they arrive.
+    #[instrument(skip_all)]
+    pub async fn watch_substrate_blocks(&self) -> eyre::Result<()> {
+        if let Some(period) = self.prepare_block_period {
+            if let Err(e) = self.prepare_blocks(period).await {
+                error!("Preparing blocks ahead of tasks failed: {:?}", e);
+            }
+            warn!("Stopped preparing blocks ahead of tasks");
+        }
+        std::future::pending().await
+    }
+
+    /// Prepares the blocks the aggregator creates tasks for, assuming it picks the multiples of
+    /// `period`. No substrate event announces a task, a wrong period only wastes executions.
+    async fn prepare_blocks(&self, period: u32) -> eyre::Result<()> {
+        let heads = finalized_heads::<Block>(&self.substrate_client_uri).await?;
+        futures::pin_mut!(heads);
+        // heads may skip numbers when several blocks are finalized at once
+        let mut next = None;
+        while let Some(head) = heads.next().await {
+            let head = head?;
+            let from = next.unwrap_or(head).max(head.saturating_sub(period));
+            for block_number in (from..=head).filter(|n| n % period == 0) {
+                if self.pressure.level() >= PressureLevel::Elevated {
+                    debug!(
+                        "Not preparing block {} under resource pressure",
+                        block_number
+                    );
+                    continue;
+                }
+                if quota().exhausted() {
+                    debug!(
+                        "Not preparing block {} past the substrate quota",
+                        block_number
+                    );
+                    continue;
+                }
+                // the task will be created about now, so verified as of the current block
+                let verifier = match self.current_verifier(TaskType::ExecuteBlock).await {
+                    Ok(verifier) => verifier,
+                    Err(e) => {
+                        warn!("Not preparing block {}: {:?}", block_number, e);
+                        continue;
+                    }
+                };
+                match self
+                    .verify_block(verifier, block_number, &CancellationToken::new())
+                    .await
+                {
+                    Ok(proofs) => {
+                        debug!("Prepared block {} ahead of its task", block_number);
+                        let mut prepared = self.prepared.lock().expect("poisoned lock");
+                        prepared.insert(block_number, (verifier.version(), proofs));
+                        while prepared.len() > PREPARED_BLOCKS {
+                            if let Some((unused, _)) = prepared.pop_first() {
+                                warn!(
+                                    "Block {} was prepared but no task was created for it, check that --prepare-block-period matches the period of the aggregator",
+                                    unused
+                                );
+                            }
+                        }
+                    }
+                    Err(e) => warn!("Preparing block {} failed: {:?}", block_number, e),
+                }
+            }
+            next = Some(head + 1);
+        }
+        Ok(())
+    }
+
+    /// Result of the block prepared ahead if it was verified with `version`.
+    fn take_prepared(&self, block_number: BlockNumber, version: u32) -> Option<(H256, H256)> {
+        match self
+            .prepared
+            .lock()
+            .expect("poisoned lock")
+            .remove(&block_number)
+        {
+            Some((prepared_with, proofs)) if prepared_with == version => Some(proofs),
+            Some((prepared_with, _)) => {
+                debug!(
+                    "Block {} was prepared with verifier v{}, the task needs v{}",
+                    block_number, prepared_with, version
+                );
+                None
+            }
+            None => None,
+        }
+    }
+
+    #[instrument(skip_all)]
+    pub async fn watch_pressure(&self) -> eyre::Result<()> {
+        self.pressure
+            .watch(|signal| self.api_state.set_autoscale(signal))
+            .await
+    }
+
+    /// Processes the tasks created while the operator was offline that are still open,
+    /// returning the index of the latest task seen.
+    #[instrument(skip_all)]
+    async fn catch_up(&self) -> eyre::Result<Option<u32>> {
+        let window = self.avs_contracts.task_response_window().await?;
+        let current = self.client.get_block_number().await?.as_u32();
+        let checkpoint = match &self.store {
+            Some(store) => store.run(|s| s.checkpoint()).await?,
+            None => None,
+        };
+        let plan = SyncPlan::new(checkpoint, current, window);
+        match (plan.source, plan.lag_blocks()) {
+            (SyncSource::Checkpoint, Some(lag)) => info!(
+                "Resuming from the checkpoint at block {}, {} blocks behind the head",
+                plan.from_block, lag
+            ),
+            (_, Some(lag)) => info!(
+                "Checkpoint {} blocks behind the head, older tasks expired, backfilling the last {} blocks only",
+                lag, window
+            ),
+            (_, None) => info!("No checkpoint, backfilling the last {} blocks", window),
+        }
+        let events = self
+            .avs_contracts
+            .tasks_created_since(plan.from_block.into())
+            .await?;
+        let received = Instant::now();
+        let last = events.iter().map(|e| e.task_index).max();
+        let last_block = events.iter().map(|e| e.task.task_created_block).max();
+        if let (Some(gossip), Some(last)) = (&self.gossip, last) {
+            gossip.task_created(last);
+        }
+
+        let mut open = vec![];
+        let mut expired = 0;
+        for event in events {
+            if event.task.task_created_block.saturating_add(window) < current {
+                expired += 1;
+                self.record_missed(event.task_index).await;
+                continue;
+            }
+            let task_index = event.task_index;
+            let recorded = match &self.store {
+                Some(store) => store.run(move |s| s.has_task(task_index)).await?,
+                None => false,
+            };
+            if recorded
+                || self
+                    .avs_contracts
+                    .is_task_responded(event.task_index)
+                    .await?
+            {
+                continue;
+            }
+            open.push(event);
+        }
+        if open.is_empty() {
+            info!(
+                "No missed tasks to catch up on, skipped {} expired",
+                expired
+            );
+            self.advance_checkpoint(last_block).await?;
+            return Ok(last);
+        }
+        if self.api_state.is_paused() {
+            warn!("Operator paused, skipping {} missed tasks", open.len());
+            for event in &open {
+                self.record_missed(event.task_index).await;
+            }
+            return Ok(last);
+        }
+        for event in &open {
+            self.track_pending(event, window);
+        }
+
+        let total = open.len();
+        let recent_ms: Vec<u64> = match &self.store {
+            Some(store) => store
+                .recent_tasks(CATCH_UP_ESTIMATE_SAMPLES)?
+                .into_iter()
+                .filter_map(|task| task.memo.map(|m| m.verification_ms))
+                .collect(),
+            None => vec![],
+        };
+        info!(
+            "Catching up on {} missed tasks, skipped {} expired, estimated time {}",
+            total,
+            expired,
+            match estimate_catch_up(total, &recent_ms, self.catch_up_concurrency) {
+                Some(eta) => format!("{:?}", eta),
+                None => "unknown".into(),
+            }
+        );
+        let mut results = futures::stream::iter(&open)
+            .map(|event| async move {
+                // backfill pauses under critical memory or CPU pressure, the live tasks queued
+                // meanwhile only drain once it completes so they must not hold it back
+                self.pressure
+                    .wait_resources_below(PressureLevel::Critical)
+                    .await;
+                (event.task_index, self.process_task(event, received).await)
+            })
+            .buffer_unordered(self.catch_up_concurrency);
+        let mut done = 0;
+        while let Some((task_index, res)) = results.next().await {
+            done += 1;
+            if let Err(e) = res {
+                error!("Catch-up of task {} failed: {:?}", task_index, e);
+            }
+            info!("Catch-up {} {}/{}", progress_bar(done, total), done, total);
+        }
+        self.advance_checkpoint(last_block).await?;
+        Ok(last)
+    }
+
+    /// Records that the tasks created up to `block` were handled, live tasks are handled in
+    /// order and catch-up only advances the checkpoint once complete.
+    async fn advance_checkpoint(&self, block: Option<u32>) -> eyre::Result<()> {
+        match (&self.store, block) {
+            (Some(store), Some(block)) => store.run(move |s| s.advance_checkpoint(block)).await,
+            _ => Ok(()),
+        }
+    }
+
+    #[instrument(skip_all, fields(task_index = event.task_index))]
+    async fn process_task(
+        &self,
+        event: &NewTaskCreatedFilter,
+        received: Instant,
+    ) -> eyre::Result<bool> {
+        let task_index = event.task_index;
+        if let Some(store) = &self.store {
+            if store.run(move |s| s.is_quarantined(task_index)).await? {
+                warn!("Task {} is quarantined, not verified", event.task_index);
+                self.api_state.untrack_task(event.task_index);
+                self.record_missed(task_index).await;
+                return Ok(false);
+            }
+        }
+        self.hooks
+            .fire(HookPayload::new(HookPoint::TaskReceived, event));
+        let received_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let mut timer = TaskTimer::start(event.task_index, received, self.latency_budget);
+        let window = self.avs_contracts.task_response_window().await?;
+        let expires_at = event.task.task_created_block.saturating_add(window);
+        let cancel = CancellationToken::new();
+        let res = tokio::select! {
+            res = self.respond_task(event, &mut timer, &cancel) => res,
+            never = self.cancel_on_expiry(expires_at, &cancel) => match never {},
+        };
+        if let Err(e) = &res {
+            self.hooks.fire(HookPayload {
+                error: Some(format!("{:#}", e)),
+                ..HookPayload::new(HookPoint::Error, event)
+            });
+        }
+        let res = match res {
+            Err(e) if e.is::<Cancelled>() && self.idle() => {
+                info!(
+                    "Abandoned task {} during a chain halt, its response window expired at block {}",
+                    event.task_index, expires_at
+                );
+                Ok(false)
+            }
+            Err(e) if e.is::<Cancelled>() => {
+                warn!(
+                    "Abandoned task {} after {:?}, its response window expired at block {}",
+                    event.task_index,
+                    timer.elapsed(),
+                    expires_at
+                );
+                metrics().tasks_abandoned.inc();
+                Ok(false)
+            }
+            // failures while a chain is down say nothing about the task
+            Err(e) if self.idle() => Err(e),
+            Err(e) => self.record_failure(task_index, e).await,
+            Ok(responded) => {
+                if let Some(store) = &self.store {
+                    store.run(move |s| s.clear_failures(task_index)).await?;
+                }
+                Ok(responded)
+            }
+        };
+        timer.log_stages();
+        self.pressure.task_verified();
+        self.api_state.untrack_task(event.task_index);
+        let responded = matches!(res, Ok(true));
+        self.record_outcome(TaskOutcome {
+            task_index: event.task_index,
+            received_at,
+            responded,
+            latency_ms: responded.then(|| timer.elapsed().as_millis() as u64),
+            diverged: false,
+        })
+        .await;
+        res
+    }
+
+    /// Triggers `cancel` once the chain head passed block `expires_at`, a response would then
+    /// be rejected. Pends forever afterwards.
+    async fn cancel_on_expiry(&self, expires_at: u32, cancel: &CancellationToken) -> Infallible {
+        let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match self.client.get_block_number().await {
+                Ok(head) if head.as_u32() > expires_at => break,
+                Ok(_) => {}
+                Err(e) => debug!("Cannot read the chain head to check task expiry: {}", e),
+            }
+        }
+        cancel.cancel();
+        std::future::pending().await
+    }
+
+    /// Verifies the task block and sends the signed response, returns whether it was accepted
+    /// by the aggregator. Fails with [`Cancelled`] once `cancel` is triggered before the
+    /// response is sent.
+    async fn respond_task(
+        &self,
+        event: &NewTaskCreatedFilter,
+        timer: &mut TaskTimer,
+        cancel: &CancellationToken,
+    ) -> eyre::Result<bool> {
+        let task_index = event.task_index;
+        let previous = match &self.store {
+            Some(store) => store.run(move |s| s.get_broadcast(task_index)).await?,
+            None => None,
+        };
+        if previous.is_some() {
+            if let Some(landed) = self.broadcast_landed(event.task_index).await {
+                info!(
+                    "Response to task {} was sent before the restart and {}, not sending it again",
+                    event.task_index, landed
+                );
+                metrics()
+                    .restored_broadcasts
+                    .with_label_values(&["landed"])
+                    .inc();
+                return Ok(true);
+            }
+        }
+        let block_number = event.task.block_number.as_u32();
+        self.api_state
+            .set_task_state(event.task_index, VerificationState::Executing);
+        let verifier = self
+            .verifiers
+            .select(TaskType::from(event), event.task.task_created_block)?;
+        let prepared = self.take_prepared(block_number, verifier.version());
+        let proofs = match prepared {
+            Some(proofs) => {
+                info!("Using block prepared ahead for task: {:?}", event);
+                proofs
+            }
+            None => {
+                info!(
+                    "Executing a Block with verifier v{} for task: {:?}",
+                    verifier.version(),
+                    event
+                );
+                self.verify_block(verifier, block_number, cancel).await?
+            }
+        };
+        timer.stage("execute");
+        debug!("Block executed successfully");
+        self.api_state
+            .set_task_state(event.task_index, VerificationState::Reviewing);
+
+        let config = self.config_snapshot();
+        let elapsed_ms = timer.elapsed().as_millis() as u64;
+        if wal::degraded(&config, elapsed_ms) {
+            warn!(
+                "Degraded mode: task received {:?} ago, over the {:?} latency budget",
+                timer.elapsed(),
+                self.latency_budget.unwrap_or_default()
+            );
+            metrics().task_budget_exceeded.inc();
+        }
+
+        let agreed_hash = cancellable(cancel, self.quorum_block_hash(block_number))
+            .await?
+            .map_err(|e| format!("{:?}", e))
+            .transpose();
+        timer.stage("cross_check");
+
+        let inputs = DecisionInputs {
+            event: event.clone(),
+            config,
+            executed: proofs,
+            prepared: prepared.is_some(),
+            elapsed_ms,
+            agreed_hash,
+        };
+        let decision = WalRecord {
+            recorded_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            decision: wal::decide(&inputs),
+            inputs,
+        };
+        if let Some(wal) = &self.wal {
+            wal.append(&decision)?;
+        }
+        let (payload, shadow) = match decision.decision.clone() {
+            Decision::Skip { reason } => {
+                error!("Skipping task {}: {}", event.task_index, reason);
+                return Ok(false);
+            }
+            Decision::Respond { response, .. } => (response, false),
+            Decision::Shadow { response, .. } => (response, true),
+        };
+        self.remember_result(event.task_index, proofs);
+
+        let review = TaskReview {
+            task_index: event.task_index,
+            block_number,
+            block_hash: proofs.0,
+            storage_proof_hash: proofs.1,
+            quorum_numbers: event.task.quorum_numbers.to_vec(),
+        };
+        if let Some(reason) = cancellable(cancel, self.plugins.review(&review)).await? {
+            error!("Skipping task {}: {}", event.task_index, reason);
+            return Ok(false);
+        }
+        timer.stage("plugins");
+        if self.min_confirmation_delay > 0 && previous.is_none() {
+            self.api_state
+                .set_task_state(event.task_index, VerificationState::Confirming);
+            cancellable(cancel, self.wait_confirmations(event)).await??;
+            timer.stage("delay");
+        }
+        self.api_state
+            .set_task_state(event.task_index, VerificationState::Signing);
+
+        let digest = task_response_digest(&payload);
+        let resent = previous.is_some();
+        let json = match previous {
+            // a new signature could differ and be sent along the first one
+            Some(previous) => {
+                if previous.digest != digest {
+                    warn!(
+                        "Task {} verified to a different response than the one sent before the restart, sending that one again",
+                        event.task_index
+                    );
+                }
+                metrics()
+                    .restored_broadcasts
+                    .with_label_values(&["resent"])
+                    .inc();
+                previous.response
+            }
+            None => match cancellable(cancel, self.sign_task_response(payload)).await? {
+                Ok(json) => json,
+                Err(e) => {
+                    error!("Skipping task {}: {:?}", event.task_index, e);
+                    return Ok(false);
+                }
+            },
+        };
+        timer.stage("sign");
+        self.api_state.untrack_task(event.task_index);
+        self.hooks.fire(HookPayload {
+            response: serde_json::from_str(&json).ok(),
+            ..HookPayload::new(HookPoint::Signed, event)
+        });
+        let memo = TaskMemo {
+            verifier: format!(
+                "{}+{}",
+                env!("CARGO_PKG_VERSION"),
+                GIT_COMMIT.unwrap_or("unknown")
+            ),
+            verification_ms: timer.elapsed().as_millis() as u64,
+            prepared: prepared.is_some(),
+            data_source: self.data_source(),
+        };
+        if shadow {
+            info!(
+                "Shadow mode, response to task {} signed but not sent",
+                event.task_index
+            );
+            return Ok(false);
+        }
+        if cancel.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        if !resent {
+            self.record_broadcast(event, digest, &json).await?;
+        }
+        let response = serde_json::from_str(&json).unwrap_or_default();
+        if timer.over_budget() {
+            // gossip is optional, the response goes straight to the aggregator
+            warn!(
+                "Degraded mode: not gossiping the response to task {}",
+                event.task_index
+            );
+        } else if let Err(e) = self.gossip_response(&json).await {
+            warn!(
+                "Cannot gossip the response to task {}: {:?}",
+                event.task_index, e
+            );
+        }
+        let sent = self.send_response(event.task_index, json).await;
+        if !matches!(sent, Ok(true)) {
+            if let Some(gossip) = &self.gossip {
+                gossip.aggregate_later(event.task_index, event.task.clone());
+            }
+        }
+        let accepted = sent?;
+        timer.stage("respond");
+        self.hooks.fire(HookPayload {
+            accepted: Some(accepted),
+            ..HookPayload::new(HookPoint::Submitted, event)
+        });
+        self.remember_broadcast(event.task_index);
+
+        if accepted {
+            info!("Task finished successfuly and sent to AVS service");
+            let record = self.record_task(event, proofs, memo).await?;
+            if let Some(archive) = &self.archive {
+                archive.task(&TaskArchive {
+                    task_index: event.task_index,
+                    node_id: self.identity.id.clone(),
+                    decision,
+                    record,
+                    response,
+                });
+            }
+            if let Err(e) = self.record_quorum_snapshot(event).await {
+                warn!(
+                    "Cannot record the quorums of task {}: {:?}",
+                    event.task_index, e
+                );
+            }
+        }
+
+        if timer.over_budget() {
+            warn!(
+                "Task {} took {:?}, over the {:?} latency budget",
+                event.task_index,
+                timer.elapsed(),
+                self.latency_budget.unwrap_or_default()
+            );
+        }
+        Ok(accepted)
+    }
+
+    /// Waits for `--min-confirmation-delay-blocks` blocks on top of the block the task was
+    /// created at, less if that would leave no time to respond.
+    async fn wait_confirmations(&self, event: &NewTaskCreatedFilter) -> eyre::Result<()> {
+        let created = event.task.task_created_block;
+        let window = self.avs_contracts.task_response_window().await?;
+        let target = confirmation_target(
+            created,
+            self.min_confirmation_delay,
+            created.saturating_add(window),
+        );
+        if target < created.saturating_add(self.min_confirmation_delay) {
+            warn!(
+                "Task {} response window of {} blocks is too short for {} confirmations, waiting until block {}",
+                event.task_index, window, self.min_confirmation_delay, target
+            );
+        }
+        let mut interval = tokio::time::interval(CONFIRMATION_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match self.client.get_block_number().await {
+                Ok(head) if head.as_u32() >= target => return Ok(()),
+                Ok(_) => {}
+                Err(e) => debug!("Cannot read the chain head to count confirmations: {}", e),
+            }
+        }
+    }
+
+    /// Writes the signed response ahead of sending it, to send it again rather than a new
+    /// one if the node restarts before the task is responded on chain. Forgets the responses
+    /// whose task response window closed, the tasks responded by others are never removed
+    /// otherwise.
+    async fn record_broadcast(
+        &self,
+        event: &NewTaskCreatedFilter,
+        digest: H256,
+        json: &str,
+    ) -> eyre::Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let window = self.avs_contracts.task_response_window().await?;
+        let record = BroadcastRecord {
+            task_index: event.task_index,
+            broadcast_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            expires_at_block: event.task.task_created_block.saturating_add(window),
+            digest,
+            response: json.to_owned(),
+        };
+        store.run(move |s| s.put_broadcast(&record)).await?;
+        match self.client.get_block_number().await {
+            Ok(head) => {
+                let head = head.as_u32();
+                match store.run(move |s| s.prune_broadcasts(head)).await {
+                    Ok(0) => {}
+                    Ok(pruned) => debug!("Forgot {} responses past their response window", pruned),
+                    Err(e) => warn!("Cannot forget the expired responses: {:?}", e),
+                }
+            }
+            Err(e) => debug!(
+                "Cannot read the chain head to forget expired responses: {}",
+                e
+            ),
+        }
+        Ok(())
+    }
+
+    /// Where the response to `task_index` sent before a restart landed, `None` if it is
+    /// neither responded on chain nor in a pending `respondToTask` transaction. A failed
+    /// lookup counts as not landed, the response is then sent again.
+    async fn broadcast_landed(&self, task_index: u32) -> Option<String> {
+        match self.avs_contracts.is_task_responded(task_index).await {
+            Ok(true) => return Some("was responded on chain".into()),
+            Ok(false) => {}
+            Err(e) => {
+                warn!(
+                    "Cannot check whether task {} was responded: {:?}",
+                    task_index, e
+                );
+                return None;
+            }
+        }
+        match self.avs_contracts.pending_response(task_index).await {
+            Ok(tx) => tx.map(|tx| format!("is pending in {:?}", tx)),
+            Err(e) => {
+                warn!(
+                    "Cannot check the pending responses to task {}: {:?}",
+                    task_index, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Sends the BLS signed response to the gossip peers, if any.
+    async fn gossip_response(&self, json: &str) -> eyre::Result<()> {
+        let Some(gossip) = &self.gossip else {
+            return Ok(());
+        };
+        let public = (self.bls_key.public(), self.bls_key.public_g2());
+        gossip.publish(self.client.signer(), public, json).await
+    }
+
+    /// Hands the signed response to task `task_index` to the relayer, or sends it to the
+    /// aggregator without a relayer or when it fails. Returns whether it was acknowledged by
+    /// the relayer or accepted by the aggregator.
+    async fn send_response(&self, task_index: u32, json: String) -> eyre::Result<bool> {
+        if let Some(relayer) = &self.relayer {
+            let relayed = relayer.relay(task_index, &json).await;
+            let mut record = RelayRecord {
+                task_index,
+                relayed_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+                relay_id: None,
+                fallback_reason: None,
+            };
+            match relayed {
+                Ok(ack) => {
+                    info!("Response to task {} relayed as {}", task_index, ack.id);
+                    record.relay_id = Some(ack.id);
+                }
+                Err(e) => {
+                    warn!(
+                        "Relayer failed on task {}, sending the response to the aggregator: {:?}",
+                        task_index, e
+                    );
+                    record.fallback_reason = Some(format!("{:?}", e));
+                }
+            }
+            let outcome = match record.relay_id {
+                Some(_) => "acknowledged",
+                None => "fallback",
+            };
+            metrics()
+                .relayed_responses
+                .with_label_values(&[outcome])
+                .inc();
+            if let Some(store) = &self.store {
+                let record = record.clone();
+                store.run(move |s| s.put_relay(&record)).await?;
+            }
+            if record.relay_id.is_some() {
+                return Ok(true);
+            }
+        }
+
+        let response = self.rpc.send_task_response(json).await?;
+        match response.error_for_status_ref() {
+            Err(e) => {
+                error!("{} - {}", e, response.text().await?);
+                Ok(false)
+            }
+            Ok(_) => Ok(true),
+        }
+    }
+
+    /// Compares the responses accepted by the task manager with the locally computed results,
+    /// alerting when the operator diverges from the signing majority. In shadow mode, reports
+    /// diffs against the shadowed operator on the tasks it signed. A failing monitor does
+    /// not stop the node, it pends forever after logging the failure.
+    #[instrument(skip_all)]
+    pub async fn watch_divergence(&self) -> eyre::Result<()> {
+        if let Err(e) = self.monitor_divergence().await {
+            error!("Divergence monitor failed: {:?}", e);
+        }
+        warn!("Divergence monitor stopped");
+        std::future::pending().await
+    }
+
+    async fn monitor_divergence(&self) -> eyre::Result<()> {
+        let shadowed_id = match self.shadow_of {
+            Some(address) => Some(
+                self.avs_contracts
+                    .operator_id_of(address)
+                    .await?
+                    .ok_or_else(|| {
+                        eyre::eyre!("shadowed operator {:?} is not registered", address)
+                    })?,
+            ),
+            None => None,
+        };
+        let mut stream = self.avs_contracts.task_responded_stream().await?;
+
+        while let Some((log, meta)) = stream.next().await {
+            let Some(event) = self.avs_contracts.decode_task_responded(&log) else {
+                continue;
+            };
+            let accepted = &event.task_response;
+            self.observe_confirmation(accepted.reference_task_index);
+            if let Err(e) = self
+                .observe_finality_lag(accepted.reference_task_index, &meta)
+                .await
+            {
+                warn!(
+                    "Cannot measure the finality lag of task {}: {:?}",
+                    accepted.reference_task_index, e
+                );
+            }
+            if let Some(store) = &self.store {
+                let task_index = accepted.reference_task_index;
+                if let Err(e) = store.run(move |s| s.remove_broadcast(task_index)).await {
+                    warn!(
+                        "Cannot forget the response sent to task {}: {:?}",
+                        accepted.reference_task_index, e
+                    );
+                }
+            }
+            // the accepted response is the one of the shadowed operator only if it signed it
+            let shadow_signed = match shadowed_id {
+                Some(id) => match self
+                    .avs_contracts
+                    .signed_response(meta.transaction_hash, id)
+                    .await
+                {
+                    Ok(signed) => Some(signed),
+                    Err(e) => {
+                        warn!(
+                            "Cannot tell whether the shadowed operator signed task {}: {:?}",
+                            accepted.reference_task_index, e
+                        );
+                        None
+                    }
+                },
+                None => None,
+            };
+            if shadow_signed == Some(false) {
+                info!(
+                    "Shadowed operator did not sign task {}, not compared",
+                    accepted.reference_task_index
+                );
+                continue;
+            }
+            let Some((block_hash, storage_proof_hash)) =
+                self.local_result(accepted.reference_task_index)?
+            else {
+                debug!(
+                    "No local result for task {}, not compared",
+                    accepted.reference_task_index
+                );
+                continue;
+            };
+            let matches_local = block_hash.to_fixed_bytes() == accepted.block_hash
+                && storage_proof_hash.to_fixed_bytes() == accepted.storage_proof_hash;
+            if let Some(archive) = &self.archive {
+                archive.receipt(&TaskReceipt {
+                    task_index: accepted.reference_task_index,
+                    node_id: self.identity.id.clone(),
+                    response_tx: meta.transaction_hash,
+                    response_block: meta.block_number.as_u64(),
+                    accepted_block_hash: accepted.block_hash.into(),
+                    accepted_storage_proof_hash: accepted.storage_proof_hash.into(),
+                    matches_local,
+                });
+            }
+            if matches_local {
+                debug!(
+                    "Task {} response matches the local result",
+                    accepted.reference_task_index
+                );
+                continue;
+            }
+
+            if shadow_signed.is_some() {
+                metrics().shadow_diffs.inc();
+                error!(
+                    "Shadow diff on task {}: shadowed operator signed block hash {:x} storage proof {:x}, local block hash {:x} storage proof {:x}",
+                    accepted.reference_task_index,
+                    H256::from(accepted.block_hash),
+                    H256::from(accepted.storage_proof_hash),
+                    block_hash,
+                    storage_proof_hash
+                );
+                continue;
+            }
+            metrics().task_divergence.inc();
+            if let Some(store) = &self.store {
+                let task_index = accepted.reference_task_index;
+                if let Err(e) = store.run(move |s| s.mark_diverged(task_index)).await {
+                    warn!(
+                        "Cannot record the divergence on task {}: {:?}",
+                        task_index, e
+                    );
+                }
+                if let Some(rolling) = &mut *self.rolling_reputation.lock().expect("poisoned lock")
+                {
+                    rolling.mark_diverged(task_index);
+                }
+            }
+            let metadata = &event.task_response_metadata;
+            error!(
+                "Diverged from the majority on task {}: accepted block hash {:x} storage proof {:x} signed by {:?} of {:?} stake, local block hash {:x} storage proof {:x}",
+                accepted.reference_task_index,
+                H256::from(accepted.block_hash),
+                H256::from(accepted.storage_proof_hash),
+                metadata.quroum_stake_signed,
+                metadata.quroum_stake_totals,
+                block_hash,
+                storage_proof_hash
+            );
+            let evidence = TaskEvidence {
+                task_index: accepted.reference_task_index,
+                node_id: self.identity.id.clone(),
+                response_tx: meta.transaction_hash,
+                accepted_block_hash: accepted.block_hash.into(),
+                accepted_storage_proof_hash: accepted.storage_proof_hash.into(),
+                local_block_hash: block_hash,
+                local_storage_proof_hash: storage_proof_hash,
+                record: None,
+                decisions: vec![],
+            };
+            if let Err(e) = self.report_evidence(evidence).await {
+                error!(
+                    "Cannot gather the evidence of task {}: {:?}",
+                    accepted.reference_task_index, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Gathers what was recorded when verifying a diverging task and alerts with it.
+    async fn report_evidence(&self, evidence: TaskEvidence) -> eyre::Result<()> {
+        let evidence = evidence
+            .gather(self.store.as_ref(), self.wal.as_ref())
+            .await?;
+        let location = match &self.evidence_dir {
+            Some(dir) => evidence.write(dir)?.display().to_string(),
+            None => "not written, no evidence directory".into(),
+        };
+        error!(
+            "Evidence of task {} in response {:x}: verified by {:?}, {} recorded decisions, {}",
+            evidence.task_index,
+            evidence.response_tx,
+            evidence
+                .record
+                .as_ref()
+                .and_then(|r| r.memo.as_ref())
+                .map(|m| &m.verifier),
+            evidence.decisions.len(),
+            location
+        );
+        Ok(())
+    }
+
+    fn remember_result(&self, task_index: u32, proofs: (H256, H256)) {
+        let mut results = self.recent_results.lock().expect("poisoned lock");
+        results.insert(task_index, proofs);
+        while results.len() > RECENT_RESULTS {
+            results.pop_first();
+        }
+    }
+
+    /// Lists a received task on the operator API until it is signed or given up.
+    fn track_pending(&self, event: &NewTaskCreatedFilter, window: u32) {
+        self.api_state.track_task(PendingTask {
+            task_index: event.task_index,
+            block_number: event.task.block_number.as_u32(),
+            task_created_block: event.task.task_created_block,
+            expires_at_block: event.task.task_created_block.saturating_add(window),
+            received_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            state: VerificationState::Queued,
+        });
+    }
+
+    fn remember_broadcast(&self, task_index: u32) {
+        let mut broadcast_at = self.broadcast_at.lock().expect("poisoned lock");
+        broadcast_at.insert(task_index, Instant::now());
+        while broadcast_at.len() > RECENT_RESULTS {
+            broadcast_at.pop_first();
+        }
+    }
+
+    /// Times the `confirm` stage of a task this node responded to, from the broadcast of its
+    /// response to the `TaskResponded` event.
+    fn observe_confirmation(&self, task_index: u32) {
+        let Some(sent) = self
+            .broadcast_at
+            .lock()
+            .expect("poisoned lock")
+            .remove(&task_index)
+        else {
+            return;
+        };
+        let took = sent.elapsed();
+        observe_stage(task_index, "confirm", took);
+        info!(
+            task_index,
+            confirm_ms = took.as_millis() as u64,
+            "Task {} response confirmed on chain",
+            task_index
+        );
+    }
+
+    /// Measures the finality lag of a task up to the Ethereum block its response landed in.
+    async fn observe_finality_lag(&self, task_index: u32, meta: &LogMeta) -> eyre::Result<()> {
+        let block = self
+            .client
+            .get_block(meta.block_number)
+            .await?
+            .ok_or_else(|| eyre::eyre!("block {} not found", meta.block_number))?;
+        if self
+            .finality_lag
+            .accepted(task_index, block.timestamp.as_u64())
+            .is_some()
+        {
+            self.api_state.set_finality_lag(self.finality_lag.report());
+        }
+        Ok(())
+    }
+
+    fn local_result(&self, task_index: u32) -> eyre::Result<Option<(H256, H256)>> {
+        if let Some(proofs) = self
+            .recent_results
+            .lock()
+            .expect("poisoned lock")
+            .get(&task_index)
+        {
+            return Ok(Some(*proofs));
+        }
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        Ok(store
+            .get_task(task_index)?
+            .map(|record| (record.block_hash, record.storage_proof_hash)))
+    }
+
+    /// Periodically tops up stake from the configured treasury, pending forever if disabled or
+    /// in shadow mode.
+    #[instrument(skip_all)]
+    pub async fn watch_stake(&self) -> eyre::Result<()> {
+        if self.stake_top_up.top_up_treasury.is_none() || self.shadow_of.is_some() {
+            return std::future::pending().await;
+        }
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(self.stake_top_up.top_up_interval_secs));
+        loop {
+            interval.tick().await;
+            if self.pressure.level() >= PressureLevel::Elevated {
+                debug!("Skipping stake top-up check under resource pressure");
+                continue;
+            }
+            if let Err(e) = self.top_up_stake().await {
+                error!("Stake top-up failed: {:?}", e);
+            }
+        }
+    }
+
+    /// Periodically deletes the archived objects and local task records past their retention,
+    /// pending forever without an archive.
+    #[instrument(skip_all)]
+    pub async fn watch_archive(&self) -> eyre::Result<()> {
+        let Some(archive) = &self.archive else {
+            return std::future::pending().await;
+        };
+        let mut interval = tokio::time::interval(ARCHIVE_RETENTION_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = archive.enforce_retention().await {
+                error!("Archive retention failed: {:?}", e);
+            }
+        }
+    }
+
+    /// Periodically samples the operator stake share per quorum, pending forever if disabled.
+    #[instrument(skip_all)]
+    pub async fn watch_stake_share(&self) -> eyre::Result<()> {
+        if self.stake_share_interval.is_zero() {
+            return std::future::pending().await;
+        }
+        let mut interval = tokio::time::interval(self.stake_share_interval);
+        loop {
+            interval.tick().await;
+            if self.pressure.level() >= PressureLevel::Elevated {
+                debug!("Skipping stake share sample under resource pressure");
+                continue;
+            }
+            if let Err(e) = self.record_stake_share().await {
+                error!("Stake share sampling failed: {:?}", e);
+            }
+        }
+    }
+
+    async fn record_stake_share(&self) -> eyre::Result<()> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        for (quorum_number, stake, total_stake) in self.avs_contracts.stake_shares().await? {
+            let share = share_pct(stake, total_stake);
+            metrics()
+                .stake_share_pct
+                .with_label_values(&[&quorum_number.to_string()])
+                .set(share);
+            debug!("Stake share of quorum {}: {:.4}%", quorum_number, share);
+            if self
+                .stake_share_alert_pct
+                .is_some_and(|alert| share < alert)
+            {
+                warn!(
+                    "Stake share of quorum {} is {:.4}%, below the {}% alert threshold",
+                    quorum_number,
+                    share,
+                    self.stake_share_alert_pct.unwrap_or_default()
+                );
+            }
+            if let Some(store) = &self.store {
+                let record = StakeShareRecord {
+                    timestamp,
+                    quorum_number,
+                    stake,
+                    total_stake,
+                    share_pct: share,
+                };
+                store.run(move |s| s.put_stake_share(&record)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Follows the strategies whitelisted for deposit, picking up new ones without a restart,
+    /// and exports the shares delegated to the operator in each. Pending forever if disabled.
+    #[instrument(skip_all)]
+    pub async fn watch_strategies(&self) -> eyre::Result<()> {
+        if self.strategy_sync_interval.is_zero() {
+            return std::future::pending().await;
+        }
+        let mut interval = tokio::time::interval(self.strategy_sync_interval);
+        loop {
+            interval.tick().await;
+            if self.pressure.level() >= PressureLevel::Elevated {
+                debug!("Skipping strategy sync under resource pressure");
+                continue;
+            }
+            if let Err(e) = self.record_strategy_shares().await {
+                error!("Strategy sync failed: {:?}", e);
+            }
+        }
+    }
+
+    /// Follows the withdrawals queued by the stakers delegated to the operator and warns when
+    /// they will drop its stake below a quorum minimum at the next stake update. Pending
+    /// forever if disabled.
+    #[instrument(skip_all)]
+    pub async fn watch_withdrawals(&self) -> eyre::Result<()> {
+        if self.withdrawal_check_interval.is_zero() {
+            return std::future::pending().await;
+        }
+        let mut interval = tokio::time::interval(self.withdrawal_check_interval);
+        loop {
+            interval.tick().await;
+            if self.pressure.level() >= PressureLevel::Elevated {
+                debug!("Skipping withdrawal check under resource pressure");
+                continue;
+            }
+            if let Err(e) = self.check_withdrawals().await {
+                error!("Withdrawal check failed: {:?}", e);
+            }
+        }
+    }
+
+    /// Records when substrate blocks are finalized to measure the finality lag of their tasks,
+    /// and alerts on the blocks and tasks overdue. Only alerts once the subscription fails.
+    #[instrument(skip_all)]
+    pub async fn watch_finalized_blocks(&self) -> eyre::Result<()> {
+        let record = async {
+            if let Err(e) = self.record_finalized_blocks().await {
+                error!("Following the substrate finalized heads failed: {:?}", e);
+            }
+            warn!("Stopped measuring the finality lag");
+        };
+        let check_overdue = async {
+            let mut interval = tokio::time::interval(FINALITY_LAG_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                self.finality_lag.check_overdue(now);
+            }
+        };
+        tokio::join!(record, check_overdue);
+        std::future::pending().await
+    }
+
+    async fn record_finalized_blocks(&self) -> eyre::Result<()> {
+        let heads = finalized_heads::<Block>(&self.substrate_client_uri).await?;
+        futures::pin_mut!(heads);
+        // heads may skip numbers when several blocks are finalized at once
+        let mut next = None;
+        while let Some(head) = heads.next().await {
+            let head = head?;
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            self.finality_lag
+                .finalized(next.unwrap_or(head)..=head, now);
+            next = Some(head + 1);
+        }
+        Ok(())
+    }
+
+    /// Whether a followed chain halted, the node then idles.
+    fn idle(&self) -> bool {
+        self.chain_halts.as_ref().is_some_and(|halts| halts.idle())
+    }
+
+    /// Follows the Ethereum head and the substrate finalized head to detect halted chains.
+    /// Pending forever if disabled, only Ethereum is followed once the substrate subscription
+    /// fails.
+    #[instrument(skip_all)]
+    pub async fn watch_chain_halts(&self) -> eyre::Result<()> {
+        let Some(halts) = &self.chain_halts else {
+            return std::future::pending().await;
+        };
+        tokio::select! {
+            never = self.follow_ethereum_head(halts) => match never {},
+            res = self.follow_substrate_head(halts) => {
+                if let Err(e) = res {
+                    error!("Following the substrate finalized heads failed: {:?}", e);
+                }
+                warn!("Stopped detecting substrate halts");
+            }
+        }
+        match self.follow_ethereum_head(halts).await {}
+    }
+
+    async fn follow_ethereum_head(&self, halts: &ChainHalts) -> Infallible {
+        let mut interval = tokio::time::interval(HALT_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            // an unreachable node is not a halted chain
+            match self.client.get_block_number().await {
+                Ok(head) => halts.observe(FollowedChain::Ethereum, head.as_u64()),
+                Err(e) => debug!("Cannot read the chain head to detect halts: {}", e),
+            }
+        }
+    }
+
+    async fn follow_substrate_head(&self, halts: &ChainHalts) -> eyre::Result<()> {
+        let heads = finalized_heads::<Block>(&self.substrate_client_uri).await?;
+        futures::pin_mut!(heads);
+        let mut interval = tokio::time::interval(HALT_CHECK_INTERVAL);
+        let mut head = None;
+        loop {
+            tokio::select! {
+                next = heads.next() => match next {
+                    Some(next) => head = Some(next?),
+                    None => return Err(eyre::eyre!("finalized heads subscription ended")),
+                },
+                _ = interval.tick() => {}
+            }
+            if let Some(head) = head {
+                halts.observe(FollowedChain::Substrate, head.into());
+            }
+        }
+    }
+
+    /// Periodically aggregates the partial signatures gossiped by registered operators for
+    /// the tasks the aggregator did not take the response of, serving them on the operator
+    /// API for a backup aggregator. Pending forever without gossip peers.
+    #[instrument(skip_all)]
+    pub async fn watch_gossip(&self) -> eyre::Result<()> {
+        let Some(gossip) = &self.gossip else {
+            return std::future::pending().await;
+        };
+        loop {
+            // aggregation rounds are spaced out under pressure, partials keep until the next
+            tokio::time::sleep(self.pressure.stretch(GOSSIP_AGGREGATE_INTERVAL)).await;
+            for (task_index, task, partials) in gossip.fallback_partials() {
+                if let Err(e) = self
+                    .aggregate_gossip(gossip, task_index, &task, partials)
+                    .await
+                {
+                    warn!(
+                        "Cannot aggregate the gossip of task {}: {:?}",
+                        task_index, e
+                    );
+                }
+            }
+        }
+    }
 
-use bindings::{
-    mangata_task_manager::NewTaskCreatedFilter,
-    shared_types::{G1Point, G2Point, TaskResponse},
-};
-use ethers::prelude::*;
-use node_executor::ExecutorDispatch;
-use node_primitives::BlockNumber;
+    /// Aggregates the partial signatures of operators which were members of the task quorums
+    /// at its reference block, however long ago, as `checkSignatures` weighs the signers
+    /// and non-signers by their stake at that block.
+    async fn aggregate_gossip(
+        &self,
+        gossip: &Gossip,
+        task_index: u32,
+        task: &Task,
+        partials: Vec<gossip::VerifiedPartial>,
+    ) -> eyre::Result<()> {
+        let reference_block = task.task_created_block;
+        let snapshot = self
+            .avs_contracts
+            .quorum_snapshot(reference_block, &task.quorum_numbers)
+            .await?;
+        let mut registered = vec![];
+        for partial in partials {
+            if snapshot.is_member(partial.operator_id)
+                && self
+                    .avs_contracts
+                    .operator_address_of_id(partial.operator_id)
+                    .await?
+                    == partial.eth_address
+            {
+                registered.push(partial);
+            }
+        }
+        // our own response decides the digest, the most signed one without it
+        let own = self.operator_id();
+        let digest = match registered.iter().find(|p| p.operator_id == own) {
+            Some(partial) => partial.digest,
+            None => {
+                let mut counts = HashMap::new();
+                for partial in &registered {
+                    *counts.entry(partial.digest).or_insert(0) += 1;
+                }
+                match counts.into_iter().max_by_key(|(_, count)| *count) {
+                    Some((digest, _)) => digest,
+                    None => return Ok(()),
+                }
+            }
+        };
+        let signers: Vec<H256> = registered
+            .iter()
+            .filter(|p| p.digest == digest)
+            .map(|p| p.operator_id)
+            .collect();
+        let operator_ids = snapshot.non_signers(&signers);
+        let indices = self
+            .avs_contracts
+            .check_signatures_indices(reference_block, &task.quorum_numbers, &operator_ids)
+            .await?;
+        let non_signers = NonSigners {
+            reference_block,
+            quorum_numbers: task.quorum_numbers.clone(),
+            operator_ids,
+            indices,
+        };
+        let Some(aggregate) = gossip::aggregate(task_index, digest, &registered, non_signers)
+        else {
+            return Ok(());
+        };
+        if gossip.set_aggregate(aggregate.clone()) {
+            info!(
+                "Aggregated the gossiped signatures of task {}: {}",
+                task_index,
+                serde_json::to_string(&aggregate)?
+            );
+        }
+        Ok(())
+    }
 
-use serde::Serialize;
-use sp_runtime::traits::BlakeTwo256;
-use sp_runtime::{generic, OpaqueExtrinsic};
-use std::sync::Arc;
-use tracing::{debug, error, info, instrument};
+    /// Periodically compares the code behind the AVS contracts with the previous check,
+    /// logging an [`UpgradeNotice`] for each contract upgraded meanwhile. Pending forever if
+    /// disabled or once the initial read fails.
+    #[instrument(skip_all)]
+    pub async fn watch_upgrades(&self) -> eyre::Result<()> {
+        if !self.upgrade_check_interval.is_zero() {
+            if let Err(e) = self.follow_upgrades().await {
+                error!("Reading the AVS contract code failed: {:?}", e);
+            }
+            warn!("Stopped checking AVS contract upgrades");
+        }
+        std::future::pending().await
+    }
 
-pub type Header = generic::HeaderVer<node_primitives::BlockNumber, BlakeTwo256>;
-pub type Block = generic::Block<Header, OpaqueExtrinsic>;
+    async fn follow_upgrades(&self) -> eyre::Result<()> {
+        let contracts = self.avs_contracts.contracts();
+        let mut checked_to = self.client.get_block_number().await?.as_u64();
+        let mut deployments = vec![];
+        for (name, address, bindings) in &contracts {
+            let deployment = Deployment::read(self.client.as_ref(), *address).await?;
+            let diff = SelectorDiff::new(bindings, &deployment.selectors);
+            if !diff.missing.is_empty() {
+                warn!(
+                    "The {} contract deployed at {:?} differs from the compiled bindings: {}",
+                    name,
+                    address,
+                    serde_json::to_string_pretty(&diff)?
+                );
+            }
+            deployments.push(deployment);
+        }
 
-#[derive(Debug, Serialize)]
-pub struct OperatorStatus {
-    pub eth_address: Address,
-    pub registered_with_eigen: bool,
-    pub bls_key_registered: bool,
-    pub bls_g1: G1Point,
-    pub bls_g2: G2Point,
-    pub registered_with_avs: bool,
-    pub operator_id: Option<OperatorId>,
-    // opted_in_salshing_by_avs: bool,
-    // frozen: bool,
-}
+        let mut interval = tokio::time::interval(self.upgrade_check_interval);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = self
+                .check_upgrades(&contracts, &mut deployments, &mut checked_to)
+                .await
+            {
+                error!("Contract upgrade check failed: {:?}", e);
+            }
+        }
+    }
 
-#[derive(Debug)]
-pub struct Operator {
-    pub client: Arc<Client>,
-    avs_contracts: AvsContracts,
-    el_contracts: ElContracts,
-    bls_keypair: BlsKeypair,
-    substrate_client_uri: String,
-    chain_id: u64,
-    rpc: Rpc,
-}
-impl Operator {
-    #[instrument(name = "create_operator", skip_all)]
-    pub async fn from_cli(cfg: &CliArgs) -> eyre::Result<Self> {
-        let client = Arc::new(build_eth_client(cfg).await?);
-        let avs_contracts = AvsContracts::build(cfg, client.clone()).await?;
-        let slasher = avs_contracts.slasher_address().await?;
-        let el_contracts = ElContracts::build(cfg, slasher, client.clone()).await?;
+    async fn check_upgrades(
+        &self,
+        contracts: &[(&'static str, Address, &'static Abi)],
+        deployments: &mut [Deployment],
+        checked_to: &mut u64,
+    ) -> eyre::Result<()> {
+        let head = self.client.get_block_number().await?.as_u64();
+        for ((name, address, bindings), previous) in contracts.iter().zip(deployments.iter_mut()) {
+            let current = Deployment::read(self.client.as_ref(), *address).await?;
+            let upgraded_at =
+                upgrades::upgraded_at(self.client.as_ref(), *address, *checked_to + 1, head)
+                    .await?;
+            if let Some(notice) =
+                UpgradeNotice::new(name, *address, bindings, previous, &current, upgraded_at)
+            {
+                warn!(
+                    "AVS contract upgraded: {}",
+                    serde_json::to_string_pretty(&notice)?
+                );
+                metrics().contract_upgrades.with_label_values(&[name]).inc();
+            }
+            *previous = current;
+        }
+        *checked_to = head;
+        Ok(())
+    }
 
-        info!("Decrypting BLS keypair...");
-        let bls_key = cfg.get_bls_keystore()?.into_bls_keypair()?;
+    /// Syncs the stake and strategy caches to the chain head and writes them to `path` as a
+    /// checkpoint signed by the operator. Seeded by `--sync-checkpoint`, only the events
+    /// after the previous checkpoint are replayed.
+    pub async fn export_sync_checkpoint(&self, path: &Path) -> eyre::Result<()> {
+        let head = self.client.get_block_number().await?.as_u64();
+        self.el_contracts.sync_strategies(head).await?;
+        self.avs_contracts.sync_stakes(head).await?;
+        let checkpoint = RegistryCheckpoint {
+            chain_id: self.chain_id,
+            block: head,
+            strategies: self.el_contracts.whitelisted_strategies(),
+            stakes: self.avs_contracts.cached_stakes(),
+        };
+        SignedCheckpoint::sign(&checkpoint, self.client.signer())
+            .await?
+            .write(path)?;
         info!(
-            "Bls Keypair decrypted with operator id: {:x}",
-            bls_key.operator_id()
+            "Wrote the registry checkpoint at block {} to {}",
+            head,
+            path.display()
         );
+        Ok(())
+    }
 
-        let rpc = Rpc::build(cfg);
+    async fn record_strategy_shares(&self) -> eyre::Result<()> {
+        let shares = &metrics().operator_strategy_shares;
+        let head = self.client.get_block_number().await?.as_u64();
+        for change in self.el_contracts.sync_strategies(head).await? {
+            match change {
+                StrategyChange::Added(strategy) => {
+                    info!("Tracking strategy {:x} whitelisted for deposit", strategy)
+                }
+                StrategyChange::Removed(strategy) => {
+                    info!("Strategy {:x} removed from the deposit whitelist", strategy);
+                    let label = format!("{:?}", strategy);
+                    let _ = shares.remove_label_values(&[&label]);
+                    let _ = metrics()
+                        .operator_strategy_underlying
+                        .remove_label_values(&[&label]);
+                }
+            }
+        }
+        let strategies = self.el_contracts.whitelisted_strategies();
+        for (strategy, amount) in self
+            .el_contracts
+            .operator_shares(self.client.address(), &strategies)
+            .await?
+        {
+            let label = format!("{:?}", strategy);
+            shares
+                .with_label_values(&[&label])
+                .set(u128::try_from(amount).unwrap_or(u128::MAX) as f64);
+            match self
+                .el_contracts
+                .shares_to_underlying(strategy, amount)
+                .await
+            {
+                Ok(underlying) => metrics()
+                    .operator_strategy_underlying
+                    .with_label_values(&[&label])
+                    .set(underlying.to_f64()),
+                Err(e) => warn!(
+                    "Cannot convert the shares of strategy {:x} to its token: {:?}",
+                    strategy, e
+                ),
+            }
+        }
+        Ok(())
+    }
 
-        Ok(Self {
-            avs_contracts,
-            el_contracts,
-            substrate_client_uri: cfg.substrate_rpc_url.to_owned(),
-            client,
-            bls_keypair: bls_key,
-            chain_id: cfg.chain_id,
-            rpc,
-        })
+    /// Projects the stake of each quorum at the next stake update. Queued withdrawals already
+    /// left the delegated shares, the registry stake only drops once the stakes are updated.
+    async fn check_withdrawals(&self) -> eyre::Result<()> {
+        let operator = self.client.address();
+        let head = self.client.get_block_number().await?.as_u64();
+        let pending = self.el_contracts.sync_withdrawals(operator, head).await?;
+        for (quorum_number, stake, _) in self.avs_contracts.stake_shares().await? {
+            if stake == 0 {
+                continue;
+            }
+            let weights = self.avs_contracts.quorum_weights(quorum_number).await?;
+            let strategies: Vec<Address> = weights.multipliers.iter().map(|m| m.strategy).collect();
+            let (strategies, shares): (Vec<_>, Vec<_>) = self
+                .el_contracts
+                .operator_shares(operator, &strategies)
+                .await?
+                .into_iter()
+                .unzip();
+            let projected = weights.weigh(&strategies, &shares);
+            metrics()
+                .projected_stake
+                .with_label_values(&[&quorum_number.to_string()])
+                .set(projected as f64);
+            if projected < stake && projected < weights.minimum_stake {
+                let queued = pending
+                    .iter()
+                    .filter(|w| weights.weigh(&w.strategies, &w.shares) > 0)
+                    .count();
+                warn!(
+                    "{} queued withdrawals drop the stake in quorum {} from {} to {} at the next stake update, below the {} minimum",
+                    queued, quorum_number, stake, projected, weights.minimum_stake
+                );
+            }
+        }
+        Ok(())
     }
 
+    /// Periodically checks the ETH balance of the operator account against the gas float.
     #[instrument(skip_all)]
-    pub async fn watch_new_tasks(&self) -> eyre::Result<()> {
-        let evs = self.avs_contracts.new_task_stream();
-        let mut stream: stream::EventStream<'_, _, NewTaskCreatedFilter, _> =
-            evs.subscribe().await?;
-
-        while let Some(Ok(event)) = stream.next().await {
-            info!("Executing a Block for task: {:?}", event);
-            let proofs = self.execute_block(event.task.block_number.as_u32()).await?;
-            debug!("Block executed successfully");
-
-            let payload = TaskResponse {
-                reference_task_index: event.task_index,
-                block_hash: proofs.0.as_fixed_bytes().to_owned(),
-                storage_proof_hash: proofs.1.as_fixed_bytes().to_owned(),
-            };
+    pub async fn watch_balance(&self) -> eyre::Result<()> {
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            self.balance.balance_check_interval_secs,
+        ));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check_balance().await {
+                error!("Balance check failed: {:?}", e);
+            }
+        }
+    }
 
-            let response = self
-                .rpc
-                .send_task_response(payload, &self.bls_keypair)
-                .await?;
+    async fn check_balance(&self) -> eyre::Result<()> {
+        let balance = self.client.get_balance(self.client.address(), None).await?;
+        let balance_eth = units::wei_to_eth(balance);
+        metrics().wallet_balance_eth.set(balance_eth);
 
-            match response.error_for_status_ref() {
-                Err(e) => error!("{} - {}", e, response.text().await?),
-                Ok(_) => info!("Task finished successfuly and sent to AVS service"),
-            }
+        let low = match self.balance.min_balance_eth {
+            Some(min) => balance < units::eth_to_wei(min)?,
+            None => false,
+        };
+        let was_low = self.low_balance.swap(low, Ordering::Relaxed);
+        if low {
+            warn!(
+                "Operator balance {} ETH is below the {} ETH gas float{}",
+                balance_eth,
+                self.balance.min_balance_eth.unwrap_or_default(),
+                if self.balance.halt_below_min_balance {
+                    ", transactions are halted"
+                } else {
+                    ""
+                }
+            );
+        } else if was_low {
+            info!(
+                "Operator balance {} ETH is back above the gas float",
+                balance_eth
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether transactions are halted because the balance fell below the gas float.
+    fn transactions_halted(&self) -> bool {
+        self.balance.halt_below_min_balance && self.low_balance.load(Ordering::Relaxed)
+    }
+
+    #[instrument(skip_all)]
+    pub(crate) async fn top_up_stake(&self) -> eyre::Result<()> {
+        let (Some(treasury), Some(amount)) = (
+            self.stake_top_up.top_up_treasury,
+            self.stake_top_up.top_up_amount,
+        ) else {
+            return Ok(());
+        };
+
+        if self.transactions_halted() {
+            warn!("Not checking for a stake top-up, transactions are halted on low balance");
+            return Ok(());
+        }
+        let stake = self.avs_contracts.operator_stake().await?;
+        let minimum = self.avs_contracts.minimum_stake().await?;
+        let threshold =
+            minimum.saturating_mul(100 + self.stake_top_up.top_up_margin_pct as u128) / 100;
+        if stake >= threshold {
+            debug!(
+                "Stake {} above top-up threshold {}",
+                format_stake(stake),
+                format_stake(threshold)
+            );
+            return Ok(());
+        }
+
+        warn!(
+            "Stake {} below top-up threshold {} (minimum {}), pulling funds from treasury {:x}",
+            format_stake(stake),
+            format_stake(threshold),
+            format_stake(minimum),
+            treasury
+        );
+        let strategy = self.avs_contracts.quorum_strategy().await?.strategy;
+        let deposited = self
+            .el_contracts
+            .top_up_from_treasury(treasury, strategy, amount.into())
+            .await?;
+
+        if deposited.is_zero() {
+            warn!(
+                "Treasury {:x} allowance exhausted, stake not topped up",
+                treasury
+            );
+        } else {
+            info!(
+                "Deposited {} of token {:x} into strategy {:x}",
+                deposited, deposited.token, strategy
+            );
         }
         Ok(())
     }
 
-    pub(crate) async fn execute_block(
+    async fn record_task(
+        &self,
+        event: &NewTaskCreatedFilter,
+        proofs: (H256, H256),
+        memo: TaskMemo,
+    ) -> eyre::Result<TaskRecord> {
+        let record = TaskRecord {
+            task_index: event.task_index,
+            block_number: event.task.block_number.as_u32(),
+            block_hash: proofs.0,
+            storage_proof_hash: proofs.1,
+            responded_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            memo: Some(memo),
+        };
+        if let Some(store) = &self.store {
+            let record = record.clone();
+            store.run(move |s| s.put_task(&record)).await?;
+        }
+        Ok(record)
+    }
+
+    /// Stores the task quorums at its reference block, once per block.
+    async fn record_quorum_snapshot(&self, event: &NewTaskCreatedFilter) -> eyre::Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let reference_block = event.task.task_created_block;
+        if store
+            .run(move |s| s.has_quorum_snapshot(reference_block))
+            .await?
+        {
+            return Ok(());
+        }
+        let snapshot = self
+            .avs_contracts
+            .quorum_snapshot(reference_block, &event.task.quorum_numbers)
+            .await?;
+        store.run(move |s| s.put_quorum_snapshot(&snapshot)).await
+    }
+
+    /// Fingerprint of the substrate endpoints, identifying the data source of a response
+    /// without recording the endpoints and their credentials.
+    fn data_source(&self) -> String {
+        let uris = match &self.substrate_consensus {
+            Some((uris, quorum)) => format!("{}\n{}", uris.join("\n"), quorum),
+            None => self.substrate_client_uri.clone(),
+        };
+        hex::encode(&Sha256::digest(uris.as_bytes())[..16])
+    }
+
+    /// Counts a failed verification of a task, quarantining it once it keeps failing with the
+    /// same error. The error is passed on unless the task got quarantined.
+    async fn record_failure(&self, task_index: u32, e: eyre::Report) -> eyre::Result<bool> {
+        let Some(store) = &self.store else {
+            return Err(e);
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let (error, threshold) = (format!("{:#}", e), self.quarantine_after);
+        let record = store
+            .run(move |s| s.record_failure(task_index, &error, now, threshold))
+            .await?;
+        if !record.quarantined {
+            return Err(e);
+        }
+        metrics().tasks_quarantined.inc();
+        error!(
+            "Quarantined task {} after {} failures with the same error: {}",
+            task_index, record.failures, record.reason
+        );
+        Ok(false)
+    }
+
+    /// Persists the outcome of a received task and refreshes the reputation metrics. Every
+    /// task ends with an outcome, a store failure is logged rather than stopping the tasks.
+    async fn record_outcome(&self, outcome: TaskOutcome) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if self.shadow_of.is_some() {
+            // a shadow node responds to nothing, it has no reputation of its own
+            return;
+        }
+        let task_index = outcome.task_index;
+        let stored = outcome.clone();
+        if let Err(e) = store.run(move |s| s.put_outcome(&stored)).await {
+            warn!("Cannot record the outcome of task {}: {:?}", task_index, e);
+        }
+        let reputation = self
+            .with_rolling_reputation(|rolling, now| {
+                rolling.record(outcome);
+                rolling.reputation(now)
+            })
+            .await;
+        match reputation {
+            Ok(reputation) => {
+                metrics().reputation_score.set(reputation.score);
+                metrics().task_response_rate.set(reputation.response_rate);
+            }
+            Err(e) => warn!("Cannot refresh the reputation: {:?}", e),
+        }
+    }
+
+    /// Records task `task_index` as not responded to, e.g. skipped or expired.
+    async fn record_missed(&self, task_index: u32) {
+        let received_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.record_outcome(TaskOutcome {
+            task_index,
+            received_at,
+            responded: false,
+            latency_ms: None,
+            diverged: false,
+        })
+        .await
+    }
+
+    /// Runs `f` on the reputation over the window with the current unix time, reading the
+    /// outcomes in the window from the store on first use.
+    async fn with_rolling_reputation<T>(
+        &self,
+        f: impl FnOnce(&mut RollingReputation, u64) -> T,
+    ) -> eyre::Result<T> {
+        let store = self
+            .store
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("reputation requires a store, set --db-path or --db-url"))?;
+        let window = self.reputation_window.as_secs();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        if self
+            .rolling_reputation
+            .lock()
+            .expect("poisoned lock")
+            .is_none()
+        {
+            let since = now.saturating_sub(window);
+            let outcomes = store.run(move |s| s.outcomes_since(since)).await?;
+            self.rolling_reputation
+                .lock()
+                .expect("poisoned lock")
+                .get_or_insert_with(|| RollingReputation::new(window, outcomes));
+        }
+        let mut rolling = self.rolling_reputation.lock().expect("poisoned lock");
+        Ok(f(rolling.as_mut().expect("read above; qed"), now))
+    }
+
+    pub(crate) async fn quarantined_tasks(&self) -> eyre::Result<Vec<QuarantineRecord>> {
+        self.store
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("quarantine requires a store, set --db-path or --db-url"))?
+            .run(|s| s.quarantined())
+            .await
+    }
+
+    /// Releases `task_index` from quarantine and verifies it again, see [`Self::submit_task`].
+    pub(crate) async fn retry_quarantined(&self, task_index: u32) -> eyre::Result<bool> {
+        self.store
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("quarantine requires a store, set --db-path or --db-url"))?
+            .run(move |s| s.clear_failures(task_index))
+            .await?;
+        info!("Task {} released from quarantine", task_index);
+        self.submit_task(task_index).await
+    }
+
+    /// Reputation of the operator over the configured window, from the local store.
+    pub(crate) async fn reputation(&self) -> eyre::Result<Reputation> {
+        self.with_rolling_reputation(|rolling, now| rolling.reputation(now))
+            .await
+    }
+
+    pub(crate) async fn attest_reputation(
+        &self,
+        reputation: Reputation,
+    ) -> eyre::Result<ReputationAttestation> {
+        reputation::attest(reputation, self.operator_id(), self.client.signer()).await
+    }
+
+    pub(crate) async fn attest_build(&self) -> eyre::Result<BuildAttestation> {
+        build_info::attest(
+            BuildInfo::current(),
+            self.operator_id(),
+            self.client.signer(),
+        )
+        .await
+    }
+
+    pub(crate) async fn verify_block(
         &self,
+        verifier: &dyn Verifier,
         block_number: BlockNumber,
+        cancel: &CancellationToken,
     ) -> eyre::Result<(H256, H256)> {
-        use sc_executor::{sp_wasm_interface::ExtendedHostFunctions, NativeExecutionDispatch};
-        let res = execute_block::<
-            Block,
-            ExtendedHostFunctions<
-                sp_io::SubstrateHostFunctions,
-                <ExecutorDispatch as NativeExecutionDispatch>::ExtendHostFunctions,
-            >,
-        >(&self.substrate_client_uri, block_number)
-        .await?;
+        match &self.workers {
+            Some(workers) => {
+                cancellable(
+                    cancel,
+                    workers.verify(verifier, &self.substrate_client_uri, block_number),
+                )
+                .await?
+            }
+            None => {
+                verifier
+                    .verify(&self.substrate_client_uri, block_number, cancel)
+                    .await
+            }
+        }
+    }
+
+    /// Verifier of the tasks of `task_type` created at the current block.
+    async fn current_verifier(&self, task_type: TaskType) -> eyre::Result<&dyn Verifier> {
+        let head = self.client.get_block_number().await?.as_u32();
+        self.verifiers.select(task_type, head)
+    }
+
+    /// Signs `payload` with the operator BLS key and encodes it for the aggregator.
+    pub(crate) async fn sign_task_response(&self, payload: TaskResponse) -> eyre::Result<String> {
+        let signature = self
+            .bls_key
+            .sign(&SignRequest::task_response(&payload))
+            .await?;
+        encode_bls_task_response(payload, signature, self.bls_key.operator_id())
+    }
+
+    /// Hash of the block agreed on by the substrate node quorum, `None` if no quorum is configured.
+    async fn quorum_block_hash(&self, block_number: BlockNumber) -> eyre::Result<Option<H256>> {
+        let Some((uris, quorum)) = &self.substrate_consensus else {
+            return Ok(None);
+        };
+        Ok(Some(
+            agreed_block_hash::<Block>(uris, block_number, *quorum).await?,
+        ))
+    }
+
+    fn config_snapshot(&self) -> ConfigSnapshot {
+        ConfigSnapshot {
+            latency_budget_ms: self.latency_budget.map(|budget| budget.as_millis() as u64),
+            shadow_of: self.shadow_of,
+        }
+    }
+
+    /// Ensures the executed block hash matches the one agreed on by the substrate node quorum.
+    pub(crate) async fn cross_check_block(
+        &self,
+        block_number: BlockNumber,
+        block_hash: H256,
+    ) -> eyre::Result<()> {
+        let Some(agreed) = self.quorum_block_hash(block_number).await? else {
+            return Ok(());
+        };
+        if agreed != block_hash {
+            return Err(eyre::eyre!(
+                "executed block {} hash {:x} differs from quorum agreed hash {:x}",
+                block_number,
+                block_hash,
+                agreed
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn shadow_of(&self) -> Option<Address> {
+        self.shadow_of
+    }
 
-        Ok(res)
+    pub(crate) fn identity(&self) -> &NodeIdentity {
+        &self.identity
     }
 
     pub(crate) fn operator_id(&self) -> OperatorId {
-        self.bls_keypair.operator_id()
+        self.bls_key.operator_id()
     }
 
     #[instrument(skip_all)]
@@ -137,18 +2425,307 @@ impl Operator {
             .await?;
 
         let id = self.avs_contracts.operator_id().await?;
+        let strategy = self.avs_contracts.quorum_strategy().await?.strategy;
+        let shares = self
+            .el_contracts
+            .operator_shares(self.client.address(), &[strategy])
+            .await?
+            .first()
+            .map_or(U256::zero(), |(_, shares)| *shares);
+        let stake = StakeStatus {
+            strategy,
+            shares,
+            underlying: self
+                .el_contracts
+                .shares_to_underlying(strategy, shares)
+                .await?,
+            weight: self.avs_contracts.operator_stake().await?,
+        };
 
         Ok(OperatorStatus {
+            node_id: self.identity.id.clone(),
             eth_address: self.client.address(),
             registered_with_eigen: el_status,
             bls_key_registered: pubkey_status,
-            bls_g1: EthConvert::to_g1(self.bls_keypair.public).unwrap_or_default(),
-            bls_g2: EthConvert::to_g2(self.bls_keypair.public_g2()).unwrap_or_default(),
+            bls_g1: EthConvert::to_g1(self.bls_key.public()).unwrap_or_default(),
+            bls_g2: EthConvert::to_g2(self.bls_key.public_g2()).unwrap_or_default(),
             operator_id: id,
             registered_with_avs: id.is_some(),
+            stake,
+            reputation: match self.store {
+                Some(_) => Some(self.reputation().await?),
+                None => None,
+            },
+            features: self.features.clone(),
+        })
+    }
+
+    /// Samples the values followed by `print-status --watch`.
+    pub(crate) async fn status_sample(&self) -> eyre::Result<StatusSample> {
+        let eth_head = self.client.get_block_number().await?.as_u64();
+        let checkpoint = match &self.store {
+            Some(store) => store.run(|s| s.checkpoint()).await?,
+            None => None,
+        };
+        Ok(StatusSample {
+            eth_head,
+            task_count: self.avs_contracts.task_count().await?,
+            operator_stake: self.avs_contracts.operator_stake().await?,
+            registered_with_avs: self.avs_contracts.operator_id().await?.is_some(),
+            lag_blocks: checkpoint.map(|block| eth_head.saturating_sub(block.into())),
+        })
+    }
+
+    /// Runs the verification, signing and encoding pipeline on a synthetic task for
+    /// `block_number` without sending the response, then verifies the encoded signature.
+    #[instrument(skip(self))]
+    pub(crate) async fn self_test(
+        &self,
+        block_number: BlockNumber,
+    ) -> eyre::Result<SelfTestReport> {
+        let verifier = self.current_verifier(TaskType::ExecuteBlock).await?;
+        let proofs = self
+            .verify_block(verifier, block_number, &CancellationToken::new())
+            .await?;
+        self.cross_check_block(block_number, proofs.0).await?;
+
+        let payload = TaskResponse {
+            reference_task_index: u32::MAX,
+            block_hash: proofs.0.to_fixed_bytes(),
+            storage_proof_hash: proofs.1.to_fixed_bytes(),
+        };
+        let json = self.sign_task_response(payload).await?;
+        let signature_valid = verify_task_response(&json, self.bls_key.public_g2())?;
+        let pubkey_registered = self
+            .el_contracts
+            .operator_pubkey_hash(self.client.address())
+            .await?
+            == self.operator_id();
+
+        Ok(SelfTestReport {
+            block_number,
+            block_hash: proofs.0,
+            storage_proof_hash: proofs.1,
+            pubkey_registered,
+            signature_valid,
+            payload: serde_json::from_str(&json)?,
+        })
+    }
+
+    /// Signs `keccak256(SIGN_DIGEST_DOMAIN ++ digest)` with the BLS key and `digest` as an
+    /// EIP-191 message with the ECDSA key, neither can pass for a task response or a
+    /// transaction.
+    #[instrument(skip(self))]
+    pub(crate) async fn sign_digest(&self, digest: H256) -> eyre::Result<DigestSignatures> {
+        let request = SignRequest::Digest { digest };
+        let bls_message = request.message()?;
+        let bls_signature = self.bls_key.sign(&request).await?;
+        let ecdsa_signature = self.client.signer().sign_message(digest).await?;
+        Ok(DigestSignatures {
+            digest,
+            operator_id: self.operator_id(),
+            bls_message,
+            bls_signature: EthConvert::to_g1(bls_signature)
+                .ok_or_else(|| eyre::eyre!("cannot convert BLS signature"))?,
+            eth_address: self.client.address(),
+            ecdsa_signature: ecdsa_signature.to_vec().into(),
         })
     }
 
+    /// Verifies task `task_index` and sends its response like a task received live, for
+    /// tasks created within the response window. Returns whether the aggregator accepted it.
+    #[instrument(skip(self))]
+    pub(crate) async fn submit_task(&self, task_index: u32) -> eyre::Result<bool> {
+        let window = self.avs_contracts.task_response_window().await?;
+        let head = self.client.get_block_number().await?.as_u32();
+        let event = self
+            .avs_contracts
+            .tasks_created_since(head.saturating_sub(window).into())
+            .await?
+            .into_iter()
+            .find(|event| event.task_index == task_index)
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "task {} was not created within the last {} blocks",
+                    task_index,
+                    window
+                )
+            })?;
+        self.track_pending(&event, window);
+        self.process_task(&event, Instant::now()).await
+    }
+
+    #[instrument(skip_all)]
+    pub(crate) async fn quorum_status(&self) -> eyre::Result<Vec<QuorumStatus>> {
+        self.avs_contracts.quorum_status().await
+    }
+
+    #[instrument(skip_all)]
+    pub(crate) async fn advise_quorums(&self) -> eyre::Result<Vec<QuorumAdvice>> {
+        self.avs_contracts.advise_quorums().await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_deposits(
+        &self,
+        stakers: &[Address],
+        page_size: usize,
+    ) -> eyre::Result<Vec<StakerDeposits>> {
+        self.el_contracts
+            .get_deposits_batch(stakers, page_size)
+            .await
+    }
+
+    /// Average gas cost of the last `samples` task responses of the past `lookback_blocks`
+    /// blocks per task type and quorums, with the margin left from `expected_reward_eth`.
+    #[instrument(skip(self))]
+    pub(crate) async fn economics(
+        &self,
+        lookback_blocks: u64,
+        samples: usize,
+        expected_reward_eth: Option<f64>,
+    ) -> eyre::Result<Vec<TaskEconomics>> {
+        let head = self.client.get_block_number().await?.as_u64();
+        let from_block = head.saturating_sub(lookback_blocks);
+        let tasks: HashMap<u32, NewTaskCreatedFilter> = self
+            .avs_contracts
+            .tasks_created_since(from_block)
+            .await?
+            .into_iter()
+            .map(|event| (event.task_index, event))
+            .collect();
+        let responses = self.avs_contracts.task_responses_since(from_block).await?;
+        // responses to tasks created before the lookback have no known type or quorums
+        let sampled: Vec<_> = responses
+            .iter()
+            .filter_map(|(response, meta)| {
+                let task = tasks.get(&response.task_response.reference_task_index)?;
+                Some((task, meta.transaction_hash))
+            })
+            .rev()
+            .take(samples)
+            .collect();
+        info!(
+            "Sampling {} of {} task responses since block {}",
+            sampled.len(),
+            responses.len(),
+            from_block
+        );
+
+        let receipts = futures::future::try_join_all(
+            sampled
+                .iter()
+                .map(|(_, tx_hash)| self.client.get_transaction_receipt(*tx_hash)),
+        )
+        .await?;
+        let costs: Vec<ResponseCost> = sampled
+            .iter()
+            .zip(receipts)
+            .filter_map(|((task, _), receipt)| {
+                let receipt = receipt?;
+                Some(ResponseCost {
+                    task_type: TaskType::from(*task),
+                    quorum_numbers: task.task.quorum_numbers.to_vec(),
+                    gas_used: receipt.gas_used?.as_u64(),
+                    gas_price: receipt.effective_gas_price?.as_u128(),
+                })
+            })
+            .collect();
+        Ok(economics::summarize(&costs, expected_reward_eth))
+    }
+
+    /// Freezes and ejections since `from_block`, of this operator only unless
+    /// `all_operators`.
+    #[instrument(skip(self))]
+    pub(crate) async fn slashing_history(
+        &self,
+        from_block: u64,
+        all_operators: bool,
+    ) -> eyre::Result<SlashingHistory> {
+        let to_block = self.client.get_block_number().await?.as_u64();
+        let operator = (!all_operators).then(|| self.client.address());
+        let (freezes, deregistrations) = futures::try_join!(
+            self.el_contracts
+                .slasher_events(from_block, to_block, operator),
+            self.avs_contracts
+                .deregistrations(from_block, to_block, operator),
+        )?;
+
+        let mut events: Vec<SlashingEvent> = freezes
+            .into_iter()
+            .filter_map(|(event, meta)| SlashingEvent::from_slasher(event, &meta))
+            .collect();
+        for (deregistration, meta) in deregistrations {
+            if self
+                .avs_contracts
+                .is_ejection(meta.transaction_hash)
+                .await?
+            {
+                events.push(SlashingEvent::new(
+                    &meta,
+                    deregistration.operator,
+                    SlashingAction::Ejected,
+                ));
+            }
+        }
+        Ok(SlashingHistory::new(from_block, to_block, operator, events))
+    }
+
+    pub(crate) async fn operator_settings(&self) -> eyre::Result<OperatorSettings> {
+        self.el_contracts
+            .operator_settings(self.client.address())
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn set_operator_details(
+        &self,
+        args: &SetOperatorDetailsArgs,
+    ) -> eyre::Result<OperatorSettings> {
+        let current = self.operator_settings().await?;
+        if args.earnings_receiver.is_some()
+            || args.delegation_approver.is_some()
+            || args.staker_opt_out_window_blocks.is_some()
+        {
+            let details = OperatorDetails {
+                earnings_receiver: args.earnings_receiver.unwrap_or(current.earnings_receiver),
+                delegation_approver: args
+                    .delegation_approver
+                    .unwrap_or(current.delegation_approver),
+                staker_opt_out_window_blocks: args
+                    .staker_opt_out_window_blocks
+                    .unwrap_or(current.staker_opt_out_window_blocks),
+            };
+            current.validate(&details)?;
+            let receipt = self.el_contracts.modify_operator_details(details).await?;
+            info!("Operator details updated in {:?}", receipt.transaction_hash);
+        }
+        if let Some(uri) = &args.metadata_uri {
+            let url = reqwest::Url::parse(uri)
+                .map_err(|e| eyre::eyre!("invalid metadata uri {}: {}", uri, e))?;
+            if !["http", "https", "ipfs"].contains(&url.scheme()) {
+                return Err(eyre::eyre!(
+                    "metadata uri must be an http(s) or ipfs url, got {}",
+                    uri
+                ));
+            }
+            let receipt = self
+                .el_contracts
+                .update_operator_metadata_uri(uri.clone())
+                .await?;
+            info!(
+                "Operator metadata uri updated in {:?}",
+                receipt.transaction_hash
+            );
+        }
+        self.operator_settings().await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn prove_ownership(&self, challenge: &str) -> eyre::Result<OwnershipProof> {
+        ownership::prove(challenge, self.client.signer(), self.bls_key.keypair()?).await
+    }
+
     #[instrument(skip_all)]
     pub(crate) async fn register(&self) -> eyre::Result<()> {
         let status = self
@@ -163,7 +2740,7 @@ impl Operator {
             );
 
             self.el_contracts
-                .register_bls_pub_key(&self.bls_keypair, self.chain_id)
+                .register_bls_pub_key(self.bls_key.keypair()?, self.chain_id)
                 .await?;
 
             self.el_contracts
@@ -178,6 +2755,16 @@ impl Operator {
         Ok(())
     }
 
+    /// Accounts of the operator and of the task manager roles.
+    pub(crate) async fn role_accounts(&self) -> eyre::Result<Vec<(&'static str, Address)>> {
+        let (aggregator, generator) = self.avs_contracts.aggregator_and_generator().await?;
+        Ok(vec![
+            ("operator", self.client.address()),
+            ("aggregator", aggregator),
+            ("generator", generator),
+        ])
+    }
+
     #[instrument(skip_all)]
     pub(crate) async fn opt_in_avs(&self) -> eyre::Result<()> {
         if self.avs_contracts.operator_id().await?.is_some() {
@@ -185,7 +2772,7 @@ impl Operator {
         } else {
             info!("Registering Operator {:x} with AVS", self.client.address());
             self.avs_contracts
-                .register_with_avs(&self.bls_keypair)
+                .register_with_avs(self.bls_key.public())
                 .await?;
             let id = self
                 .avs_contracts
@@ -197,11 +2784,57 @@ impl Operator {
         Ok(())
     }
 
+    /// Detects the roles the ECDSA key can act as on the AVS and EigenLayer contracts.
+    pub(crate) async fn capabilities(&self) -> eyre::Result<Capabilities> {
+        let account = self.client.address();
+        let (owner, operator, pauser, whitelister) = futures::try_join!(
+            self.avs_contracts.owner(),
+            self.el_contracts.is_operator_registered(account),
+            self.avs_contracts.is_pauser(account),
+            self.el_contracts.strategy_whitelister(),
+        )?;
+        let roles = [
+            (owner == account, Role::Owner),
+            (operator, Role::Operator),
+            (pauser, Role::Pauser),
+            (whitelister == account, Role::Whitelister),
+        ]
+        .into_iter()
+        .filter_map(|(has, role)| has.then_some(role))
+        .collect();
+        Ok(Capabilities { account, roles })
+    }
+
+    /// Broadcasts the intent to exit after `grace`. The task manager has no exit
+    /// announcement, only the aggregator is told.
+    async fn announce_exit(&self, operator_id: OperatorId, grace: Duration) -> eyre::Result<()> {
+        let intent = exit::sign(operator_id, self.chain_id, grace, self.client.signer()).await?;
+        let response = self.rpc.send_exit_intent(&intent).await?;
+        match response.error_for_status_ref() {
+            Ok(_) => info!("Intent to exit in {:?} sent to the aggregator", grace),
+            // exiting without the announcement only risks an ejection in the meantime
+            Err(e) => warn!(
+                "Aggregator rejected the intent to exit: {} - {}",
+                e,
+                response.text().await?
+            ),
+        }
+        Ok(())
+    }
+
+    /// Deregisters from the AVS. With a non zero `grace`, first broadcasts a signed intent to
+    /// exit to the aggregator and waits for the grace period, a running node keeps answering
+    /// tasks meanwhile.
     #[instrument(skip_all)]
-    pub(crate) async fn opt_out_avs(&self) -> eyre::Result<()> {
-        if self.avs_contracts.operator_id().await?.is_some() {
+    pub(crate) async fn opt_out_avs(&self, grace: Duration) -> eyre::Result<()> {
+        if let Some(operator_id) = self.avs_contracts.operator_id().await? {
+            if !grace.is_zero() {
+                self.announce_exit(operator_id, grace).await?;
+                info!("Waiting {:?} before deregistering", grace);
+                tokio::time::sleep(grace).await;
+            }
             self.avs_contracts
-                .deregister_with_avs(&self.bls_keypair)
+                .deregister_with_avs(self.bls_key.public())
                 .await?;
             info!("Operator opted out with AVS sucessfully");
         } else {
@@ -211,3 +2844,99 @@ impl Operator {
         Ok(())
     }
 }
+
+/// Roles sharing an account, which hides authorization bugs between them.
+pub(crate) fn shared_role_accounts(roles: &[(&str, Address)]) -> Vec<String> {
+    roles
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (role, address))| {
+            roles[i + 1..]
+                .iter()
+                .filter(move |(_, other)| other == address)
+                .map(move |(other_role, _)| {
+                    format!("{} and {} share account {:?}", role, other_role, address)
+                })
+        })
+        .collect()
+}
+
+#[test]
+fn test_shared_role_accounts() {
+    let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+    assert!(shared_role_accounts(&[("operator", a), ("staker", b)]).is_empty());
+    assert_eq!(
+        shared_role_accounts(&[("operator", a), ("staker", b), ("aggregator", a)]),
+        [format!("operator and aggregator share account {:?}", a)]
+    );
+}
+
+#[test]
+fn test_status_to_prometheus() {
+    let status = OperatorStatus {
+        node_id: "00000000-0000-4000-8000-000000000000".into(),
+        eth_address: Address::repeat_byte(1),
+        registered_with_eigen: true,
+        bls_key_registered: true,
+        bls_g1: Default::default(),
+        bls_g2: Default::default(),
+        registered_with_avs: false,
+        operator_id: None,
+        stake: StakeStatus {
+            strategy: Address::repeat_byte(2),
+            shares: U256::from(1_500_000),
+            underlying: units::TokenAmount {
+                token: Address::repeat_byte(3),
+                raw: U256::from(1_600_000),
+                decimals: 6,
+            },
+            weight: 1_500_000_000_000_000_000,
+        },
+        reputation: None,
+        features: vec![Feature {
+            name: "relayer",
+            compiled: true,
+            enabled: false,
+        }],
+    };
+    let text = status.to_prometheus().unwrap();
+    assert!(text.contains("operator_registered_with_eigen 1\n"));
+    assert!(text.contains("operator_registered_with_avs 0\n"));
+    assert!(text.contains(&format!(
+        "operator_info{{eth_address=\"{:?}\",node_id=\"{}\",operator_id=\"\"}} 1\n",
+        status.eth_address, status.node_id
+    )));
+    assert!(text.contains("operator_feature_enabled{compiled=\"true\",feature=\"relayer\"} 0\n"));
+    assert!(!text.contains("reputation_score"));
+
+    let printed = format!("{:?}", status.stake);
+    assert!(printed.contains("shares: 1.5,"), "{}", printed);
+    assert!(printed.contains("underlying: 1.6 of token"), "{}", printed);
+    assert!(printed.contains("weight: 1.5 "), "{}", printed);
+}
+
+#[test]
+fn test_status_sample_describe() {
+    let before = StatusSample {
+        eth_head: 100,
+        task_count: 40,
+        operator_stake: 1_000 * 10_u128.pow(18),
+        registered_with_avs: true,
+        lag_blocks: Some(2),
+    };
+    assert_eq!(
+        before.describe(None),
+        "head 100 | tasks 40 | stake 1000 | lag 2 blocks"
+    );
+    let after = StatusSample {
+        eth_head: 105,
+        task_count: 42,
+        operator_stake: 899_500_000_000_000_000_000,
+        registered_with_avs: false,
+        lag_blocks: Some(2),
+    };
+    assert_eq!(
+        after.describe(Some(&before)),
+        "head 105 | tasks 42 (+2) | stake 899.5 (-100.5) | lag 2 blocks | no longer registered with the AVS"
+    );
+}
@@ -0,0 +1,260 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use ethers::types::H256;
+use eyre::eyre;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, ChildStdout, Command},
+    sync::Mutex,
+};
+use tracing::{info, warn};
+
+use crate::metrics::metrics;
+
+/// Version of the plugin protocol, a plugin answering the handshake with another version is
+/// not loaded.
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+/// Task result submitted to the plugins before it is signed.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskReview {
+    pub task_index: u32,
+    pub block_number: u32,
+    pub block_hash: H256,
+    pub storage_proof_hash: H256,
+    pub quorum_numbers: Vec<u8>,
+}
+
+/// Answer of a plugin to a [`TaskReview`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Verdict {
+    pub accept: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Deployment specific score of the task, exported as the `plugin_task_score` metric
+    #[serde(default)]
+    pub score: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum Request<'a> {
+    Hello { api_version: u32 },
+    Review { task: &'a TaskReview },
+}
+
+#[derive(Deserialize)]
+struct Hello {
+    name: String,
+    version: String,
+    api_version: u32,
+}
+
+/// Deployment specific logic, e.g. scoring or extra validation rules, run as a separate
+/// executable so it cannot crash or block the operator. The plugin reads one JSON request
+/// per line on stdin and writes one JSON answer per line on stdout, starting with a
+/// `hello` handshake. It runs without the operator environment and is restarted after an
+/// error or a timeout.
+#[derive(Debug)]
+pub struct Plugin {
+    path: PathBuf,
+    timeout: Duration,
+    process: Mutex<Option<PluginProcess>>,
+}
+
+#[derive(Debug)]
+struct PluginProcess {
+    name: String,
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl PluginProcess {
+    async fn call<T: DeserializeOwned>(
+        &mut self,
+        request: &Request<'_>,
+        timeout: Duration,
+    ) -> eyre::Result<T> {
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        let exchange = async {
+            self.stdin.write_all(&line).await?;
+            self.stdin.flush().await?;
+            let mut answer = String::new();
+            if self.stdout.read_line(&mut answer).await? == 0 {
+                return Err(eyre!("plugin exited"));
+            }
+            Ok(serde_json::from_str(&answer)?)
+        };
+        tokio::time::timeout(timeout, exchange)
+            .await
+            .map_err(|_| eyre!("plugin did not answer within {:?}", timeout))?
+    }
+}
+
+impl Plugin {
+    pub fn new(path: &Path, timeout: Duration) -> Self {
+        Self {
+            path: path.to_owned(),
+            timeout,
+            process: Mutex::new(None),
+        }
+    }
+
+    async fn spawn(&self) -> eyre::Result<PluginProcess> {
+        let mut child = Command::new(&self.path)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| eyre!("cannot start plugin {}: {}", self.path.display(), e))?;
+        let mut process = PluginProcess {
+            name: self.path.display().to_string(),
+            stdin: child.stdin.take().expect("piped stdin"),
+            stdout: BufReader::new(child.stdout.take().expect("piped stdout")),
+            _child: child,
+        };
+        let hello: Hello = process
+            .call(
+                &Request::Hello {
+                    api_version: PLUGIN_API_VERSION,
+                },
+                self.timeout,
+            )
+            .await?;
+        if hello.api_version != PLUGIN_API_VERSION {
+            return Err(eyre!(
+                "plugin {} speaks version {} of the plugin protocol, expected {}",
+                hello.name,
+                hello.api_version,
+                PLUGIN_API_VERSION
+            ));
+        }
+        info!(
+            "Loaded plugin {} {} from {}",
+            hello.name,
+            hello.version,
+            self.path.display()
+        );
+        process.name = hello.name;
+        Ok(process)
+    }
+
+    /// Name the plugin reported in its handshake, its path until it is started.
+    pub async fn name(&self) -> String {
+        match &*self.process.lock().await {
+            Some(process) => process.name.clone(),
+            None => self.path.display().to_string(),
+        }
+    }
+
+    pub async fn review(&self, task: &TaskReview) -> eyre::Result<Verdict> {
+        let mut process = self.process.lock().await;
+        if process.is_none() {
+            *process = Some(self.spawn().await?);
+        }
+        let running = process.as_mut().expect("spawned above");
+        let res = running
+            .call::<Verdict>(&Request::Review { task }, self.timeout)
+            .await;
+        match res {
+            Ok(verdict) => {
+                if let Some(score) = verdict.score {
+                    metrics()
+                        .plugin_task_score
+                        .with_label_values(&[&running.name])
+                        .set(score);
+                }
+                Ok(verdict)
+            }
+            Err(e) => {
+                // the process is killed on drop and restarted on the next review
+                *process = None;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Plugins loaded for the deployment, all must accept a task for it to be signed.
+#[derive(Debug, Default)]
+pub struct Plugins(Vec<Plugin>);
+
+impl Plugins {
+    pub fn load(paths: &[PathBuf], timeout: Duration) -> Self {
+        Self(
+            paths
+                .iter()
+                .map(|path| Plugin::new(path, timeout))
+                .collect(),
+        )
+    }
+
+    /// Reason of the first plugin rejecting `task`, `None` when all accept it. A failing
+    /// plugin rejects the task.
+    pub async fn review(&self, task: &TaskReview) -> Option<String> {
+        for plugin in &self.0 {
+            match plugin.review(task).await {
+                Ok(verdict) if verdict.accept => continue,
+                Ok(verdict) => {
+                    return Some(format!(
+                        "rejected by plugin {}: {}",
+                        plugin.name().await,
+                        verdict.reason.as_deref().unwrap_or("no reason given")
+                    ))
+                }
+                Err(e) => {
+                    warn!("Plugin {} failed: {:?}", plugin.name().await, e);
+                    return Some(format!("plugin {} failed: {}", plugin.name().await, e));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_plugin_protocol() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::temp_dir().join(format!("plugin-test-{}.sh", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"#!/bin/sh
+while read line; do
+  case "$line" in
+    *hello*) echo '{"name":"odd-blocks","version":"0.1.0","api_version":1}' ;;
+    *'"block_number":7'*) echo '{"accept":false,"reason":"odd block"}' ;;
+    *) echo '{"accept":true,"score":0.5}' ;;
+  esac
+done
+"#,
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let task = |block_number| TaskReview {
+        task_index: 1,
+        block_number,
+        block_hash: H256::zero(),
+        storage_proof_hash: H256::zero(),
+        quorum_numbers: vec![0],
+    };
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let plugins = Plugins::load(std::slice::from_ref(&path), Duration::from_secs(5));
+    assert_eq!(runtime.block_on(plugins.review(&task(8))), None);
+    assert_eq!(
+        runtime.block_on(plugins.review(&task(7))),
+        Some("rejected by plugin odd-blocks: odd block".into())
+    );
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    sync::{
+        atomic::{AtomicU8, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tracing::{debug, instrument, warn};
+
+use crate::{cli::PressureArgs, metrics::metrics};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// Fraction of a limit above which the pressure is elevated.
+const ELEVATED_RATIO: f64 = 0.8;
+/// Clock ticks per second of `/proc/self/stat` times, fixed on Linux.
+const CLOCK_TICKS: f64 = 100.0;
+const PAGE_SIZE: u64 = 4096;
+/// Window over which the verification throughput is measured.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(600);
+
+/// Resource pressure driving load-shedding: under `Elevated` pressure periodic chores are
+/// skipped and batch intervals stretched, under `Critical` pressure catch-up of missed tasks
+/// pauses as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PressureLevel {
+    Normal = 0,
+    Elevated = 1,
+    Critical = 2,
+}
+
+impl PressureLevel {
+    fn from_u8(level: u8) -> Self {
+        match level {
+            0 => Self::Normal,
+            1 => Self::Elevated,
+            _ => Self::Critical,
+        }
+    }
+}
+
+/// Demand on the task verification, for an autoscaler (KEDA, HPA) to scale verifier workers
+/// on. `load` is 1 when the backlog drains in `--autoscale-target-drain-secs` at the
+/// measured throughput, scaling to `ceil(replicas * load)` keeps it there.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct AutoscaleSignal {
+    pub task_backlog: usize,
+    /// Tasks verified per minute over the last ten minutes
+    pub throughput_per_minute: f64,
+    /// Time to verify the backlog at that throughput, `None` without any throughput yet
+    pub drain_secs: Option<f64>,
+    pub load: f64,
+}
+
+impl AutoscaleSignal {
+    pub fn new(
+        task_backlog: usize,
+        verified: usize,
+        window: Duration,
+        target_drain: Duration,
+    ) -> Self {
+        let throughput_per_minute = verified as f64 * 60.0 / window.as_secs_f64();
+        let drain_secs = (throughput_per_minute > 0.0)
+            .then(|| task_backlog as f64 * 60.0 / throughput_per_minute);
+        let load = match drain_secs {
+            Some(secs) => secs / target_drain.as_secs_f64(),
+            // nothing verified lately, each waiting task asks for a worker
+            None => task_backlog as f64,
+        };
+        Self {
+            task_backlog,
+            throughput_per_minute,
+            drain_secs,
+            load,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ResourceSample {
+    pub memory_bytes: Option<u64>,
+    pub cpu_pct: Option<f64>,
+    pub task_backlog: usize,
+}
+
+/// Tracks the task backlog and periodically samples process resources against the limits.
+#[derive(Debug)]
+pub struct Pressure {
+    limits: PressureArgs,
+    level: AtomicU8,
+    /// Level of the memory and CPU alone, without the task backlog
+    resource_level: AtomicU8,
+    backlog: AtomicUsize,
+    /// When the tasks of the throughput window were verified
+    verified: Mutex<VecDeque<Instant>>,
+}
+
+impl Pressure {
+    pub fn new(limits: PressureArgs) -> Self {
+        Self {
+            limits,
+            level: AtomicU8::new(PressureLevel::Normal as u8),
+            resource_level: AtomicU8::new(PressureLevel::Normal as u8),
+            backlog: AtomicUsize::new(0),
+            verified: Mutex::default(),
+        }
+    }
+
+    pub fn limits(&self) -> &PressureArgs {
+        &self.limits
+    }
+
+    pub fn level(&self) -> PressureLevel {
+        PressureLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    /// Pressure of the memory and CPU, ignoring the task backlog.
+    pub fn resource_level(&self) -> PressureLevel {
+        PressureLevel::from_u8(self.resource_level.load(Ordering::Relaxed))
+    }
+
+    /// `interval` doubled per pressure level, for batches which can wait under pressure.
+    pub fn stretch(&self, interval: Duration) -> Duration {
+        interval * (1 << self.level() as u32)
+    }
+
+    pub fn task_queued(&self) {
+        self.backlog.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn task_dequeued(&self) {
+        self.backlog.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Counts a task verified, live or caught up, towards the throughput.
+    pub fn task_verified(&self) {
+        self.verified
+            .lock()
+            .expect("poisoned lock")
+            .push_back(Instant::now());
+    }
+
+    fn autoscale_signal(&self, task_backlog: usize) -> AutoscaleSignal {
+        let mut verified = self.verified.lock().expect("poisoned lock");
+        while verified
+            .front()
+            .is_some_and(|at| at.elapsed() > THROUGHPUT_WINDOW)
+        {
+            verified.pop_front();
+        }
+        AutoscaleSignal::new(
+            task_backlog,
+            verified.len(),
+            THROUGHPUT_WINDOW,
+            Duration::from_secs(self.limits.autoscale_target_drain_secs),
+        )
+    }
+
+    /// Waits until the memory and CPU pressure drops below `level`. The task backlog is
+    /// ignored, the waiting caller may be what holds it back.
+    pub async fn wait_resources_below(&self, level: PressureLevel) {
+        while self.resource_level() >= level {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+        }
+    }
+
+    /// Samples resources every few seconds, updating the pressure level and its metrics, and
+    /// hands each autoscaling signal to `on_signal`.
+    #[instrument(skip_all)]
+    pub async fn watch(&self, on_signal: impl Fn(AutoscaleSignal)) -> eyre::Result<()> {
+        let mut cpu = CpuSampler::default();
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let sample = ResourceSample {
+                memory_bytes: memory_bytes(),
+                cpu_pct: cpu.sample(),
+                task_backlog: self.backlog.load(Ordering::Relaxed),
+            };
+            let level = level_of(&sample, &self.limits);
+            let resource_level = level_of(
+                &ResourceSample {
+                    task_backlog: 0,
+                    ..sample
+                },
+                &self.limits,
+            );
+            let previous = self.level();
+            self.level.store(level as u8, Ordering::Relaxed);
+            self.resource_level
+                .store(resource_level as u8, Ordering::Relaxed);
+
+            let m = metrics();
+            m.pressure_level.set(level as i64);
+            m.task_backlog.set(sample.task_backlog as i64);
+            if let Some(memory) = sample.memory_bytes {
+                m.memory_bytes.set(memory as i64);
+            }
+            let signal = self.autoscale_signal(sample.task_backlog);
+            m.task_throughput.set(signal.throughput_per_minute);
+            m.autoscaling_load.set(signal.load);
+            on_signal(signal);
+            if level != previous {
+                warn!(
+                    "Resource pressure {:?} -> {:?}: {:?}",
+                    previous, level, sample
+                );
+            } else {
+                debug!("Resource pressure {:?}: {:?}", level, sample);
+            }
+        }
+    }
+}
+
+fn level_of(sample: &ResourceSample, limits: &PressureArgs) -> PressureLevel {
+    let ratios = [
+        sample
+            .memory_bytes
+            .zip(limits.max_memory_mb)
+            .map(|(used, max)| used as f64 / (max * 1024 * 1024) as f64),
+        sample
+            .cpu_pct
+            .zip(limits.max_cpu_pct)
+            .map(|(used, max)| used / max),
+        Some(sample.task_backlog as f64 / limits.max_task_backlog as f64),
+    ];
+    let ratio = ratios.into_iter().flatten().fold(0.0, f64::max);
+    if ratio >= 1.0 {
+        PressureLevel::Critical
+    } else if ratio >= ELEVATED_RATIO {
+        PressureLevel::Elevated
+    } else {
+        PressureLevel::Normal
+    }
+}
+
+/// Resident set size of the process, only available on Linux.
+fn memory_bytes() -> Option<u64> {
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let resident: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(resident * PAGE_SIZE)
+}
+
+/// CPU usage of the process since the previous sample, as a percentage of one core.
+#[derive(Default)]
+struct CpuSampler {
+    last: Option<(Instant, f64)>,
+}
+
+impl CpuSampler {
+    fn sample(&mut self) -> Option<f64> {
+        let now = (Instant::now(), cpu_seconds()?);
+        let pct = self.last.map(|(at, secs)| {
+            (now.1 - secs) * 100.0 / now.0.duration_since(at).as_secs_f64().max(f64::EPSILON)
+        });
+        self.last = Some(now);
+        pct
+    }
+}
+
+fn cpu_seconds() -> Option<f64> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // fields after the parenthesized command name, utime and stime are the 14th and 15th
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: f64 = fields.get(11)?.parse().ok()?;
+    let stime: f64 = fields.get(12)?.parse().ok()?;
+    Some((utime + stime) / CLOCK_TICKS)
+}
+
+#[test]
+fn test_level_of() {
+    let limits = PressureArgs {
+        max_memory_mb: Some(100),
+        max_cpu_pct: None,
+        max_task_backlog: 10,
+        task_queue_capacity: 10,
+        task_queue_drop_policy: crate::queue::DropPolicy::Block,
+        autoscale_target_drain_secs: 60,
+    };
+    let sample = |memory_mb: u64, task_backlog| ResourceSample {
+        memory_bytes: Some(memory_mb * 1024 * 1024),
+        cpu_pct: Some(1000.0),
+        task_backlog,
+    };
+    assert_eq!(level_of(&sample(10, 0), &limits), PressureLevel::Normal);
+    assert_eq!(level_of(&sample(85, 0), &limits), PressureLevel::Elevated);
+    assert_eq!(level_of(&sample(10, 10), &limits), PressureLevel::Critical);
+}
+
+#[test]
+fn test_autoscale_signal() {
+    let window = Duration::from_secs(600);
+    let target = Duration::from_secs(60);
+    assert_eq!(AutoscaleSignal::new(0, 0, window, target).load, 0.0);
+    assert_eq!(AutoscaleSignal::new(3, 0, window, target).load, 3.0);
+    // 2 tasks a minute drain 4 tasks in 2 minutes, twice the target
+    let signal = AutoscaleSignal::new(4, 20, window, target);
+    assert_eq!(signal.throughput_per_minute, 2.0);
+    assert_eq!(signal.drain_secs, Some(120.0));
+    assert_eq!(signal.load, 2.0);
+}
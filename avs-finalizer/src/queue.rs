@@ -0,0 +1,127 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use crate::metrics::metrics;
+
+/// What a full [`BoundedQueue`] does with a new item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum DropPolicy {
+    /// Wait for room, slowing down the producer
+    Block,
+    /// Drop the new item
+    DropNewest,
+    /// Drop the oldest queued item, the closest to its deadline
+    DropOldest,
+}
+
+/// Bounded queue between two pipeline stages, its depth and drops are exported as the
+/// `queue_depth` and `queue_dropped_total` metrics labelled with its name.
+#[derive(Debug)]
+pub struct BoundedQueue<T> {
+    name: &'static str,
+    capacity: usize,
+    policy: DropPolicy,
+    items: Mutex<VecDeque<T>>,
+    not_empty: Notify,
+    not_full: Notify,
+}
+
+impl<T> BoundedQueue<T> {
+    pub fn new(name: &'static str, capacity: usize, policy: DropPolicy) -> Self {
+        Self {
+            name,
+            capacity: capacity.max(1),
+            policy,
+            items: Mutex::new(VecDeque::with_capacity(capacity)),
+            not_empty: Notify::new(),
+            not_full: Notify::new(),
+        }
+    }
+
+    /// Queues `item`, returning the item dropped to respect the capacity if any.
+    pub async fn push(&self, item: T) -> Option<T> {
+        let mut item = Some(item);
+        loop {
+            let not_full = self.not_full.notified();
+            {
+                let mut items = self.items.lock().expect("poisoned lock");
+                let dropped = if items.len() < self.capacity {
+                    None
+                } else {
+                    match self.policy {
+                        DropPolicy::Block => None,
+                        DropPolicy::DropNewest => item.take(),
+                        DropPolicy::DropOldest => items.pop_front(),
+                    }
+                };
+                if items.len() < self.capacity {
+                    items.extend(item.take());
+                }
+                if item.is_none() {
+                    self.queued(items.len(), dropped.is_some());
+                    return dropped;
+                }
+            }
+            not_full.await;
+        }
+    }
+
+    /// Waits for and removes the oldest item.
+    pub async fn pop(&self) -> T {
+        loop {
+            let not_empty = self.not_empty.notified();
+            {
+                let mut items = self.items.lock().expect("poisoned lock");
+                if let Some(item) = items.pop_front() {
+                    metrics()
+                        .queue_depth
+                        .with_label_values(&[self.name])
+                        .set(items.len() as i64);
+                    self.not_full.notify_one();
+                    return item;
+                }
+            }
+            not_empty.await;
+        }
+    }
+
+    fn queued(&self, len: usize, dropped: bool) {
+        let m = metrics();
+        m.queue_depth
+            .with_label_values(&[self.name])
+            .set(len as i64);
+        if dropped {
+            m.queue_dropped.with_label_values(&[self.name]).inc();
+        }
+        self.not_empty.notify_one();
+    }
+}
+
+#[test]
+fn test_drop_policies() {
+    use futures::executor::block_on;
+
+    let oldest = BoundedQueue::new("test_oldest", 2, DropPolicy::DropOldest);
+    assert_eq!(block_on(oldest.push(1)), None);
+    assert_eq!(block_on(oldest.push(2)), None);
+    assert_eq!(block_on(oldest.push(3)), Some(1));
+    assert_eq!(block_on(oldest.pop()), 2);
+
+    let newest = BoundedQueue::new("test_newest", 1, DropPolicy::DropNewest);
+    assert_eq!(block_on(newest.push(1)), None);
+    assert_eq!(block_on(newest.push(2)), Some(2));
+    assert_eq!(block_on(newest.pop()), 1);
+
+    let block = std::sync::Arc::new(BoundedQueue::new("test_block", 1, DropPolicy::Block));
+    assert_eq!(block_on(block.push(1)), None);
+    let producer = {
+        let block = block.clone();
+        std::thread::spawn(move || block_on(block.push(2)))
+    };
+    assert_eq!(block_on(block.pop()), 1);
+    assert_eq!(producer.join().unwrap(), None);
+    assert_eq!(block_on(block.pop()), 2);
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use node_primitives::BlockNumber;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::{cli::SubstrateQuotaArgs, metrics::metrics};
+
+static QUOTA: OnceLock<SubstrateQuota> = OnceLock::new();
+
+/// Spec version of the runtime which executed the last block, 0 before the first one.
+static SPEC_VERSION: AtomicU32 = AtomicU32::new(0);
+
+/// Usage collected in a verification worker since its last reply, `None` in the node.
+static WORKER_USAGE: Mutex<Option<SubstrateUsage>> = Mutex::new(None);
+
+/// Period over which the substrate traffic is accounted, as providers bill it.
+const WINDOW: Duration = Duration::from_secs(3600);
+
+/// Process wide accounting of the traffic to the substrate nodes, set up with
+/// [`SubstrateQuota::configure`] and unlimited until then.
+pub fn quota() -> &'static SubstrateQuota {
+    QUOTA.get_or_init(|| SubstrateQuota::new(SubstrateQuotaArgs::default()))
+}
+
+/// Substrate traffic and runtime version of the verifications of a worker, sent back to the
+/// node with its reply as the metrics and quota of a worker process are not the node's.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubstrateUsage {
+    /// Requests per JSON-RPC method
+    pub calls: BTreeMap<String, u64>,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Spec version and name of the runtime which executed the block, when it was read
+    pub runtime: Option<(u32, String)>,
+}
+
+/// Collects the substrate usage of this process for [`take_usage`] instead of accounting
+/// it, in verification workers.
+pub fn collect_usage() {
+    *WORKER_USAGE.lock().expect("poisoned lock") = Some(SubstrateUsage::default());
+}
+
+/// Usage collected since the previous call.
+pub fn take_usage() -> SubstrateUsage {
+    WORKER_USAGE
+        .lock()
+        .expect("poisoned lock")
+        .as_mut()
+        .map(std::mem::take)
+        .unwrap_or_default()
+}
+
+/// Accounts the usage of a worker verifying `block` as if the node made the calls.
+pub fn account_usage(usage: &SubstrateUsage, block: BlockNumber) {
+    for (method, calls) in &usage.calls {
+        metrics().record_rpc_calls("substrate", method, *calls);
+    }
+    quota().record(
+        Instant::now(),
+        usage.calls.values().sum(),
+        usage.bytes_sent,
+        usage.bytes_received,
+    );
+    if let Some((spec_version, spec_name)) = &usage.runtime {
+        observe_runtime(*spec_version, spec_name, block);
+    }
+}
+
+/// Applies `f` to the usage being collected, returns `false` in the node.
+fn collected(f: impl FnOnce(&mut SubstrateUsage)) -> bool {
+    match WORKER_USAGE.lock().expect("poisoned lock").as_mut() {
+        Some(usage) => {
+            f(usage);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Counts a request to the substrate node with the JSON-RPC `params` it sends.
+pub fn record_substrate_call(method: &str, params: Value) {
+    let request = json!({ "jsonrpc": "2.0", "id": 0, "method": method, "params": params });
+    let sent = request.to_string().len() as u64;
+    let collected = collected(|usage| {
+        *usage.calls.entry(method.to_owned()).or_default() += 1;
+        usage.bytes_sent += sent;
+    });
+    if !collected {
+        metrics().record_rpc_call("substrate", method);
+        quota().record(Instant::now(), 1, sent, 0);
+    }
+}
+
+/// Counts the bytes of a response from the substrate node, where its size is known.
+pub fn record_substrate_response(bytes: usize) {
+    if !collected(|usage| usage.bytes_received += bytes as u64) {
+        quota().record(Instant::now(), 0, 0, bytes as u64);
+    }
+}
+
+/// Records the version of the runtime which executed `block`, read from its parent state.
+pub fn record_runtime_version(spec_version: u32, spec_name: &str, block: BlockNumber) {
+    if !collected(|usage| usage.runtime = Some((spec_version, spec_name.to_owned()))) {
+        observe_runtime(spec_version, spec_name, block);
+    }
+}
+
+fn observe_runtime(spec_version: u32, spec_name: &str, block: BlockNumber) {
+    metrics().substrate_spec_version.set(spec_version.into());
+    let previous = SPEC_VERSION.swap(spec_version, Ordering::Relaxed);
+    if previous != 0 && previous != spec_version {
+        info!(
+            "Runtime upgraded from spec version {} to {} of {}, executed block {} with the new code",
+            previous, spec_version, spec_name, block
+        );
+    }
+}
+
+/// Requests and bytes exchanged with the substrate nodes in the current hour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct QuotaWindow {
+    started: Instant,
+    requests: u64,
+    bytes_sent: u64,
+    /// Only the fetched blocks, the state downloaded to execute them is not measured
+    bytes_received: u64,
+}
+
+impl QuotaWindow {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            requests: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+        }
+    }
+
+    /// Starts a new window once the hour elapsed, returns whether it did.
+    fn roll(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.started) < WINDOW {
+            return false;
+        }
+        *self = Self::new(now);
+        true
+    }
+
+    fn exhausted(&self, limits: &SubstrateQuotaArgs) -> bool {
+        let over = |used, limit: Option<u64>| limit.is_some_and(|limit| used >= limit);
+        over(self.requests, limits.substrate_max_requests_per_hour)
+            || over(
+                self.bytes_sent + self.bytes_received,
+                limits.substrate_max_bytes_per_hour,
+            )
+    }
+}
+
+/// Hourly request and bandwidth quotas of the substrate node provider. Once a quota is used
+/// up, work that can be skipped such as preparing blocks ahead of tasks stops until the next
+/// hour, tasks are still verified since missing them costs more than the overage.
+#[derive(Debug)]
+pub struct SubstrateQuota {
+    limits: SubstrateQuotaArgs,
+    window: Mutex<QuotaWindow>,
+}
+
+impl SubstrateQuota {
+    fn new(limits: SubstrateQuotaArgs) -> Self {
+        Self {
+            limits,
+            window: Mutex::new(QuotaWindow::new(Instant::now())),
+        }
+    }
+
+    /// Sets the quotas, fails if traffic was accounted against other quotas already.
+    pub fn configure(limits: SubstrateQuotaArgs) -> eyre::Result<()> {
+        QUOTA
+            .set(Self::new(limits))
+            .map_err(|_| eyre::eyre!("substrate quotas are already set"))
+    }
+
+    fn record(&self, now: Instant, requests: u64, sent: u64, received: u64) {
+        let mut window = self.window.lock().expect("poisoned lock");
+        if window.roll(now) {
+            metrics().substrate_quota_exhausted.set(0);
+        }
+        let was_exhausted = window.exhausted(&self.limits);
+        window.requests += requests;
+        window.bytes_sent += sent;
+        window.bytes_received += received;
+        metrics().substrate_bytes_sent.inc_by(sent);
+        metrics().substrate_bytes_received.inc_by(received);
+        if !was_exhausted && window.exhausted(&self.limits) {
+            warn!(
+                "Substrate quota used up with {} requests and {} bytes this hour, blocks are no longer prepared ahead of tasks",
+                window.requests,
+                window.bytes_sent + window.bytes_received
+            );
+            metrics().substrate_quota_exhausted.set(1);
+        }
+    }
+
+    /// Whether optional substrate traffic should wait for the next hour.
+    pub fn exhausted(&self) -> bool {
+        let mut window = self.window.lock().expect("poisoned lock");
+        window.roll(Instant::now());
+        window.exhausted(&self.limits)
+    }
+}
+
+#[test]
+fn test_quota_window() {
+    let start = Instant::now();
+    let quota = SubstrateQuota {
+        limits: SubstrateQuotaArgs {
+            substrate_max_requests_per_hour: Some(2),
+            substrate_max_bytes_per_hour: Some(1_000),
+        },
+        window: Mutex::new(QuotaWindow::new(start)),
+    };
+    quota.record(start, 1, 100, 0);
+    assert!(!quota.exhausted());
+    quota.record(start, 0, 0, 900);
+    assert!(quota.exhausted());
+
+    let usage = *quota.window.lock().unwrap();
+    assert_eq!((usage.requests, usage.bytes_received), (1, 900));
+
+    // the next hour starts from zero
+    quota.record(start + WINDOW, 1, 100, 0);
+    let usage = *quota.window.lock().unwrap();
+    assert_eq!(
+        (usage.requests, usage.bytes_sent, usage.bytes_received),
+        (1, 100, 0)
+    );
+    assert!(!quota.exhausted());
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::cli::RelayerArgs;
+
+/// Body of a relay request, the signed response as encoded for the aggregator.
+#[derive(Debug, Serialize)]
+struct RelayRequest {
+    task_index: u32,
+    response: serde_json::Value,
+}
+
+/// Acknowledgement of a relayed response, `id` identifies it with the relayer.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RelayAck {
+    pub id: String,
+}
+
+/// External service submitting signed task responses and paying the gas on behalf of the
+/// operator.
+#[derive(Debug)]
+pub struct Relayer {
+    url: String,
+    token: Option<String>,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl Relayer {
+    /// The configured relayer, `None` without `--relayer-url`.
+    pub fn from_cli(cfg: &RelayerArgs) -> Option<Self> {
+        cfg.relayer_url.as_ref().map(|url| Self {
+            url: url.trim_end_matches('/').to_owned(),
+            token: cfg.relayer_token.clone(),
+            timeout: Duration::from_millis(cfg.relayer_timeout_ms),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Hands the signed response to task `task_index` to the relayer, which acknowledges it
+    /// once it took over its submission.
+    #[instrument(skip(self, json))]
+    pub async fn relay(&self, task_index: u32, json: &str) -> eyre::Result<RelayAck> {
+        let mut req = self
+            .client
+            .post(format!("{}/responses", self.url))
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&RelayRequest {
+                task_index,
+                response: serde_json::from_str(json)?,
+            })?);
+        if let Some(token) = &self.token {
+            req = req.bearer_auth(token);
+        }
+        let res = req.send().await?;
+        let status = res.status();
+        let body = res.text().await?;
+        if !status.is_success() {
+            return Err(eyre!("relayer replied {}: {}", status, body));
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+}
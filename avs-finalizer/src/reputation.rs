@@ -0,0 +1,266 @@
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, Signature},
+};
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::bn254::OperatorId;
+use crate::store::TaskOutcome;
+
+/// Performance of the operator over a rolling window of received tasks.
+///
+/// The task manager has no challenge mechanism, tasks whose accepted response differs
+/// from the local result are counted as `divergences` instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reputation {
+    pub window_secs: u64,
+    pub tasks: u32,
+    pub responded: u32,
+    /// responded / tasks, 0 when no task was received in the window
+    pub response_rate: f64,
+    pub avg_latency_ms: Option<u64>,
+    pub divergences: u32,
+    /// 100 × response_rate × (1 − divergences / responded), rounded to two decimals
+    pub score: f64,
+}
+
+/// Running totals of a set of outcomes.
+#[derive(Debug, Default, Clone, Copy)]
+struct Counters {
+    tasks: u32,
+    responded: u32,
+    divergences: u32,
+    latency_sum_ms: u64,
+    latencies: u64,
+}
+
+impl Counters {
+    fn add(&mut self, outcome: &TaskOutcome) {
+        self.apply(outcome, 1);
+    }
+
+    fn remove(&mut self, outcome: &TaskOutcome) {
+        self.apply(outcome, -1);
+    }
+
+    fn apply(&mut self, outcome: &TaskOutcome, sign: i8) {
+        let step = |n: &mut u32, on: bool| {
+            if on {
+                *n = if sign > 0 {
+                    *n + 1
+                } else {
+                    n.saturating_sub(1)
+                };
+            }
+        };
+        step(&mut self.tasks, true);
+        step(&mut self.responded, outcome.responded);
+        step(&mut self.divergences, outcome.responded && outcome.diverged);
+        if let (true, Some(latency_ms)) = (outcome.responded, outcome.latency_ms) {
+            if sign > 0 {
+                self.latency_sum_ms += latency_ms;
+                self.latencies += 1;
+            } else {
+                self.latency_sum_ms = self.latency_sum_ms.saturating_sub(latency_ms);
+                self.latencies = self.latencies.saturating_sub(1);
+            }
+        }
+    }
+
+    fn reputation(&self, window_secs: u64) -> Reputation {
+        let ratio = |n: u32, d: u32| if d == 0 { 0.0 } else { n as f64 / d as f64 };
+        let response_rate = ratio(self.responded, self.tasks);
+        let divergence_rate = ratio(self.divergences, self.responded);
+        let score = (100.0 * response_rate * (1.0 - divergence_rate) * 100.0).round() / 100.0;
+
+        Reputation {
+            window_secs,
+            tasks: self.tasks,
+            responded: self.responded,
+            response_rate,
+            avg_latency_ms: self.latency_sum_ms.checked_div(self.latencies),
+            divergences: self.divergences,
+            score,
+        }
+    }
+}
+
+/// Reputation over the rolling window kept up to date as outcomes are recorded, rather than
+/// recounted from the store for every task.
+#[derive(Debug)]
+pub struct RollingReputation {
+    window_secs: u64,
+    /// Outcomes in the window by task index, which grows with time
+    outcomes: BTreeMap<u32, TaskOutcome>,
+    counters: Counters,
+}
+
+impl RollingReputation {
+    /// Starts from the `outcomes` already in the window, as read from the store.
+    pub fn new(window_secs: u64, outcomes: Vec<TaskOutcome>) -> Self {
+        let mut rolling = Self {
+            window_secs,
+            outcomes: BTreeMap::new(),
+            counters: Counters::default(),
+        };
+        for outcome in outcomes {
+            rolling.record(outcome);
+        }
+        rolling
+    }
+
+    /// Adds `outcome`, replacing the previous outcome of its task.
+    pub fn record(&mut self, outcome: TaskOutcome) {
+        self.counters.add(&outcome);
+        if let Some(previous) = self.outcomes.insert(outcome.task_index, outcome) {
+            self.counters.remove(&previous);
+        }
+    }
+
+    pub fn mark_diverged(&mut self, task_index: u32) {
+        if let Some(outcome) = self.outcomes.get_mut(&task_index) {
+            self.counters.remove(outcome);
+            outcome.diverged = true;
+            self.counters.add(outcome);
+        }
+    }
+
+    /// Reputation over the window ending at `now`, forgetting the outcomes that left it.
+    pub fn reputation(&mut self, now: u64) -> Reputation {
+        let since = now.saturating_sub(self.window_secs);
+        while let Some(entry) = self.outcomes.first_entry() {
+            if entry.get().received_at >= since {
+                break;
+            }
+            self.counters.remove(&entry.remove());
+        }
+        self.counters.reputation(self.window_secs)
+    }
+}
+
+/// Reputation of an operator as attested by its ECDSA key.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttestedReputation {
+    pub eth_address: Address,
+    pub operator_id: OperatorId,
+    pub issued_at: u64,
+    pub reputation: Reputation,
+}
+
+/// Published attestation, `attestation` holds the exact JSON that was EIP-191 signed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReputationAttestation {
+    pub attestation: String,
+    pub signature: Signature,
+}
+
+pub async fn attest(
+    reputation: Reputation,
+    operator_id: OperatorId,
+    wallet: &LocalWallet,
+) -> eyre::Result<ReputationAttestation> {
+    let attestation = serde_json::to_string(&AttestedReputation {
+        eth_address: wallet.address(),
+        operator_id,
+        issued_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        reputation,
+    })?;
+    let signature = wallet.sign_message(&attestation).await?;
+    Ok(ReputationAttestation {
+        attestation,
+        signature,
+    })
+}
+
+/// Checks that the attestation was signed by the address it names. Whether that address
+/// is a registered operator must be checked on-chain.
+pub fn verify(attestation: &ReputationAttestation) -> eyre::Result<AttestedReputation> {
+    let attested: AttestedReputation = serde_json::from_str(&attestation.attestation)?;
+    attestation
+        .signature
+        .verify(attestation.attestation.as_str(), attested.eth_address)
+        .map_err(|e| eyre!("invalid reputation attestation signature: {}", e))?;
+    Ok(attested)
+}
+
+#[test]
+fn test_compute_reputation() {
+    let outcome = |task_index, responded, latency_ms, diverged| TaskOutcome {
+        task_index,
+        received_at: 0,
+        responded,
+        latency_ms,
+        diverged,
+    };
+    let outcomes = [
+        outcome(1, true, Some(100), false),
+        outcome(2, true, Some(300), true),
+        outcome(3, false, None, false),
+        outcome(4, true, None, false),
+    ];
+    let reputation = RollingReputation::new(60, outcomes.to_vec()).reputation(0);
+    assert_eq!(reputation.tasks, 4);
+    assert_eq!(reputation.responded, 3);
+    assert_eq!(reputation.avg_latency_ms, Some(200));
+    assert_eq!(reputation.divergences, 1);
+    assert_eq!(reputation.score, 50.0);
+
+    let empty = RollingReputation::new(60, vec![]).reputation(0);
+    assert_eq!(
+        (empty.response_rate, empty.avg_latency_ms, empty.score),
+        (0.0, None, 0.0)
+    );
+}
+
+#[test]
+fn test_rolling_reputation() {
+    let outcome = |task_index, received_at, responded| TaskOutcome {
+        task_index,
+        received_at,
+        responded,
+        latency_ms: responded.then_some(100),
+        diverged: false,
+    };
+    let mut rolling = RollingReputation::new(60, vec![outcome(1, 10, true)]);
+    rolling.record(outcome(2, 20, false));
+    rolling.record(outcome(3, 30, true));
+    rolling.mark_diverged(3);
+    let reputation = rolling.reputation(70);
+    assert_eq!(
+        (
+            reputation.tasks,
+            reputation.responded,
+            reputation.divergences
+        ),
+        (3, 2, 1)
+    );
+    assert_eq!(reputation.avg_latency_ms, Some(100));
+    // replaces the outcome of a task verified again
+    rolling.record(outcome(2, 20, true));
+    assert_eq!(rolling.reputation(70).responded, 3);
+    // task 1 left the window
+    let reputation = rolling.reputation(75);
+    assert_eq!((reputation.tasks, reputation.divergences), (2, 1));
+    assert_eq!(rolling.reputation(200).tasks, 0);
+}
+
+#[tokio::test]
+async fn test_attest_verify() {
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+    let reputation = RollingReputation::new(60, vec![]).reputation(0);
+    let mut signed = attest(reputation.clone(), OperatorId::random(), &wallet)
+        .await
+        .unwrap();
+    let attested = verify(&signed).unwrap();
+    assert_eq!(attested.eth_address, wallet.address());
+    assert_eq!(attested.reputation, reputation);
+
+    signed.attestation = signed.attestation.replace("\"tasks\":0", "\"tasks\":9");
+    assert!(verify(&signed).is_err());
+}
@@ -0,0 +1,85 @@
+use ethers::types::Address;
+use eyre::eyre;
+use serde::Serialize;
+
+use crate::cli::Commands;
+
+/// On-chain roles the ECDSA key can act as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    /// Owner of the AVS service manager
+    Owner,
+    /// Operator registered with the EigenLayer `DelegationManager`
+    Operator,
+    /// Pauser of the AVS contracts
+    Pauser,
+    /// Whitelister of the strategies accepting deposits
+    Whitelister,
+}
+
+impl Role {
+    fn explanation(&self) -> &'static str {
+        match self {
+            Role::Owner => "it is not the owner of the AVS service manager",
+            Role::Operator => {
+                "it is not registered as an operator with the EigenLayer DelegationManager"
+            }
+            Role::Pauser => "it is not a pauser in the AVS pauser registry",
+            Role::Whitelister => "it is not the strategy whitelister of the StrategyManager",
+        }
+    }
+}
+
+/// Roles of the loaded ECDSA key, detected from the contracts at startup.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub account: Address,
+    pub roles: Vec<Role>,
+}
+
+impl Capabilities {
+    pub fn has(&self, role: Role) -> bool {
+        self.roles.contains(&role)
+    }
+
+    /// Denies `cmd` when the key lacks the role it needs, instead of letting the transaction
+    /// revert.
+    pub fn ensure_allowed(&self, cmd: &Commands) -> eyre::Result<()> {
+        match required_role(cmd) {
+            Some(role) if !self.has(role) => Err(eyre!(
+                "{:?} needs the {:?} role but the ECDSA key {:?} cannot act as it: {}",
+                cmd,
+                role,
+                self.account,
+                role.explanation()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Role the key needs to run `cmd`, `None` for commands anyone can run.
+pub fn required_role(cmd: &Commands) -> Option<Role> {
+    match cmd {
+        // the registry coordinator only registers EigenLayer operators
+        Commands::OptInAvs | Commands::SetOperatorDetails(_) => Some(Role::Operator),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_ensure_allowed() {
+    let caps = Capabilities {
+        account: Address::zero(),
+        roles: vec![Role::Pauser],
+    };
+    assert!(caps.ensure_allowed(&Commands::OptInAvs).is_err());
+    assert!(caps.ensure_allowed(&Commands::QuorumStatus).is_ok());
+
+    let caps = Capabilities {
+        roles: vec![Role::Operator],
+        ..caps
+    };
+    assert!(caps.ensure_allowed(&Commands::OptInAvs).is_ok());
+}
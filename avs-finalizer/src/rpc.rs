@@ -1,82 +1,12 @@
-use crate::{
-    cli::CliArgs,
-    crypto::bn254::{BlsKeypair, BlsSignature, PrivateKey},
-};
-use ark_ec::AffineRepr;
-use ark_ff::PrimeField;
-use bindings::shared_types::TaskResponse;
-use ethers::abi::AbiEncode;
+use crate::{cli::CliArgs, exit::SignedExitIntent};
 use reqwest::Response;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
-use serde::{ser::SerializeStruct, Serialize};
-use sp_runtime::traits::{Hash, Keccak256};
 use tracing::instrument;
 
-type Bytes32 = [u8; 32];
-
-#[derive(Serialize)]
-struct SignedTaskResponse {
-    #[serde(rename = "TaskResponse")]
-    task_response: TaskResponseWire,
-    #[serde(rename = "BlsSignature")]
-    bls_signature: BlsSignatureWire,
-    #[serde(rename = "OperatorId")]
-    operator_id: Bytes32,
-}
-
-#[derive(Serialize)]
-struct TaskResponseWire {
-    #[serde(rename = "ReferenceTaskIndex")]
-    pub reference_task_index: u32,
-    #[serde(rename = "BlockHash")]
-    pub block_hash: Bytes32,
-    #[serde(rename = "StorageProofHash")]
-    pub storage_proof_hash: Bytes32,
-}
-
-impl From<TaskResponse> for TaskResponseWire {
-    fn from(value: TaskResponse) -> Self {
-        Self {
-            reference_task_index: value.reference_task_index,
-            block_hash: value.block_hash,
-            storage_proof_hash: value.storage_proof_hash,
-        }
-    }
-}
-
-#[derive(Serialize)]
-struct BlsSignatureWire {
-    g1_point: G1PointWire,
-}
-
-impl From<BlsSignature> for BlsSignatureWire {
-    fn from(value: BlsSignature) -> Self {
-        Self {
-            g1_point: G1PointWire {
-                x: value.x().unwrap().into_bigint(),
-                y: value.y().unwrap().into_bigint(),
-            },
-        }
-    }
-}
-
-struct G1PointWire {
-    x: <PrivateKey as PrimeField>::BigInt,
-    y: <PrivateKey as PrimeField>::BigInt,
-}
-
-impl Serialize for G1PointWire {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let mut s = serializer.serialize_struct("g1_point", 2)?;
-        s.serialize_field("X", &self.x.to_string())?;
-        s.serialize_field("Y", &self.y.to_string())?;
-        s.end()
-    }
-}
+pub use avs_operator_sdk::response::{
+    decode_bls_task_response, encode_bls_task_response, task_response_digest, verify_task_response,
+};
 
 #[derive(Debug)]
 pub struct Rpc {
@@ -96,28 +26,21 @@ impl Rpc {
         }
     }
 
-    #[instrument(skip(self, keypair))]
-    pub async fn send_task_response(
-        &self,
-        task_response: TaskResponse,
-        keypair: &BlsKeypair,
-    ) -> eyre::Result<Response> {
-        let req = create_response(task_response, keypair)?;
-        let json: String = serde_json::to_string(&req)?;
-
+    /// Sends a signed task response, as encoded by [`encode_bls_task_response`].
+    #[instrument(skip_all)]
+    pub async fn send_task_response(&self, json: String) -> eyre::Result<Response> {
         Ok(self.client.post(&self.avs_url).body(json).send().await?)
     }
-}
 
-fn create_response(task: TaskResponse, keypair: &BlsKeypair) -> eyre::Result<SignedTaskResponse> {
-    let encoded = task.clone().encode();
-
-    let hash = Keccak256::hash(encoded.as_ref());
-    let sig = keypair.sign(hash.as_bytes())?;
-
-    Ok(SignedTaskResponse {
-        bls_signature: sig.into(),
-        task_response: task.into(),
-        operator_id: keypair.operator_id().to_fixed_bytes(),
-    })
+    /// Announces to the aggregator that the operator deregisters soon.
+    #[instrument(skip_all)]
+    pub async fn send_exit_intent(&self, intent: &SignedExitIntent) -> eyre::Result<Response> {
+        let url = format!("{}/exit-intent", self.avs_url.trim_end_matches('/'));
+        Ok(self
+            .client
+            .post(url)
+            .body(serde_json::to_string(intent)?)
+            .send()
+            .await?)
+    }
 }
@@ -0,0 +1,136 @@
+use std::any::TypeId;
+
+use clap::{Arg, ArgAction, CommandFactory};
+use serde_json::{json, Map, Value};
+
+use crate::cli::CliArgs;
+
+/// JSON Schema of the node configuration, derived from the command line arguments. Each
+/// property is named after its flag in snake case and lists the environment variable and
+/// flag setting it (`x-env`, `x-flag`), for configuration files to be validated in CI or
+/// completed by an IDE. Groups of alternative arguments, e.g. the key sources, are not
+/// expressed and none of them is required.
+pub fn config_schema() -> Value {
+    let cmd = CliArgs::command();
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for arg in cmd.get_arguments() {
+        let id = arg.get_id().as_str();
+        if matches!(id, "help" | "version") {
+            continue;
+        }
+        if arg.is_required_set() {
+            required.push(id);
+        }
+        properties.insert(id.to_owned(), property(arg));
+    }
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "avs-finalizer configuration",
+        "description": format!("Configuration of avs-finalizer {}", env!("CARGO_PKG_VERSION")),
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn property(arg: &Arg) -> Value {
+    let item = value_schema(arg);
+    let defaults: Vec<Value> = arg
+        .get_default_values()
+        .iter()
+        .map(|v| typed(&v.to_string_lossy(), &item))
+        .collect();
+    let mut property = match multiple(arg) {
+        true => json!({ "type": "array", "items": item, "default": defaults }),
+        false => match defaults.into_iter().next() {
+            Some(default) => json!({ "default": default }),
+            None => json!({}),
+        },
+    };
+    let schema = property.as_object_mut().expect("schemas are objects");
+    if !multiple(arg) {
+        schema.extend(item.as_object().cloned().unwrap_or_default());
+    }
+    if let Some(help) = arg.get_long_help().or(arg.get_help()) {
+        schema.insert("description".into(), help.to_string().into());
+    }
+    if let Some(env) = arg.get_env() {
+        schema.insert("x-env".into(), env.to_string_lossy().into());
+    }
+    if let Some(long) = arg.get_long() {
+        schema.insert("x-flag".into(), format!("--{}", long).into());
+    }
+    property
+}
+
+fn multiple(arg: &Arg) -> bool {
+    arg.get_value_delimiter().is_some() || matches!(arg.get_action(), ArgAction::Append)
+}
+
+/// Schema of a single value of `arg`, from its possible values or the type it parses to.
+fn value_schema(arg: &Arg) -> Value {
+    if matches!(arg.get_action(), ArgAction::SetTrue | ArgAction::SetFalse) {
+        return json!({ "type": "boolean" });
+    }
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .map(|v| v.get_name().to_owned())
+        .collect();
+    if !possible.is_empty() {
+        return json!({ "type": "string", "enum": possible });
+    }
+    let parses_to = arg.get_value_parser().type_id();
+    let integer = [
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<usize>(),
+    ];
+    if integer.iter().any(|t| parses_to == *t) {
+        json!({ "type": "integer", "minimum": 0 })
+    } else if parses_to == TypeId::of::<f64>() {
+        json!({ "type": "number" })
+    } else if parses_to == TypeId::of::<bool>() {
+        json!({ "type": "boolean" })
+    } else {
+        json!({ "type": "string" })
+    }
+}
+
+/// Default value `raw` as the JSON type of `schema`, left a string if it does not parse.
+fn typed(raw: &str, schema: &Value) -> Value {
+    let parsed = match schema.get("type").and_then(Value::as_str) {
+        Some("integer") => raw.parse::<u64>().ok().map(Value::from),
+        Some("number") => raw.parse::<f64>().ok().map(Value::from),
+        Some("boolean") => raw.parse::<bool>().ok().map(Value::from),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| raw.into())
+}
+
+#[test]
+fn test_config_schema() {
+    let schema = config_schema();
+    let properties = &schema["properties"];
+    assert_eq!(properties["eth_rpc_url"]["type"], "string");
+    assert_eq!(properties["eth_rpc_url"]["x-env"], "ETH_RPC_URL");
+    assert!(schema["required"]
+        .as_array()
+        .unwrap()
+        .contains(&"eth_rpc_url".into()));
+    assert_eq!(properties["verify_workers"]["type"], "integer");
+    assert_eq!(properties["verify_workers"]["default"], 0);
+    assert_eq!(properties["db_check"]["type"], "boolean");
+    assert_eq!(properties["gossip_peers"]["type"], "array");
+    assert_eq!(properties["gossip_peers"]["items"]["type"], "string");
+    assert_eq!(properties["gossip_peers"]["x-flag"], "--gossip-peers");
+    assert!(properties["task_queue_drop_policy"]["enum"]
+        .as_array()
+        .unwrap()
+        .contains(&"drop-oldest".into()));
+    assert!(properties.get("help").is_none());
+}
@@ -0,0 +1,130 @@
+use bindings::shared_types::G1Point;
+use ethers::types::{Address, Bytes, H256};
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::instrument;
+
+use crate::{crypto::bn254::OperatorId, operator::Operator};
+
+/// Prefix of the digests signed by `sign_digest`, so a scripted signature can never pass for
+/// the signature of a task response.
+pub const SIGN_DIGEST_DOMAIN: &[u8] = b"avs-finalizer/script/sign-digest";
+
+/// Request read from a line of stdin, `id` is echoed in its reply.
+#[derive(Debug, Deserialize)]
+struct ScriptLine {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(flatten)]
+    request: ScriptRequest,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum ScriptRequest {
+    /// Registration status of the operator, as `print-status`
+    Status,
+    /// Signs a digest with the operator keys, to check them without a task
+    SignDigest { digest: H256 },
+    /// Verifies a task created within the response window and sends its response
+    SubmitResponse { task_index: u32 },
+}
+
+/// Reply written as a line of stdout, with either `result` or `error`.
+#[derive(Debug, Serialize)]
+struct ScriptReply {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ScriptReply {
+    fn new(id: Option<Value>, res: eyre::Result<Value>) -> Self {
+        match res {
+            Ok(result) => Self {
+                id,
+                ok: true,
+                result: Some(result),
+                error: None,
+            },
+            Err(e) => Self {
+                id,
+                ok: false,
+                result: None,
+                error: Some(format!("{:#}", e)),
+            },
+        }
+    }
+}
+
+/// Signatures of `keccak256(SIGN_DIGEST_DOMAIN ++ digest)` by the BLS key and of the digest
+/// as an EIP-191 message by the ECDSA key.
+#[derive(Debug, Serialize)]
+pub struct DigestSignatures {
+    pub digest: H256,
+    pub operator_id: OperatorId,
+    pub bls_message: H256,
+    pub bls_signature: G1Point,
+    pub eth_address: Address,
+    pub ecdsa_signature: Bytes,
+}
+
+/// Answers the newline-delimited JSON requests read on stdin with one JSON line each on
+/// stdout until stdin closes. Logs go to stderr meanwhile.
+#[instrument(skip_all)]
+pub async fn run(operator: &Operator) -> eyre::Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<ScriptLine>(&line) {
+            Ok(line) => ScriptReply::new(line.id, handle(operator, line.request).await),
+            Err(e) => ScriptReply::new(None, Err(eyre!("malformed request: {}", e))),
+        };
+        let mut out = serde_json::to_vec(&reply)?;
+        out.push(b'\n');
+        stdout.write_all(&out).await?;
+        stdout.flush().await?;
+    }
+    Ok(())
+}
+
+async fn handle(operator: &Operator, request: ScriptRequest) -> eyre::Result<Value> {
+    Ok(match request {
+        ScriptRequest::Status => serde_json::to_value(operator.get_status().await?)?,
+        ScriptRequest::SignDigest { digest } => {
+            serde_json::to_value(operator.sign_digest(digest).await?)?
+        }
+        ScriptRequest::SubmitResponse { task_index } => {
+            json!({ "accepted": operator.submit_task(task_index).await? })
+        }
+    })
+}
+
+#[test]
+fn test_script_lines() {
+    let line: ScriptLine =
+        serde_json::from_str(r#"{"id": 7, "cmd": "submit_response", "task_index": 3}"#).unwrap();
+    assert_eq!(line.id, Some(json!(7)));
+    assert_eq!(
+        line.request,
+        ScriptRequest::SubmitResponse { task_index: 3 }
+    );
+    let line: ScriptLine = serde_json::from_str(r#"{"cmd": "status"}"#).unwrap();
+    assert_eq!((line.id, line.request), (None, ScriptRequest::Status));
+    assert!(serde_json::from_str::<ScriptLine>(r#"{"cmd": "shutdown"}"#).is_err());
+
+    let reply = ScriptReply::new(Some(json!("a")), Err(eyre!("task 3 expired")));
+    assert_eq!(
+        serde_json::to_value(reply).unwrap(),
+        json!({ "id": "a", "ok": false, "error": "task 3 expired" })
+    );
+}
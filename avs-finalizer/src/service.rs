@@ -0,0 +1,366 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Service manager the operator is installed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServicePlatform {
+    /// Linux systemd unit
+    Systemd,
+    /// macOS launchd property list
+    Launchd,
+    /// Windows service configuration for the WinSW service wrapper
+    Windows,
+}
+
+impl ServicePlatform {
+    pub fn current() -> Self {
+        match std::env::consts::OS {
+            "macos" => Self::Launchd,
+            "windows" => Self::Windows,
+            _ => Self::Systemd,
+        }
+    }
+}
+
+/// Node invocation a service definition is generated for.
+#[derive(Debug)]
+pub struct ServiceSpec<'a> {
+    pub name: &'a str,
+    pub exe: &'a Path,
+    pub args: &'a [String],
+    /// Service managers do not start services in the directory they were installed from,
+    /// relative paths in `args` are resolved from here
+    pub working_dir: &'a Path,
+    /// `KEY=value` lines, referenced by systemd as its `EnvironmentFile` and passed to the
+    /// node as `--env-file` by launchd and WinSW, so the secrets stay out of the definition
+    pub env_file: Option<&'a Path>,
+}
+
+/// Renders the service definition of `spec` for `platform`. Every platform stops the node
+/// with a signal it handles as a graceful shutdown: SIGTERM for systemd and launchd, a
+/// console Ctrl+C event for WinSW.
+pub fn definition(platform: ServicePlatform, spec: &ServiceSpec) -> String {
+    match platform {
+        ServicePlatform::Systemd => systemd_unit(spec),
+        ServicePlatform::Launchd => launchd_plist(spec),
+        ServicePlatform::Windows => winsw_config(spec),
+    }
+}
+
+/// Arguments of the node, preceded by `--env-file` for the service managers without an
+/// environment file of their own.
+fn node_args(spec: &ServiceSpec) -> Vec<String> {
+    let env_file = spec
+        .env_file
+        .map(|path| ["--env-file".to_owned(), path.display().to_string()]);
+    env_file
+        .into_iter()
+        .flatten()
+        .chain(spec.args.iter().cloned())
+        .collect()
+}
+
+fn systemd_unit(spec: &ServiceSpec) -> String {
+    let exec = std::iter::once(spec.exe.display().to_string())
+        .chain(spec.args.iter().cloned())
+        .map(|arg| systemd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let env_file = spec
+        .env_file
+        .map(|path| format!("EnvironmentFile={}\n", path.display()))
+        .unwrap_or_default();
+    format!(
+        "[Unit]
+Description=Mangata AVS operator ({name})
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+ExecStart={exec}
+WorkingDirectory={dir}
+{env_file}Restart=always
+RestartSec=5
+KillSignal=SIGTERM
+TimeoutStopSec=30
+
+[Install]
+WantedBy=multi-user.target
+",
+        name = spec.name,
+        dir = spec.working_dir.display(),
+    )
+}
+
+fn launchd_plist(spec: &ServiceSpec) -> String {
+    let args: String = std::iter::once(spec.exe.display().to_string())
+        .chain(node_args(spec))
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(&arg)))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{name}</string>
+    <key>ProgramArguments</key>
+    <array>
+{args}    </array>
+    <key>WorkingDirectory</key>
+    <string>{dir}</string>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>ExitTimeOut</key>
+    <integer>30</integer>
+    <key>StandardOutPath</key>
+    <string>/usr/local/var/log/{name}.log</string>
+    <key>StandardErrorPath</key>
+    <string>/usr/local/var/log/{name}.log</string>
+</dict>
+</plist>
+"#,
+        name = xml_escape(spec.name),
+        dir = xml_escape(&spec.working_dir.display().to_string()),
+    )
+}
+
+fn winsw_config(spec: &ServiceSpec) -> String {
+    let args = node_args(spec)
+        .iter()
+        .map(|arg| windows_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        r#"<service>
+  <id>{name}</id>
+  <name>{name}</name>
+  <description>Mangata AVS operator</description>
+  <executable>{exe}</executable>
+  <arguments>{args}</arguments>
+  <workingdirectory>{dir}</workingdirectory>
+  <stoptimeout>30 sec</stoptimeout>
+  <onfailure action="restart" delay="5 sec"/>
+  <log mode="roll-by-size"/>
+</service>
+"#,
+        name = xml_escape(spec.name),
+        exe = xml_escape(&spec.exe.display().to_string()),
+        args = xml_escape(&args),
+        dir = xml_escape(&spec.working_dir.display().to_string()),
+    )
+}
+
+/// Sets the variables of the `--env-file` preceding the node command, if any, which are not
+/// already set. Run before the arguments are parsed, as they are read from the environment.
+pub fn load_env_file() -> eyre::Result<()> {
+    let mut args = std::env::args()
+        .skip(1)
+        .take_while(|arg| arg != "service-definition");
+    let path = loop {
+        match args.next() {
+            Some(arg) if arg == "--env-file" => break args.next(),
+            Some(arg) => {
+                if let Some(path) = arg.strip_prefix("--env-file=") {
+                    break Some(path.to_owned());
+                }
+            }
+            None => break None,
+        }
+    };
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| eyre::eyre!("failed to read the env file {}: {}", path, e))?;
+    for (key, value) in parse_env_file(&content) {
+        if std::env::var_os(&key).is_none() {
+            std::env::set_var(key, value);
+        }
+    }
+    Ok(())
+}
+
+fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim();
+            let unquoted = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            (key.trim().to_owned(), unquoted.to_owned())
+        })
+        .collect()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn systemd_quote(arg: &str) -> String {
+    // systemd expands specifiers and variables in ExecStart
+    let arg = arg.replace('%', "%%").replace('$', "$$");
+    if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg
+    }
+}
+
+fn windows_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        return arg.to_owned();
+    }
+    // backslashes are only special before a quote
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push(c);
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Resolves with the name of the first signal a service manager or a terminal stops the node
+/// with.
+#[cfg(unix)]
+pub async fn shutdown_signal() -> eyre::Result<&'static str> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    tokio::select! {
+        _ = terminate.recv() => Ok("SIGTERM"),
+        _ = interrupt.recv() => Ok("SIGINT"),
+    }
+}
+
+/// Resolves with the name of the first console event a service wrapper or a terminal stops
+/// the node with. Windows services do not receive signals, WinSW sends Ctrl+C on stop.
+#[cfg(windows)]
+pub async fn shutdown_signal() -> eyre::Result<&'static str> {
+    use tokio::signal::windows::{ctrl_break, ctrl_c, ctrl_close, ctrl_shutdown};
+
+    let mut ctrl_c = ctrl_c()?;
+    let mut ctrl_break = ctrl_break()?;
+    let mut close = ctrl_close()?;
+    let mut shutdown = ctrl_shutdown()?;
+    tokio::select! {
+        _ = ctrl_c.recv() => Ok("Ctrl+C"),
+        _ = ctrl_break.recv() => Ok("Ctrl+Break"),
+        _ = close.recv() => Ok("console close"),
+        _ = shutdown.recv() => Ok("system shutdown"),
+    }
+}
+
+/// Expands a leading `~` to the home directory, and `%VAR%` environment variables on Windows,
+/// as services get paths unexpanded by a shell.
+pub fn expand_path(path: &Path) -> PathBuf {
+    let Some(s) = path.to_str() else {
+        return path.to_owned();
+    };
+    let home = || std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"));
+    let mut expanded = match s.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => match home() {
+            Ok(home) => format!("{}{}", home, rest),
+            Err(_) => s.to_owned(),
+        },
+        _ => s.to_owned(),
+    };
+    if cfg!(windows) {
+        expanded = expand_windows_vars(&expanded, |name| std::env::var(name).ok());
+    }
+    PathBuf::from(expanded)
+}
+
+fn expand_windows_vars(s: &str, var: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find('%') {
+        let Some(len) = rest[start + 1..].find('%') else {
+            break;
+        };
+        let name = &rest[start + 1..start + 1 + len];
+        out.push_str(&rest[..start]);
+        match var(name) {
+            Some(value) if !name.is_empty() => out.push_str(&value),
+            _ => out.push_str(&rest[start..start + len + 2]),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[test]
+fn test_quoting() {
+    assert_eq!(systemd_quote("--db-path"), "--db-path");
+    assert_eq!(systemd_quote("/var/lib/my keys"), "\"/var/lib/my keys\"");
+    assert_eq!(systemd_quote("100%"), "100%%");
+    assert_eq!(windows_quote(r"C:\keys\bls.json"), r"C:\keys\bls.json");
+    assert_eq!(
+        windows_quote(r"C:\Program Files\keys\"),
+        r#""C:\Program Files\keys\\""#
+    );
+    assert_eq!(windows_quote(r#"a "b""#), r#""a \"b\"""#);
+    assert_eq!(xml_escape("a<b & \"c\""), "a&lt;b &amp; &quot;c&quot;");
+}
+
+#[test]
+fn test_env_file_referenced() {
+    let spec = ServiceSpec {
+        name: "avs-finalizer",
+        exe: Path::new("/usr/local/bin/avs-finalizer"),
+        args: &["--db-path=db".to_owned()],
+        working_dir: Path::new("/var/lib/avs"),
+        env_file: Some(Path::new("/etc/avs/env")),
+    };
+    for platform in [ServicePlatform::Launchd, ServicePlatform::Windows] {
+        let definition = definition(platform, &spec);
+        assert!(definition.contains("--env-file"), "{}", definition);
+        assert!(definition.contains("/etc/avs/env"), "{}", definition);
+    }
+    assert!(definition(ServicePlatform::Systemd, &spec).contains("EnvironmentFile=/etc/avs/env"));
+    assert_eq!(
+        parse_env_file("# keys\nECDSA_KEY_PASSWORD=\"a b\"\n\nBLS_KEY_PASSWORD = c\n"),
+        vec![
+            ("ECDSA_KEY_PASSWORD".to_owned(), "a b".to_owned()),
+            ("BLS_KEY_PASSWORD".to_owned(), "c".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_expand_windows_vars() {
+    let var = |name: &str| (name == "APPDATA").then(|| r"C:\Users\op\AppData\Roaming".to_owned());
+    assert_eq!(
+        expand_windows_vars(r"%APPDATA%\avs\bls.json", var),
+        r"C:\Users\op\AppData\Roaming\avs\bls.json"
+    );
+    assert_eq!(expand_windows_vars("%UNSET%\\x %", var), "%UNSET%\\x %");
+}
@@ -0,0 +1,101 @@
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use hyper::{
+    header::AUTHORIZATION, server::conn::Http, service::service_fn, Body, Method, Request,
+    Response, StatusCode,
+};
+use tokio::net::TcpListener;
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::{
+    api::{constant_time_eq, read_body},
+    crypto::{
+        bn254::BlsKeypair,
+        threshold::{sign_partial, SignRequest},
+    },
+};
+
+/// Largest partial signature request accepted, an encoded task response is about 300 bytes.
+const MAX_SIGN_BODY: usize = 4 * 1024;
+
+/// Key share held by a signer daemon.
+#[derive(Debug)]
+struct Share {
+    index: u32,
+    keypair: BlsKeypair,
+    token: Option<String>,
+}
+
+/// Serves partial signatures of the BLS key share `index` on `POST /sign`, the request body
+/// being a [`SignRequest`]. Every request must carry `token` as a bearer token when set, each
+/// daemon having its own.
+///
+/// The daemon only signs task responses and domain separated digests, but it signs any of
+/// them it is sent, it must only be reachable by the finalizer.
+#[instrument(skip(keypair, token))]
+pub async fn serve(
+    addr: SocketAddr,
+    index: u32,
+    keypair: BlsKeypair,
+    token: Option<String>,
+) -> eyre::Result<()> {
+    if token.is_none() {
+        warn!("No threshold signer token configured, any client can request signatures");
+    }
+    let share = Arc::new(Share {
+        index,
+        keypair,
+        token,
+    });
+
+    let listener = TcpListener::bind(addr).await?;
+    info!(
+        "Serving partial signatures of key share {} on {}",
+        index, addr
+    );
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let share = share.clone();
+        tokio::spawn(async move {
+            let svc = service_fn(move |req| handle(req, share.clone()));
+            if let Err(e) = Http::new().serve_connection(stream, svc).await {
+                debug!("Signer connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle(req: Request<Body>, share: Arc<Share>) -> Result<Response<Body>, Infallible> {
+    if let Some(token) = &share.token {
+        let authorized = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()));
+        if !authorized {
+            return Ok(status(StatusCode::UNAUTHORIZED));
+        }
+    }
+    if (req.method(), req.uri().path()) != (&Method::POST, "/sign") {
+        return Ok(status(StatusCode::NOT_FOUND));
+    }
+
+    let res = async {
+        let body = read_body(req.into_body(), MAX_SIGN_BODY).await?;
+        let request: SignRequest = serde_json::from_slice(&body)?;
+        let partial = sign_partial(&share.keypair, share.index, request.message()?.as_bytes())?;
+        eyre::Ok(Response::new(Body::from(serde_json::to_vec(&partial)?)))
+    }
+    .await;
+    Ok(res.unwrap_or_else(|e| {
+        error!("Partial signature request failed: {:?}", e);
+        status(StatusCode::BAD_REQUEST)
+    }))
+}
+
+fn status(code: StatusCode) -> Response<Body> {
+    let mut res = Response::new(Body::empty());
+    *res.status_mut() = code;
+    res
+}
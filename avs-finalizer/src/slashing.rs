@@ -0,0 +1,139 @@
+use std::collections::BTreeSet;
+
+use bindings::slasher::SlasherEvents;
+use ethers::{
+    contract::LogMeta,
+    types::{Address, H256},
+};
+use serde::Serialize;
+
+/// What happened to an operator in a [`SlashingEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum SlashingAction {
+    /// Frozen by a contract the operator opted into slashing by
+    Frozen { slashing_contract: Address },
+    /// Frozen status reset by the slasher owner
+    Unfrozen,
+    /// Deregistered from the AVS by the ejector
+    Ejected,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlashingEvent {
+    pub block_number: u64,
+    pub transaction_hash: H256,
+    pub operator: Address,
+    #[serde(flatten)]
+    pub action: SlashingAction,
+}
+
+impl SlashingEvent {
+    pub fn new(meta: &LogMeta, operator: Address, action: SlashingAction) -> Self {
+        Self {
+            block_number: meta.block_number.as_u64(),
+            transaction_hash: meta.transaction_hash,
+            operator,
+            action,
+        }
+    }
+
+    /// Maps the freezes of the EigenLayer slasher, `None` for its other events.
+    pub fn from_slasher(event: SlasherEvents, meta: &LogMeta) -> Option<Self> {
+        let (operator, action) = match event {
+            SlasherEvents::OperatorFrozenFilter(e) => (
+                e.slashed_operator,
+                SlashingAction::Frozen {
+                    slashing_contract: e.slashing_contract,
+                },
+            ),
+            SlasherEvents::FrozenStatusResetFilter(e) => {
+                (e.previously_slashed_address, SlashingAction::Unfrozen)
+            }
+            _ => return None,
+        };
+        Some(Self::new(meta, operator, action))
+    }
+}
+
+/// Freezes and ejections over a block range, of a single operator or of all of them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlashingHistory {
+    pub from_block: u64,
+    pub to_block: u64,
+    /// `None` when the events of all operators were scanned
+    pub operator: Option<Address>,
+    pub frozen: usize,
+    pub unfrozen: usize,
+    pub ejected: usize,
+    /// Operators whose last freeze in the range was not reset
+    pub still_frozen: Vec<Address>,
+    pub events: Vec<SlashingEvent>,
+}
+
+impl SlashingHistory {
+    pub fn new(
+        from_block: u64,
+        to_block: u64,
+        operator: Option<Address>,
+        mut events: Vec<SlashingEvent>,
+    ) -> Self {
+        events.sort_by_key(|e| e.block_number);
+        let count = |matches: fn(&SlashingAction) -> bool| {
+            events.iter().filter(|e| matches(&e.action)).count()
+        };
+        let mut still_frozen = BTreeSet::new();
+        for event in &events {
+            match event.action {
+                SlashingAction::Frozen { .. } => still_frozen.insert(event.operator),
+                SlashingAction::Unfrozen => still_frozen.remove(&event.operator),
+                SlashingAction::Ejected => continue,
+            };
+        }
+        Self {
+            from_block,
+            to_block,
+            operator,
+            frozen: count(|a| matches!(a, SlashingAction::Frozen { .. })),
+            unfrozen: count(|a| matches!(a, SlashingAction::Unfrozen)),
+            ejected: count(|a| matches!(a, SlashingAction::Ejected)),
+            still_frozen: still_frozen.into_iter().collect(),
+            events,
+        }
+    }
+}
+
+#[test]
+fn test_slashing_history() {
+    let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+    let event = |block_number, operator, action| SlashingEvent {
+        block_number,
+        transaction_hash: H256::zero(),
+        operator,
+        action,
+    };
+    let frozen = SlashingAction::Frozen {
+        slashing_contract: Address::zero(),
+    };
+    let history = SlashingHistory::new(
+        10,
+        20,
+        None,
+        vec![
+            event(15, a, SlashingAction::Unfrozen),
+            event(12, a, frozen),
+            event(13, b, frozen),
+            event(18, b, SlashingAction::Ejected),
+        ],
+    );
+    assert_eq!(
+        (history.frozen, history.unfrozen, history.ejected),
+        (2, 1, 1)
+    );
+    assert_eq!(history.still_frozen, vec![b]);
+    assert_eq!(history.events[0].block_number, 12);
+    assert_eq!(
+        serde_json::to_value(&history.events[3]).unwrap()["kind"],
+        "ejected"
+    );
+}
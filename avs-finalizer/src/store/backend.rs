@@ -0,0 +1,132 @@
+use std::fmt::Debug;
+
+/// Key-value storage under the [`super::Store`], grouping keys in named trees. Keys are
+/// compared bytewise, the store encodes numbers big endian so they iterate in order.
+pub trait Backend: Debug + Send + Sync {
+    /// Creates `tree` if missing.
+    fn open_tree(&self, tree: &str) -> eyre::Result<()>;
+
+    fn get(&self, tree: &str, key: &[u8]) -> eyre::Result<Option<Vec<u8>>>;
+
+    fn insert(&self, tree: &str, key: &[u8], value: &[u8]) -> eyre::Result<()>;
+
+    fn remove(&self, tree: &str, key: &[u8]) -> eyre::Result<()>;
+
+    /// Atomically stores the greatest of `value` and the stored value, compared bytewise.
+    fn insert_max(&self, tree: &str, key: &[u8], value: &[u8]) -> eyre::Result<()>;
+
+    fn contains_key(&self, tree: &str, key: &[u8]) -> eyre::Result<bool> {
+        Ok(self.get(tree, key)?.is_some())
+    }
+
+    fn is_empty(&self, tree: &str) -> eyre::Result<bool> {
+        Ok(self.iter_rev(tree).next().is_none())
+    }
+
+    /// Entries of `tree` from the greatest key down.
+    fn iter_rev<'a>(&'a self, tree: &str) -> Box<dyn Iterator<Item = eyre::Result<Entry>> + 'a>;
+
+    /// Persists the pending writes.
+    fn flush(&self) -> eyre::Result<()>;
+
+    /// Checksum of all the stored data, to compare copies of a store.
+    fn checksum(&self) -> eyre::Result<u32>;
+}
+
+pub type Entry = (Vec<u8>, Vec<u8>);
+
+/// Embedded store in a local directory, for single instance deployments.
+#[derive(Debug)]
+pub struct SledBackend {
+    db: sled::Db,
+}
+
+impl SledBackend {
+    pub fn open(path: &std::path::Path) -> eyre::Result<Self> {
+        Ok(Self {
+            db: sled::open(path)?,
+        })
+    }
+}
+
+impl Backend for SledBackend {
+    fn open_tree(&self, tree: &str) -> eyre::Result<()> {
+        self.db.open_tree(tree)?;
+        Ok(())
+    }
+
+    fn get(&self, tree: &str, key: &[u8]) -> eyre::Result<Option<Vec<u8>>> {
+        Ok(self.db.open_tree(tree)?.get(key)?.map(|v| v.to_vec()))
+    }
+
+    fn insert(&self, tree: &str, key: &[u8], value: &[u8]) -> eyre::Result<()> {
+        self.db.open_tree(tree)?.insert(key, value)?;
+        Ok(())
+    }
+
+    fn remove(&self, tree: &str, key: &[u8]) -> eyre::Result<()> {
+        self.db.open_tree(tree)?.remove(key)?;
+        Ok(())
+    }
+
+    fn insert_max(&self, tree: &str, key: &[u8], value: &[u8]) -> eyre::Result<()> {
+        self.db.open_tree(tree)?.fetch_and_update(key, |old| {
+            Some(match old {
+                Some(old) if old > value => old.to_vec(),
+                _ => value.to_vec(),
+            })
+        })?;
+        Ok(())
+    }
+
+    fn contains_key(&self, tree: &str, key: &[u8]) -> eyre::Result<bool> {
+        Ok(self.db.open_tree(tree)?.contains_key(key)?)
+    }
+
+    fn is_empty(&self, tree: &str) -> eyre::Result<bool> {
+        Ok(self.db.open_tree(tree)?.is_empty())
+    }
+
+    fn iter_rev<'a>(&'a self, tree: &str) -> Box<dyn Iterator<Item = eyre::Result<Entry>> + 'a> {
+        match self.db.open_tree(tree) {
+            Ok(tree) => Box::new(tree.iter().rev().map(|entry| {
+                let (key, value) = entry?;
+                Ok((key.to_vec(), value.to_vec()))
+            })),
+            Err(e) => Box::new(std::iter::once(Err(e.into()))),
+        }
+    }
+
+    fn flush(&self) -> eyre::Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    fn checksum(&self) -> eyre::Result<u32> {
+        Ok(self.db.checksum()?)
+    }
+}
+
+#[test]
+fn test_sled_backend() {
+    let backend = SledBackend {
+        db: sled::Config::new().temporary(true).open().unwrap(),
+    };
+    assert!(backend.is_empty("t").unwrap());
+    for i in [2u32, 1, 3] {
+        backend.insert("t", &i.to_be_bytes(), b"v").unwrap();
+    }
+    let keys: Vec<Vec<u8>> = backend.iter_rev("t").map(|e| e.unwrap().0).collect();
+    assert_eq!(keys, [3u32, 2, 1].map(|i| i.to_be_bytes().to_vec()));
+
+    backend
+        .insert_max("t", b"max", &5u32.to_be_bytes())
+        .unwrap();
+    backend
+        .insert_max("t", b"max", &4u32.to_be_bytes())
+        .unwrap();
+    assert_eq!(
+        backend.get("t", b"max").unwrap(),
+        Some(5u32.to_be_bytes().to_vec())
+    );
+}
@@ -0,0 +1,108 @@
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use eyre::eyre;
+use scrypt::{scrypt, Params as ScryptParams};
+
+const NONCE_LEN: usize = 12;
+pub(super) const SALT_LEN: usize = 32;
+/// Sealed with the store key when encryption is enabled, to detect a wrong key on open.
+const CHECK_PLAINTEXT: &[u8] = b"avs-finalizer store";
+
+/// Source of the key encrypting the store values at rest.
+#[derive(Clone)]
+pub enum StoreKey {
+    /// Derived with scrypt and the salt kept in the store
+    Passphrase(String),
+    /// Used as is, e.g. a data key kept in a secrets manager
+    Raw([u8; 32]),
+}
+
+impl std::fmt::Debug for StoreKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Passphrase(_) => "Passphrase(..)",
+            Self::Raw(_) => "Raw(..)",
+        })
+    }
+}
+
+impl StoreKey {
+    /// Parses a hex encoded 32 bytes key.
+    pub fn from_hex(hex_key: &str) -> eyre::Result<Self> {
+        let bytes = ethers::utils::hex::decode(hex_key.trim().trim_start_matches("0x"))?;
+        Ok(Self::Raw(bytes.try_into().map_err(|_| {
+            eyre!("the store key must be 32 bytes long")
+        })?))
+    }
+}
+
+/// AES-256-GCM encryption of store values, each value is stored as `nonce || ciphertext`.
+#[derive(Clone)]
+pub(super) struct StoreCipher {
+    cipher: Aes256Gcm,
+}
+
+impl std::fmt::Debug for StoreCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StoreCipher")
+    }
+}
+
+impl StoreCipher {
+    pub(super) fn new(key: &StoreKey, salt: &[u8]) -> eyre::Result<Self> {
+        let key = match key {
+            StoreKey::Raw(key) => *key,
+            StoreKey::Passphrase(passphrase) => {
+                let mut key = [0; 32];
+                scrypt(
+                    passphrase.as_bytes(),
+                    salt,
+                    &ScryptParams::new(15, 8, 1)?,
+                    &mut key,
+                )?;
+                key
+            }
+        };
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        })
+    }
+
+    pub(super) fn random_salt() -> [u8; SALT_LEN] {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        salt
+    }
+
+    pub(super) fn seal(&self, plaintext: &[u8]) -> eyre::Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| eyre!("cannot encrypt store value"))?;
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    pub(super) fn open(&self, sealed: &[u8]) -> eyre::Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return Err(eyre!("encrypted store value too short"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| eyre!("cannot decrypt store value, wrong key or corrupted store"))
+    }
+
+    pub(super) fn check_value(&self) -> eyre::Result<Vec<u8>> {
+        self.seal(CHECK_PLAINTEXT)
+    }
+
+    pub(super) fn verify_check_value(&self, sealed: &[u8]) -> eyre::Result<()> {
+        match self.open(sealed) {
+            Ok(plaintext) if plaintext == CHECK_PLAINTEXT => Ok(()),
+            _ => Err(eyre!("wrong store encryption key")),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use super::{
+    Backend, ARCHIVED_TREE, BROADCASTS_TREE, QUARANTINE_TREE, QUORUM_SNAPSHOTS_TREE,
+    RELAYED_RESPONSES_TREE, STAKE_SHARES_TREE, TASKS_TREE, TASK_OUTCOMES_TREE,
+};
+
+/// A forward only schema migration, applied once when the store version is below `version`.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub apply: fn(&dyn Backend) -> eyre::Result<()>,
+}
+
+/// Ordered list of all migrations, append new ones at the end with an increasing version.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create tasks tree",
+        apply: |db| db.open_tree(TASKS_TREE),
+    },
+    Migration {
+        version: 2,
+        description: "create stake shares tree",
+        apply: |db| db.open_tree(STAKE_SHARES_TREE),
+    },
+    Migration {
+        version: 3,
+        description: "create task outcomes tree",
+        apply: |db| db.open_tree(TASK_OUTCOMES_TREE),
+    },
+    Migration {
+        version: 4,
+        description: "create quorum snapshots tree",
+        apply: |db| db.open_tree(QUORUM_SNAPSHOTS_TREE),
+    },
+    Migration {
+        version: 5,
+        description: "create task quarantine tree",
+        apply: |db| db.open_tree(QUARANTINE_TREE),
+    },
+    Migration {
+        version: 6,
+        description: "create relayed responses tree",
+        apply: |db| db.open_tree(RELAYED_RESPONSES_TREE),
+    },
+    Migration {
+        version: 7,
+        description: "create broadcast responses tree",
+        apply: |db| db.open_tree(BROADCASTS_TREE),
+    },
+    Migration {
+        version: 8,
+        description: "create archived objects tree",
+        apply: |db| db.open_tree(ARCHIVED_TREE),
+    },
+];
+
+#[test]
+fn test_migrations_are_ordered() {
+    assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+    assert!(MIGRATIONS.first().is_some_and(|m| m.version == 1));
+}
@@ -0,0 +1,696 @@
+use std::sync::Arc;
+
+use ethers::types::H256;
+use eyre::eyre;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{info, instrument};
+
+mod backend;
+mod cipher;
+mod migrations;
+mod postgres;
+
+use cipher::StoreCipher;
+use migrations::{Migration, MIGRATIONS};
+
+pub use backend::{Backend, SledBackend};
+pub use cipher::StoreKey;
+pub use postgres::PostgresBackend;
+
+const META_TREE: &str = "meta";
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+const ENCRYPTION_SALT_KEY: &[u8] = b"encryption_salt";
+const ENCRYPTION_CHECK_KEY: &[u8] = b"encryption_check";
+const CHECKPOINT_KEY: &[u8] = b"checkpoint_block";
+pub(crate) const TASKS_TREE: &str = "tasks";
+pub(crate) const STAKE_SHARES_TREE: &str = "stake_shares";
+pub(crate) const TASK_OUTCOMES_TREE: &str = "task_outcomes";
+pub(crate) const QUORUM_SNAPSHOTS_TREE: &str = "quorum_snapshots";
+pub(crate) const QUARANTINE_TREE: &str = "quarantine";
+pub(crate) const RELAYED_RESPONSES_TREE: &str = "relayed_responses";
+pub(crate) const BROADCASTS_TREE: &str = "broadcasts";
+pub(crate) const ARCHIVED_TREE: &str = "archived";
+
+/// Persistent store of the operator, versioned by [`MIGRATIONS`], kept by an embedded or a
+/// shared [`Backend`]. When opened with a [`StoreKey`] the values are encrypted at rest, keys
+/// (task indexes and timestamps) are not. Its calls block, tasks go through [`Store::run`].
+#[derive(Debug, Clone)]
+pub struct Store {
+    db: Arc<dyn Backend>,
+    cipher: Option<StoreCipher>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StoreReport {
+    pub schema_version: u32,
+    pub latest_version: u32,
+    pub pending_migrations: Vec<&'static str>,
+    pub encrypted: bool,
+    pub checksum: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub task_index: u32,
+    pub block_number: u32,
+    pub block_hash: H256,
+    pub storage_proof_hash: H256,
+    pub responded_at: u64,
+    /// Absent from the records of tasks responded to before memos were kept
+    #[serde(default)]
+    pub memo: Option<TaskMemo>,
+}
+
+/// How a response was verified, kept locally for audits as the task response format has no
+/// room for auxiliary data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskMemo {
+    /// `{version}+{commit}` of the verifier
+    pub verifier: String,
+    pub verification_ms: u64,
+    /// Whether the block was executed ahead of the task event
+    pub prepared: bool,
+    /// Hash of the substrate endpoints the block was executed and cross-checked against
+    pub data_source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeShareRecord {
+    pub timestamp: u64,
+    pub quorum_number: u8,
+    pub stake: u128,
+    pub total_stake: u128,
+    pub share_pct: f64,
+}
+
+/// Members of the task quorums and their stake at a task reference block, as read by the
+/// aggregator to check signatures, kept to answer disputes without an archive node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuorumSnapshot {
+    pub reference_block: u32,
+    pub quorums: Vec<QuorumMembers>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuorumMembers {
+    pub quorum_number: u8,
+    pub total_stake: u128,
+    /// (operator id, stake) pairs
+    pub operators: Vec<(H256, u128)>,
+}
+
+impl QuorumSnapshot {
+    /// Whether `operator_id` was a member of any of the quorums.
+    pub fn is_member(&self, operator_id: H256) -> bool {
+        self.quorums
+            .iter()
+            .any(|q| q.operators.iter().any(|(id, _)| *id == operator_id))
+    }
+
+    /// Members of the quorums other than `signers`, in the ascending order of their ids
+    /// `checkSignatures` expects.
+    pub fn non_signers(&self, signers: &[H256]) -> Vec<H256> {
+        let mut non_signers: Vec<H256> = self
+            .quorums
+            .iter()
+            .flat_map(|q| q.operators.iter().map(|(id, _)| *id))
+            .filter(|id| !signers.contains(id))
+            .collect();
+        non_signers.sort_unstable();
+        non_signers.dedup();
+        non_signers
+    }
+}
+
+/// Outcome of every task received by the operator, responded or not.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskOutcome {
+    pub task_index: u32,
+    pub received_at: u64,
+    pub responded: bool,
+    pub latency_ms: Option<u64>,
+    /// Set when the response accepted by the task manager differs from the local result
+    #[serde(default)]
+    pub diverged: bool,
+}
+
+/// Failures of a task whose verification keeps failing. Once quarantined the task is no
+/// longer verified until released.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub task_index: u32,
+    /// Error of the last failure
+    pub reason: String,
+    /// Consecutive failures with the same error
+    pub failures: u32,
+    pub first_failed_at: u64,
+    pub last_failed_at: u64,
+    pub quarantined: bool,
+}
+
+/// Hand-off of a task response to the relayer, acknowledged or sent to the aggregator
+/// directly after the relayer failed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayRecord {
+    pub task_index: u32,
+    pub relayed_at: u64,
+    /// Id the relayer acknowledged the response with
+    pub relay_id: Option<String>,
+    /// Error of the relayer when the response was sent directly instead
+    pub fallback_reason: Option<String>,
+}
+
+/// Signed response written before it is sent, until its task is responded on chain or its
+/// response window closes. After a restart the same response is sent again rather than a newly
+/// signed one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BroadcastRecord {
+    pub task_index: u32,
+    pub broadcast_at: u64,
+    /// Last block of the task response window, the record is pruned past it
+    pub expires_at_block: u32,
+    /// Digest of the signed task response
+    pub digest: H256,
+    /// Response as encoded for the aggregator
+    pub response: String,
+}
+
+/// Object written to the archive, deleted from it once the archive retention passed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedObject {
+    pub key: String,
+    pub task_index: u32,
+    pub archived_at: u64,
+}
+
+impl ArchivedObject {
+    /// Store key, by archival time then object key so objects iterate in archival order.
+    fn store_key(&self) -> Vec<u8> {
+        let mut key = self.archived_at.to_be_bytes().to_vec();
+        key.extend_from_slice(self.key.as_bytes());
+        key
+    }
+}
+
+impl QuarantineRecord {
+    /// Counts a failure with `reason` at `now`, quarantining the task once it failed
+    /// `threshold` times in a row with the same error. A different error is likely transient
+    /// and restarts the count.
+    fn fail(
+        previous: Option<Self>,
+        task_index: u32,
+        reason: &str,
+        now: u64,
+        threshold: u32,
+    ) -> Self {
+        let mut record = match previous {
+            Some(record) if record.reason == reason => record,
+            _ => Self {
+                task_index,
+                reason: reason.to_owned(),
+                failures: 0,
+                first_failed_at: now,
+                last_failed_at: now,
+                quarantined: false,
+            },
+        };
+        record.failures += 1;
+        record.last_failed_at = now;
+        record.quarantined = threshold > 0 && record.failures >= threshold;
+        record
+    }
+}
+
+impl Store {
+    /// Opens the store kept by `db`, applying all pending migrations. Encryption can only be
+    /// enabled on a new store, an encrypted store can only be opened with its key.
+    #[instrument]
+    pub fn open(db: Arc<dyn Backend>, key: Option<&StoreKey>) -> eyre::Result<Self> {
+        let mut store = Self { db, cipher: None };
+        let current = store.schema_version()?;
+        ensure_supported(current)?;
+
+        for migration in pending(current) {
+            info!(
+                "Migrating store to version {}: {}",
+                migration.version, migration.description
+            );
+            (migration.apply)(store.db.as_ref())?;
+            store.set_schema_version(migration.version)?;
+        }
+        store.cipher = store.encryption(key)?;
+        store.db.flush()?;
+
+        Ok(store)
+    }
+
+    fn encryption(&self, key: Option<&StoreKey>) -> eyre::Result<Option<StoreCipher>> {
+        let salt = self.db.get(META_TREE, ENCRYPTION_SALT_KEY)?;
+        let check = self.db.get(META_TREE, ENCRYPTION_CHECK_KEY)?;
+        let Some(key) = key else {
+            if salt.is_some() {
+                return Err(eyre!(
+                    "store is encrypted, set --db-passphrase or --db-key-vault-path"
+                ));
+            }
+            return Ok(None);
+        };
+
+        if let (Some(salt), Some(check)) = (salt, check) {
+            let cipher = StoreCipher::new(key, &salt)?;
+            cipher.verify_check_value(&check)?;
+            return Ok(Some(cipher));
+        }
+        for tree in [
+            TASKS_TREE,
+            STAKE_SHARES_TREE,
+            TASK_OUTCOMES_TREE,
+            QUORUM_SNAPSHOTS_TREE,
+            QUARANTINE_TREE,
+        ] {
+            if !self.db.is_empty(tree)? {
+                return Err(eyre!(
+                    "store holds unencrypted data, encryption can only be enabled on a new store"
+                ));
+            }
+        }
+        info!("Enabling store encryption");
+        let salt = StoreCipher::random_salt();
+        let cipher = StoreCipher::new(key, &salt)?;
+        self.db.insert(META_TREE, ENCRYPTION_SALT_KEY, &salt)?;
+        self.db
+            .insert(META_TREE, ENCRYPTION_CHECK_KEY, &cipher.check_value()?)?;
+        Ok(Some(cipher))
+    }
+
+    fn encode<T: Serialize>(&self, value: &T) -> eyre::Result<Vec<u8>> {
+        let json = serde_json::to_vec(value)?;
+        match &self.cipher {
+            Some(cipher) => cipher.seal(&json),
+            None => Ok(json),
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> eyre::Result<T> {
+        Ok(match &self.cipher {
+            Some(cipher) => serde_json::from_slice(&cipher.open(bytes)?)?,
+            None => serde_json::from_slice(bytes)?,
+        })
+    }
+
+    /// Inspects the store kept by `db` without migrating it.
+    #[instrument]
+    pub fn check(db: Arc<dyn Backend>) -> eyre::Result<StoreReport> {
+        let store = Self { db, cipher: None };
+        let schema_version = store.schema_version()?;
+        ensure_supported(schema_version)?;
+
+        Ok(StoreReport {
+            schema_version,
+            latest_version: latest_version(),
+            pending_migrations: pending(schema_version).map(|m| m.description).collect(),
+            encrypted: store.db.contains_key(META_TREE, ENCRYPTION_SALT_KEY)?,
+            checksum: store.db.checksum()?,
+        })
+    }
+
+    /// Runs `f` on a blocking thread, as a shared backend waits on the network.
+    pub async fn run<T, F>(&self, f: F) -> eyre::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Store) -> eyre::Result<T> + Send + 'static,
+    {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || f(&store)).await?
+    }
+
+    pub fn schema_version(&self) -> eyre::Result<u32> {
+        Ok(match self.db.get(META_TREE, SCHEMA_VERSION_KEY)? {
+            Some(v) => u32::from_be_bytes(
+                v.as_slice()
+                    .try_into()
+                    .map_err(|_| eyre!("corrupted schema version"))?,
+            ),
+            None => 0,
+        })
+    }
+
+    fn set_schema_version(&self, version: u32) -> eyre::Result<()> {
+        self.db
+            .insert(META_TREE, SCHEMA_VERSION_KEY, &version.to_be_bytes())
+    }
+
+    pub fn put_task(&self, record: &TaskRecord) -> eyre::Result<()> {
+        self.db.insert(
+            TASKS_TREE,
+            &record.task_index.to_be_bytes(),
+            &self.encode(record)?,
+        )
+    }
+
+    /// Appends a stake share sample, keyed by timestamp then quorum so samples are time ordered.
+    pub fn put_stake_share(&self, record: &StakeShareRecord) -> eyre::Result<()> {
+        let mut key = record.timestamp.to_be_bytes().to_vec();
+        key.push(record.quorum_number);
+        self.db
+            .insert(STAKE_SHARES_TREE, &key, &self.encode(record)?)
+    }
+
+    pub fn put_outcome(&self, outcome: &TaskOutcome) -> eyre::Result<()> {
+        self.db.insert(
+            TASK_OUTCOMES_TREE,
+            &outcome.task_index.to_be_bytes(),
+            &self.encode(outcome)?,
+        )
+    }
+
+    /// Flags the outcome of `task_index` as diverged, returns false if it was never recorded.
+    pub fn mark_diverged(&self, task_index: u32) -> eyre::Result<bool> {
+        let Some(v) = self.db.get(TASK_OUTCOMES_TREE, &task_index.to_be_bytes())? else {
+            return Ok(false);
+        };
+        let mut outcome: TaskOutcome = self.decode(&v)?;
+        outcome.diverged = true;
+        self.put_outcome(&outcome)?;
+        Ok(true)
+    }
+
+    /// Outcomes of the tasks received at or after `since`, a unix timestamp in seconds.
+    pub fn outcomes_since(&self, since: u64) -> eyre::Result<Vec<TaskOutcome>> {
+        let mut all = vec![];
+        // task indexes grow with time, walk back from the latest until the window is left
+        for entry in self.db.iter_rev(TASK_OUTCOMES_TREE) {
+            let outcome: TaskOutcome = self.decode(&entry?.1)?;
+            if outcome.received_at < since {
+                break;
+            }
+            all.push(outcome);
+        }
+        all.reverse();
+        Ok(all)
+    }
+
+    pub fn remove_task(&self, task_index: u32) -> eyre::Result<()> {
+        self.db.remove(TASKS_TREE, &task_index.to_be_bytes())
+    }
+
+    pub fn get_task(&self, task_index: u32) -> eyre::Result<Option<TaskRecord>> {
+        self.db
+            .get(TASKS_TREE, &task_index.to_be_bytes())?
+            .map(|v| self.decode(&v))
+            .transpose()
+    }
+
+    pub fn has_task(&self, task_index: u32) -> eyre::Result<bool> {
+        self.db.contains_key(TASKS_TREE, &task_index.to_be_bytes())
+    }
+
+    /// Creation block of the latest task handled, where catch-up resumes after a restart.
+    pub fn checkpoint(&self) -> eyre::Result<Option<u32>> {
+        self.db
+            .get(META_TREE, CHECKPOINT_KEY)?
+            .map(|v| {
+                Ok(u32::from_be_bytes(
+                    v.as_slice()
+                        .try_into()
+                        .map_err(|_| eyre!("corrupted checkpoint"))?,
+                ))
+            })
+            .transpose()
+    }
+
+    /// Moves the checkpoint forward to `block`, never back.
+    pub fn advance_checkpoint(&self, block: u32) -> eyre::Result<()> {
+        // big endian encoded blocks compare bytewise like numbers
+        self.db
+            .insert_max(META_TREE, CHECKPOINT_KEY, &block.to_be_bytes())
+    }
+
+    /// Latest `limit` responded tasks, newest first.
+    pub fn recent_tasks(&self, limit: usize) -> eyre::Result<Vec<TaskRecord>> {
+        self.db
+            .iter_rev(TASKS_TREE)
+            .take(limit)
+            .map(|entry| self.decode(&entry?.1))
+            .collect()
+    }
+
+    /// Records a failed verification of `task_index`, see [`QuarantineRecord`].
+    pub fn record_failure(
+        &self,
+        task_index: u32,
+        reason: &str,
+        now: u64,
+        threshold: u32,
+    ) -> eyre::Result<QuarantineRecord> {
+        let key = task_index.to_be_bytes();
+        let previous = self
+            .db
+            .get(QUARANTINE_TREE, &key)?
+            .map(|v| self.decode(&v))
+            .transpose()?;
+        let record = QuarantineRecord::fail(previous, task_index, reason, now, threshold);
+        self.db
+            .insert(QUARANTINE_TREE, &key, &self.encode(&record)?)?;
+        Ok(record)
+    }
+
+    pub fn is_quarantined(&self, task_index: u32) -> eyre::Result<bool> {
+        Ok(self
+            .db
+            .get(QUARANTINE_TREE, &task_index.to_be_bytes())?
+            .map(|v| self.decode::<QuarantineRecord>(&v))
+            .transpose()?
+            .is_some_and(|record| record.quarantined))
+    }
+
+    /// Quarantined tasks, newest first.
+    pub fn quarantined(&self) -> eyre::Result<Vec<QuarantineRecord>> {
+        let mut all = vec![];
+        for entry in self.db.iter_rev(QUARANTINE_TREE) {
+            let record: QuarantineRecord = self.decode(&entry?.1)?;
+            if record.quarantined {
+                all.push(record);
+            }
+        }
+        Ok(all)
+    }
+
+    /// Forgets the failures of `task_index`, releasing it from quarantine.
+    pub fn clear_failures(&self, task_index: u32) -> eyre::Result<()> {
+        self.db.remove(QUARANTINE_TREE, &task_index.to_be_bytes())
+    }
+
+    pub fn put_relay(&self, record: &RelayRecord) -> eyre::Result<()> {
+        self.db.insert(
+            RELAYED_RESPONSES_TREE,
+            &record.task_index.to_be_bytes(),
+            &self.encode(record)?,
+        )
+    }
+
+    pub fn put_broadcast(&self, record: &BroadcastRecord) -> eyre::Result<()> {
+        self.db.insert(
+            BROADCASTS_TREE,
+            &record.task_index.to_be_bytes(),
+            &self.encode(record)?,
+        )
+    }
+
+    pub fn get_broadcast(&self, task_index: u32) -> eyre::Result<Option<BroadcastRecord>> {
+        self.db
+            .get(BROADCASTS_TREE, &task_index.to_be_bytes())?
+            .map(|v| self.decode(&v))
+            .transpose()
+    }
+
+    pub fn remove_broadcast(&self, task_index: u32) -> eyre::Result<()> {
+        self.db.remove(BROADCASTS_TREE, &task_index.to_be_bytes())
+    }
+
+    /// Removes the broadcasts whose response window closed before block `head`, returns how
+    /// many were removed.
+    pub fn prune_broadcasts(&self, head: u32) -> eyre::Result<usize> {
+        let mut expired = vec![];
+        for entry in self.db.iter_rev(BROADCASTS_TREE) {
+            let record: BroadcastRecord = self.decode(&entry?.1)?;
+            if record.expires_at_block < head {
+                expired.push(record.task_index);
+            }
+        }
+        for task_index in &expired {
+            self.remove_broadcast(*task_index)?;
+        }
+        Ok(expired.len())
+    }
+
+    pub fn put_archived(&self, object: &ArchivedObject) -> eyre::Result<()> {
+        self.db
+            .insert(ARCHIVED_TREE, &object.store_key(), &self.encode(object)?)
+    }
+
+    /// Objects archived before `before`, oldest first.
+    pub fn archived_before(&self, before: u64) -> eyre::Result<Vec<ArchivedObject>> {
+        let mut objects = vec![];
+        for entry in self.db.iter_rev(ARCHIVED_TREE) {
+            let (key, value) = entry?;
+            if key.len() >= 8 && key[..8] >= before.to_be_bytes()[..] {
+                continue;
+            }
+            objects.push(self.decode(&value)?);
+        }
+        objects.reverse();
+        Ok(objects)
+    }
+
+    pub fn remove_archived(&self, object: &ArchivedObject) -> eyre::Result<()> {
+        self.db.remove(ARCHIVED_TREE, &object.store_key())
+    }
+
+    pub fn put_quorum_snapshot(&self, snapshot: &QuorumSnapshot) -> eyre::Result<()> {
+        self.db.insert(
+            QUORUM_SNAPSHOTS_TREE,
+            &snapshot.reference_block.to_be_bytes(),
+            &self.encode(snapshot)?,
+        )
+    }
+
+    pub fn get_quorum_snapshot(
+        &self,
+        reference_block: u32,
+    ) -> eyre::Result<Option<QuorumSnapshot>> {
+        self.db
+            .get(QUORUM_SNAPSHOTS_TREE, &reference_block.to_be_bytes())?
+            .map(|v| self.decode(&v))
+            .transpose()
+    }
+
+    pub fn has_quorum_snapshot(&self, reference_block: u32) -> eyre::Result<bool> {
+        self.db
+            .contains_key(QUORUM_SNAPSHOTS_TREE, &reference_block.to_be_bytes())
+    }
+}
+
+fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or_default()
+}
+
+fn pending(current: u32) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS.iter().filter(move |m| m.version > current)
+}
+
+fn ensure_supported(version: u32) -> eyre::Result<()> {
+    if version > latest_version() {
+        return Err(eyre!(
+            "store schema version {} is newer than supported version {}, refusing to downgrade",
+            version,
+            latest_version()
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_quarantine_record() {
+    let fail = |previous, reason| QuarantineRecord::fail(previous, 1, reason, 10, 2);
+    let first = fail(None, "bad block");
+    assert!(!first.quarantined);
+    let transient = fail(Some(first.clone()), "timeout");
+    assert_eq!((transient.failures, transient.quarantined), (1, false));
+    let second = fail(Some(first), "bad block");
+    assert_eq!((second.failures, second.quarantined), (2, true));
+    assert!(!QuarantineRecord::fail(None, 1, "bad block", 10, 0).quarantined);
+}
+
+#[test]
+fn test_encrypted_store() {
+    let path = std::env::temp_dir().join(format!("store-test-{}", std::process::id()));
+    let key = StoreKey::Passphrase("correct horse".into());
+    let record = TaskRecord {
+        task_index: 1,
+        block_number: 2,
+        block_hash: H256::repeat_byte(3),
+        storage_proof_hash: H256::repeat_byte(4),
+        responded_at: 5,
+        memo: None,
+    };
+    let open =
+        |key: Option<&StoreKey>| Store::open(Arc::new(SledBackend::open(&path).unwrap()), key);
+    {
+        let store = open(Some(&key)).unwrap();
+        store.put_task(&record).unwrap();
+        let raw = store.db.get(TASKS_TREE, &1u32.to_be_bytes());
+        assert!(serde_json::from_slice::<TaskRecord>(&raw.unwrap().unwrap()).is_err());
+    }
+    assert!(open(None).is_err());
+    assert!(open(Some(&StoreKey::Passphrase("wrong".into()))).is_err());
+    let store = open(Some(&key)).unwrap();
+    assert_eq!(
+        store.get_task(1).unwrap().unwrap().block_hash,
+        record.block_hash
+    );
+    let broadcast = BroadcastRecord {
+        task_index: 1,
+        broadcast_at: 5,
+        expires_at_block: 100,
+        digest: H256::repeat_byte(6),
+        response: "{}".into(),
+    };
+    store.put_broadcast(&broadcast).unwrap();
+    assert_eq!(store.get_broadcast(1).unwrap(), Some(broadcast.clone()));
+    store.remove_broadcast(1).unwrap();
+    assert_eq!(store.get_broadcast(1).unwrap(), None);
+    let expired = BroadcastRecord {
+        task_index: 2,
+        expires_at_block: 50,
+        ..broadcast.clone()
+    };
+    store.put_broadcast(&broadcast).unwrap();
+    store.put_broadcast(&expired).unwrap();
+    assert_eq!(store.prune_broadcasts(50).unwrap(), 0);
+    assert_eq!(store.prune_broadcasts(51).unwrap(), 1);
+    assert_eq!(store.get_broadcast(2).unwrap(), None);
+    assert_eq!(store.get_broadcast(1).unwrap(), Some(broadcast));
+    store.remove_broadcast(1).unwrap();
+    let archived = |key: &str, archived_at| ArchivedObject {
+        key: key.into(),
+        task_index: 1,
+        archived_at,
+    };
+    for object in [archived("b", 20), archived("a", 10), archived("c", 30)] {
+        store.put_archived(&object).unwrap();
+    }
+    assert_eq!(
+        store.archived_before(30).unwrap(),
+        vec![archived("a", 10), archived("b", 20)]
+    );
+    store.remove_archived(&archived("a", 10)).unwrap();
+    store.remove_task(1).unwrap();
+    assert_eq!(store.archived_before(30).unwrap(), vec![archived("b", 20)]);
+    assert!(store.get_task(1).unwrap().is_none());
+    drop(store);
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn test_quorum_snapshot_non_signers() {
+    let id = H256::repeat_byte;
+    let snapshot = QuorumSnapshot {
+        reference_block: 10,
+        quorums: vec![
+            QuorumMembers {
+                quorum_number: 0,
+                total_stake: 3,
+                operators: vec![(id(3), 1), (id(1), 1), (id(2), 1)],
+            },
+            QuorumMembers {
+                quorum_number: 1,
+                total_stake: 2,
+                operators: vec![(id(3), 1), (id(4), 1)],
+            },
+        ],
+    };
+    assert!(snapshot.is_member(id(4)));
+    assert!(!snapshot.is_member(id(5)));
+    assert_eq!(snapshot.non_signers(&[id(2)]), vec![id(1), id(3), id(4)]);
+}
@@ -0,0 +1,210 @@
+use std::{collections::VecDeque, fs::File, future::Future, io::BufReader, path::Path, sync::Arc};
+
+use eyre::eyre;
+use rustls::{pki_types::CertificateDer, ClientConfig, RootCertStore};
+use sha2::{Digest, Sha256};
+use tokio::sync::oneshot;
+use tokio_postgres::{config::SslMode, Client, Config};
+use tokio_postgres_rustls::MakeRustlsConnect;
+use tracing::{error, info};
+
+use super::backend::{Backend, Entry};
+
+/// Entries fetched at once when iterating a tree.
+const PAGE_SIZE: i64 = 256;
+
+/// Store in a shared Postgres database, for deployments running several instances against
+/// the same state. All trees live in the `avs_store` table.
+#[derive(Debug)]
+pub struct PostgresBackend {
+    client: Client,
+}
+
+impl PostgresBackend {
+    /// Connects to the database at `url`, a `postgres://` connection string, over TLS. The
+    /// server certificate is verified against `ca_cert` when given, else the webpki roots.
+    ///
+    /// The connection runs on a thread of its own, queries can then wait on it from any
+    /// thread without depending on the runtime of the caller.
+    pub async fn connect(url: &str, ca_cert: Option<&Path>) -> eyre::Result<Self> {
+        let mut config: Config = url.parse()?;
+        if config.get_ssl_mode() == SslMode::Disable {
+            return Err(eyre!("the store database connection must be encrypted"));
+        }
+        config.ssl_mode(SslMode::Require);
+        let tls = tls_connector(ca_cert)?;
+
+        let (connected, client) = oneshot::channel::<eyre::Result<Client>>();
+        std::thread::Builder::new()
+            .name("store-db".into())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime,
+                    Err(e) => return drop(connected.send(Err(e.into()))),
+                };
+                runtime.block_on(async move {
+                    match config.connect(tls).await {
+                        Ok((client, connection)) => {
+                            let _ = connected.send(Ok(client));
+                            if let Err(e) = connection.await {
+                                error!("Store database connection closed: {}", e);
+                            }
+                        }
+                        Err(e) => drop(connected.send(Err(e.into()))),
+                    }
+                })
+            })?;
+        let client = client.await??;
+
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS avs_store (
+                    tree TEXT NOT NULL,
+                    key BYTEA NOT NULL,
+                    value BYTEA NOT NULL,
+                    PRIMARY KEY (tree, key)
+                )",
+            )
+            .await?;
+        info!("Connected to the store database");
+        Ok(Self { client })
+    }
+
+    fn page(&self, tree: &str, before: Option<&[u8]>) -> eyre::Result<VecDeque<Entry>> {
+        let rows = block_on(self.client.query(
+            "SELECT key, value FROM avs_store WHERE tree = $1 AND ($2::BYTEA IS NULL OR key < $2)
+            ORDER BY key DESC LIMIT $3",
+            &[&tree, &before, &PAGE_SIZE],
+        ))?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+}
+
+fn tls_connector(ca_cert: Option<&Path>) -> eyre::Result<MakeRustlsConnect> {
+    let mut roots = RootCertStore::empty();
+    match ca_cert {
+        Some(path) => {
+            let mut reader = BufReader::new(File::open(path)?);
+            for cert in rustls_pemfile::certs(&mut reader)? {
+                roots.add(CertificateDer::from(cert))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(MakeRustlsConnect::new(config))
+}
+
+/// Waits for a query on the calling thread, the connection makes progress on its own thread.
+/// Tasks call the store through [`super::Store::run`] to keep the runtime responsive.
+fn block_on<F: Future>(future: F) -> F::Output {
+    futures::executor::block_on(future)
+}
+
+impl Backend for PostgresBackend {
+    fn open_tree(&self, _tree: &str) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    fn get(&self, tree: &str, key: &[u8]) -> eyre::Result<Option<Vec<u8>>> {
+        let row = block_on(self.client.query_opt(
+            "SELECT value FROM avs_store WHERE tree = $1 AND key = $2",
+            &[&tree, &key],
+        ))?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    fn insert(&self, tree: &str, key: &[u8], value: &[u8]) -> eyre::Result<()> {
+        block_on(self.client.execute(
+            "INSERT INTO avs_store (tree, key, value) VALUES ($1, $2, $3)
+            ON CONFLICT (tree, key) DO UPDATE SET value = EXCLUDED.value",
+            &[&tree, &key, &value],
+        ))?;
+        Ok(())
+    }
+
+    fn remove(&self, tree: &str, key: &[u8]) -> eyre::Result<()> {
+        block_on(self.client.execute(
+            "DELETE FROM avs_store WHERE tree = $1 AND key = $2",
+            &[&tree, &key],
+        ))?;
+        Ok(())
+    }
+
+    fn insert_max(&self, tree: &str, key: &[u8], value: &[u8]) -> eyre::Result<()> {
+        block_on(self.client.execute(
+            "INSERT INTO avs_store (tree, key, value) VALUES ($1, $2, $3)
+            ON CONFLICT (tree, key) DO UPDATE SET value = GREATEST(avs_store.value, EXCLUDED.value)",
+            &[&tree, &key, &value],
+        ))?;
+        Ok(())
+    }
+
+    fn iter_rev<'a>(&'a self, tree: &str) -> Box<dyn Iterator<Item = eyre::Result<Entry>> + 'a> {
+        Box::new(Pages {
+            backend: self,
+            tree: tree.to_string(),
+            page: VecDeque::new(),
+            before: None,
+            done: false,
+        })
+    }
+
+    fn flush(&self) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    fn checksum(&self) -> eyre::Result<u32> {
+        let rows = block_on(self.client.query(
+            "SELECT tree, key, value FROM avs_store ORDER BY tree, key",
+            &[],
+        ))?;
+        let mut hasher = Sha256::new();
+        for row in rows {
+            hasher.update(row.get::<_, &str>(0));
+            hasher.update(row.get::<_, &[u8]>(1));
+            hasher.update(row.get::<_, &[u8]>(2));
+        }
+        let digest = hasher.finalize();
+        Ok(u32::from_be_bytes(digest[..4].try_into()?))
+    }
+}
+
+/// Walks a tree from its greatest key down, a page at a time.
+struct Pages<'a> {
+    backend: &'a PostgresBackend,
+    tree: String,
+    page: VecDeque<Entry>,
+    /// Smallest key returned so far
+    before: Option<Vec<u8>>,
+    done: bool,
+}
+
+impl Iterator for Pages<'_> {
+    type Item = eyre::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.page.is_empty() && !self.done {
+            match self.backend.page(&self.tree, self.before.as_deref()) {
+                Ok(page) => {
+                    self.done = (page.len() as i64) < PAGE_SIZE;
+                    self.page = page;
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        let entry = self.page.pop_front()?;
+        self.before = Some(entry.0.clone());
+        Some(Ok(entry))
+    }
+}
@@ -0,0 +1,167 @@
+use std::{path::Path, time::Duration};
+
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, Signature},
+};
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::store::QuorumMembers;
+
+/// Where catch-up starts after a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncSource {
+    /// Resume from the local checkpoint, still within the response window
+    Checkpoint,
+    /// Backfill the response window only, no checkpoint or one whose tasks all expired
+    ResponseWindow,
+}
+
+/// Startup sync decision, comparing the local checkpoint with the chain head.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyncPlan {
+    pub source: SyncSource,
+    pub checkpoint_block: Option<u32>,
+    pub head_block: u32,
+    pub from_block: u32,
+}
+
+impl SyncPlan {
+    /// Tasks older than the response window can no longer be answered, so a checkpoint
+    /// further behind than the window needs no resync, only the window is backfilled.
+    pub fn new(checkpoint_block: Option<u32>, head_block: u32, window: u32) -> Self {
+        let window_start = head_block.saturating_sub(window);
+        let (source, from_block) = match checkpoint_block {
+            Some(block) if block >= window_start => (SyncSource::Checkpoint, block),
+            _ => (SyncSource::ResponseWindow, window_start),
+        };
+        Self {
+            source,
+            checkpoint_block,
+            head_block,
+            from_block,
+        }
+    }
+
+    /// Blocks between the checkpoint and the head, `None` without a checkpoint.
+    pub fn lag_blocks(&self) -> Option<u32> {
+        self.checkpoint_block
+            .map(|block| self.head_block.saturating_sub(block))
+    }
+}
+
+/// Time to verify `tasks` open tasks `concurrency` at a time, from the verification times
+/// of recent tasks.
+pub fn estimate_catch_up(tasks: usize, recent_ms: &[u64], concurrency: usize) -> Option<Duration> {
+    if recent_ms.is_empty() {
+        return None;
+    }
+    let avg_ms = recent_ms.iter().sum::<u64>() / recent_ms.len() as u64;
+    let rounds = tasks.div_ceil(concurrency.max(1)) as u64;
+    Some(Duration::from_millis(avg_ms * rounds))
+}
+
+/// Registry state replayed from the contract events up to `block`, seeding the caches of
+/// another node so it only replays the events after `block` instead of all from genesis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryCheckpoint {
+    pub chain_id: u64,
+    pub block: u64,
+    /// Strategies whitelisted for deposit
+    pub strategies: Vec<Address>,
+    /// Latest stake of every operator per quorum
+    pub stakes: Vec<QuorumMembers>,
+}
+
+/// Checkpoint file, `checkpoint` holds the exact JSON that was EIP-191 signed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignedCheckpoint {
+    pub checkpoint: String,
+    pub signature: Bytes,
+}
+
+impl SignedCheckpoint {
+    pub async fn sign(checkpoint: &RegistryCheckpoint, wallet: &LocalWallet) -> eyre::Result<Self> {
+        let checkpoint = serde_json::to_string(checkpoint)?;
+        let signature = wallet.sign_message(&checkpoint).await?;
+        Ok(Self {
+            checkpoint,
+            signature: signature.to_vec().into(),
+        })
+    }
+
+    /// Returns the checkpoint and its signer, once checked that one of `trusted` signed it
+    /// for `chain_id`.
+    pub fn verify(
+        &self,
+        chain_id: u64,
+        trusted: &[Address],
+    ) -> eyre::Result<(RegistryCheckpoint, Address)> {
+        let signer =
+            Signature::try_from(self.signature.as_ref())?.recover(self.checkpoint.as_str())?;
+        if !trusted.contains(&signer) {
+            return Err(eyre!("checkpoint signed by untrusted {:?}", signer));
+        }
+        let checkpoint: RegistryCheckpoint = serde_json::from_str(&self.checkpoint)?;
+        if checkpoint.chain_id != chain_id {
+            return Err(eyre!(
+                "checkpoint of chain {}, expected {}",
+                checkpoint.chain_id,
+                chain_id
+            ));
+        }
+        Ok((checkpoint, signer))
+    }
+
+    pub fn read(path: &Path) -> eyre::Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn write(&self, path: &Path) -> eyre::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sync_plan() {
+    let plan = SyncPlan::new(Some(950), 1000, 100);
+    assert_eq!(plan.source, SyncSource::Checkpoint);
+    assert_eq!(plan.from_block, 950);
+    assert_eq!(plan.lag_blocks(), Some(50));
+
+    let behind = SyncPlan::new(Some(500), 1000, 100);
+    assert_eq!(behind.source, SyncSource::ResponseWindow);
+    assert_eq!(behind.from_block, 900);
+    assert_eq!(SyncPlan::new(None, 1000, 100).from_block, 900);
+
+    assert_eq!(
+        estimate_catch_up(5, &[1000, 3000], 2),
+        Some(Duration::from_secs(6))
+    );
+    assert_eq!(estimate_catch_up(5, &[], 2), None);
+}
+
+#[tokio::test]
+async fn test_signed_checkpoint() {
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+    let checkpoint = RegistryCheckpoint {
+        chain_id: 1,
+        block: 100,
+        strategies: vec![Address::repeat_byte(1)],
+        stakes: vec![],
+    };
+    let mut signed = SignedCheckpoint::sign(&checkpoint, &wallet).await.unwrap();
+    let trusted = [wallet.address()];
+    assert_eq!(
+        signed.verify(1, &trusted).unwrap(),
+        (checkpoint, wallet.address())
+    );
+    assert!(signed.verify(2, &trusted).is_err());
+    assert!(signed.verify(1, &[Address::zero()]).is_err());
+
+    signed.checkpoint = signed.checkpoint.replace("100", "101");
+    assert!(signed.verify(1, &trusted).is_err());
+}
@@ -0,0 +1,220 @@
+use std::{
+    fmt,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+use crate::metrics::metrics;
+
+pub use avs_operator_sdk::task::TaskType;
+
+/// How far the node got in verifying a [`PendingTask`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationState {
+    /// Waiting in the task queue
+    Queued,
+    /// Executing the task block
+    Executing,
+    /// Cross-checking the block hash and reviewing the result with the plugins
+    Reviewing,
+    /// Waiting for `--min-confirmation-delay-blocks` on top of the task event
+    Confirming,
+    /// Signing the response
+    Signing,
+}
+
+/// Task known to the node but not signed yet, served on `GET /pending-tasks`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PendingTask {
+    pub task_index: u32,
+    pub block_number: u32,
+    pub task_created_block: u32,
+    /// Last block at which the task manager accepts a response
+    pub expires_at_block: u32,
+    /// Unix time the node received the task
+    pub received_at: u64,
+    pub state: VerificationState,
+}
+
+/// Measures the pipeline stages of a task against an optional latency budget. The stages
+/// follow the task from its event to its confirmation:
+/// - `queue` from the task event to the start of processing
+/// - `execute`, `cross_check` and `plugins` from fetching the block to its verification
+/// - `delay` waiting for the confirmations of the task event, if configured
+/// - `sign` from the verification to the signature
+/// - `respond` from the signature to the broadcast to the aggregator
+/// - `confirm` from the broadcast to the `TaskResponded` event, see [`observe_stage`]
+pub struct TaskTimer {
+    task_index: u32,
+    started: Instant,
+    last: Instant,
+    budget: Option<Duration>,
+    stages: Vec<(&'static str, Duration)>,
+}
+
+impl TaskTimer {
+    /// Starts timing the processing of a task whose event was received at `received`, the
+    /// time spent queued counts against the budget.
+    pub fn start(task_index: u32, received: Instant, budget: Option<Duration>) -> Self {
+        let now = Instant::now();
+        let mut timer = Self {
+            task_index,
+            started: received,
+            last: now,
+            budget,
+            stages: vec![],
+        };
+        timer.record("queue", now.saturating_duration_since(received));
+        timer
+    }
+
+    /// Records the time spent in `stage` since the previous stage ended.
+    pub fn stage(&mut self, stage: &'static str) -> Duration {
+        let now = Instant::now();
+        let took = now - self.last;
+        self.last = now;
+        self.record(stage, took);
+        #[cfg(feature = "fault-injection")]
+        crate::faults::crash_point(stage);
+        took
+    }
+
+    fn record(&mut self, stage: &'static str, took: Duration) {
+        observe_stage(self.task_index, stage, took);
+        self.stages.push((stage, took));
+    }
+
+    fn stage_ms(&self, stage: &str) -> Option<u64> {
+        self.stages
+            .iter()
+            .find(|(name, _)| *name == stage)
+            .map(|(_, took)| took.as_millis() as u64)
+    }
+
+    /// Logs the duration of every stage reached as structured fields.
+    pub fn log_stages(&self) {
+        info!(
+            task_index = self.task_index,
+            queue_ms = self.stage_ms("queue"),
+            execute_ms = self.stage_ms("execute"),
+            cross_check_ms = self.stage_ms("cross_check"),
+            plugins_ms = self.stage_ms("plugins"),
+            sign_ms = self.stage_ms("sign"),
+            respond_ms = self.stage_ms("respond"),
+            total_ms = self.elapsed().as_millis() as u64,
+            "Task {} stage timings",
+            self.task_index
+        );
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn over_budget(&self) -> bool {
+        self.budget.is_some_and(|budget| self.elapsed() > budget)
+    }
+}
+
+/// Exports the time a task spent in `stage` to the `task_stage_seconds` histogram.
+pub fn observe_stage(task_index: u32, stage: &'static str, took: Duration) {
+    metrics()
+        .task_stage_seconds
+        .with_label_values(&[stage])
+        .observe(took.as_secs_f64());
+    debug!("Task {} stage {} took {:?}", task_index, stage, took);
+}
+
+/// Error of a task step abandoned through its cancellation token, e.g. once the task
+/// response window expired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Runs `fut` until `cancel` is triggered, dropping it and failing with [`Cancelled`] then.
+pub async fn cancellable<F: Future>(
+    cancel: &CancellationToken,
+    fut: F,
+) -> Result<F::Output, Cancelled> {
+    tokio::select! {
+        biased;
+        () = cancel.cancelled() => Err(Cancelled),
+        output = fut => Ok(output),
+    }
+}
+
+/// Blocks left between the latest block a response is signed at and the close of its
+/// response window, for the aggregator to aggregate and submit it.
+pub const DEADLINE_MARGIN_BLOCKS: u32 = 2;
+
+/// Block to wait for before signing the response to a task created at `created_block`,
+/// `delay` blocks later but never within [`DEADLINE_MARGIN_BLOCKS`] of `expires_at`.
+pub fn confirmation_target(created_block: u32, delay: u32, expires_at: u32) -> u32 {
+    created_block
+        .saturating_add(delay)
+        .min(expires_at.saturating_sub(DEADLINE_MARGIN_BLOCKS))
+        .max(created_block)
+}
+
+/// Renders catch-up progress as a fixed width bar followed by the percentage done.
+pub fn progress_bar(done: usize, total: usize) -> String {
+    const WIDTH: usize = 20;
+    let pct = (done * 100).checked_div(total).unwrap_or(100);
+    let filled = pct * WIDTH / 100;
+    format!(
+        "[{}{}] {:>3}%",
+        "#".repeat(filled),
+        ".".repeat(WIDTH - filled),
+        pct
+    )
+}
+
+#[test]
+fn test_progress_bar() {
+    assert_eq!(progress_bar(0, 4), "[....................]   0%");
+    assert_eq!(progress_bar(1, 4), "[#####...............]  25%");
+    assert_eq!(progress_bar(4, 4), "[####################] 100%");
+    assert_eq!(progress_bar(0, 0), "[####################] 100%");
+}
+
+#[test]
+fn test_confirmation_target() {
+    assert_eq!(confirmation_target(100, 0, 130), 100);
+    assert_eq!(confirmation_target(100, 5, 130), 105);
+    // late enough for the aggregator to use the response
+    assert_eq!(confirmation_target(100, 50, 130), 128);
+    assert_eq!(confirmation_target(100, 5, 101), 100);
+}
+
+#[tokio::test]
+async fn test_cancellable() {
+    let cancel = CancellationToken::new();
+    assert_eq!(cancellable(&cancel, async { 1 }).await, Ok(1));
+    cancel.cancel();
+    assert_eq!(
+        cancellable(&cancel, std::future::pending::<()>()).await,
+        Err(Cancelled)
+    );
+}
+
+#[test]
+fn test_timer_stages() {
+    let received = Instant::now() - Duration::from_millis(200);
+    let mut timer = TaskTimer::start(1, received, None);
+    timer.stage("execute");
+    assert!(timer.stage_ms("queue").is_some_and(|ms| ms >= 200));
+    assert!(timer.stage_ms("execute").is_some());
+    assert_eq!(timer.stage_ms("sign"), None);
+}
@@ -0,0 +1,175 @@
+use std::fmt;
+
+use ethers::{
+    providers::{Middleware, MiddlewareError},
+    types::{transaction::eip2718::TypedTransaction, Address, TransactionRequest, U256},
+};
+use eyre::eyre;
+use serde::{Serialize, Serializer};
+
+/// Decimals of ETH amounts counted in wei.
+pub const ETH_DECIMALS: u8 = 18;
+/// Decimals of gwei amounts counted in wei.
+pub const GWEI_DECIMALS: u8 = 9;
+/// Decimals of tokens which do not implement the optional ERC20 `decimals()`.
+pub const DEFAULT_TOKEN_DECIMALS: u8 = 18;
+
+/// Decimals of the stake registry weights. A weight is the shares times the strategy
+/// multiplier divided by 1e18, so it keeps the decimals of 18 decimals shares weighted 1e18.
+pub const STAKE_DECIMALS: u8 = 18;
+
+/// Selector of the ERC20 `decimals()`.
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// Amount of a token in its smallest unit, displayed in whole tokens per its decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TokenAmount {
+    pub token: Address,
+    /// Amount in the smallest unit of the token
+    #[serde(serialize_with = "serialize_decimal")]
+    pub raw: U256,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn is_zero(&self) -> bool {
+        self.raw.is_zero()
+    }
+
+    /// Amount in whole tokens, rounded to the closest `f64`, for metrics.
+    pub fn to_f64(self) -> f64 {
+        to_f64(self.raw, self.decimals)
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_units(self.raw, self.decimals))
+    }
+}
+
+pub(crate) fn serialize_decimal<S: Serializer>(
+    amount: &U256,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(amount)
+}
+
+/// Formats `amount` of a unit with `decimals` decimals as an exact decimal number of whole
+/// units, without trailing zeros, e.g. 1500000 with 6 decimals as `1.5`.
+pub fn format_units(amount: U256, decimals: u8) -> String {
+    let digits = amount.to_string();
+    let decimals = usize::from(decimals);
+    let (whole, fraction) = if digits.len() > decimals {
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        (whole.to_owned(), fraction.to_owned())
+    } else {
+        (
+            "0".to_owned(),
+            format!("{:0>width$}", digits, width = decimals),
+        )
+    };
+    match fraction.trim_end_matches('0') {
+        "" => whole,
+        fraction => format!("{}.{}", whole, fraction),
+    }
+}
+
+/// Parses a decimal number of whole units into the smallest unit with `decimals` decimals,
+/// failing rather than rounding when it has more decimals.
+pub fn parse_units(amount: &str, decimals: u8) -> eyre::Result<U256> {
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(eyre!("{:?} is not a decimal amount", amount));
+    }
+    if fraction.len() > usize::from(decimals) {
+        return Err(eyre!("{} has more than {} decimals", amount, decimals));
+    }
+    let digits = format!(
+        "{}{:0<width$}",
+        whole,
+        fraction,
+        width = usize::from(decimals)
+    );
+    U256::from_dec_str(&digits).map_err(|e| eyre!("{} is out of range: {}", amount, e))
+}
+
+/// `amount` with `decimals` decimals in whole units, rounded to the closest `f64`.
+pub fn to_f64(amount: U256, decimals: u8) -> f64 {
+    format_units(amount, decimals).parse().unwrap_or(f64::MAX)
+}
+
+pub fn wei_to_eth(wei: U256) -> f64 {
+    to_f64(wei, ETH_DECIMALS)
+}
+
+pub fn wei_to_gwei(wei: U256) -> f64 {
+    to_f64(wei, GWEI_DECIMALS)
+}
+
+/// Wei in `eth`, as the decimal number it is displayed as.
+pub fn eth_to_wei(eth: f64) -> eyre::Result<U256> {
+    parse_units(&eth.to_string(), ETH_DECIMALS)
+}
+
+/// Decimals of the ERC20 `token`, [`DEFAULT_TOKEN_DECIMALS`] when it does not implement the
+/// optional `decimals()`.
+pub async fn token_decimals<M: Middleware>(client: &M, token: Address) -> eyre::Result<u8> {
+    let call: TypedTransaction = TransactionRequest::new()
+        .to(token)
+        .data(DECIMALS_SELECTOR.to_vec())
+        .into();
+    let output = match client.call(&call, None).await {
+        Ok(output) => output,
+        Err(e) if e.as_error_response().is_some_and(|r| r.is_revert()) => {
+            return Ok(DEFAULT_TOKEN_DECIMALS)
+        }
+        Err(e) => return Err(eyre!("cannot read the decimals of {:?}: {}", token, e)),
+    };
+    if output.is_empty() {
+        return Ok(DEFAULT_TOKEN_DECIMALS);
+    }
+    if output.len() != 32 {
+        return Err(eyre!("{:?} returned malformed decimals {}", token, output));
+    }
+    u8::try_from(U256::from_big_endian(&output))
+        .map_err(|_| eyre!("{:?} returned out of range decimals {}", token, output))
+}
+
+#[test]
+fn test_units() {
+    let units = |s| parse_units(s, 6).unwrap();
+    assert_eq!(units("1.5"), U256::from(1_500_000));
+    assert_eq!(units("0.000001"), U256::one());
+    assert_eq!(units(".5"), U256::from(500_000));
+    assert_eq!(units("2"), U256::from(2_000_000));
+    for invalid in ["", ".", "1.0000001", "-1", "1e6", "1,5"] {
+        assert!(parse_units(invalid, 6).is_err(), "{:?}", invalid);
+    }
+
+    assert_eq!(format_units(1_500_000.into(), 6), "1.5");
+    assert_eq!(format_units(1.into(), 6), "0.000001");
+    assert_eq!(format_units(2_000_000.into(), 6), "2");
+    assert_eq!(format_units(0.into(), 6), "0");
+    assert_eq!(format_units(42.into(), 0), "42");
+    let max = format_units(U256::MAX, ETH_DECIMALS);
+    assert_eq!(parse_units(&max, ETH_DECIMALS).unwrap(), U256::MAX);
+
+    // the same raw amount is a million times more of a 6 decimals token
+    let usdc = TokenAmount {
+        token: Address::zero(),
+        raw: 2_500_000.into(),
+        decimals: 6,
+    };
+    assert_eq!(usdc.to_string(), "2.5");
+    assert_eq!(wei_to_eth(usdc.raw), 0.0000000000025);
+    assert_eq!(
+        serde_json::to_value(usdc).unwrap()["raw"],
+        serde_json::json!("2500000")
+    );
+
+    assert_eq!(eth_to_wei(0.1).unwrap(), U256::exp10(17));
+    assert_eq!(wei_to_gwei(U256::exp10(9) * 3 / 2), 1.5);
+}
@@ -0,0 +1,143 @@
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use ethers::types::{Address, Signature};
+use eyre::{eyre, OptionExt};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{error, info, instrument, warn};
+
+use crate::cli::UpdateArgs;
+
+/// Address of the release signing key, embedded at build time from `AVS_RELEASE_SIGNER`.
+const RELEASE_SIGNER: Option<&str> = option_env!("AVS_RELEASE_SIGNER");
+
+/// Release manifest as signed by the release key.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    #[serde(default)]
+    pub critical: bool,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub artifacts: Vec<ReleaseArtifact>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseArtifact {
+    /// `{os}-{arch}`, e.g. `linux-x86_64`
+    pub platform: String,
+    pub url: String,
+    pub sha256: String,
+}
+
+/// Published manifest, `manifest` holds the exact JSON that was EIP-191 signed.
+#[derive(Debug, Deserialize)]
+struct SignedManifest {
+    manifest: String,
+    signature: Signature,
+}
+
+/// Periodically checks the release manifest, notifying when a newer version is published and
+/// downloading critical updates when `--update-download-dir` is set.
+#[instrument(skip_all)]
+pub async fn watch(cfg: UpdateArgs) {
+    let Some(signer) = release_signer(&cfg) else {
+        return warn!("No release signer embedded or configured, update checks are disabled");
+    };
+    let mut interval = tokio::time::interval(Duration::from_secs(cfg.update_check_interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(e) = check(&cfg, signer).await {
+            error!("Update check failed: {:?}", e);
+        }
+    }
+}
+
+fn release_signer(cfg: &UpdateArgs) -> Option<Address> {
+    cfg.update_signer
+        .or_else(|| RELEASE_SIGNER.and_then(|s| Address::from_str(s).ok()))
+}
+
+async fn check(cfg: &UpdateArgs, signer: Address) -> eyre::Result<()> {
+    let url = cfg
+        .update_manifest_url
+        .as_deref()
+        .ok_or_eyre("--update-manifest-url not configured")?;
+    let body = reqwest::get(url).await?.error_for_status()?.text().await?;
+    let manifest = verify(&body, signer)?;
+
+    let running = Version::parse(env!("CARGO_PKG_VERSION"))?;
+    let latest = Version::parse(&manifest.version)?;
+    if latest <= running {
+        info!("Running the latest version {}", running);
+        return Ok(());
+    }
+    if !manifest.critical {
+        info!(
+            "Version {} is available, running {}: {}",
+            latest, running, manifest.notes
+        );
+        return Ok(());
+    }
+
+    warn!(
+        "Critical update {} is available, running {}: {}",
+        latest, running, manifest.notes
+    );
+    if let Some(dir) = &cfg.update_download_dir {
+        let path = download(&manifest, dir).await?;
+        warn!("Critical update downloaded to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Checks the manifest signature against the release `signer` and parses it.
+pub fn verify(body: &str, signer: Address) -> eyre::Result<ReleaseManifest> {
+    let signed: SignedManifest = serde_json::from_str(body)?;
+    signed
+        .signature
+        .verify(signed.manifest.as_str(), signer)
+        .map_err(|e| eyre!("invalid release manifest signature: {}", e))?;
+    Ok(serde_json::from_str(&signed.manifest)?)
+}
+
+async fn download(manifest: &ReleaseManifest, dir: &std::path::Path) -> eyre::Result<PathBuf> {
+    let platform = format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH);
+    let artifact = manifest
+        .artifacts
+        .iter()
+        .find(|a| a.platform == platform)
+        .ok_or_else(|| eyre!("no release artifact for {}", platform))?;
+
+    let bytes = reqwest::get(&artifact.url)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let digest = hex::encode(Sha256::digest(&bytes));
+    if !digest.eq_ignore_ascii_case(artifact.sha256.trim_start_matches("0x")) {
+        return Err(eyre!(
+            "release artifact checksum {} does not match manifest {}",
+            digest,
+            artifact.sha256
+        ));
+    }
+
+    let path = dir.join(format!("avs-finalizer-{}-{}", manifest.version, platform));
+    tokio::fs::write(&path, &bytes).await?;
+    Ok(path)
+}
+
+#[tokio::test]
+async fn test_verify_manifest() {
+    use ethers::signers::{LocalWallet, Signer};
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+    let manifest = r#"{"version":"9.9.9","critical":true}"#;
+    let signature = wallet.sign_message(manifest).await.unwrap();
+    let body = serde_json::json!({ "manifest": manifest, "signature": signature }).to_string();
+
+    assert!(verify(&body, wallet.address()).unwrap().critical);
+    assert!(verify(&body, Address::random()).is_err());
+}
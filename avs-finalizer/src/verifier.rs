@@ -0,0 +1,205 @@
+use std::{collections::HashMap, fmt::Debug, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use ethers::types::H256;
+use eyre::eyre;
+use node_primitives::BlockNumber;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::{executor::execute::execute_block, operator::Block, task::TaskType};
+
+/// Block hash and storage proof hash of a verified block.
+pub type Proofs = (H256, H256);
+
+/// Version of the verification algorithm of a task type used when no switchover applies.
+pub const INITIAL_VERSION: u32 = 1;
+
+/// Verification algorithm of a version of a task type. A protocol upgrade changing how tasks
+/// are verified ships as a new version next to the previous ones, selected by a
+/// [`Switchover`], so the tasks created before it still verify the old way.
+#[async_trait]
+pub trait Verifier: Debug + Send + Sync {
+    fn task_type(&self) -> TaskType;
+
+    fn version(&self) -> u32;
+
+    /// [`Proofs`] of the substrate block `block_number`. Stops with
+    /// [`crate::task::Cancelled`] once `cancel` is triggered.
+    async fn verify(
+        &self,
+        substrate_uri: &str,
+        block_number: BlockNumber,
+        cancel: &CancellationToken,
+    ) -> eyre::Result<Proofs>;
+}
+
+/// Re-executes the block with the runtime it was produced by.
+#[derive(Debug)]
+pub struct ExecuteBlockV1;
+
+#[async_trait]
+impl Verifier for ExecuteBlockV1 {
+    fn task_type(&self) -> TaskType {
+        TaskType::ExecuteBlock
+    }
+
+    fn version(&self) -> u32 {
+        1
+    }
+
+    async fn verify(
+        &self,
+        substrate_uri: &str,
+        block_number: BlockNumber,
+        cancel: &CancellationToken,
+    ) -> eyre::Result<Proofs> {
+        use node_executor::ExecutorDispatch;
+        use sc_executor::{sp_wasm_interface::ExtendedHostFunctions, NativeExecutionDispatch};
+        execute_block::<
+            Block,
+            ExtendedHostFunctions<
+                sp_io::SubstrateHostFunctions,
+                <ExecutorDispatch as NativeExecutionDispatch>::ExtendHostFunctions,
+            >,
+        >(substrate_uri, block_number, cancel)
+        .await
+    }
+}
+
+/// Tasks of `task_type` created from block `from_block` on are verified with `version`,
+/// given as `<task-type>:<version>@<block>`, e.g. `execute-block:2@19000000`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Switchover {
+    pub task_type: TaskType,
+    pub version: u32,
+    pub from_block: u32,
+}
+
+impl FromStr for Switchover {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        let malformed = || eyre!("expected <task-type>:<version>@<block>, got {:?}", s);
+        let (task_type, rest) = s.split_once(':').ok_or_else(malformed)?;
+        let (version, from_block) = rest.split_once('@').ok_or_else(malformed)?;
+        Ok(Self {
+            task_type: clap::ValueEnum::from_str(task_type, true).map_err(|e| eyre!(e))?,
+            version: version.trim_start_matches('v').parse()?,
+            from_block: from_block.parse()?,
+        })
+    }
+}
+
+/// Verifiers of every task type and version, with the blocks switching between them.
+#[derive(Debug)]
+pub struct Verifiers {
+    registered: HashMap<(TaskType, u32), Arc<dyn Verifier>>,
+    switchovers: Vec<Switchover>,
+}
+
+impl Verifiers {
+    /// Verifiers built into the node, failing if a switchover names a version it lacks.
+    pub fn builtin(switchovers: &[Switchover]) -> eyre::Result<Self> {
+        Self::new(vec![Arc::new(ExecuteBlockV1)], switchovers)
+    }
+
+    pub fn new(
+        verifiers: Vec<Arc<dyn Verifier>>,
+        switchovers: &[Switchover],
+    ) -> eyre::Result<Self> {
+        let registered: HashMap<_, _> = verifiers
+            .into_iter()
+            .map(|v| ((v.task_type(), v.version()), v))
+            .collect();
+        if let Some(s) = switchovers
+            .iter()
+            .find(|s| !registered.contains_key(&(s.task_type, s.version)))
+        {
+            return Err(eyre!(
+                "no version {} verifier of {:?} tasks for the switchover at block {}, this release verifies {:?}",
+                s.version,
+                s.task_type,
+                s.from_block,
+                registered.keys().collect::<Vec<_>>()
+            ));
+        }
+        let mut switchovers = switchovers.to_vec();
+        switchovers.sort_by_key(|s| s.from_block);
+        Ok(Self {
+            registered,
+            switchovers,
+        })
+    }
+
+    /// Verifier of the tasks of `task_type` created at block `created_block`.
+    pub fn select(&self, task_type: TaskType, created_block: u32) -> eyre::Result<&dyn Verifier> {
+        let version = self
+            .switchovers
+            .iter()
+            .rev()
+            .find(|s| s.task_type == task_type && s.from_block <= created_block)
+            .map_or(INITIAL_VERSION, |s| s.version);
+        self.get(task_type, version)
+    }
+
+    /// Verifier `version` of `task_type`.
+    pub fn get(&self, task_type: TaskType, version: u32) -> eyre::Result<&dyn Verifier> {
+        self.registered
+            .get(&(task_type, version))
+            .map(|v| v.as_ref())
+            .ok_or_else(|| eyre!("no version {} verifier of {:?} tasks", version, task_type))
+    }
+}
+
+#[test]
+fn test_verifier_selection() {
+    #[derive(Debug)]
+    struct ExecuteBlockV2;
+
+    #[async_trait]
+    impl Verifier for ExecuteBlockV2 {
+        fn task_type(&self) -> TaskType {
+            TaskType::ExecuteBlock
+        }
+
+        fn version(&self) -> u32 {
+            2
+        }
+
+        async fn verify(
+            &self,
+            _: &str,
+            _: BlockNumber,
+            _: &CancellationToken,
+        ) -> eyre::Result<Proofs> {
+            Ok((H256::zero(), H256::zero()))
+        }
+    }
+
+    let switchover: Switchover = "execute-block:v2@100".parse().unwrap();
+    assert_eq!(
+        switchover,
+        Switchover {
+            task_type: TaskType::ExecuteBlock,
+            version: 2,
+            from_block: 100
+        }
+    );
+    assert!("execute-block:2".parse::<Switchover>().is_err());
+    assert!("unknown:2@100".parse::<Switchover>().is_err());
+
+    assert!(Verifiers::builtin(&[switchover]).is_err());
+    let verifiers = Verifiers::new(
+        vec![Arc::new(ExecuteBlockV1), Arc::new(ExecuteBlockV2)],
+        &[switchover],
+    )
+    .unwrap();
+    let version = |block| {
+        verifiers
+            .select(TaskType::ExecuteBlock, block)
+            .unwrap()
+            .version()
+    };
+    assert_eq!((version(99), version(100), version(101)), (1, 2, 2));
+}
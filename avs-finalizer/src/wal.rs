@@ -0,0 +1,231 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use bindings::{mangata_task_manager::NewTaskCreatedFilter, shared_types::TaskResponse};
+use ethers::types::{Address, H256};
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+/// Configuration the signing decision depends on, captured with every record so a decision
+/// can be replayed without the node configuration it was made with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    pub latency_budget_ms: Option<u64>,
+    pub shadow_of: Option<Address>,
+}
+
+/// Everything that influenced the decision on a task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionInputs {
+    pub event: NewTaskCreatedFilter,
+    pub config: ConfigSnapshot,
+    /// Block hash and storage proof hash from the substrate block execution
+    pub executed: (H256, H256),
+    /// Whether the block was executed ahead of the task event
+    pub prepared: bool,
+    /// Time spent on the task when the decision was made
+    pub elapsed_ms: u64,
+    /// Block hash agreed on by the substrate node quorum, or the error querying it.
+    /// `None` when no quorum is configured.
+    pub agreed_hash: Option<Result<H256, String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    Skip {
+        reason: String,
+    },
+    Respond {
+        response: TaskResponse,
+    },
+    /// Signed but not sent, in shadow mode
+    Shadow {
+        response: TaskResponse,
+    },
+}
+
+/// Whether the task already exceeded the latency budget after `elapsed_ms`.
+pub fn degraded(config: &ConfigSnapshot, elapsed_ms: u64) -> bool {
+    config
+        .latency_budget_ms
+        .is_some_and(|budget| elapsed_ms > budget)
+}
+
+/// Decides whether and what to sign for a task. Must only depend on `inputs` so that
+/// recorded decisions can be replayed.
+pub fn decide(inputs: &DecisionInputs) -> Decision {
+    let config = &inputs.config;
+    let (block_hash, storage_proof_hash) = inputs.executed;
+    match &inputs.agreed_hash {
+        Some(Err(e)) => {
+            return Decision::Skip {
+                reason: format!("substrate quorum unavailable: {}", e),
+            }
+        }
+        Some(Ok(agreed)) if *agreed != block_hash => {
+            return Decision::Skip {
+                reason: format!(
+                    "executed block {} hash {:x} differs from quorum agreed hash {:x}",
+                    inputs.event.task.block_number, block_hash, agreed
+                ),
+            }
+        }
+        _ => {}
+    }
+
+    let response = TaskResponse {
+        reference_task_index: inputs.event.task_index,
+        block_hash: block_hash.to_fixed_bytes(),
+        storage_proof_hash: storage_proof_hash.to_fixed_bytes(),
+    };
+    match config.shadow_of {
+        Some(_) => Decision::Shadow { response },
+        None => Decision::Respond { response },
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalRecord {
+    pub recorded_at: u64,
+    pub inputs: DecisionInputs,
+    pub decision: Decision,
+}
+
+/// Append-only log of signing decisions. Records are bincode encoded and prefixed with
+/// their little endian `u32` length, each one is flushed before the decision is acted on.
+#[derive(Debug)]
+pub struct Wal {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Wal {
+    pub fn open(path: &Path) -> eyre::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_owned(),
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &WalRecord) -> eyre::Result<()> {
+        let bytes = bincode::serialize(record)?;
+        let mut frame = Vec::with_capacity(4 + bytes.len());
+        frame.extend_from_slice(&u32::try_from(bytes.len())?.to_le_bytes());
+        frame.extend_from_slice(&bytes);
+
+        let mut file = self.file.lock().expect("poisoned lock");
+        file.write_all(&frame)?;
+        file.sync_data()
+            .map_err(|e| eyre!("cannot sync WAL {}: {}", self.path.display(), e))
+    }
+}
+
+/// Reads all records of the WAL at `path`. A truncated last record, left by a crash while
+/// appending, is ignored.
+pub fn read(path: &Path) -> eyre::Result<Vec<WalRecord>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut records = vec![];
+    loop {
+        let mut len = [0; 4];
+        match reader.read_exact(&mut len) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            res => res?,
+        }
+        let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+        match reader.read_exact(&mut bytes) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            res => res?,
+        }
+        records.push(bincode::deserialize(&bytes)?);
+    }
+    Ok(records)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayedDecision {
+    pub task_index: u32,
+    pub recorded_at: u64,
+    pub inputs: DecisionInputs,
+    pub recorded: Decision,
+    pub replayed: Decision,
+    pub matches: bool,
+}
+
+/// Re-derives the decisions of the WAL at `path`, optionally only for `task_index`.
+pub fn replay(path: &Path, task_index: Option<u32>) -> eyre::Result<Vec<ReplayedDecision>> {
+    Ok(read(path)?
+        .into_iter()
+        .filter(|r| task_index.is_none_or(|i| r.inputs.event.task_index == i))
+        .map(|record| {
+            let replayed = decide(&record.inputs);
+            ReplayedDecision {
+                task_index: record.inputs.event.task_index,
+                recorded_at: record.recorded_at,
+                matches: replayed == record.decision,
+                inputs: record.inputs,
+                recorded: record.decision,
+                replayed,
+            }
+        })
+        .collect())
+}
+
+#[test]
+fn test_wal_replay() {
+    let path = std::env::temp_dir().join(format!("wal-test-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut inputs = DecisionInputs {
+        event: NewTaskCreatedFilter {
+            task_index: 7,
+            ..Default::default()
+        },
+        config: ConfigSnapshot {
+            latency_budget_ms: Some(1000),
+            shadow_of: None,
+        },
+        executed: (H256::repeat_byte(1), H256::repeat_byte(2)),
+        prepared: false,
+        elapsed_ms: 10,
+        agreed_hash: Some(Ok(H256::repeat_byte(3))),
+    };
+    assert!(matches!(decide(&inputs), Decision::Skip { .. }));
+    // over budget, the diverging quorum hash still is not signed
+    inputs.elapsed_ms = 2000;
+    assert!(matches!(decide(&inputs), Decision::Skip { .. }));
+    inputs.agreed_hash = Some(Ok(H256::repeat_byte(1)));
+    assert!(matches!(decide(&inputs), Decision::Respond { .. }));
+
+    let wal = Wal::open(&path).unwrap();
+    for decision in [decide(&inputs), Decision::Skip { reason: "x".into() }] {
+        wal.append(&WalRecord {
+            recorded_at: 0,
+            inputs: inputs.clone(),
+            decision,
+        })
+        .unwrap();
+    }
+    // a crash while appending leaves a partial record
+    wal.file
+        .lock()
+        .unwrap()
+        .write_all(&[9, 0, 0, 0, 1])
+        .unwrap();
+
+    let replayed = replay(&path, Some(7)).unwrap();
+    assert_eq!(
+        replayed.iter().map(|r| r.matches).collect::<Vec<_>>(),
+        [true, false]
+    );
+    assert!(replay(&path, Some(8)).unwrap().is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,226 @@
+use std::process::Stdio;
+
+use eyre::eyre;
+use node_primitives::BlockNumber;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, ChildStdout, Command},
+    sync::{Mutex, Semaphore},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    headers::{self, set_headers, RpcHeader},
+    metrics::metrics,
+    quota::{self, SubstrateUsage},
+    task::TaskType,
+    verifier::{Proofs, Verifier, Verifiers},
+};
+
+/// First argument starting the binary as a verification worker instead of a node.
+pub const WORKER_COMMAND: &str = "verify-worker";
+
+/// Block to verify, sent by the node to a worker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifyRequest {
+    pub task_type: TaskType,
+    pub version: u32,
+    pub substrate_uri: String,
+    /// Headers of `substrate_uri`, as workers do not get the node arguments
+    #[serde(default)]
+    pub substrate_headers: Vec<RpcHeader>,
+    pub block_number: BlockNumber,
+}
+
+/// Answer of a worker to a [`VerifyRequest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifyReply {
+    #[serde(flatten)]
+    pub outcome: VerifyOutcome,
+    /// Substrate traffic of the verification, accounted by the node
+    pub usage: SubstrateUsage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyOutcome {
+    Proofs(Proofs),
+    Error(String),
+}
+
+/// Runs the worker side of the pool: verifies the [`VerifyRequest`] read on each line of
+/// stdin with the built-in verifiers and writes the [`VerifyReply`] on stdout, one at a time.
+/// Workers never hold keys, they get neither the node arguments nor its environment.
+pub async fn serve() -> eyre::Result<()> {
+    let verifiers = Verifiers::builtin(&[])?;
+    quota::collect_usage();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        let request: VerifyRequest = serde_json::from_str(&line)?;
+        set_headers(&request.substrate_uri, &request.substrate_headers);
+        let outcome = match verifiers.get(request.task_type, request.version) {
+            Ok(verifier) => verifier
+                .verify(
+                    &request.substrate_uri,
+                    request.block_number,
+                    // the node kills the worker to cancel a verification
+                    &CancellationToken::new(),
+                )
+                .await
+                .map_or_else(
+                    |e| VerifyOutcome::Error(format!("{:?}", e)),
+                    VerifyOutcome::Proofs,
+                ),
+            Err(e) => VerifyOutcome::Error(e.to_string()),
+        };
+        let reply = VerifyReply {
+            outcome,
+            usage: quota::take_usage(),
+        };
+        let mut answer = serde_json::to_vec(&reply)?;
+        answer.push(b'\n');
+        stdout.write_all(&answer).await?;
+        stdout.flush().await?;
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+struct WorkerProcess {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl WorkerProcess {
+    fn spawn() -> eyre::Result<Self> {
+        let mut command = Command::new(std::env::current_exe()?);
+        command
+            .arg(WORKER_COMMAND)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true);
+        if let Ok(filter) = std::env::var("RUST_LOG") {
+            command.env("RUST_LOG", filter);
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|e| eyre!("cannot start a verification worker: {}", e))?;
+        Ok(Self {
+            stdin: child.stdin.take().expect("piped stdin"),
+            stdout: BufReader::new(child.stdout.take().expect("piped stdout")),
+            _child: child,
+        })
+    }
+
+    async fn verify(&mut self, request: &VerifyRequest) -> eyre::Result<VerifyReply> {
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        self.stdin.write_all(&line).await?;
+        self.stdin.flush().await?;
+        let mut answer = String::new();
+        if self.stdout.read_line(&mut answer).await? == 0 {
+            return Err(eyre!("verification worker exited"));
+        }
+        Ok(serde_json::from_str(&answer)?)
+    }
+}
+
+/// Verification workers, each a process of this binary started with [`WORKER_COMMAND`] and
+/// verifying one block at a time. A worker crashing or cancelled mid-verification is killed
+/// and replaced on the next verification, the node and its keys are not affected.
+#[derive(Debug)]
+pub struct WorkerPool {
+    idle: Semaphore,
+    workers: Vec<Mutex<Option<WorkerProcess>>>,
+}
+
+impl WorkerPool {
+    /// A pool of `size` workers, started on first use. `None` for a size of 0, verifying in
+    /// the node process.
+    pub fn new(size: usize) -> Option<Self> {
+        (size > 0).then(|| Self {
+            idle: Semaphore::new(size),
+            workers: (0..size).map(|_| Mutex::new(None)).collect(),
+        })
+    }
+
+    /// Verifies `block_number` in a worker with the `verifier` version, dropping the future
+    /// kills the worker.
+    #[instrument(skip(self, verifier, substrate_uri))]
+    pub async fn verify(
+        &self,
+        verifier: &dyn Verifier,
+        substrate_uri: &str,
+        block_number: BlockNumber,
+    ) -> eyre::Result<Proofs> {
+        let _permit = self.idle.acquire().await?;
+        let mut slot = self
+            .workers
+            .iter()
+            .find_map(|worker| worker.try_lock().ok())
+            .expect("a worker per permit");
+        // out of the slot until it answers, so that a dropped call does not leave a worker
+        // with a reply pending
+        let mut worker = match slot.take() {
+            Some(worker) => worker,
+            None => {
+                info!("Starting a verification worker");
+                WorkerProcess::spawn()?
+            }
+        };
+        let request = VerifyRequest {
+            task_type: verifier.task_type(),
+            version: verifier.version(),
+            substrate_uri: substrate_uri.to_owned(),
+            substrate_headers: headers::configured(substrate_uri),
+            block_number,
+        };
+        match worker.verify(&request).await {
+            Ok(reply) => {
+                *slot = Some(worker);
+                quota::account_usage(&reply.usage, block_number);
+                match reply.outcome {
+                    VerifyOutcome::Proofs(proofs) => Ok(proofs),
+                    VerifyOutcome::Error(e) => Err(eyre!("verification worker failed: {}", e)),
+                }
+            }
+            Err(e) => {
+                warn!("Verification worker lost: {:?}", e);
+                metrics().worker_restarts.inc();
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_verify_reply_encoding() {
+    use ethers::types::H256;
+
+    let mut usage = SubstrateUsage {
+        bytes_sent: 10,
+        runtime: Some((1_002, "mangata-parachain".into())),
+        ..Default::default()
+    };
+    usage.calls.insert("chain_getBlock".into(), 2);
+    let reply = VerifyReply {
+        outcome: VerifyOutcome::Proofs((H256::repeat_byte(1), H256::repeat_byte(2))),
+        usage,
+    };
+    let line = serde_json::to_string(&reply).unwrap();
+    assert!(line.starts_with("{\"proofs\":["));
+    assert_eq!(serde_json::from_str::<VerifyReply>(&line).unwrap(), reply);
+    let error = VerifyReply {
+        outcome: VerifyOutcome::Error("x".into()),
+        usage: SubstrateUsage::default(),
+    };
+    let line = serde_json::to_string(&error).unwrap();
+    assert!(line.starts_with("{\"error\":\"x\","));
+    assert_eq!(serde_json::from_str::<VerifyReply>(&line).unwrap(), error);
+}